@@ -0,0 +1,320 @@
+use crate::{
+    game_manager::{GameManager, MoveErr},
+    moves::Move,
+    pgn::{PgnLine, PgnMove},
+};
+
+#[derive(Clone, Debug)]
+pub struct VariationNode {
+    pub mv: Move,
+    pub san: String,
+    // The first child continues the line, the others are alternatives to it
+    pub children: Vec<VariationNode>,
+}
+
+// Tree of lines explored from a starting position. Positions in the tree are addressed by
+// their path: the index of the child taken at every ply, the main line being all zeroes.
+#[derive(Clone, Debug)]
+pub struct AnalysisBoard {
+    root: GameManager,
+    children: Vec<VariationNode>,
+    path: Vec<usize>,
+    position: GameManager,
+}
+
+impl AnalysisBoard {
+    pub fn new(root: GameManager) -> Self {
+        Self {
+            position: root.clone(),
+            root,
+            children: vec![],
+            path: vec![],
+        }
+    }
+
+    pub fn position(&self) -> &GameManager {
+        &self.position
+    }
+
+    pub fn path(&self) -> &[usize] {
+        &self.path
+    }
+
+    fn children_at(&self, path: &[usize]) -> Option<&Vec<VariationNode>> {
+        let mut children = &self.children;
+        for i in path {
+            children = &children.get(*i)?.children;
+        }
+        Some(children)
+    }
+
+    pub fn current(&self) -> Option<&VariationNode> {
+        let (last, parent) = self.path.split_last()?;
+        self.children_at(parent)?.get(*last)
+    }
+
+    // Moves that can be played from the current position, the main continuation first
+    pub fn variations(&self) -> Vec<Move> {
+        self.children_at(&self.path)
+            .map(|children| children.iter().map(|n| n.mv).collect())
+            .unwrap_or_default()
+    }
+
+    // Follows `mv` when it is already in the tree, otherwise adds it as the last
+    // alternative of the current position.
+    pub fn play(&mut self, mv: &Move) -> Result<(), MoveErr> {
+        let san = self.position.san(mv)?;
+        self.position.play(mv)?;
+        let children =
+            children_at_mut(&mut self.children, &self.path).expect("current path is in the tree");
+        let index = match children.iter().position(|n| n.mv == *mv) {
+            Some(index) => index,
+            None => {
+                children.push(VariationNode {
+                    mv: *mv,
+                    san,
+                    children: vec![],
+                });
+                children.len() - 1
+            }
+        };
+        self.path.push(index);
+        Ok(())
+    }
+
+    pub fn back(&mut self) -> bool {
+        if self.path.pop().is_none() {
+            return false;
+        }
+        self.position.undo();
+        true
+    }
+
+    // Steps into the main continuation of the current position
+    pub fn forward(&mut self) -> bool {
+        self.enter_variation(0)
+    }
+
+    pub fn enter_variation(&mut self, index: usize) -> bool {
+        let mv = match self.children_at(&self.path).and_then(|c| c.get(index)) {
+            Some(node) => node.mv,
+            None => return false,
+        };
+        self.position.play(&mv).is_ok() && {
+            self.path.push(index);
+            true
+        }
+    }
+
+    pub fn goto(&mut self, path: &[usize]) -> bool {
+        if self.children_at(path).is_none() {
+            return false;
+        }
+        self.position = self.root.clone();
+        self.path.clear();
+        path.iter().all(|i| self.enter_variation(*i))
+    }
+
+    pub fn goto_start(&mut self) {
+        self.goto(&[]);
+    }
+
+    // Swaps the variation holding the current position with the previous alternative at the
+    // closest point where it branches off.
+    pub fn promote_variation(&mut self) -> bool {
+        let depth = match self.path.iter().rposition(|i| *i > 0) {
+            Some(depth) => depth,
+            None => return false,
+        };
+        let index = self.path[depth];
+        let children = children_at_mut(&mut self.children, &self.path[..depth])
+            .expect("current path is in the tree");
+        children.swap(index, index - 1);
+        self.path[depth] -= 1;
+        true
+    }
+
+    // Makes the line of the current position the main line
+    pub fn promote_to_main_line(&mut self) {
+        while self.promote_variation() {}
+    }
+
+    // Removes the current move with everything following it, going back to the position
+    // before it.
+    pub fn delete_variation(&mut self) -> bool {
+        let index = match self.path.last() {
+            Some(index) => *index,
+            None => return false,
+        };
+        self.back();
+        children_at_mut(&mut self.children, &self.path)
+            .expect("current path is in the tree")
+            .remove(index);
+        true
+    }
+
+    pub fn main_line(&self) -> Vec<Move> {
+        let mut moves = vec![];
+        let mut children = &self.children;
+        while let Some(node) = children.first() {
+            moves.push(node.mv);
+            children = &node.children;
+        }
+        moves
+    }
+
+    pub fn to_pgn_line(&self) -> PgnLine {
+        pgn_line(&self.children)
+    }
+
+    // Paths of the other positions of the tree identical to the current one, in tree order.
+    // The length of a path is the ply the position is reached at, so a path of the same
+    // length is a transposition and a shorter one also a repetition.
+    pub fn transpositions(&self) -> Vec<Vec<usize>> {
+        let hash = self.position.zobrist_hash();
+        let mut found = vec![];
+        if self.root.zobrist_hash() == hash && !self.path.is_empty() {
+            found.push(vec![]);
+        }
+        let mut gm = self.root.clone();
+        let mut path = vec![];
+        find_position(&self.children, &mut gm, &mut path, hash, &mut found);
+        found.retain(|p| *p != self.path);
+        found
+    }
+}
+
+fn find_position(
+    children: &[VariationNode],
+    gm: &mut GameManager,
+    path: &mut Vec<usize>,
+    hash: u64,
+    found: &mut Vec<Vec<usize>>,
+) {
+    for (i, node) in children.iter().enumerate() {
+        let undo = gm.make_move(&node.mv);
+        path.push(i);
+        if gm.zobrist_hash() == hash {
+            found.push(path.clone());
+        }
+        find_position(&node.children, gm, path, hash, found);
+        path.pop();
+        gm.unmake_move(undo);
+    }
+}
+
+fn children_at_mut<'a>(
+    mut children: &'a mut Vec<VariationNode>,
+    path: &[usize],
+) -> Option<&'a mut Vec<VariationNode>> {
+    for i in path {
+        children = &mut children.get_mut(*i)?.children;
+    }
+    Some(children)
+}
+
+fn pgn_line(mut children: &[VariationNode]) -> PgnLine {
+    let mut line = PgnLine::default();
+    while let Some((main, alternatives)) = children.split_first() {
+        let mut mv = PgnMove::new(&main.san);
+        mv.variations = alternatives
+            .iter()
+            .map(|alt| {
+                let mut variation = pgn_line(&alt.children);
+                variation.moves.insert(0, PgnMove::new(&alt.san));
+                variation
+            })
+            .collect();
+        line.moves.push(mv);
+        children = &main.children;
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AnalysisBoard;
+    use crate::{
+        game_manager::GameManager,
+        moves::Move,
+        pgn::{PgnGame, PgnWriter},
+        square::Square,
+    };
+
+    #[test]
+    fn test_variations() {
+        let e4 = Move::new(Square::E2, Square::E4);
+        let d4 = Move::new(Square::D2, Square::D4);
+        let e5 = Move::new(Square::E7, Square::E5);
+        let c5 = Move::new(Square::C7, Square::C5);
+        let mut board = AnalysisBoard::new(GameManager::new());
+        board.play(&e4).unwrap();
+        board.play(&e5).unwrap();
+        assert!(board.back());
+        board.play(&c5).unwrap();
+        assert_eq!(&[0, 1], board.path());
+        assert_eq!("c5", board.current().unwrap().san);
+        board.goto_start();
+        board.play(&d4).unwrap();
+        assert!(board.play(&Move::new(Square::D4, Square::D6)).is_err());
+        assert_eq!(vec![e4, e5], board.main_line());
+
+        // Replaying a known move follows the tree instead of adding a line
+        board.goto_start();
+        board.play(&e4).unwrap();
+        assert_eq!(vec![e5, c5], board.variations());
+        assert!(board.enter_variation(1));
+        assert_eq!(GameManager::new().zobrist_hash(), {
+            let mut gm = board.position().clone();
+            gm.undo();
+            gm.undo();
+            gm.zobrist_hash()
+        });
+
+        let mut game = PgnWriter::new().game(&GameManager::new());
+        game.mainline = board.to_pgn_line();
+        let pgn = game.to_pgn();
+        assert!(pgn.contains("1. e4 (1. d4) 1... e5 (1... c5) *"));
+        assert_eq!(game.mainline, PgnGame::parse(&pgn).unwrap().mainline);
+
+        board.promote_to_main_line();
+        assert_eq!(&[0, 0], board.path());
+        assert_eq!(vec![e4, c5], board.main_line());
+        assert!(!board.promote_variation());
+        assert!(board.goto(&[1]));
+        assert!(board.promote_variation());
+        assert_eq!(vec![d4], board.main_line());
+        assert!(!board.goto(&[2]));
+        assert_eq!(&[0], board.path());
+
+        assert!(board.goto(&[1, 1]));
+        assert!(board.delete_variation());
+        assert_eq!(&[1], board.path());
+        assert_eq!(vec![c5], board.variations());
+        board.goto_start();
+        assert!(!board.delete_variation());
+    }
+
+    #[test]
+    fn test_transpositions() {
+        let mut board = AnalysisBoard::new(GameManager::new());
+        for uci in ["g1f3", "d7d5", "d2d4"] {
+            board.play(&Move::from_uci(uci).unwrap()).unwrap();
+        }
+        assert!(board.transpositions().is_empty());
+        board.goto_start();
+        for uci in ["d2d4", "d7d5", "g1f3"] {
+            board.play(&Move::from_uci(uci).unwrap()).unwrap();
+        }
+        assert_eq!(vec![vec![0, 0, 0]], board.transpositions());
+        assert!(board.goto(&[0, 0, 0]));
+        assert_eq!(vec![vec![1, 0, 0]], board.transpositions());
+
+        // Knights out and back repeat the start position
+        board.goto_start();
+        for uci in ["g1f3", "g8f6", "f3g1", "f6g8"] {
+            board.play(&Move::from_uci(uci).unwrap()).unwrap();
+        }
+        assert_eq!(vec![Vec::<usize>::new()], board.transpositions());
+    }
+}
@@ -0,0 +1,112 @@
+use crate::{
+    eval::{ClassicalEval, Evaluator, Score},
+    game_manager::GameManager,
+    pgn::{PgnEval, PgnGame, PgnLine, PgnMove, PgnWriter},
+    piece::Player,
+    search::{mate_in, root_scores},
+};
+
+// Numeric Annotation Glyphs of the PGN standard
+pub const NAG_GOOD: u8 = 1;
+pub const NAG_MISTAKE: u8 = 2;
+pub const NAG_BLUNDER: u8 = 4;
+pub const NAG_DUBIOUS: u8 = 6;
+
+// Centipawns lost against the best move for each annotation
+const DUBIOUS_LOSS: Score = 50;
+const MISTAKE_LOSS: Score = 100;
+const BLUNDER_LOSS: Score = 300;
+// A move is good when it is the only one keeping the score within this margin
+const ONLY_MOVE_MARGIN: Score = 150;
+
+// Grades the moves of a game with a fixed depth search: ?!, ? and ?? for moves losing ground
+// against the best one, which is then given as a variation, and ! for only moves. The score
+// of every move goes in an `[%eval]` command.
+#[derive(Clone, Debug)]
+pub struct Annotator<E = ClassicalEval> {
+    depth: u8,
+    eval: E,
+}
+
+impl Annotator {
+    pub fn new(depth: u8) -> Self {
+        Self::with_evaluator(depth, ClassicalEval)
+    }
+}
+
+impl<E: Evaluator> Annotator<E> {
+    pub fn with_evaluator(depth: u8, eval: E) -> Self {
+        Self { depth, eval }
+    }
+
+    pub fn annotate(&self, gm: &GameManager, writer: &PgnWriter) -> PgnGame {
+        let mut game = writer.game(gm);
+        let mut position = gm.initial_position();
+        for (record, pgn_move) in gm.history().iter().zip(game.mainline.moves.iter_mut()) {
+            let mut scores = root_scores(&position, self.depth, &self.eval);
+            scores.sort_by_key(|(_, score)| std::cmp::Reverse(*score));
+            let played = scores.iter().find(|(mv, _)| *mv == record.mv);
+            if let (Some((best, best_score)), Some((_, score))) = (scores.first(), played) {
+                let loss = best_score - score;
+                let white = if position.turn == Player::White {
+                    1
+                } else {
+                    -1
+                };
+                pgn_move.set_eval(match mate_in(*score) {
+                    Some(moves) => PgnEval::Mate(white * moves),
+                    None => PgnEval::Centipawns(white * score),
+                });
+                let nag = if loss >= BLUNDER_LOSS {
+                    Some(NAG_BLUNDER)
+                } else if loss >= MISTAKE_LOSS {
+                    Some(NAG_MISTAKE)
+                } else if loss >= DUBIOUS_LOSS {
+                    Some(NAG_DUBIOUS)
+                } else if loss == 0
+                    && scores
+                        .get(1)
+                        .is_some_and(|(_, second)| best_score - second >= ONLY_MOVE_MARGIN)
+                {
+                    Some(NAG_GOOD)
+                } else {
+                    None
+                };
+                pgn_move.nags.extend(nag);
+                if loss >= MISTAKE_LOSS {
+                    let san = position.san(best).unwrap_or_else(|_| best.to_uci());
+                    pgn_move.variations.push(PgnLine {
+                        comments: vec![],
+                        moves: vec![PgnMove::new(&san)],
+                    });
+                }
+            }
+            position.make_move(&record.mv);
+        }
+        game
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Annotator, NAG_BLUNDER, NAG_GOOD};
+    use crate::{
+        game_manager::GameManager,
+        pgn::{PgnEval, PgnWriter},
+    };
+
+    #[test]
+    fn test_annotate() {
+        let gm =
+            GameManager::from_moves(&["e4", "e5", "Qh5", "Nc6", "Bc4", "Nf6", "Qxf7#"]).unwrap();
+        let game = Annotator::new(2).annotate(&gm, &PgnWriter::new());
+        let moves = &game.mainline.moves;
+        assert_eq!(vec![NAG_BLUNDER], moves[5].nags);
+        let best = &moves[5].variations[0].moves[0].san;
+        assert!(["g6", "d5", "Qe7", "Qf6"].contains(&best.as_str()));
+        assert_eq!(vec![NAG_GOOD], moves[6].nags);
+        assert!(moves[..5].iter().all(|mv| !mv.nags.contains(&NAG_BLUNDER)));
+        assert!(game.to_pgn().contains("Nf6 $4 {[%eval #1]} (3... "));
+        assert_eq!(Some(PgnEval::Mate(1)), moves[6].eval());
+    }
+}
@@ -0,0 +1,274 @@
+use crate::{
+    game_manager::{CheckKind, GameManager, MoveErr},
+    moves::Move,
+    piece::{Kind, Position},
+};
+
+// Words an announcement is made of, to speak moves in another language or style
+pub trait Phrases {
+    fn piece(&self, kind: Kind) -> &str;
+    // Between the piece and the square, e.g. "to" and "takes on"
+    fn moves_to(&self) -> &str;
+    fn takes_on(&self) -> &str;
+    fn castles(&self, king_side: bool) -> &str;
+    fn en_passant(&self) -> &str;
+    fn promotes_to(&self) -> &str;
+    fn check(&self) -> &str;
+    fn checkmate(&self) -> &str;
+    fn stalemate(&self) -> &str;
+
+    // Plain `check` unless the table tells these apart
+    fn discovered_check(&self) -> &str {
+        self.check()
+    }
+
+    fn double_check(&self) -> &str {
+        self.check()
+    }
+
+    fn square(&self, pos: Position) -> String {
+        pos.to_string()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct English;
+
+impl Phrases for English {
+    fn piece(&self, kind: Kind) -> &str {
+        match kind {
+            Kind::Pawn => "pawn",
+            Kind::Knight => "knight",
+            Kind::Bishop => "bishop",
+            Kind::Rook => "rook",
+            Kind::Queen => "queen",
+            Kind::King => "king",
+        }
+    }
+
+    fn moves_to(&self) -> &str {
+        "to"
+    }
+
+    fn takes_on(&self) -> &str {
+        "takes on"
+    }
+
+    fn castles(&self, king_side: bool) -> &str {
+        if king_side {
+            "castles king side"
+        } else {
+            "castles queen side"
+        }
+    }
+
+    fn en_passant(&self) -> &str {
+        "en passant"
+    }
+
+    fn promotes_to(&self) -> &str {
+        "promotes to"
+    }
+
+    fn check(&self) -> &str {
+        "check"
+    }
+
+    fn checkmate(&self) -> &str {
+        "checkmate"
+    }
+
+    fn stalemate(&self) -> &str {
+        "stalemate"
+    }
+
+    fn discovered_check(&self) -> &str {
+        "discovered check"
+    }
+
+    fn double_check(&self) -> &str {
+        "double check"
+    }
+}
+
+impl GameManager {
+    // Move of the side to move spoken out with what it leads to, e.g. "knight takes on f6,
+    // check", for blindfold play and screen readers
+    pub fn announce(&self, mv: &Move) -> Result<String, MoveErr> {
+        self.announce_with(mv, &English)
+    }
+
+    pub fn announce_with<P: Phrases + ?Sized>(
+        &self,
+        mv: &Move,
+        phrases: &P,
+    ) -> Result<String, MoveErr> {
+        if !self.legal_moves_iter().any(|m| m == *mv) {
+            return Err(self.illegal_move_err(mv));
+        }
+        let kind = self
+            .pieces_of(self.turn)
+            .iter()
+            .find(|p| Position::from_piece(p) == mv.from)
+            .map(|p| p.kind)
+            .ok_or(MoveErr::NoPiece { at: mv.from })?;
+        let mut after = self.clone();
+        let (_, flags) = after.make_flagged_move(mv);
+        let mut words = vec![];
+        if flags.castling {
+            words.push(phrases.castles(mv.to.column > mv.from.column).to_string());
+        } else {
+            let verb = if flags.capture {
+                phrases.takes_on()
+            } else {
+                phrases.moves_to()
+            };
+            words.push(format!(
+                "{} {} {}",
+                phrases.piece(kind),
+                verb,
+                phrases.square(mv.to)
+            ));
+        }
+        if flags.en_passant {
+            words[0] = format!("{} {}", words[0], phrases.en_passant());
+        }
+        if let Some(promotion) = mv.promotion {
+            words.push(format!(
+                "{} {}",
+                phrases.promotes_to(),
+                phrases.piece(promotion)
+            ));
+        }
+        if flags.checkmate {
+            words.push(phrases.checkmate().to_string());
+        } else if let Some(check) = flags.check_kind {
+            words.push(
+                match check {
+                    CheckKind::Direct => phrases.check(),
+                    CheckKind::Discovered => phrases.discovered_check(),
+                    CheckKind::Double => phrases.double_check(),
+                }
+                .to_string(),
+            );
+        } else if after.legal_moves_iter().next().is_none() {
+            words.push(phrases.stalemate().to_string());
+        }
+        Ok(words.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{English, Phrases};
+    use crate::{game_manager::GameManager, moves::Move, piece::Kind, square::Square};
+
+    struct French;
+
+    impl Phrases for French {
+        fn piece(&self, kind: Kind) -> &str {
+            match kind {
+                Kind::Pawn => "pion",
+                Kind::Queen => "dame",
+                _ => English.piece(kind),
+            }
+        }
+
+        fn moves_to(&self) -> &str {
+            "en"
+        }
+
+        fn takes_on(&self) -> &str {
+            "prend en"
+        }
+
+        fn castles(&self, king_side: bool) -> &str {
+            if king_side {
+                "petit roque"
+            } else {
+                "grand roque"
+            }
+        }
+
+        fn en_passant(&self) -> &str {
+            "en passant"
+        }
+
+        fn promotes_to(&self) -> &str {
+            "promotion en"
+        }
+
+        fn check(&self) -> &str {
+            "échec"
+        }
+
+        fn checkmate(&self) -> &str {
+            "échec et mat"
+        }
+
+        fn stalemate(&self) -> &str {
+            "pat"
+        }
+    }
+
+    fn played(moves: &[&str]) -> GameManager {
+        let mut gm = GameManager::new();
+        for uci in moves {
+            gm.play(&Move::from_uci(uci).unwrap()).unwrap();
+        }
+        gm
+    }
+
+    #[test]
+    fn test_announce() {
+        let gm = played(&["e2e4", "d7d5", "e4e5", "f7f5"]);
+        assert_eq!(
+            "knight to f3",
+            gm.announce(&Move::new(Square::G1, Square::F3)).unwrap()
+        );
+        assert_eq!(
+            "pawn takes on f6 en passant",
+            gm.announce(&Move::new(Square::E5, Square::F6)).unwrap()
+        );
+        assert!(gm.announce(&Move::new(Square::E5, Square::E7)).is_err());
+
+        let gm = GameManager::from_fen("r3k3/1P6/8/8/8/8/8/4K2R w Kq - 0 1").unwrap();
+        let promotion = Move::with_promotion(Square::B7, Square::A8, Kind::Queen);
+        assert_eq!(
+            "pawn takes on a8, promotes to queen, check",
+            gm.announce(&promotion).unwrap()
+        );
+        assert_eq!(
+            "castles king side",
+            gm.announce(&Move::new(Square::E1, Square::G1)).unwrap()
+        );
+        assert_eq!(
+            "pion prend en a8, promotion en dame, échec",
+            gm.announce_with(&promotion, &French).unwrap()
+        );
+
+        let gm = played(&["f2f3", "e7e5", "g2g4"]);
+        assert_eq!(
+            "dame en h4, échec et mat",
+            gm.announce_with(&Move::new(Square::D8, Square::H4), &French)
+                .unwrap()
+        );
+        let gm = GameManager::from_fen("k7/8/1Q6/8/8/8/8/K7 w - - 0 1").unwrap();
+        assert_eq!(
+            "queen to c7, stalemate",
+            gm.announce(&Move::new(Square::B6, Square::C7)).unwrap()
+        );
+
+        let gm = GameManager::from_fen("4k3/8/8/8/4N3/8/8/Q3R2K w - - 0 1").unwrap();
+        assert_eq!(
+            "knight to c5, discovered check",
+            gm.announce(&Move::new(Square::E4, Square::C5)).unwrap()
+        );
+        let double = Move::new(Square::E4, Square::D6);
+        assert_eq!("knight to d6, double check", gm.announce(&double).unwrap());
+        assert_eq!(
+            "knight en d6, échec",
+            gm.announce_with(&double, &French).unwrap()
+        );
+    }
+}
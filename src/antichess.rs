@@ -0,0 +1,108 @@
+use crate::{
+    game_manager::{CastlingRights, GameManager, GameStatus},
+    moves::Move,
+    piece::Kind,
+    rules::Rules,
+};
+
+const ANTICHESS_PROMOTIONS: [Kind; 5] = [
+    Kind::Queen,
+    Kind::Rook,
+    Kind::Bishop,
+    Kind::Knight,
+    Kind::King,
+];
+
+// Losing chess: captures are mandatory, there is no check nor castling and the king is an
+// ordinary piece pawns may promote to. A side without pieces or without moves wins.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Antichess;
+
+impl Rules for Antichess {
+    fn name(&self) -> &str {
+        "Antichess"
+    }
+
+    fn legal_moves<'a>(&self, gm: &'a GameManager) -> Box<dyn Iterator<Item = Move> + 'a> {
+        let moves = gm.pseudo_legal_moves().filter(|mv| {
+            let king = gm.pieces_of(gm.turn).iter().any(|p| {
+                p.kind == Kind::King && p.row == mv.from.row && p.column == mv.from.column
+            });
+            !king || gm.castle(Kind::King, mv, &gm.castling).is_none()
+        });
+        Box::new(gm.forced_captures(moves).into_iter())
+    }
+
+    fn status(&self, gm: &GameManager) -> GameStatus {
+        if gm.pieces_of(gm.turn).is_empty() || gm.legal_moves_iter().next().is_none() {
+            GameStatus::VariantWin(gm.turn)
+        } else {
+            GameStatus::Ongoing
+        }
+    }
+
+    fn promotions(&self) -> &[Kind] {
+        &ANTICHESS_PROMOTIONS
+    }
+}
+
+impl GameManager {
+    // Starting position played as Antichess, without castling rights
+    pub fn antichess() -> Self {
+        let mut gm = GameManager::with_rules(Antichess);
+        gm.castling = CastlingRights::none();
+        gm
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Antichess;
+    use crate::{
+        game_manager::{GameManager, GameStatus},
+        moves::Move,
+        piece::{Kind, Player},
+        square::Square,
+    };
+
+    #[test]
+    fn test_antichess() {
+        let mut gm = GameManager::antichess();
+        assert_eq!("Antichess", gm.rules().name());
+        assert_eq!(20, gm.legal_moves().len());
+        for (from, to) in [(Square::E2, Square::E4), (Square::D7, Square::D5)] {
+            gm.play(&Move::new(from, to)).unwrap();
+        }
+        // The capture is forced
+        assert_eq!(vec![Move::new(Square::E4, Square::D5)], gm.legal_moves());
+        assert!(gm.play(&Move::new(Square::G1, Square::F3)).is_err());
+
+        // No castling, the king may be left attacked and pawns promote to kings
+        let mut gm = GameManager::from_fen("r3k3/1P6/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+        gm.set_rules(Antichess);
+        assert_eq!(
+            vec![Move::with_promotion(Square::B7, Square::A8, Kind::King)],
+            gm.legal_moves()
+                .into_iter()
+                .filter(|mv| mv.promotion == Some(Kind::King))
+                .collect::<Vec<_>>()
+        );
+        let mut gm = GameManager::from_fen("4k3/8/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+        gm.set_rules(Antichess);
+        assert!(!gm
+            .legal_moves()
+            .contains(&Move::new(Square::E1, Square::G1)));
+
+        // Losing every piece wins, as being stalemated does
+        let mut gm = GameManager::from_fen("8/8/8/8/8/8/1p6/R7 b - - 0 1").unwrap();
+        gm.set_rules(Antichess);
+        assert_eq!(GameStatus::Ongoing, gm.status());
+        assert_eq!(5, gm.legal_moves().len());
+        gm.play(&Move::with_promotion(Square::B2, Square::A1, Kind::Queen))
+            .unwrap();
+        assert_eq!(GameStatus::VariantWin(Player::White), gm.status());
+        let mut gm = GameManager::from_fen("8/8/8/p7/P7/8/8/8 w - - 0 1").unwrap();
+        gm.set_rules(Antichess);
+        assert_eq!(GameStatus::VariantWin(Player::White), gm.status());
+    }
+}
@@ -0,0 +1,217 @@
+use crate::{
+    piece::{Kind, Piece, Player, Position, MAX_COLUMN, MAX_ROW},
+    square::Square,
+};
+
+const KNIGHT_OFFSETS: [(i8, i8); 8] = [
+    (2, 1),
+    (2, -1),
+    (-2, 1),
+    (-2, -1),
+    (1, 2),
+    (1, -2),
+    (-1, 2),
+    (-1, -2),
+];
+const KING_OFFSETS: [(i8, i8); 8] = [
+    (1, 0),
+    (-1, 0),
+    (0, 1),
+    (0, -1),
+    (1, 1),
+    (1, -1),
+    (-1, 1),
+    (-1, -1),
+];
+
+// Squares attacked from every square of the board by the pieces moving a fixed step, as bits
+// indexed like `Square`
+pub static KNIGHT_ATTACKS: [u64; 64] = step_attacks(&KNIGHT_OFFSETS);
+pub static KING_ATTACKS: [u64; 64] = step_attacks(&KING_OFFSETS);
+// White pawns first
+pub static PAWN_ATTACKS: [[u64; 64]; 2] = [
+    step_attacks(&[(1, -1), (1, 1)]),
+    step_attacks(&[(-1, -1), (-1, 1)]),
+];
+
+const fn step_attacks(steps: &[(i8, i8)]) -> [u64; 64] {
+    let mut table = [0; 64];
+    let mut square = 0;
+    while square < 64 {
+        let (row, column) = ((square / 8) as i8, (square % 8) as i8);
+        let mut i = 0;
+        while i < steps.len() {
+            let (to_row, to_column) = (row + steps[i].0, column + steps[i].1);
+            if to_row >= 0 && to_row < 8 && to_column >= 0 && to_column < 8 {
+                table[square] |= 1 << (to_row * 8 + to_column);
+            }
+            i += 1;
+        }
+        square += 1;
+    }
+    table
+}
+
+// Number of pieces of one side attacking every square of the board
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AttackMap {
+    counts: [u8; 64],
+}
+
+impl AttackMap {
+    pub fn new() -> Self {
+        Self { counts: [0; 64] }
+    }
+
+    pub fn count(&self, pos: impl Into<Position>) -> u8 {
+        match Square::try_from(pos.into()) {
+            Ok(square) => self.counts[square.index() as usize],
+            Err(_) => 0,
+        }
+    }
+
+    pub fn is_attacked(&self, pos: impl Into<Position>) -> bool {
+        self.count(pos) > 0
+    }
+
+    pub fn squares(&self) -> impl Iterator<Item = Position> + '_ {
+        Square::ALL
+            .iter()
+            .filter(|square| self.counts[square.index() as usize] > 0)
+            .map(|square| Position::from(*square))
+    }
+
+    pub(crate) fn add(&mut self, pos: &Position, amount: i8) {
+        if let Some(index) = pos.index() {
+            let count = &mut self.counts[index as usize];
+            *count = count.wrapping_add_signed(amount);
+        }
+    }
+}
+
+impl Default for AttackMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn offset(pos: &Position, (rows, columns): (i8, i8)) -> Option<Position> {
+    let row = pos.row.checked_add_signed(rows)?;
+    let column = pos.column.checked_add_signed(columns)?;
+    (row < MAX_ROW && column < MAX_COLUMN).then(|| Position::new(row, column))
+}
+
+fn slider_directions(kind: Kind) -> &'static [(i8, i8)] {
+    match kind {
+        Kind::Rook => &KING_OFFSETS[..4],
+        Kind::Bishop => &KING_OFFSETS[4..],
+        Kind::Queen => &KING_OFFSETS,
+        _ => &[],
+    }
+}
+
+// Calls `f` on every square attacked by `piece`, sliders stop on the first square for
+// which `occupied` is true.
+pub fn for_each_attack(
+    piece: &Piece,
+    occupied: impl Fn(&Position) -> bool,
+    mut f: impl FnMut(Position),
+) {
+    let start = Position::from_piece(piece);
+    let table = match (piece.kind, piece.player) {
+        (Kind::Pawn, Player::White) => Some(&PAWN_ATTACKS[0]),
+        (Kind::Pawn, Player::Black) => Some(&PAWN_ATTACKS[1]),
+        (Kind::Knight, _) => Some(&KNIGHT_ATTACKS),
+        (Kind::King, _) => Some(&KING_ATTACKS),
+        _ => None,
+    };
+    if let (Some(table), Some(index)) = (table, start.index()) {
+        let mut bits = table[index as usize];
+        while bits != 0 {
+            let square = bits.trailing_zeros() as u8;
+            f(Position::new(square / MAX_COLUMN, square % MAX_COLUMN));
+            bits &= bits - 1;
+        }
+    }
+    for direction in slider_directions(piece.kind) {
+        let mut current = start;
+        while let Some(pos) = offset(&current, *direction) {
+            f(pos);
+            if occupied(&pos) {
+                break;
+            }
+            current = pos;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{for_each_attack, KING_ATTACKS, KNIGHT_ATTACKS, PAWN_ATTACKS};
+    use crate::{
+        game_manager::{CastlingRights, GameManager, START_BLACKS, START_WHITES},
+        piece::{Kind, Piece, PieceList, Player, Position},
+        square::Square,
+    };
+
+    #[test]
+    fn test_for_each_attack() {
+        let count = |piece: &Piece, blocker: Position| {
+            let mut count = 0;
+            for_each_attack(piece, |pos| *pos == blocker, |_| count += 1);
+            count
+        };
+        let nowhere = Position::new(8, 8);
+        assert_eq!(
+            2,
+            count(&Piece::new(Player::White, Kind::Knight, 0, 0), nowhere)
+        );
+        assert_eq!(
+            8,
+            count(&Piece::new(Player::Black, Kind::King, 4, 4), nowhere)
+        );
+        assert_eq!(
+            1,
+            count(&Piece::new(Player::Black, Kind::Pawn, 6, 0), nowhere)
+        );
+        assert_eq!(
+            14,
+            count(&Piece::new(Player::White, Kind::Rook, 0, 0), nowhere)
+        );
+        // The blocker itself is attacked but nothing behind it
+        let rook = Piece::new(Player::White, Kind::Rook, 0, 0);
+        assert_eq!(9, count(&rook, Position::new(2, 0)));
+    }
+
+    #[test]
+    fn test_const_tables() {
+        let bits = |squares: &[Square]| squares.iter().fold(0, |b, s| b | 1 << s.index());
+        assert_eq!(
+            bits(&[Square::B3, Square::C2]),
+            KNIGHT_ATTACKS[Square::A1.index() as usize]
+        );
+        assert_eq!(
+            bits(&[Square::G8, Square::G7, Square::H7]),
+            KING_ATTACKS[Square::H8.index() as usize]
+        );
+        assert_eq!(
+            bits(&[Square::D6, Square::F6]),
+            PAWN_ATTACKS[1][Square::E7.index() as usize]
+        );
+
+        // Built at compile time
+        const PIECES: (PieceList, PieceList) = (START_WHITES, START_BLACKS);
+        const CASTLING: CastlingRights = CastlingRights::all();
+        let gm = GameManager {
+            whites: PIECES.0,
+            blacks: PIECES.1,
+            castling: CASTLING,
+            ..GameManager::empty()
+        };
+        assert_eq!(GameManager::new(), gm);
+        assert_eq!(
+            Position::from(Square::E7),
+            Position::from_piece(&START_BLACKS[12])
+        );
+    }
+}
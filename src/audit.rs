@@ -0,0 +1,401 @@
+use std::{
+    error::Error,
+    fmt,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use sha2::{Digest, Sha256};
+
+use crate::{
+    fen::FenErr,
+    game_manager::{DrawRule, GameManager, MoveErr},
+    moves::Move,
+    piece::{Kind, Player, Position},
+};
+
+const MAGIC: &[u8; 8] = b"LCAUDIT1";
+
+pub type Hash = [u8; 32];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Move(Move),
+    OfferDraw(Player),
+    AcceptDraw(Player),
+    DeclineDraw(Player),
+    ClaimDraw(Player, DrawRule),
+    Resign(Player),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AuditEntry {
+    pub action: Action,
+    // Milliseconds since the Unix epoch
+    pub timestamp: u64,
+    // Chains the entry to everything before it
+    pub hash: Hash,
+}
+
+#[derive(Debug)]
+pub enum AuditErr {
+    // The bytes end in the middle of an entry
+    Truncated,
+    InvalidHeader,
+    Fen(FenErr),
+    // Entry that can't be decoded, by index
+    InvalidAction(usize),
+    // Entry altered after it was logged, or anything before it
+    HashMismatch(usize),
+    Move(MoveErr),
+    GameOver,
+    // Accepting or declining a draw the opponent didn't offer
+    NoDrawOffer,
+    // Offering a draw while the opponent's offer waits for an answer
+    DrawOffered,
+    ClaimRejected(DrawRule),
+}
+
+impl fmt::Display for AuditErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuditErr::Truncated => write!(f, "truncated audit log"),
+            AuditErr::InvalidHeader => write!(f, "invalid audit log header"),
+            AuditErr::Fen(err) => err.fmt(f),
+            AuditErr::InvalidAction(index) => write!(f, "invalid action in entry {}", index),
+            AuditErr::HashMismatch(index) => write!(f, "hash mismatch at entry {}", index),
+            AuditErr::Move(err) => err.fmt(f),
+            AuditErr::GameOver => write!(f, "the game is over"),
+            AuditErr::NoDrawOffer => write!(f, "no draw was offered"),
+            AuditErr::DrawOffered => write!(f, "a draw offer is waiting for an answer"),
+            AuditErr::ClaimRejected(rule) => write!(f, "{:?} doesn't apply", rule),
+        }
+    }
+}
+
+impl Error for AuditErr {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AuditErr::Fen(err) => Some(err),
+            AuditErr::Move(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+// Append-only record of what happened in a game, each entry hashed with the one before it so
+// changing any of them breaks every hash after it. Publishing the last hash commits to the
+// whole record.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuditLog {
+    start_fen: String,
+    entries: Vec<AuditEntry>,
+}
+
+impl AuditLog {
+    // Log of a game starting from the position of `gm`
+    pub fn new(gm: &GameManager) -> Self {
+        Self {
+            start_fen: gm.to_fen(),
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn start_fen(&self) -> &str {
+        &self.start_fen
+    }
+
+    pub fn entries(&self) -> &[AuditEntry] {
+        &self.entries
+    }
+
+    // Hash of the last entry, or of the header when nothing was logged yet
+    pub fn hash(&self) -> Hash {
+        self.entries
+            .last()
+            .map_or_else(|| header_hash(&self.start_fen), |entry| entry.hash)
+    }
+
+    // Plays `action` on `gm`, the game this log follows, and logs it when it succeeds
+    pub fn apply(
+        &mut self,
+        gm: &mut GameManager,
+        action: Action,
+        at: SystemTime,
+    ) -> Result<(), AuditErr> {
+        let timestamp = at
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_millis() as u64);
+        self.perform(gm, action)?;
+        let hash = entry_hash(&self.hash(), timestamp, &action);
+        self.entries.push(AuditEntry {
+            action,
+            timestamp,
+            hash,
+        });
+        Ok(())
+    }
+
+    fn perform(&mut self, gm: &mut GameManager, action: Action) -> Result<(), AuditErr> {
+        if gm.status().is_over() {
+            return Err(AuditErr::GameOver);
+        }
+        match action {
+            Action::Move(mv) => {
+                gm.play(&mv).map_err(AuditErr::Move)?;
+            }
+            Action::OfferDraw(player) => {
+                if !gm.offer_draw(&player) {
+                    return Err(AuditErr::DrawOffered);
+                }
+            }
+            Action::AcceptDraw(player) | Action::DeclineDraw(player) => {
+                if gm.draw_offer() != Some(player.opponent()) {
+                    return Err(AuditErr::NoDrawOffer);
+                }
+                gm.decline_draw();
+                if let Action::AcceptDraw(_) = action {
+                    gm.agree_draw();
+                }
+            }
+            Action::ClaimDraw(_, rule) => {
+                if !gm.claim_draw(rule) {
+                    return Err(AuditErr::ClaimRejected(rule));
+                }
+            }
+            Action::Resign(player) => {
+                gm.resign(&player);
+            }
+        }
+        Ok(())
+    }
+
+    // Game the log describes, replayed from its starting position
+    pub fn replay(&self) -> Result<GameManager, AuditErr> {
+        let mut gm = GameManager::from_fen(&self.start_fen).map_err(AuditErr::Fen)?;
+        let mut log = Self::new(&gm);
+        for entry in &self.entries {
+            log.perform(&mut gm, entry.action)?;
+        }
+        Ok(gm)
+    }
+
+    // Canonical encoding: the header with the starting FEN, then every entry with its hash
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = header(&self.start_fen);
+        for entry in &self.entries {
+            bytes.extend(entry.timestamp.to_be_bytes());
+            encode_action(&entry.action, &mut bytes);
+            bytes.extend(entry.hash);
+        }
+        bytes
+    }
+
+    // Reads a log written by `to_bytes`, checking the hashes and replaying every action
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, AuditErr> {
+        let mut reader = Reader(bytes);
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(AuditErr::InvalidHeader);
+        }
+        let len = u16::from_be_bytes(reader.array()?) as usize;
+        let fen = std::str::from_utf8(reader.take(len)?).map_err(|_| AuditErr::InvalidHeader)?;
+        let mut gm = GameManager::from_fen(fen).map_err(AuditErr::Fen)?;
+        let mut log = Self::new(&gm);
+        // The FEN written back has to hash the same
+        if log.start_fen != fen {
+            return Err(AuditErr::InvalidHeader);
+        }
+        while !reader.0.is_empty() {
+            let index = log.entries.len();
+            let timestamp = u64::from_be_bytes(reader.array()?);
+            let action = decode_action(&mut reader)?.ok_or(AuditErr::InvalidAction(index))?;
+            let hash: Hash = reader.array()?;
+            if entry_hash(&log.hash(), timestamp, &action) != hash {
+                return Err(AuditErr::HashMismatch(index));
+            }
+            log.perform(&mut gm, action)?;
+            log.entries.push(AuditEntry {
+                action,
+                timestamp,
+                hash,
+            });
+        }
+        Ok(log)
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], AuditErr> {
+        if self.0.len() < len {
+            return Err(AuditErr::Truncated);
+        }
+        let (taken, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], AuditErr> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+}
+
+fn header(fen: &str) -> Vec<u8> {
+    let mut bytes = MAGIC.to_vec();
+    bytes.extend((fen.len() as u16).to_be_bytes());
+    bytes.extend(fen.as_bytes());
+    bytes
+}
+
+fn header_hash(fen: &str) -> Hash {
+    Sha256::digest(header(fen)).into()
+}
+
+fn entry_hash(previous: &Hash, timestamp: u64, action: &Action) -> Hash {
+    let mut bytes = previous.to_vec();
+    bytes.extend(timestamp.to_be_bytes());
+    encode_action(action, &mut bytes);
+    Sha256::digest(bytes).into()
+}
+
+fn encode_action(action: &Action, bytes: &mut Vec<u8>) {
+    let player = |player: &Player| player.index() as u8;
+    match action {
+        Action::Move(mv) => {
+            let promotion = mv.promotion.map_or(0, |kind| kind.to_char() as u8);
+            bytes.extend([0, mv.from.row, mv.from.column, mv.to.row, mv.to.column]);
+            bytes.push(promotion);
+        }
+        Action::OfferDraw(p) => bytes.extend([1, player(p)]),
+        Action::AcceptDraw(p) => bytes.extend([2, player(p)]),
+        Action::DeclineDraw(p) => bytes.extend([3, player(p)]),
+        Action::ClaimDraw(p, rule) => {
+            let rule = match rule {
+                DrawRule::FiftyMoves => 0,
+                DrawRule::ThreefoldRepetition => 1,
+                DrawRule::InsufficientMaterial => 2,
+            };
+            bytes.extend([4, player(p), rule]);
+        }
+        Action::Resign(p) => bytes.extend([5, player(p)]),
+    }
+}
+
+// None when the bytes don't encode an action
+fn decode_action(reader: &mut Reader) -> Result<Option<Action>, AuditErr> {
+    let [tag] = reader.array()?;
+    if tag == 0 {
+        let [from_row, from_column, to_row, to_column, promotion] = reader.array()?;
+        let promotion = match promotion {
+            0 => None,
+            c => match Kind::from_char(c as char) {
+                Some(kind) if kind.to_char() as u8 == c => Some(kind),
+                _ => return Ok(None),
+            },
+        };
+        return Ok(Some(Action::Move(Move {
+            from: Position::new(from_row, from_column),
+            to: Position::new(to_row, to_column),
+            promotion,
+        })));
+    }
+    let player = match reader.array()? {
+        [0] => Player::White,
+        [1] => Player::Black,
+        _ => return Ok(None),
+    };
+    let action = match tag {
+        1 => Action::OfferDraw(player),
+        2 => Action::AcceptDraw(player),
+        3 => Action::DeclineDraw(player),
+        4 => {
+            let rule = match reader.array()? {
+                [0] => DrawRule::FiftyMoves,
+                [1] => DrawRule::ThreefoldRepetition,
+                [2] => DrawRule::InsufficientMaterial,
+                _ => return Ok(None),
+            };
+            Action::ClaimDraw(player, rule)
+        }
+        5 => Action::Resign(player),
+        _ => return Ok(None),
+    };
+    Ok(Some(action))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::{Action, AuditErr, AuditLog};
+    use crate::{
+        game_manager::{GameManager, GameStatus, MoveErr},
+        moves::Move,
+        piece::Player,
+    };
+
+    #[test]
+    fn test_audit_log() {
+        let mut gm = GameManager::new();
+        let mut log = AuditLog::new(&gm);
+        let start = log.hash();
+        let at = |seconds: u64| UNIX_EPOCH + Duration::from_secs(1_700_000_000 + seconds);
+        for (i, uci) in ["e2e4", "e7e5", "g1f3"].iter().enumerate() {
+            let mv = Move::from_uci(uci).unwrap();
+            log.apply(&mut gm, Action::Move(mv), at(i as u64)).unwrap();
+        }
+        assert_ne!(start, log.hash());
+        assert_eq!(1_700_000_002_000, log.entries()[2].timestamp);
+
+        // Refused actions aren't logged
+        let illegal = Move::from_uci("e8e6").unwrap();
+        assert!(matches!(
+            log.apply(&mut gm, Action::Move(illegal), at(3)),
+            Err(AuditErr::Move(MoveErr::InvalidMove { .. }))
+        ));
+        assert!(matches!(
+            log.apply(&mut gm, Action::AcceptDraw(Player::White), at(3)),
+            Err(AuditErr::NoDrawOffer)
+        ));
+        log.apply(&mut gm, Action::OfferDraw(Player::Black), at(4))
+            .unwrap();
+        assert_eq!(Some(Player::Black), gm.draw_offer());
+        assert!(matches!(
+            log.apply(&mut gm, Action::OfferDraw(Player::White), at(5)),
+            Err(AuditErr::DrawOffered)
+        ));
+        log.apply(&mut gm, Action::DeclineDraw(Player::White), at(5))
+            .unwrap();
+        log.apply(&mut gm, Action::Resign(Player::Black), at(6))
+            .unwrap();
+        assert!(matches!(
+            log.apply(&mut gm, Action::Resign(Player::White), at(7)),
+            Err(AuditErr::GameOver)
+        ));
+        assert_eq!(6, log.entries().len());
+
+        // The bytes give back the same log and game
+        let bytes = log.to_bytes();
+        let read = AuditLog::from_bytes(&bytes).unwrap();
+        assert_eq!(log, read);
+        assert_eq!(bytes, read.to_bytes());
+        let replayed = read.replay().unwrap();
+        assert_eq!(gm, replayed);
+        assert_eq!(GameStatus::Resignation(Player::White), replayed.status());
+
+        // Any change is caught, here the timestamp of the second move
+        let mut altered = bytes.clone();
+        let second = 8 + 2 + log.start_fen().len() + (8 + 6 + 32) + 7;
+        altered[second] ^= 1;
+        assert!(matches!(
+            AuditLog::from_bytes(&altered),
+            Err(AuditErr::HashMismatch(1))
+        ));
+        assert!(matches!(
+            AuditLog::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(AuditErr::Truncated)
+        ));
+    }
+}
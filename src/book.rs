@@ -0,0 +1,243 @@
+use std::{fs, io, path::Path};
+
+use crate::{
+    game_manager::{uniform_below, GameManager},
+    moves::Move,
+    piece::{Kind, Position},
+};
+
+const ENTRY_SIZE: usize = 16;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BookEntry {
+    pub mv: Move,
+    pub weight: u16,
+    pub learn: u32,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct RawEntry {
+    key: u64,
+    mv: u16,
+    weight: u16,
+    learn: u32,
+}
+
+// Opening book in the Polyglot `.bin` format: 16 bytes big endian entries sorted by the
+// Zobrist key of the position.
+#[derive(Clone, Debug, Default)]
+pub struct PolyglotBook {
+    entries: Vec<RawEntry>,
+}
+
+impl PolyglotBook {
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if !bytes.len().is_multiple_of(ENTRY_SIZE) {
+            return None;
+        }
+        let mut entries: Vec<RawEntry> = bytes
+            .chunks_exact(ENTRY_SIZE)
+            .map(|chunk| RawEntry {
+                key: u64::from_be_bytes(chunk[0..8].try_into().expect("8 bytes")),
+                mv: u16::from_be_bytes(chunk[8..10].try_into().expect("2 bytes")),
+                weight: u16::from_be_bytes(chunk[10..12].try_into().expect("2 bytes")),
+                learn: u32::from_be_bytes(chunk[12..16].try_into().expect("4 bytes")),
+            })
+            .collect();
+        // Books are supposed to be sorted, don't rely on it for the binary search
+        entries.sort_by_key(|e| e.key);
+        Some(Self { entries })
+    }
+
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let bytes = fs::read(path)?;
+        Self::from_bytes(&bytes).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, "truncated polyglot book entry")
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // Legal book moves of the current position, heaviest first
+    pub fn probe(&self, gm: &GameManager) -> Vec<BookEntry> {
+        let key = gm.zobrist_hash();
+        let start = self.entries.partition_point(|e| e.key < key);
+        let legal = gm.legal_moves();
+        let mut moves: Vec<BookEntry> = self.entries[start..]
+            .iter()
+            .take_while(|e| e.key == key)
+            .filter_map(|e| {
+                let mv = decode_move(gm, e.mv);
+                legal.contains(&mv).then_some(BookEntry {
+                    mv,
+                    weight: e.weight,
+                    learn: e.learn,
+                })
+            })
+            .collect();
+        moves.sort_by_key(|e| std::cmp::Reverse(e.weight));
+        moves
+    }
+
+    // Picks a book move with a probability proportional to its weight, `rng` has to return
+    // uniformly distributed numbers.
+    pub fn weighted_random_book_move(
+        &self,
+        gm: &GameManager,
+        rng: &mut impl FnMut() -> u64,
+    ) -> Option<Move> {
+        let moves = self.probe(gm);
+        let total: u64 = moves.iter().map(|e| u64::from(e.weight)).sum();
+        if total == 0 {
+            return moves.first().map(|e| e.mv);
+        }
+        let mut pick = uniform_below(rng, total);
+        for entry in &moves {
+            let weight = u64::from(entry.weight);
+            if pick < weight {
+                return Some(entry.mv);
+            }
+            pick -= weight;
+        }
+        None
+    }
+}
+
+fn decode_move(gm: &GameManager, mv: u16) -> Move {
+    let square = |bits: u16| Position::new(((bits >> 3) & 7) as u8, (bits & 7) as u8);
+    let from = square(mv >> 6);
+    let mut to = square(mv);
+    let promotion = match (mv >> 12) & 7 {
+        1 => Some(Kind::Knight),
+        2 => Some(Kind::Bishop),
+        3 => Some(Kind::Rook),
+        4 => Some(Kind::Queen),
+        _ => None,
+    };
+    // Castling is stored as the king taking its own rook, which Chess960 moves already are
+    if gm.is_chess960() {
+        return Move {
+            from,
+            to,
+            promotion,
+        };
+    }
+    let king = gm
+        .pieces_of(gm.turn)
+        .iter()
+        .any(|p| p.kind == Kind::King && Position::from_piece(p) == from);
+    let rook = gm
+        .pieces_of(gm.turn)
+        .iter()
+        .any(|p| p.kind == Kind::Rook && Position::from_piece(p) == to);
+    if king && rook && from.row == to.row {
+        to.column = if to.column > from.column { 6 } else { 2 };
+    }
+    Move {
+        from,
+        to,
+        promotion,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PolyglotBook;
+    use crate::{
+        game_manager::{CastlingRights, GameManager},
+        moves::Move,
+        piece::{Kind, Piece, Player},
+        square::Square,
+    };
+
+    fn entry(key: u64, mv: u16, weight: u16) -> Vec<u8> {
+        let mut bytes = key.to_be_bytes().to_vec();
+        bytes.extend(mv.to_be_bytes());
+        bytes.extend(weight.to_be_bytes());
+        bytes.extend(0u32.to_be_bytes());
+        bytes
+    }
+
+    // Polyglot move bits: to file, to row, from file, from row, promotion
+    fn encode(from: (u16, u16), to: (u16, u16)) -> u16 {
+        to.1 | (to.0 << 3) | (from.1 << 6) | (from.0 << 9)
+    }
+
+    #[test]
+    fn test_probe_start_position() {
+        let start = GameManager::new().zobrist_hash();
+        let mut bytes = entry(start, encode((1, 3), (3, 3)), 5);
+        bytes.extend(entry(start, encode((1, 4), (3, 4)), 10));
+        bytes.extend(entry(start + 1, encode((1, 0), (2, 0)), 100));
+        // Not legal, skipped
+        bytes.extend(entry(start, encode((1, 4), (4, 4)), 100));
+        let book = PolyglotBook::from_bytes(&bytes).unwrap();
+        assert_eq!(4, book.len());
+        let moves = book.probe(&GameManager::new());
+        assert_eq!(2, moves.len());
+        assert_eq!(Move::new(Square::E2, Square::E4), moves[0].mv);
+        assert_eq!(10, moves[0].weight);
+        assert_eq!(Move::new(Square::D2, Square::D4), moves[1].mv);
+
+        let gm = GameManager::new();
+        let mut value = 0;
+        let mut rng = || {
+            value += 7;
+            value
+        };
+        // e4 covers picks 0 to 9 out of 15: 7 and 21 % 15 fall on it, 14 on d4
+        for expected in [moves[0].mv, moves[1].mv, moves[0].mv] {
+            assert_eq!(
+                Some(expected),
+                book.weighted_random_book_move(&gm, &mut rng)
+            );
+        }
+        assert!(PolyglotBook::from_bytes(&bytes[1..]).is_none());
+    }
+
+    #[test]
+    fn test_probe_castling_and_promotion() {
+        let gm = GameManager {
+            whites: [
+                Piece::new(Player::White, Kind::King, 0, 4),
+                Piece::new(Player::White, Kind::Rook, 0, 7),
+                Piece::new(Player::White, Kind::Pawn, 6, 0),
+            ]
+            .into(),
+            blacks: [Piece::new(Player::Black, Kind::King, 7, 4)].into(),
+            castling: CastlingRights {
+                white_king_side: true,
+                ..CastlingRights::none()
+            },
+            ..GameManager::empty()
+        };
+        let key = gm.zobrist_hash();
+        let mut bytes = entry(key, encode((0, 4), (0, 7)), 1);
+        bytes.extend(entry(key, encode((6, 0), (7, 0)) | (1 << 12), 2));
+        let moves = PolyglotBook::from_bytes(&bytes).unwrap().probe(&gm);
+        assert_eq!(
+            Move::with_promotion(Square::A7, Square::A8, Kind::Knight),
+            moves[0].mv
+        );
+        assert_eq!(Move::new(Square::E1, Square::G1), moves[1].mv);
+    }
+
+    #[test]
+    fn test_probe_chess960_castling() {
+        let gm = GameManager::from_fen("1r2k1r1/pppppppp/8/8/8/8/PPPPPPPP/1R2K1R1 w GBgb - 0 1")
+            .unwrap();
+        let key = gm.zobrist_hash();
+        let bytes = entry(key, encode((0, 4), (0, 1)), 1);
+        let moves = PolyglotBook::from_bytes(&bytes).unwrap().probe(&gm);
+        assert_eq!(
+            vec![Move::new(Square::E1, Square::B1)],
+            moves.iter().map(|e| e.mv).collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,359 @@
+use std::{error::Error, fmt};
+
+use crate::{
+    chess960::CastlingFiles,
+    game_manager::{CastlingRights, GameManager},
+    piece::{Kind, Piece, Player, Position, MAX_PIECES},
+    square::Square,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BuildErr {
+    InvalidSquare,
+    SquareTaken(Position),
+    TooManyPieces(Player),
+    // Holds the number of kings found
+    KingCount(Player, usize),
+    PawnOnBackRank(Position),
+    InvalidCastling,
+    InvalidEnPassant,
+    // The side that just moved can't have its king in check
+    OpponentInCheck,
+}
+
+impl fmt::Display for BuildErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildErr::InvalidSquare => write!(f, "square outside of the board"),
+            BuildErr::SquareTaken(pos) => write!(f, "{} is already taken", pos),
+            BuildErr::TooManyPieces(player) => write!(f, "too many {:?} pieces", player),
+            BuildErr::KingCount(player, count) => {
+                write!(f, "{:?} has {} kings instead of one", player, count)
+            }
+            BuildErr::PawnOnBackRank(pos) => write!(f, "pawn on the back rank at {}", pos),
+            BuildErr::InvalidCastling => write!(f, "castling rights without the king and rook"),
+            BuildErr::InvalidEnPassant => write!(f, "invalid en passant square"),
+            BuildErr::OpponentInCheck => write!(f, "the side that just moved is in check"),
+        }
+    }
+}
+
+impl Error for BuildErr {}
+
+// Assembles an arbitrary position, `build` checks it could happen in a game
+#[derive(Clone, Debug)]
+pub struct GameManagerBuilder {
+    pieces: Vec<Piece>,
+    turn: Player,
+    castling: CastlingRights,
+    en_passant: Option<Position>,
+    halfmove_clock: u32,
+    fullmove_number: u32,
+    // First error met while adding pieces, reported by `build`
+    err: Option<BuildErr>,
+}
+
+impl GameManagerBuilder {
+    pub fn new() -> Self {
+        Self {
+            pieces: vec![],
+            turn: Player::White,
+            castling: CastlingRights::none(),
+            en_passant: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            err: None,
+        }
+    }
+
+    pub fn piece(mut self, player: Player, kind: Kind, pos: impl TryInto<Position>) -> Self {
+        let pos = match pos.try_into() {
+            Ok(pos) if Square::try_from(pos).is_ok() => pos,
+            _ => {
+                self.err.get_or_insert(BuildErr::InvalidSquare);
+                return self;
+            }
+        };
+        if self.pieces.iter().any(|p| Position::from_piece(p) == pos) {
+            self.err.get_or_insert(BuildErr::SquareTaken(pos));
+            return self;
+        }
+        self.pieces
+            .push(Piece::new(player, kind, pos.row, pos.column));
+        self
+    }
+
+    pub fn turn(mut self, player: Player) -> Self {
+        self.turn = player;
+        self
+    }
+
+    pub fn castling(mut self, castling: CastlingRights) -> Self {
+        self.castling = castling;
+        self
+    }
+
+    pub fn en_passant(mut self, pos: impl Into<Position>) -> Self {
+        self.en_passant = Some(pos.into());
+        self
+    }
+
+    pub fn halfmove_clock(mut self, halfmove_clock: u32) -> Self {
+        self.halfmove_clock = halfmove_clock;
+        self
+    }
+
+    pub fn fullmove_number(mut self, fullmove_number: u32) -> Self {
+        self.fullmove_number = fullmove_number;
+        self
+    }
+
+    pub fn build(self) -> Result<GameManager, BuildErr> {
+        if let Some(err) = self.err {
+            return Err(err);
+        }
+        for player in [Player::White, Player::Black] {
+            if self.pieces.iter().filter(|p| p.player == player).count() > MAX_PIECES {
+                return Err(BuildErr::TooManyPieces(player));
+            }
+        }
+        let gm = GameManager {
+            whites: self
+                .pieces
+                .iter()
+                .filter(|p| p.player == Player::White)
+                .copied()
+                .collect(),
+            blacks: self
+                .pieces
+                .iter()
+                .filter(|p| p.player == Player::Black)
+                .copied()
+                .collect(),
+            turn: self.turn,
+            castling: self.castling,
+            en_passant: self.en_passant,
+            halfmove_clock: self.halfmove_clock,
+            fullmove_number: self.fullmove_number,
+            ..GameManager::empty()
+        };
+        validate_position(&gm)?;
+        Ok(gm)
+    }
+}
+
+// Checks the position of `gm` could happen in a game
+pub(crate) fn validate_position(gm: &GameManager) -> Result<(), BuildErr> {
+    match position_problems(gm).into_iter().next() {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
+}
+
+// Every reason the position of `gm` can't happen in a game, in the order they are checked
+pub(crate) fn position_problems(gm: &GameManager) -> Vec<BuildErr> {
+    let mut problems = vec![];
+    let pieces = || gm.pieces().map(|(_, p)| p);
+    for player in [Player::White, Player::Black] {
+        let kings = gm
+            .pieces_of(player)
+            .iter()
+            .filter(|p| p.kind == Kind::King)
+            .count();
+        if kings != 1 {
+            problems.push(BuildErr::KingCount(player, kings));
+        }
+    }
+    // Checks only make sense with a king on each side
+    let kings = problems.is_empty();
+    problems.extend(
+        pieces()
+            .filter(|p| {
+                p.kind == Kind::Pawn
+                    && [Player::White, Player::Black]
+                        .iter()
+                        .any(|player| p.row == gm.size.promotion_row(*player))
+            })
+            .map(|pawn| BuildErr::PawnOnBackRank(Position::from_piece(pawn))),
+    );
+    let has = |player: Player, kind: Kind, row: u8, column: u8| {
+        gm.pieces_of(player)
+            .iter()
+            .any(|p| p.kind == kind && p.row == row && p.column == column)
+    };
+    let files = gm.castling_files();
+    for (right, player, rook_column) in castling_rights(&gm.castling, &files) {
+        let row = gm.size.back_row(player);
+        if right
+            && !(has(player, Kind::King, row, files.king)
+                && has(player, Kind::Rook, row, rook_column))
+        {
+            problems.push(BuildErr::InvalidCastling);
+            break;
+        }
+    }
+    if let Some(ep) = gm.en_passant {
+        // The pawn that just moved two squares stands in front of the skipped square
+        let last = gm.size.back_row(Player::Black);
+        let (ep_row, pawn_row) = match gm.turn {
+            Player::White => (last - 2, last - 3),
+            Player::Black => (2, 3),
+        };
+        let empty = |row: u8| !pieces().any(|p| p.row == row && p.column == ep.column);
+        let origin_row = 2 * ep_row - pawn_row;
+        if ep.row != ep_row
+            || !has(gm.turn.opponent(), Kind::Pawn, pawn_row, ep.column)
+            || !empty(ep_row)
+            || !empty(origin_row)
+        {
+            problems.push(BuildErr::InvalidEnPassant);
+        }
+    }
+    if kings && gm.is_in_check(&gm.turn.opponent()) {
+        problems.push(BuildErr::OpponentInCheck);
+    }
+    problems
+}
+
+// Every castling right with the side and rook column it needs
+fn castling_rights(castling: &CastlingRights, files: &CastlingFiles) -> [(bool, Player, u8); 4] {
+    let (king_side, queen_side) = (files.king_side_rook, files.queen_side_rook);
+    [
+        (castling.white_king_side, Player::White, king_side),
+        (castling.white_queen_side, Player::White, queen_side),
+        (castling.black_king_side, Player::Black, king_side),
+        (castling.black_queen_side, Player::Black, queen_side),
+    ]
+}
+
+impl Default for GameManagerBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GameManager {
+    pub fn builder() -> GameManagerBuilder {
+        GameManagerBuilder::new()
+    }
+
+    // Position with the given pieces, e.g. from a generator, checked as by `build`
+    pub fn from_pieces(
+        pieces: impl IntoIterator<Item = (Position, Player, Kind)>,
+        turn: Player,
+        castling: CastlingRights,
+    ) -> Result<GameManager, BuildErr> {
+        pieces
+            .into_iter()
+            .fold(
+                GameManager::builder().turn(turn).castling(castling),
+                |builder, (pos, player, kind)| builder.piece(player, kind, pos),
+            )
+            .build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BuildErr;
+    use crate::{
+        game_manager::{CastlingRights, GameManager},
+        piece::{Kind, Player, Position},
+        square::Square,
+    };
+
+    #[test]
+    fn test_from_pieces() {
+        let pieces = [
+            (Square::E1.into(), Player::White, Kind::King),
+            (Square::H1.into(), Player::White, Kind::Rook),
+            (Square::E8.into(), Player::Black, Kind::King),
+        ];
+        let castling = CastlingRights {
+            white_king_side: true,
+            ..CastlingRights::none()
+        };
+        let gm = GameManager::from_pieces(pieces, Player::Black, castling).unwrap();
+        assert_eq!("4k3/8/8/8/8/8/8/4K2R b K - 0 1", gm.to_fen());
+        let err = GameManager::from_pieces(pieces, Player::White, CastlingRights::all());
+        assert_eq!(Some(BuildErr::InvalidCastling), err.err());
+        let mut taken = pieces.to_vec();
+        taken.push((Square::H1.into(), Player::Black, Kind::Queen));
+        assert_eq!(
+            Some(BuildErr::SquareTaken(Square::H1.into())),
+            GameManager::from_pieces(taken, Player::White, castling).err()
+        );
+        let off_board = [(Position::new(8, 0), Player::White, Kind::Pawn)];
+        assert_eq!(
+            Some(BuildErr::InvalidSquare),
+            GameManager::from_pieces(off_board, Player::White, castling).err()
+        );
+    }
+
+    #[test]
+    fn test_build_position() {
+        let gm = GameManager::builder()
+            .piece(Player::White, Kind::King, "e1")
+            .piece(Player::White, Kind::Rook, Square::H1)
+            .piece(Player::Black, Kind::King, "e8")
+            .piece(Player::Black, Kind::Pawn, "d5")
+            .piece(Player::White, Kind::Pawn, "e5")
+            .turn(Player::White)
+            .castling(CastlingRights {
+                white_king_side: true,
+                ..CastlingRights::none()
+            })
+            .en_passant(Square::D6)
+            .fullmove_number(20)
+            .build()
+            .unwrap();
+        assert_eq!("4k3/8/8/3pP3/8/8/8/4K2R w K d6 0 20", gm.to_fen());
+    }
+
+    #[test]
+    fn test_build_errors() {
+        let kings = || {
+            GameManager::builder()
+                .piece(Player::White, Kind::King, "e1")
+                .piece(Player::Black, Kind::King, "e8")
+        };
+        let cases = [
+            (
+                kings().piece(Player::White, Kind::Rook, "i1").build(),
+                BuildErr::InvalidSquare,
+            ),
+            (
+                kings().piece(Player::White, Kind::Rook, "e1").build(),
+                BuildErr::SquareTaken(Position::new(0, 4)),
+            ),
+            (
+                GameManager::builder()
+                    .piece(Player::White, Kind::King, "e1")
+                    .build(),
+                BuildErr::KingCount(Player::Black, 0),
+            ),
+            (
+                kings().piece(Player::Black, Kind::Pawn, "a1").build(),
+                BuildErr::PawnOnBackRank(Position::new(0, 0)),
+            ),
+            (
+                kings().castling(CastlingRights::all()).build(),
+                BuildErr::InvalidCastling,
+            ),
+            (
+                kings().en_passant(Square::D6).build(),
+                BuildErr::InvalidEnPassant,
+            ),
+            (
+                kings()
+                    .piece(Player::White, Kind::Rook, "e4")
+                    .turn(Player::White)
+                    .build(),
+                BuildErr::OpponentInCheck,
+            ),
+        ];
+        for (result, err) in cases {
+            assert_eq!(Err(err), result.map(|gm| gm.to_fen()));
+        }
+    }
+}
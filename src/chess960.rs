@@ -0,0 +1,277 @@
+use crate::{
+    game_manager::{CastlingRights, GameManager},
+    piece::{BoardSize, Kind, Piece, PieceList, Player, Position, MAX_COLUMN, MAX_ROW},
+};
+
+// Files the king and the castling rooks start on, the same for both sides
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CastlingFiles {
+    pub king: u8,
+    pub king_side_rook: u8,
+    pub queen_side_rook: u8,
+}
+
+impl CastlingFiles {
+    pub const STANDARD: Self = Self {
+        king: 4,
+        king_side_rook: MAX_COLUMN - 1,
+        queen_side_rook: 0,
+    };
+}
+
+// Number of Fischer Random starting positions
+pub const CHESS960_POSITIONS: u16 = 960;
+
+// Knights placed on the five files left once the bishops and the queen are, by index
+const KNIGHTS: [(usize, usize); 10] = [
+    (0, 1),
+    (0, 2),
+    (0, 3),
+    (0, 4),
+    (1, 2),
+    (1, 3),
+    (1, 4),
+    (2, 3),
+    (2, 4),
+    (3, 4),
+];
+
+// Back rank of the Fischer Random starting position `index`, following Scharnagl's
+// numbering where 518 is the standard position
+pub fn chess960_back_rank(index: u16) -> Option<[Kind; MAX_COLUMN as usize]> {
+    if index >= CHESS960_POSITIONS {
+        return None;
+    }
+    let mut rank = [None; MAX_COLUMN as usize];
+    let mut n = index as usize;
+    // Light squared bishop on b, d, f or h, the dark squared one on a, c, e or g
+    rank[(n % 4) * 2 + 1] = Some(Kind::Bishop);
+    n /= 4;
+    rank[(n % 4) * 2] = Some(Kind::Bishop);
+    n /= 4;
+    let mut place = |nth: usize, kind: Kind| {
+        let column = (0..rank.len())
+            .filter(|c| rank[*c].is_none())
+            .nth(nth)
+            .expect("enough empty files");
+        rank[column] = Some(kind);
+    };
+    place(n % 6, Kind::Queen);
+    let (first, second) = KNIGHTS[n / 6];
+    // The second knight is counted once the first one is placed
+    place(first, Kind::Knight);
+    place(second - 1, Kind::Knight);
+    for kind in [Kind::Rook, Kind::King, Kind::Rook] {
+        place(0, kind);
+    }
+    Some(rank.map(|kind| kind.expect("every file filled")))
+}
+
+// Scharnagl number of a Fischer Random back rank, the inverse of `chess960_back_rank`
+pub fn chess960_number(rank: &[Kind; MAX_COLUMN as usize]) -> Option<u16> {
+    (0..CHESS960_POSITIONS).find(|index| chess960_back_rank(*index).as_ref() == Some(rank))
+}
+
+// Starting position number `n`, from 0 to 959, 518 being the standard one
+pub fn position_from_number(n: u16) -> Option<GameManager> {
+    GameManager::chess960(n)
+}
+
+// Number of the Fischer Random starting position `gm` stands on, whether it is played as
+// Chess960 or, for number 518, as standard chess. `None` for any other position.
+pub fn number_from_position(gm: &GameManager) -> Option<u16> {
+    if gm.board_size() != BoardSize::STANDARD {
+        return None;
+    }
+    let mut rank = [Kind::Pawn; MAX_COLUMN as usize];
+    for (column, kind) in rank.iter_mut().enumerate() {
+        let pos = Position::new(0, column as u8);
+        *kind = match gm.piece_at(&pos) {
+            Some((Player::White, piece)) => piece.kind,
+            _ => return None,
+        };
+    }
+    let n = chess960_number(&rank)?;
+    let start = GameManager::chess960(n)?;
+    (start.board() == gm.board() && start.turn == gm.turn).then_some(n)
+}
+
+impl GameManager {
+    // Fischer Random starting position `index`, from 0 to 959. Castling is then written as
+    // the king taking its own rook, e.g. "e1h1" for a king on e1 castling king side.
+    pub fn chess960(index: u16) -> Option<Self> {
+        let rank = chess960_back_rank(index)?;
+        let mut gm = GameManager::empty();
+        for (player, back, pawns) in [(Player::White, 0, 1), (Player::Black, MAX_ROW - 1, 6)] {
+            let mut pieces = PieceList::new();
+            for (column, kind) in rank.iter().enumerate() {
+                pieces.push(Piece::new(player, *kind, back, column as u8));
+            }
+            for column in 0..MAX_COLUMN {
+                pieces.push(Piece::new(player, Kind::Pawn, pawns, column));
+            }
+            match player {
+                Player::White => gm.whites = pieces,
+                Player::Black => gm.blacks = pieces,
+            }
+        }
+        let column = |kind: Kind, nth: usize| {
+            rank.iter()
+                .enumerate()
+                .filter(|(_, k)| **k == kind)
+                .nth(nth)
+                .map(|(c, _)| c as u8)
+                .expect("two rooks and a king")
+        };
+        gm.castling = CastlingRights::all();
+        gm.chess960 = Some(CastlingFiles {
+            king: column(Kind::King, 0),
+            king_side_rook: column(Kind::Rook, 1),
+            queen_side_rook: column(Kind::Rook, 0),
+        });
+        Some(gm)
+    }
+
+    pub fn is_chess960(&self) -> bool {
+        self.chess960.is_some()
+    }
+
+    pub fn castling_files(&self) -> CastlingFiles {
+        self.chess960.unwrap_or(CastlingFiles::STANDARD)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        chess960_back_rank, chess960_number, number_from_position, position_from_number,
+        CastlingFiles,
+    };
+    use crate::{
+        fen::START_FEN,
+        game_manager::GameManager,
+        moves::Move,
+        piece::{
+            Kind::{Bishop, King, Knight, Queen, Rook},
+            Position,
+        },
+        square::Square,
+    };
+
+    #[test]
+    fn test_chess960_positions() {
+        assert_eq!(
+            Some([Rook, Knight, Bishop, Queen, King, Bishop, Knight, Rook]),
+            chess960_back_rank(518)
+        );
+        assert_eq!(
+            Some([Bishop, Bishop, Queen, Knight, Knight, Rook, King, Rook]),
+            chess960_back_rank(0)
+        );
+        assert_eq!(
+            Some([Rook, King, Rook, Knight, Knight, Queen, Bishop, Bishop]),
+            chess960_back_rank(959)
+        );
+        assert_eq!(None, chess960_back_rank(960));
+        assert_eq!(START_FEN, GameManager::chess960(518).unwrap().to_fen());
+        let gm = GameManager::chess960(0).unwrap();
+        assert_eq!(
+            "bbqnnrkr/pppppppp/8/8/8/8/PPPPPPPP/BBQNNRKR w KQkq - 0 1",
+            gm.to_fen()
+        );
+        assert_eq!(
+            CastlingFiles {
+                king: 6,
+                king_side_rook: 7,
+                queen_side_rook: 5
+            },
+            gm.castling_files()
+        );
+        assert_eq!(20, gm.legal_moves().len());
+        assert!(gm
+            .legal_moves()
+            .iter()
+            .all(|mv| *mv != Move::new(Square::G1, Square::H1)));
+    }
+
+    #[test]
+    fn test_chess960_numbers() {
+        for n in [0, 518, 959] {
+            let gm = position_from_number(n).unwrap();
+            assert_eq!(Some(n), number_from_position(&gm));
+            assert_eq!(Some(n), chess960_number(&chess960_back_rank(n).unwrap()));
+        }
+        assert_eq!(None, position_from_number(960));
+        assert_eq!(Some(518), number_from_position(&GameManager::new()));
+        let gm =
+            GameManager::from_fen("nrbqkbrn/pppppppp/8/8/8/8/PPPPPPPP/NRBQKBRN w KQkq -").unwrap();
+        let n = number_from_position(&gm).unwrap();
+        assert_eq!(gm.board(), position_from_number(n).unwrap().board());
+        let mut gm = GameManager::new();
+        gm.play(&Move::new(Square::G1, Square::F3)).unwrap();
+        assert_eq!(None, number_from_position(&gm));
+        // The king isn't between the rooks
+        assert_eq!(
+            None,
+            chess960_number(&[Rook, Rook, Bishop, Queen, King, Bishop, Knight, Knight])
+        );
+    }
+
+    #[test]
+    fn test_chess960_castling() {
+        let fen = "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9";
+        let gm = GameManager::from_fen(fen).unwrap();
+        assert!(gm.is_chess960());
+        for (depth, expected) in [(1, 21), (2, 528), (3, 12189)] {
+            assert_eq!(expected, gm.perft(depth), "perft({})", depth);
+        }
+
+        let mut gm =
+            GameManager::from_fen("1r2k1r1/pppppppp/8/8/8/8/PPPPPPPP/1R2K1R1 w GBgb - 0 1")
+                .unwrap();
+        assert_eq!(
+            "1r2k1r1/pppppppp/8/8/8/8/PPPPPPPP/1R2K1R1 w KQkq - 0 1",
+            gm.to_fen()
+        );
+        let king_side = Move::new(Square::E1, Square::G1);
+        #[cfg(feature = "notation")]
+        {
+            assert_eq!("O-O", gm.san(&king_side).unwrap());
+            assert_eq!(Ok(king_side), gm.parse_san("O-O"));
+        }
+        gm.play(&king_side).unwrap();
+        assert!(gm.history()[0].flags.castling);
+        assert_eq!(
+            "1r2k1r1/pppppppp/8/8/8/8/PPPPPPPP/1R3RK1 b kq - 1 1",
+            gm.to_fen()
+        );
+        let highlights = gm.last_move_highlights().unwrap();
+        assert_eq!(Position::from(Square::G1), highlights.to);
+        assert_eq!(
+            Some((Square::G1.into(), Square::F1.into())),
+            highlights.rook
+        );
+        gm.play(&Move::new(Square::E8, Square::B8)).unwrap();
+        assert_eq!(
+            "2kr2r1/pppppppp/8/8/8/8/PPPPPPPP/1R3RK1 w - - 2 2",
+            gm.to_fen()
+        );
+        gm.undo();
+        gm.undo();
+        assert_eq!(
+            "1r2k1r1/pppppppp/8/8/8/8/PPPPPPPP/1R2K1R1 w KQkq - 0 1",
+            gm.to_fen()
+        );
+
+        // The rook leaving b1 would uncover the king on c1
+        let gm = GameManager::from_fen("4k3/8/8/8/8/8/8/rR1K4 w Q - 0 1").unwrap();
+        assert!(!gm
+            .legal_moves()
+            .contains(&Move::new(Square::D1, Square::B1)));
+        // The king doesn't move, only the rook does
+        let mut gm = GameManager::from_fen("4k3/8/8/8/8/8/8/6KR w H - 0 1").unwrap();
+        gm.play(&Move::new(Square::G1, Square::H1)).unwrap();
+        assert_eq!("4k3/8/8/8/8/8/8/5RK1 b - - 1 1", gm.to_fen());
+    }
+}
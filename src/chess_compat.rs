@@ -0,0 +1,183 @@
+use std::{error::Error, fmt, str::FromStr};
+
+use chess::{Board, ChessMove, File, Piece as ChessPiece, Rank, Square as ChessSquare};
+
+use crate::{
+    fen::FenErr,
+    game_manager::GameManager,
+    moves::Move,
+    piece::{Kind, Position},
+    square::Square,
+};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ChessErr {
+    // Square outside of the 8x8 board
+    OffBoard(Position),
+    // The chess crate only knows standard castling
+    Chess960,
+    // Position the chess crate refuses, with its reason
+    InvalidPosition(String),
+    Fen(FenErr),
+}
+
+impl fmt::Display for ChessErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChessErr::OffBoard(pos) => write!(f, "{} is off the board", pos),
+            ChessErr::Chess960 => write!(f, "Chess960 isn't supported by the chess crate"),
+            ChessErr::InvalidPosition(reason) => write!(f, "invalid position: {}", reason),
+            ChessErr::Fen(err) => err.fmt(f),
+        }
+    }
+}
+
+impl Error for ChessErr {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ChessErr::Fen(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<Position> for ChessSquare {
+    type Error = ChessErr;
+
+    fn try_from(pos: Position) -> Result<Self, Self::Error> {
+        Square::try_from(pos).map_err(|_| ChessErr::OffBoard(pos))?;
+        Ok(ChessSquare::make_square(
+            Rank::from_index(pos.row as usize),
+            File::from_index(pos.column as usize),
+        ))
+    }
+}
+
+impl From<ChessSquare> for Position {
+    fn from(square: ChessSquare) -> Self {
+        Position::new(
+            square.get_rank().to_index() as u8,
+            square.get_file().to_index() as u8,
+        )
+    }
+}
+
+impl From<Kind> for ChessPiece {
+    fn from(kind: Kind) -> Self {
+        match kind {
+            Kind::Pawn => ChessPiece::Pawn,
+            Kind::Knight => ChessPiece::Knight,
+            Kind::Bishop => ChessPiece::Bishop,
+            Kind::Rook => ChessPiece::Rook,
+            Kind::Queen => ChessPiece::Queen,
+            Kind::King => ChessPiece::King,
+        }
+    }
+}
+
+impl From<ChessPiece> for Kind {
+    fn from(piece: ChessPiece) -> Self {
+        match piece {
+            ChessPiece::Pawn => Kind::Pawn,
+            ChessPiece::Knight => Kind::Knight,
+            ChessPiece::Bishop => Kind::Bishop,
+            ChessPiece::Rook => Kind::Rook,
+            ChessPiece::Queen => Kind::Queen,
+            ChessPiece::King => Kind::King,
+        }
+    }
+}
+
+impl TryFrom<Move> for ChessMove {
+    type Error = ChessErr;
+
+    fn try_from(mv: Move) -> Result<Self, Self::Error> {
+        Ok(ChessMove::new(
+            mv.from.try_into()?,
+            mv.to.try_into()?,
+            mv.promotion.map(ChessPiece::from),
+        ))
+    }
+}
+
+impl From<ChessMove> for Move {
+    fn from(mv: ChessMove) -> Self {
+        Move {
+            from: mv.get_source().into(),
+            to: mv.get_dest().into(),
+            promotion: mv.get_promotion().map(Kind::from),
+        }
+    }
+}
+
+impl TryFrom<&GameManager> for Board {
+    type Error = ChessErr;
+
+    fn try_from(gm: &GameManager) -> Result<Self, Self::Error> {
+        if gm.is_chess960() {
+            return Err(ChessErr::Chess960);
+        }
+        Board::from_str(&gm.to_fen()).map_err(|err| ChessErr::InvalidPosition(err.to_string()))
+    }
+}
+
+// The board has no move counters, they start back at 0 and 1
+impl TryFrom<&Board> for GameManager {
+    type Error = ChessErr;
+
+    fn try_from(board: &Board) -> Result<Self, Self::Error> {
+        GameManager::from_fen(&board.to_string()).map_err(ChessErr::Fen)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chess::{Board, ChessMove, MoveGen, Piece as ChessPiece, Square as ChessSquare};
+
+    use super::ChessErr;
+    use crate::{
+        game_manager::GameManager,
+        moves::Move,
+        piece::{Kind, Position},
+        square::Square,
+    };
+
+    #[test]
+    fn test_chess_conversions() {
+        assert_eq!(
+            Ok(ChessSquare::E4),
+            ChessSquare::try_from(Position::from(Square::E4))
+        );
+        assert_eq!(Position::from(Square::H7), ChessSquare::H7.into());
+        assert_eq!(
+            Err(ChessErr::OffBoard(Position::new(0, 8))),
+            ChessSquare::try_from(Position::new(0, 8))
+        );
+        assert_eq!(Kind::Bishop, ChessPiece::from(Kind::Bishop).into());
+
+        let mv = Move::with_promotion(Square::B7, Square::A8, Kind::Knight);
+        let chess_mv = ChessMove::try_from(mv).unwrap();
+        assert_eq!("b7a8n", chess_mv.to_string());
+        assert_eq!(mv, chess_mv.into());
+
+        let fen = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+        let gm = GameManager::from_fen(fen).unwrap();
+        let board = Board::try_from(&gm).unwrap();
+        let moves = MoveGen::new_legal(&board);
+        assert_eq!(gm.legal_moves().len(), moves.len());
+        for mv in moves {
+            assert!(gm.legal_moves().contains(&mv.into()));
+        }
+        let board = board.make_move_new(ChessMove::new(ChessSquare::E1, ChessSquare::G1, None));
+        let mut expected = gm.clone();
+        expected.play(&Move::new(Square::E1, Square::G1)).unwrap();
+        let converted = GameManager::try_from(&board).unwrap();
+        assert_eq!(
+            expected.to_fen().replace("- 1 1", "- 0 1"),
+            converted.to_fen()
+        );
+
+        let gm = GameManager::chess960(0).unwrap();
+        assert_eq!(Err(ChessErr::Chess960), Board::try_from(&gm));
+    }
+}
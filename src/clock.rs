@@ -0,0 +1,356 @@
+use std::time::{Duration, Instant};
+
+use crate::piece::Player;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Bonus {
+    None,
+    // Added after every move
+    Fischer(Duration),
+    // Time spent on a move is only debited past the delay
+    Bronstein(Duration),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Stage {
+    // Moves to play within the stage, `None` for the rest of the game
+    pub moves: Option<u32>,
+    pub time: Duration,
+    pub bonus: Bonus,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimeControl {
+    pub stages: Vec<Stage>,
+}
+
+impl TimeControl {
+    pub fn sudden_death(time: Duration) -> Self {
+        Self::stage(time, Bonus::None)
+    }
+
+    pub fn fischer(time: Duration, increment: Duration) -> Self {
+        Self::stage(time, Bonus::Fischer(increment))
+    }
+
+    pub fn bronstein(time: Duration, delay: Duration) -> Self {
+        Self::stage(time, Bonus::Bronstein(delay))
+    }
+
+    // Stages follow each other, the time of a new stage adding up to what is left
+    pub fn stages(stages: Vec<Stage>) -> Self {
+        Self { stages }
+    }
+
+    fn stage(time: Duration, bonus: Bonus) -> Self {
+        Self {
+            stages: vec![Stage {
+                moves: None,
+                time,
+                bonus,
+            }],
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Side {
+    remaining: Duration,
+    moves: u32,
+    stage: usize,
+    // Moves made since the start of the current stage
+    stage_moves: u32,
+}
+
+// Frozen copy of a clock, without the time its running side spent since then
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClockState {
+    control: TimeControl,
+    sides: [Side; 2],
+    running: Option<Player>,
+}
+
+// Chess clock for both players, only the side to move has its time running
+#[derive(Clone, Debug)]
+pub struct Clock {
+    control: TimeControl,
+    sides: [Side; 2],
+    running: Option<Player>,
+    started_at: Option<Instant>,
+}
+
+impl Clock {
+    pub fn new(control: TimeControl) -> Self {
+        let time = control.stages.first().map_or(Duration::ZERO, |s| s.time);
+        let side = Side {
+            remaining: time,
+            moves: 0,
+            stage: 0,
+            stage_moves: 0,
+        };
+        Self {
+            control,
+            sides: [side; 2],
+            running: None,
+            started_at: None,
+        }
+    }
+
+    // State to restore the clock from, e.g. after an adjournment. The time of the running side
+    // is taken now, it goes on running from when the clock is restored.
+    pub fn state(&self) -> ClockState {
+        let mut sides = self.sides;
+        for player in [Player::White, Player::Black] {
+            sides[player.index()].remaining = self.remaining(&player);
+        }
+        ClockState {
+            control: self.control.clone(),
+            sides,
+            running: self.running,
+        }
+    }
+
+    pub fn from_state(state: ClockState) -> Self {
+        Self {
+            control: state.control,
+            sides: state.sides,
+            running: state.running,
+            started_at: state.running.map(|_| Instant::now()),
+        }
+    }
+
+    pub fn time_control(&self) -> &TimeControl {
+        &self.control
+    }
+
+    pub fn running(&self) -> Option<Player> {
+        self.running
+    }
+
+    pub fn start(&mut self, player: Player) {
+        self.running = Some(player);
+        self.started_at = Some(Instant::now());
+    }
+
+    // Stops the running side, debiting the time it spent so far
+    pub fn stop(&mut self) {
+        if let Some(player) = self.running {
+            self.sides[player.index()].remaining = self.remaining(&player);
+        }
+        self.running = None;
+        self.started_at = None;
+    }
+
+    pub fn moves(&self, player: &Player) -> u32 {
+        self.sides[player.index()].moves
+    }
+
+    // Time left to `player`, including what the running side has spent on its current move
+    pub fn remaining(&self, player: &Player) -> Duration {
+        self.remaining_at(player, Instant::now())
+    }
+
+    pub fn remaining_at(&self, player: &Player, now: Instant) -> Duration {
+        let side = &self.sides[player.index()];
+        match (self.running, self.started_at) {
+            (Some(running), Some(started_at)) if running == *player => {
+                let spent = self.debited(side, now.saturating_duration_since(started_at));
+                side.remaining.saturating_sub(spent)
+            }
+            _ => side.remaining,
+        }
+    }
+
+    // Sets the moves made by `player` and the stage of the time control they lead to, the
+    // time left is kept
+    pub fn set_moves(&mut self, player: &Player, moves: u32) {
+        let stages = &self.control.stages;
+        let side = &mut self.sides[player.index()];
+        side.moves = moves;
+        side.stage = 0;
+        side.stage_moves = moves;
+        while let Some(count) = stages.get(side.stage).and_then(|s| s.moves) {
+            if side.stage_moves < count || side.stage + 1 >= stages.len() {
+                break;
+            }
+            side.stage += 1;
+            side.stage_moves -= count;
+        }
+    }
+
+    // Corrects the time left to `player`, which the running side starts spending from now
+    pub(crate) fn set_remaining(&mut self, player: &Player, remaining: Duration) {
+        self.sides[player.index()].remaining = remaining;
+        if self.running == Some(*player) {
+            self.started_at = Some(Instant::now());
+        }
+    }
+
+    // Player whose time is over at `now`, servers can poll it or schedule it for when the
+    // running side runs out of time.
+    pub fn check_flag(&self, now: Instant) -> Option<Player> {
+        [Player::White, Player::Black]
+            .into_iter()
+            .find(|player| self.remaining_at(player, now).is_zero())
+    }
+
+    fn debited(&self, side: &Side, elapsed: Duration) -> Duration {
+        match self.control.stages.get(side.stage).map(|s| s.bonus) {
+            Some(Bonus::Bronstein(delay)) => elapsed.saturating_sub(delay),
+            _ => elapsed,
+        }
+    }
+
+    // Ends the move of the running side, measuring the time it took
+    pub fn press(&mut self) {
+        let elapsed = self.started_at.map_or(Duration::ZERO, |s| s.elapsed());
+        self.press_after(elapsed);
+    }
+
+    // Ends the move of the running side after it spent `elapsed` on it and starts the clock
+    // of the opponent. Does nothing while the clock is stopped.
+    pub fn press_after(&mut self, elapsed: Duration) {
+        if let Some(player) = self.running {
+            self.end_move(player, elapsed);
+        }
+    }
+
+    // Called by the game once `player` moved. A stopped clock is started for the opponent,
+    // the move that starts it isn't timed.
+    pub(crate) fn press_for(&mut self, player: Player) {
+        if self.running == Some(player) {
+            self.press();
+        } else {
+            self.end_move(player, Duration::ZERO);
+        }
+    }
+
+    fn end_move(&mut self, player: Player, elapsed: Duration) {
+        let spent = self.debited(&self.sides[player.index()], elapsed);
+        let stages = &self.control.stages;
+        let side = &mut self.sides[player.index()];
+        side.remaining = side.remaining.saturating_sub(spent);
+        // No increment once the flag fell
+        if let (false, Some(Bonus::Fischer(increment))) = (
+            side.remaining.is_zero(),
+            stages.get(side.stage).map(|s| s.bonus),
+        ) {
+            side.remaining = side.remaining.saturating_add(increment);
+        }
+        side.moves += 1;
+        side.stage_moves += 1;
+        let stage_over = stages
+            .get(side.stage)
+            .and_then(|s| s.moves)
+            .is_some_and(|moves| side.stage_moves >= moves);
+        if stage_over && side.stage + 1 < stages.len() {
+            side.stage += 1;
+            side.stage_moves = 0;
+            side.remaining = side.remaining.saturating_add(stages[side.stage].time);
+        }
+        self.start(player.opponent());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{Bonus, Clock, Stage, TimeControl};
+    use crate::piece::Player;
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn test_time_controls() {
+        let mut clock = Clock::new(TimeControl::sudden_death(secs(60)));
+        clock.press_after(secs(10));
+        assert_eq!(None, clock.running());
+        clock.start(Player::White);
+        clock.press_after(secs(10));
+        assert_eq!(Some(Player::Black), clock.running());
+        clock.press_after(secs(25));
+        assert_eq!(secs(50), clock.sides[0].remaining);
+        assert_eq!(secs(35), clock.sides[1].remaining);
+        // Stopping debits the real time White has been thinking
+        clock.stop();
+        assert_eq!(None, clock.running());
+        assert!(clock.remaining(&Player::White) <= secs(50));
+        assert!(clock.remaining(&Player::White) > secs(49));
+
+        let mut clock = Clock::new(TimeControl::fischer(secs(60), secs(2)));
+        clock.start(Player::White);
+        clock.press_after(secs(10));
+        assert_eq!(secs(52), clock.sides[0].remaining);
+
+        let mut clock = Clock::new(TimeControl::bronstein(secs(60), secs(5)));
+        clock.start(Player::White);
+        clock.press_after(secs(3));
+        clock.press_after(secs(8));
+        assert_eq!(secs(60), clock.sides[0].remaining);
+        assert_eq!(secs(57), clock.sides[1].remaining);
+    }
+
+    #[test]
+    fn test_check_flag() {
+        let mut clock = Clock::new(TimeControl::fischer(secs(60), secs(2)));
+        assert_eq!(None, clock.check_flag(Instant::now() + secs(100)));
+        clock.start(Player::White);
+        let now = Instant::now();
+        assert_eq!(None, clock.check_flag(now + secs(50)));
+        assert_eq!(Some(Player::White), clock.check_flag(now + secs(61)));
+        clock.press_after(secs(70));
+        assert_eq!(Duration::ZERO, clock.sides[0].remaining);
+        assert_eq!(Some(Player::White), clock.check_flag(Instant::now()));
+    }
+
+    #[test]
+    fn test_multi_stage() {
+        // 2 moves in 90 seconds then 30 seconds for the rest, 30 seconds increment
+        let stage = |moves, time| Stage {
+            moves,
+            time: secs(time),
+            bonus: Bonus::Fischer(secs(30)),
+        };
+        let mut clock = Clock::new(TimeControl::stages(vec![
+            stage(Some(2), 90),
+            stage(None, 30),
+        ]));
+        clock.press_for(Player::White);
+        assert_eq!(Some(Player::Black), clock.running());
+        assert_eq!(secs(120), clock.sides[0].remaining);
+        clock.press_after(secs(40));
+        clock.press_after(secs(100));
+        assert_eq!(2, clock.moves(&Player::White));
+        assert_eq!(secs(50 + 30), clock.sides[0].remaining);
+        assert_eq!(secs(80), clock.sides[1].remaining);
+        clock.press_after(secs(10));
+        assert_eq!(secs(80 + 20 + 30), clock.sides[1].remaining);
+        assert_eq!(secs(80), clock.sides[0].remaining);
+
+        // Absurd times stop at the largest duration instead of overflowing
+        let mut clock = Clock::new(TimeControl::stages(vec![
+            Stage {
+                moves: Some(1),
+                time: Duration::MAX,
+                bonus: Bonus::Fischer(Duration::MAX),
+            },
+            Stage {
+                moves: None,
+                time: Duration::MAX,
+                bonus: Bonus::Fischer(Duration::MAX),
+            },
+        ]));
+        clock.start(Player::White);
+        clock.press_after(secs(1));
+        assert_eq!(1, clock.sides[0].stage);
+        assert_eq!(Duration::MAX, clock.sides[0].remaining);
+    }
+}
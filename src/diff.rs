@@ -0,0 +1,284 @@
+use std::time::Duration;
+#[cfg(feature = "clock")]
+use std::time::Instant;
+
+use crate::{
+    editor::EditErr,
+    game_manager::{CastlingRights, GameManager, GameStatus},
+    piece::{Piece, Player, Position},
+    square::Square,
+};
+
+// Changes between two states of a game, for servers pushing updates to spectators. Only
+// the position, the clock and the status are followed, not the move history.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameDiff {
+    // Squares left empty
+    pub removed: Vec<Position>,
+    // Pieces going from one square to another, taking what stood there
+    pub moved: Vec<(Position, Position)>,
+    // Pieces put on the board, replacing what stood there, e.g. promoted pawns
+    pub placed: Vec<Piece>,
+    pub turn: Option<Player>,
+    pub castling: Option<CastlingRights>,
+    pub en_passant: Option<Option<Position>>,
+    pub halfmove_clock: Option<u32>,
+    pub fullmove_number: Option<u32>,
+    // Time left to White and Black
+    pub white_time: Option<Duration>,
+    pub black_time: Option<Duration>,
+    pub status: Option<GameStatus>,
+}
+
+impl GameDiff {
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty()
+            && self.moved.is_empty()
+            && self.placed.is_empty()
+            && self.turn.is_none()
+            && self.castling.is_none()
+            && self.en_passant.is_none()
+            && self.halfmove_clock.is_none()
+            && self.fullmove_number.is_none()
+            && self.white_time.is_none()
+            && self.black_time.is_none()
+            && self.status.is_none()
+    }
+}
+
+impl GameManager {
+    // Changes turning this game into `other`
+    pub fn diff(&self, other: &GameManager) -> GameDiff {
+        let mut diff = GameDiff::default();
+        let (before, after) = (self.board(), other.board());
+        let changed = |i: usize| before[i] != after[i];
+        let mut left: Vec<usize> = (0..64)
+            .filter(|i| changed(*i) && before[*i].is_some())
+            .collect();
+        for to in (0..64).filter(|i| changed(*i)) {
+            let Some(piece) = after[to] else {
+                continue;
+            };
+            // A piece leaving a square for this one is a move, anything else appeared there
+            match left.iter().position(|from| before[*from] == Some(piece)) {
+                Some(index) => diff
+                    .moved
+                    .push((position(left.remove(index)), position(to))),
+                None => {
+                    let pos = position(to);
+                    diff.placed
+                        .push(Piece::new(piece.0, piece.1, pos.row, pos.column));
+                }
+            }
+        }
+        // Squares left empty, those taken by another piece are already covered
+        diff.removed = left
+            .into_iter()
+            .filter(|i| after[*i].is_none())
+            .map(position)
+            .collect();
+
+        diff.turn = (self.turn != other.turn).then_some(other.turn);
+        diff.castling = (self.castling != other.castling).then_some(other.castling);
+        diff.en_passant = (self.en_passant != other.en_passant).then_some(other.en_passant);
+        diff.halfmove_clock =
+            (self.halfmove_clock != other.halfmove_clock).then_some(other.halfmove_clock);
+        diff.fullmove_number =
+            (self.fullmove_number != other.fullmove_number).then_some(other.fullmove_number);
+        #[cfg(feature = "clock")]
+        if let Some(clock) = &other.clock {
+            let now = Instant::now();
+            let before = |player: &Player| {
+                self.clock
+                    .as_ref()
+                    .map(|clock| clock.remaining_at(player, now))
+            };
+            let white = clock.remaining_at(&Player::White, now);
+            let black = clock.remaining_at(&Player::Black, now);
+            diff.white_time = (before(&Player::White) != Some(white)).then_some(white);
+            diff.black_time = (before(&Player::Black) != Some(black)).then_some(black);
+        }
+        let status = other.status();
+        diff.status = (self.status() != status).then_some(status);
+        diff
+    }
+
+    // Applies a diff made by `diff` from a game in the same state as this one. A diff with
+    // squares off the board or too many pieces for a side leaves the game untouched.
+    pub fn apply_diff(&mut self, diff: &GameDiff) -> Result<(), EditErr> {
+        let squares = diff.moved.iter().flat_map(|(from, to)| [from, to]);
+        let placed = diff.placed.iter().map(Position::from_piece);
+        if squares
+            .chain(&diff.removed)
+            .copied()
+            .chain(placed)
+            .any(|pos| Square::try_from(pos).is_err())
+        {
+            return Err(EditErr::InvalidSquare);
+        }
+        let pieces = (self.whites, self.blacks);
+        let moving: Vec<(Option<Piece>, Position)> = diff
+            .moved
+            .iter()
+            .map(|(from, to)| (self.take_piece(from), *to))
+            .collect();
+        for pos in &diff.removed {
+            self.take_piece(pos);
+        }
+        let landing = moving.into_iter().filter_map(|(piece, to)| {
+            piece.map(|mut piece| {
+                piece.r#move(to.row, to.column);
+                piece
+            })
+        });
+        for piece in landing.chain(diff.placed.iter().copied()) {
+            self.take_piece(&Position::from_piece(&piece));
+            let pushed = match piece.player {
+                Player::White => self.whites.try_push(piece),
+                Player::Black => self.blacks.try_push(piece),
+            };
+            if pushed.is_err() {
+                (self.whites, self.blacks) = pieces;
+                return Err(EditErr::TooManyPieces(piece.player));
+            }
+        }
+        self.refresh_attacks();
+
+        if let Some(turn) = diff.turn {
+            self.turn = turn;
+        }
+        if let Some(castling) = diff.castling {
+            self.castling = castling;
+        }
+        if let Some(en_passant) = diff.en_passant {
+            self.en_passant = en_passant;
+        }
+        if let Some(halfmove_clock) = diff.halfmove_clock {
+            self.halfmove_clock = halfmove_clock;
+        }
+        if let Some(fullmove_number) = diff.fullmove_number {
+            self.fullmove_number = fullmove_number;
+        }
+        #[cfg(feature = "clock")]
+        if let Some(clock) = &mut self.clock {
+            for (player, time) in [
+                (Player::White, diff.white_time),
+                (Player::Black, diff.black_time),
+            ] {
+                if let Some(time) = time {
+                    clock.set_remaining(&player, time);
+                }
+            }
+            // The side to move is the one spending its time
+            if clock.running().is_some_and(|running| running != self.turn) {
+                clock.stop();
+                clock.start(self.turn);
+            }
+        }
+        // Results decided on the board follow from the position
+        if let Some(status) = diff.status {
+            self.outcome = None;
+            if self.status() != status {
+                self.outcome = status.is_over().then_some(status);
+            }
+        }
+        Ok(())
+    }
+
+    fn take_piece(&mut self, pos: &Position) -> Option<Piece> {
+        for pieces in [&mut self.whites, &mut self.blacks] {
+            if let Some(index) = pieces.iter().position(|p| Position::from_piece(p) == *pos) {
+                return Some(pieces.swap_remove(index));
+            }
+        }
+        None
+    }
+}
+
+fn position(index: usize) -> Position {
+    Square::ALL[index].into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GameDiff;
+    use crate::{
+        editor::EditErr,
+        game_manager::{GameManager, GameStatus},
+        moves::Move,
+        piece::{Kind, Piece, Player, Position},
+        square::Square,
+    };
+
+    #[test]
+    fn test_diff() {
+        let before = GameManager::new();
+        assert!(before.diff(&before).is_empty());
+
+        let mut after = before.clone();
+        after.play(&Move::new(Square::E2, Square::E4)).unwrap();
+        let diff = before.diff(&after);
+        assert_eq!(
+            vec![(Position::from(Square::E2), Position::from(Square::E4))],
+            diff.moved
+        );
+        assert!(diff.removed.is_empty() && diff.placed.is_empty());
+        assert_eq!(Some(Player::Black), diff.turn);
+        assert_eq!(Some(Some(Square::E3.into())), diff.en_passant);
+        let mut patched = before.clone();
+        patched.apply_diff(&diff).unwrap();
+        assert_eq!(after.to_fen(), patched.to_fen());
+
+        // Castling, en passant and promotion
+        let fen = "r3k3/1P6/8/8/3pP3/8/8/R3K2R b KQq e3 0 1";
+        let before = GameManager::from_fen(fen).unwrap();
+        let mut after = before.clone();
+        for mv in ["d4e3", "e1g1", "a8a7", "b7b8q"] {
+            after.play(&Move::from_uci(mv).unwrap()).unwrap();
+        }
+        let diff = before.diff(&after);
+        assert_eq!(4, diff.moved.len());
+        assert_eq!(
+            vec![Position::from(Square::E4), Position::from(Square::B7)],
+            diff.removed
+        );
+        assert_eq!(
+            vec![(Player::White, Kind::Queen, Square::B8.into())],
+            diff.placed
+                .iter()
+                .map(|p| (p.player, p.kind, Position::from_piece(p)))
+                .collect::<Vec<_>>()
+        );
+        let mut patched = before.clone();
+        patched.apply_diff(&diff).unwrap();
+        assert_eq!(after.to_fen(), patched.to_fen());
+        assert_eq!(after, patched);
+
+        // Results decided off the board
+        let mut after = patched.clone();
+        after.resign(&Player::Black);
+        let diff = patched.diff(&after);
+        assert_eq!(Some(GameStatus::Resignation(Player::White)), diff.status);
+        patched.apply_diff(&diff).unwrap();
+        assert_eq!(GameStatus::Resignation(Player::White), patched.status());
+        assert!(patched.diff(&after).is_empty());
+
+        // Diffs from untrusted clients
+        let before = patched.clone();
+        let mut diff = GameDiff::default();
+        diff.moved.push((Square::A1.into(), Position::new(9, 0)));
+        assert_eq!(Err(EditErr::InvalidSquare), patched.apply_diff(&diff));
+        let diff = GameDiff {
+            placed: (0..16)
+                .map(|column| Piece::new(Player::White, Kind::Pawn, 2 + column / 8, column % 8))
+                .collect(),
+            ..GameDiff::default()
+        };
+        assert_eq!(
+            Err(EditErr::TooManyPieces(Player::White)),
+            patched.apply_diff(&diff)
+        );
+        assert_eq!(before, patched);
+    }
+}
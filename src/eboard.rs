@@ -0,0 +1,212 @@
+use crate::{
+    game_manager::GameManager,
+    moves::Move,
+    piece::{Kind, Position},
+};
+
+// Occupancy change reported by the sensors of an electronic board
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SquareEvent {
+    Lifted(Position),
+    Placed(Position),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BoardInput {
+    // The pieces are still moving, or were put back
+    Pending,
+    Move(Move),
+    // Legal moves leaving the same occupancy, e.g. the pieces a pawn may promote to
+    Ambiguous(Vec<Move>),
+    // No legal move touches these squares, the board has to be set back
+    Illegal,
+}
+
+// Infers the moves played on an electronic board from its occupancy changes. The board only
+// tells whether a square is occupied, so the squares touched since the last position are
+// kept to tell captures apart.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ElectronicBoard {
+    occupied: u64,
+    touched: u64,
+}
+
+impl ElectronicBoard {
+    pub fn new(gm: &GameManager) -> Self {
+        Self {
+            occupied: occupancy(gm),
+            touched: 0,
+        }
+    }
+
+    // Whether the physical board shows the position of `gm`
+    pub fn is_synced(&self, gm: &GameManager) -> bool {
+        self.occupied == occupancy(gm)
+    }
+
+    // Takes the occupancy of `gm` as the one of the physical board, e.g. once it was set up
+    pub fn sync(&mut self, gm: &GameManager) {
+        *self = Self::new(gm);
+    }
+
+    // Feeds an event, `gm` being the game the board follows. The move found isn't played, the
+    // caller plays it before the next event.
+    pub fn event(&mut self, gm: &GameManager, event: SquareEvent) -> BoardInput {
+        let pos = match event {
+            SquareEvent::Lifted(pos) => {
+                self.occupied &= !bit(&pos);
+                pos
+            }
+            SquareEvent::Placed(pos) => {
+                self.occupied |= bit(&pos);
+                pos
+            }
+        };
+        self.touched |= bit(&pos);
+        if self.is_synced(gm) {
+            self.touched = 0;
+            return BoardInput::Pending;
+        }
+        let mut possible = false;
+        let mut moves = vec![];
+        for mv in gm.legal_moves_iter() {
+            let Some((squares, after)) = move_occupancy(gm, &mv) else {
+                continue;
+            };
+            if self.touched & !squares != 0 {
+                continue;
+            }
+            possible = true;
+            // A capture shows the same occupancy as its piece lifted, the taken one has to
+            // be touched too
+            if after == self.occupied && self.touched == squares {
+                moves.push(mv);
+            }
+        }
+        match moves.len() {
+            0 if possible => BoardInput::Pending,
+            0 => BoardInput::Illegal,
+            1 => {
+                self.touched = 0;
+                BoardInput::Move(moves[0])
+            }
+            _ => BoardInput::Ambiguous(moves),
+        }
+    }
+}
+
+// Nothing for squares off the board
+fn bit(pos: &Position) -> u64 {
+    pos.index().map_or(0, |index| 1 << index)
+}
+
+fn occupancy(gm: &GameManager) -> u64 {
+    gm.pieces().fold(0, |occupied, (_, p)| {
+        occupied | bit(&Position::from_piece(p))
+    })
+}
+
+// Squares `mv` changes and the occupancy once it is played
+fn move_occupancy(gm: &GameManager, mv: &Move) -> Option<(u64, u64)> {
+    let piece = gm
+        .pieces_of(gm.turn)
+        .iter()
+        .find(|p| Position::from_piece(p) == mv.from)?;
+    let (emptied, filled) = match gm.castle(piece.kind, mv, &gm.castling) {
+        Some(castle) => (
+            bit(&mv.from) | bit(&castle.rook_from),
+            bit(&castle.king_to) | bit(&castle.rook_to),
+        ),
+        None if piece.kind == Kind::Pawn && gm.en_passant == Some(mv.to) => {
+            let captured = Position::new(mv.from.row, mv.to.column);
+            (bit(&mv.from) | bit(&captured), bit(&mv.to))
+        }
+        None => (bit(&mv.from), bit(&mv.to)),
+    };
+    Some((emptied | filled, (occupancy(gm) & !emptied) | filled))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        BoardInput, ElectronicBoard,
+        SquareEvent::{Lifted, Placed},
+    };
+    use crate::{game_manager::GameManager, moves::Move, square::Square};
+
+    #[test]
+    fn test_electronic_board() {
+        let mut gm = GameManager::from_fen("4k3/1P6/8/3p4/4P3/8/8/4K2R w K - 0 1").unwrap();
+        let mut board = ElectronicBoard::new(&gm);
+        let mut feed = |gm: &GameManager, events: &[_]| {
+            events
+                .iter()
+                .map(|event| board.event(gm, *event))
+                .collect::<Vec<_>>()
+        };
+
+        // Lifted and put back
+        let inputs = feed(&gm, &[Lifted(Square::E4.into()), Placed(Square::E4.into())]);
+        assert_eq!(vec![BoardInput::Pending; 2], inputs);
+
+        // A capture, the taken piece lifted first
+        let inputs = feed(
+            &gm,
+            &[
+                Lifted(Square::D5.into()),
+                Lifted(Square::E4.into()),
+                Placed(Square::D5.into()),
+            ],
+        );
+        let capture = Move::new(Square::E4, Square::D5);
+        assert_eq!(BoardInput::Move(capture), inputs[2]);
+        gm.play(&capture).unwrap();
+        gm.play(&Move::new(Square::E8, Square::D8)).unwrap();
+        let mut board = ElectronicBoard::new(&gm);
+
+        // Castling, the king then the rook
+        let inputs: Vec<_> = [
+            Lifted(Square::E1.into()),
+            Placed(Square::G1.into()),
+            Lifted(Square::H1.into()),
+            Placed(Square::F1.into()),
+        ]
+        .iter()
+        .map(|event| board.event(&gm, *event))
+        .collect();
+        assert_eq!(
+            vec![
+                BoardInput::Pending,
+                BoardInput::Pending,
+                BoardInput::Pending,
+                BoardInput::Move(Move::new(Square::E1, Square::G1)),
+            ],
+            inputs
+        );
+        gm.play(&Move::new(Square::E1, Square::G1)).unwrap();
+        gm.play(&Move::new(Square::D8, Square::E8)).unwrap();
+
+        // The promotion piece can't be seen
+        let mut board = ElectronicBoard::new(&gm);
+        board.event(&gm, Lifted(Square::B7.into()));
+        match board.event(&gm, Placed(Square::B8.into())) {
+            BoardInput::Ambiguous(moves) => assert_eq!(4, moves.len()),
+            input => panic!("{:?}", input),
+        }
+
+        // The king can't reach that far, the board has to be set back
+        let mut board = ElectronicBoard::new(&gm);
+        board.event(&gm, Lifted(Square::G1.into()));
+        assert_eq!(
+            BoardInput::Illegal,
+            board.event(&gm, Placed(Square::G4.into()))
+        );
+        assert!(!board.is_synced(&gm));
+        board.event(&gm, Lifted(Square::G4.into()));
+        assert_eq!(
+            BoardInput::Pending,
+            board.event(&gm, Placed(Square::G1.into()))
+        );
+        assert!(board.is_synced(&gm));
+    }
+}
@@ -0,0 +1,321 @@
+use std::{error::Error, fmt};
+
+use crate::{
+    builder::{validate_position, BuildErr},
+    game_manager::{GameManager, MoveErr},
+    moves::Move,
+    piece::{Kind, Piece, Player, Position, MAX_PIECES},
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EditErr {
+    NotInSetupMode,
+    InvalidSquare,
+    TooManyPieces(Player),
+    // The edited position can't be played
+    Position(BuildErr),
+}
+
+impl fmt::Display for EditErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EditErr::NotInSetupMode => write!(f, "the board isn't being edited"),
+            EditErr::InvalidSquare => write!(f, "square outside of the board"),
+            EditErr::TooManyPieces(player) => write!(f, "too many {:?} pieces", player),
+            EditErr::Position(err) => write!(f, "invalid position: {}", err),
+        }
+    }
+}
+
+impl Error for EditErr {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            EditErr::Position(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+// Board editor: `enter_setup` unlocks the editing methods, `finish_setup` checks the position
+// and makes it playable again. The lenient mode unlocks them too.
+impl GameManager {
+    pub fn is_in_setup(&self) -> bool {
+        self.setup
+    }
+
+    // The move history doesn't match the edited position, it is dropped
+    pub fn enter_setup(&mut self) {
+        self.setup = true;
+        self.history.clear();
+        self.redo.clear();
+        self.en_passant = None;
+    }
+
+    // Keeps only the castling rights the king and rooks still allow, then freezes the position
+    // if it is valid. The board stays in setup mode otherwise.
+    pub fn finish_setup(&mut self) -> Result<(), EditErr> {
+        if !self.setup {
+            return Err(EditErr::NotInSetupMode);
+        }
+        let has = |gm: &GameManager, player: Player, kind: Kind, row: u8, column: u8| {
+            gm.pieces_of(player)
+                .iter()
+                .any(|p| p.kind == kind && p.row == row && p.column == column)
+        };
+        let files = self.castling_files();
+        for player in [Player::White, Player::Black] {
+            let row = self.size.back_row(player);
+            let king = has(self, player, Kind::King, row, files.king);
+            let king_side = king && has(self, player, Kind::Rook, row, files.king_side_rook);
+            let queen_side = king && has(self, player, Kind::Rook, row, files.queen_side_rook);
+            match player {
+                Player::White => {
+                    self.castling.white_king_side &= king_side;
+                    self.castling.white_queen_side &= queen_side;
+                }
+                Player::Black => {
+                    self.castling.black_king_side &= king_side;
+                    self.castling.black_queen_side &= queen_side;
+                }
+            }
+        }
+        validate_position(self).map_err(EditErr::Position)?;
+        self.setup = false;
+        Ok(())
+    }
+
+    pub fn is_lenient(&self) -> bool {
+        self.lenient
+    }
+
+    // Free play for composing puzzles or teaching: any piece of either side may go to any
+    // square, kings may be left in check or taken, and the board can be edited without
+    // entering setup. Moves are still kept in the history.
+    pub fn enter_lenient_mode(&mut self) {
+        self.lenient = true;
+    }
+
+    // Back to the rules, only if the position could happen in a game
+    pub fn leave_lenient_mode(&mut self) -> Result<(), EditErr> {
+        validate_position(self).map_err(EditErr::Position)?;
+        self.lenient = false;
+        Ok(())
+    }
+
+    // Side owning the piece `mv` moves in lenient mode
+    pub(crate) fn lenient_mover(&self, mv: &Move) -> Result<Player, MoveErr> {
+        let (_, piece) = self
+            .piece_at(&mv.from)
+            .ok_or(MoveErr::NoPiece { at: mv.from })?;
+        let target = self.piece_at(&mv.to).map(|(_, p)| p);
+        if mv.from == mv.to {
+            Err(MoveErr::SamePosition)
+        } else if !self.size.contains(&mv.to) {
+            Err(MoveErr::InvalidMove {
+                piece: piece.kind,
+                from: mv.from,
+                to: mv.to,
+            })
+        } else if let Some(target) = target.filter(|p| p.player == piece.player) {
+            Err(MoveErr::FriendlyFire {
+                occupied_by: target.kind,
+            })
+        } else {
+            Ok(piece.player)
+        }
+    }
+
+    fn check_setup(&self, pos: Position) -> Result<(), EditErr> {
+        if !self.setup && !self.lenient {
+            Err(EditErr::NotInSetupMode)
+        } else if !self.size.contains(&pos) {
+            Err(EditErr::InvalidSquare)
+        } else {
+            Ok(())
+        }
+    }
+
+    // Puts `piece` on its square, replacing what stood there
+    pub fn put_piece(&mut self, piece: Piece) -> Result<(), EditErr> {
+        let pos = Position::from_piece(&piece);
+        self.check_setup(pos)?;
+        let replaced = self.piece_at(&pos).map(|(player, _)| player);
+        if replaced != Some(piece.player) && self.pieces_of(piece.player).len() == MAX_PIECES {
+            return Err(EditErr::TooManyPieces(piece.player));
+        }
+        self.remove_piece(pos)?;
+        match piece.player {
+            Player::White => self.whites.push(piece),
+            Player::Black => self.blacks.push(piece),
+        }
+        self.refresh_attacks();
+        Ok(())
+    }
+
+    // Removes and returns the piece standing on `pos`
+    pub fn remove_piece(&mut self, pos: impl Into<Position>) -> Result<Option<Piece>, EditErr> {
+        let pos = pos.into();
+        self.check_setup(pos)?;
+        let mut removed = None;
+        for pieces in [&mut self.whites, &mut self.blacks] {
+            if let Some(index) = pieces.iter().position(|p| Position::from_piece(p) == pos) {
+                removed = Some(pieces.swap_remove(index));
+            }
+        }
+        if removed.is_some() {
+            self.refresh_attacks();
+        }
+        Ok(removed)
+    }
+
+    pub fn clear_board(&mut self) -> Result<(), EditErr> {
+        if !self.setup && !self.lenient {
+            return Err(EditErr::NotInSetupMode);
+        }
+        self.whites.clear();
+        self.blacks.clear();
+        self.refresh_attacks();
+        Ok(())
+    }
+
+    pub fn set_turn(&mut self, player: Player) -> Result<(), EditErr> {
+        if !self.setup && !self.lenient {
+            return Err(EditErr::NotInSetupMode);
+        }
+        self.turn = player;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EditErr;
+    use crate::{
+        builder::BuildErr,
+        game_manager::{GameManager, MoveErr},
+        moves::Move,
+        piece::{BoardSize, Kind, Piece, Player, Position},
+        square::Square,
+    };
+
+    #[test]
+    fn test_board_editor() {
+        let mut gm = GameManager::new();
+        let queen = Piece::new(Player::White, Kind::Queen, 3, 3);
+        assert_eq!(Err(EditErr::NotInSetupMode), gm.put_piece(queen));
+        assert_eq!(Err(EditErr::NotInSetupMode), gm.finish_setup());
+        gm.play(&Move::new(Square::E2, Square::E4)).unwrap();
+
+        gm.enter_setup();
+        assert!(gm.is_in_setup());
+        assert!(gm.history().is_empty());
+        assert!(matches!(
+            gm.play(&Move::new(Square::E7, Square::E5)),
+            Err(MoveErr::SetupMode)
+        ));
+        gm.clear_board().unwrap();
+        gm.put_piece(Piece::new(Player::White, Kind::King, 0, 4))
+            .unwrap();
+        gm.put_piece(Piece::new(Player::White, Kind::Rook, 0, 7))
+            .unwrap();
+        gm.put_piece(queen).unwrap();
+        // Replaces the queen
+        gm.put_piece(Piece::new(Player::Black, Kind::Rook, 3, 3))
+            .unwrap();
+        assert_eq!(
+            Err(EditErr::Position(BuildErr::KingCount(Player::Black, 0))),
+            gm.finish_setup()
+        );
+        assert!(gm.is_in_setup());
+        gm.put_piece(Piece::new(Player::Black, Kind::King, 7, 4))
+            .unwrap();
+        assert_eq!(
+            Some(Kind::Rook),
+            gm.remove_piece(Square::D4).unwrap().map(|p| p.kind)
+        );
+        assert!(gm.remove_piece(Square::D4).unwrap().is_none());
+        assert_eq!(
+            Some(EditErr::InvalidSquare),
+            gm.remove_piece(Position::new(8, 0)).err()
+        );
+        gm.set_turn(Player::White).unwrap();
+        gm.finish_setup().unwrap();
+        assert!(!gm.is_in_setup());
+        assert_eq!("4k3/8/8/8/8/8/8/4K2R w K - 0 1", gm.to_fen());
+        gm.play(&Move::new(Square::E1, Square::G1)).unwrap();
+        assert_eq!("4k3/8/8/8/8/8/8/5RK1 b - - 1 1", gm.to_fen());
+    }
+
+    #[test]
+    fn test_lenient_mode() {
+        let mut gm = GameManager::new();
+        gm.enter_lenient_mode();
+        assert!(gm.is_lenient());
+        // White moves twice then its queen jumps over the pawns
+        gm.play(&Move::new(Square::E2, Square::E4)).unwrap();
+        gm.play(&Move::new(Square::D2, Square::D4)).unwrap();
+        gm.play(&Move::new(Square::D1, Square::D8)).unwrap();
+        assert_eq!(Player::Black, gm.turn);
+        assert!(matches!(
+            gm.play(&Move::new(Square::A1, Square::A2)),
+            Err(MoveErr::FriendlyFire {
+                occupied_by: Kind::Pawn
+            })
+        ));
+        assert!(matches!(
+            gm.play(&Move::new(Square::E5, Square::E6)),
+            Err(MoveErr::NoPiece { at }) if at == Square::E5.into()
+        ));
+        // The king is taken
+        gm.play(&Move::new(Square::D8, Square::E8)).unwrap();
+        assert_eq!(4, gm.history().len());
+        assert_eq!(
+            Some(EditErr::Position(BuildErr::KingCount(Player::Black, 0))),
+            gm.leave_lenient_mode().err()
+        );
+        gm.undo();
+        gm.remove_piece(Square::D8).unwrap();
+        gm.put_piece(Piece::new(Player::White, Kind::Queen, 0, 3))
+            .unwrap();
+        gm.set_turn(Player::White).unwrap();
+        gm.leave_lenient_mode().unwrap();
+        assert!(!gm.is_lenient());
+        assert!(matches!(
+            gm.play(&Move::new(Square::D1, Square::D8)),
+            Err(MoveErr::InvalidMove {
+                piece: Kind::Queen,
+                ..
+            })
+        ));
+        assert!(gm.remove_piece(Square::D1).is_err());
+    }
+
+    #[test]
+    fn test_board_editor_sized() {
+        let mut gm =
+            GameManager::from_sized_fen("4k/5/5/5/4K w - - 0 1", BoardSize::MINICHESS).unwrap();
+        gm.enter_setup();
+        assert_eq!(
+            Err(EditErr::InvalidSquare),
+            gm.put_piece(Piece::new(Player::White, Kind::Rook, 5, 0))
+        );
+        assert_eq!(
+            Err(EditErr::InvalidSquare),
+            gm.put_piece(Piece::new(Player::White, Kind::Rook, 0, 5))
+        );
+        gm.put_piece(Piece::new(Player::White, Kind::Rook, 0, 0))
+            .unwrap();
+        gm.finish_setup().unwrap();
+        assert_eq!("4k/5/5/5/R3K w - - 0 1", gm.to_fen());
+
+        gm.enter_lenient_mode();
+        assert!(matches!(
+            gm.play(&Move::new(Square::A1, Square::A6)),
+            Err(MoveErr::InvalidMove {
+                piece: Kind::Rook,
+                ..
+            })
+        ));
+        gm.play(&Move::new(Square::A1, Square::A5)).unwrap();
+    }
+}
@@ -0,0 +1,256 @@
+use crate::{
+    game_manager::GameManager,
+    piece::{Kind, Piece, Player, MAX_COLUMN},
+};
+
+// Centipawns, from the point of view of the side to move
+pub type Score = i32;
+
+// Static evaluation used by the search. Anything from a handcrafted function to a neural
+// network can be plugged in, closures taking the game work too.
+pub trait Evaluator {
+    fn evaluate(&self, gm: &GameManager) -> Score;
+
+    // Terms the evaluation adds up, for GUIs showing why a side is better. Evaluations that
+    // can't tell keep the default.
+    fn breakdown(&self, _gm: &GameManager) -> Option<EvalBreakdown> {
+        None
+    }
+}
+
+impl<F: Fn(&GameManager) -> Score> Evaluator for F {
+    fn evaluate(&self, gm: &GameManager) -> Score {
+        self(gm)
+    }
+}
+
+// What one side gets from each part of the evaluation
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EvalTerms {
+    pub material: Score,
+    // Bonus of the squares the pieces stand on
+    pub piece_squares: Score,
+    // Penalties for doubled and isolated pawns
+    pub pawn_structure: Score,
+    // Pawns sheltering the king
+    pub king_safety: Score,
+    // Squares attacked
+    pub mobility: Score,
+}
+
+impl EvalTerms {
+    pub fn total(&self) -> Score {
+        self.material + self.piece_squares + self.pawn_structure + self.king_safety + self.mobility
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EvalBreakdown {
+    pub white: EvalTerms,
+    pub black: EvalTerms,
+}
+
+impl EvalBreakdown {
+    // Evaluation from the point of view of `player`
+    pub fn score(&self, player: &Player) -> Score {
+        match player {
+            Player::White => self.white.total() - self.black.total(),
+            Player::Black => self.black.total() - self.white.total(),
+        }
+    }
+}
+
+// Material, piece-square tables, pawn structure, king shelter and mobility, the default
+// evaluation of the search
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ClassicalEval;
+
+const DOUBLED_PAWN: Score = -10;
+const ISOLATED_PAWN: Score = -15;
+const SHELTER_PAWN: Score = 15;
+const ATTACKED_SQUARE: Score = 2;
+
+impl Evaluator for ClassicalEval {
+    fn evaluate(&self, gm: &GameManager) -> Score {
+        classical_breakdown(gm).score(&gm.turn)
+    }
+
+    fn breakdown(&self, gm: &GameManager) -> Option<EvalBreakdown> {
+        Some(classical_breakdown(gm))
+    }
+}
+
+fn classical_breakdown(gm: &GameManager) -> EvalBreakdown {
+    let terms = |player: Player| {
+        let pieces = gm.pieces_of(player);
+        let mut pawns = [0; MAX_COLUMN as usize];
+        for p in pieces.iter().filter(|p| p.kind == Kind::Pawn) {
+            pawns[p.column as usize] += 1;
+        }
+        let pawn_structure = (0..pawns.len())
+            .map(|column| {
+                let count = pawns[column];
+                let neighbours = column.checked_sub(1).map_or(0, |c| pawns[c])
+                    + pawns.get(column + 1).copied().unwrap_or(0);
+                let isolated = if neighbours == 0 { count } else { 0 };
+                (count - 1).max(0) * DOUBLED_PAWN + isolated * ISOLATED_PAWN
+            })
+            .sum();
+        EvalTerms {
+            material: pieces.iter().map(|p| material(p.kind)).sum(),
+            piece_squares: pieces.iter().map(piece_square_bonus).sum(),
+            pawn_structure,
+            king_safety: gm.king_shelter(&player) as Score * SHELTER_PAWN,
+            mobility: gm.attacked_squares(&player).squares().count() as Score * ATTACKED_SQUARE,
+        }
+    };
+    EvalBreakdown {
+        white: terms(Player::White),
+        black: terms(Player::Black),
+    }
+}
+
+fn material(kind: Kind) -> Score {
+    kind.material_value() as Score * 100
+}
+
+// Material plus the bonus of the square the piece stands on
+pub fn piece_value(piece: &Piece) -> Score {
+    material(piece.kind) + piece_square_bonus(piece)
+}
+
+fn piece_square_bonus(piece: &Piece) -> Score {
+    let table = match piece.kind {
+        Kind::Pawn => &PAWN_TABLE,
+        Kind::Knight => &KNIGHT_TABLE,
+        Kind::Bishop => &BISHOP_TABLE,
+        Kind::Rook => &ROOK_TABLE,
+        Kind::Queen => &QUEEN_TABLE,
+        Kind::King => &KING_TABLE,
+    };
+    // Tables are written from White's side, the eighth rank first
+    let row = match piece.player {
+        Player::White => 7 - piece.row as usize,
+        Player::Black => piece.row as usize,
+    };
+    table[row * 8 + piece.column as usize]
+}
+
+#[rustfmt::skip]
+const PAWN_TABLE: [Score; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+    50, 50, 50, 50, 50, 50, 50, 50,
+    10, 10, 20, 30, 30, 20, 10, 10,
+     5,  5, 10, 25, 25, 10,  5,  5,
+     0,  0,  0, 20, 20,  0,  0,  0,
+     5, -5,-10,  0,  0,-10, -5,  5,
+     5, 10, 10,-20,-20, 10, 10,  5,
+     0,  0,  0,  0,  0,  0,  0,  0,
+];
+
+#[rustfmt::skip]
+const KNIGHT_TABLE: [Score; 64] = [
+    -50,-40,-30,-30,-30,-30,-40,-50,
+    -40,-20,  0,  0,  0,  0,-20,-40,
+    -30,  0, 10, 15, 15, 10,  0,-30,
+    -30,  5, 15, 20, 20, 15,  5,-30,
+    -30,  0, 15, 20, 20, 15,  0,-30,
+    -30,  5, 10, 15, 15, 10,  5,-30,
+    -40,-20,  0,  5,  5,  0,-20,-40,
+    -50,-40,-30,-30,-30,-30,-40,-50,
+];
+
+#[rustfmt::skip]
+const BISHOP_TABLE: [Score; 64] = [
+    -20,-10,-10,-10,-10,-10,-10,-20,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -10,  0,  5, 10, 10,  5,  0,-10,
+    -10,  5,  5, 10, 10,  5,  5,-10,
+    -10,  0, 10, 10, 10, 10,  0,-10,
+    -10, 10, 10, 10, 10, 10, 10,-10,
+    -10,  5,  0,  0,  0,  0,  5,-10,
+    -20,-10,-10,-10,-10,-10,-10,-20,
+];
+
+#[rustfmt::skip]
+const ROOK_TABLE: [Score; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+     5, 10, 10, 10, 10, 10, 10,  5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+     0,  0,  0,  5,  5,  0,  0,  0,
+];
+
+#[rustfmt::skip]
+const QUEEN_TABLE: [Score; 64] = [
+    -20,-10,-10, -5, -5,-10,-10,-20,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -10,  0,  5,  5,  5,  5,  0,-10,
+     -5,  0,  5,  5,  5,  5,  0, -5,
+      0,  0,  5,  5,  5,  5,  0, -5,
+    -10,  5,  5,  5,  5,  5,  0,-10,
+    -10,  0,  5,  0,  0,  0,  0,-10,
+    -20,-10,-10, -5, -5,-10,-10,-20,
+];
+
+#[rustfmt::skip]
+const KING_TABLE: [Score; 64] = [
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -20,-30,-30,-40,-40,-30,-30,-20,
+    -10,-20,-20,-20,-20,-20,-20,-10,
+     20, 20,  0,  0,  0,  0, 20, 20,
+     20, 30, 10,  0,  0, 10, 30, 20,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::{ClassicalEval, Evaluator, Score};
+    use crate::{game_manager::GameManager, piece::Player};
+
+    #[test]
+    fn test_classical_eval() {
+        assert_eq!(0, ClassicalEval.evaluate(&GameManager::new()));
+        // White is a queen up, both sides see it
+        let white = GameManager::from_fen("4k3/8/8/8/8/8/8/3QK3 w - - 0 1").unwrap();
+        let black = GameManager::from_fen("4k3/8/8/8/8/8/8/3QK3 b - - 0 1").unwrap();
+        assert!(ClassicalEval.evaluate(&white) > 800);
+        assert_eq!(
+            ClassicalEval.evaluate(&white),
+            -ClassicalEval.evaluate(&black)
+        );
+        // Mirrored positions are worth the same for the side to move
+        let mirrored = GameManager::from_fen("3qk3/8/8/8/8/8/8/4K3 b - - 0 1").unwrap();
+        assert_eq!(
+            ClassicalEval.evaluate(&white),
+            ClassicalEval.evaluate(&mirrored)
+        );
+
+        let mobility = |gm: &GameManager| gm.legal_moves().len() as Score;
+        assert_eq!(20, mobility.evaluate(&GameManager::new()));
+        assert_eq!(None, mobility.breakdown(&GameManager::new()));
+    }
+
+    #[test]
+    fn test_eval_breakdown() {
+        // Doubled isolated pawns against a healthy pair, the white king has no shelter
+        let gm = GameManager::from_fen("6k1/5pp1/8/8/8/2P5/2P5/5K2 w - - 0 1").unwrap();
+        let breakdown = ClassicalEval.breakdown(&gm).unwrap();
+        assert_eq!(200, breakdown.white.material);
+        assert_eq!(200, breakdown.black.material);
+        assert_eq!(-10 - 2 * 15, breakdown.white.pawn_structure);
+        assert_eq!(0, breakdown.black.pawn_structure);
+        assert_eq!(0, breakdown.white.king_safety);
+        assert_eq!(2 * 15, breakdown.black.king_safety);
+        assert_eq!(ClassicalEval.evaluate(&gm), breakdown.score(&Player::White));
+        assert_eq!(
+            breakdown.white.total() - breakdown.black.total(),
+            breakdown.score(&Player::White)
+        );
+    }
+}
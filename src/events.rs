@@ -0,0 +1,147 @@
+use std::{fmt, sync::Arc};
+
+use crate::{
+    game_manager::{GameManager, GameStatus, MoveRecord},
+    piece::{Kind, Piece, Player},
+};
+
+type Callback<T> = Arc<dyn Fn(&T) + Send + Sync>;
+
+// Callbacks run by `GameManager::play`. They belong to the manager they were registered on,
+// clones made for analysis or replays start without any.
+#[derive(Default)]
+pub(crate) struct Listeners {
+    on_move: Vec<Callback<MoveRecord>>,
+    on_capture: Vec<Callback<Piece>>,
+    on_check: Vec<Callback<Player>>,
+    on_promotion: Vec<Callback<Kind>>,
+    on_game_end: Vec<Callback<GameStatus>>,
+}
+
+impl Clone for Listeners {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl fmt::Debug for Listeners {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let count = self.on_move.len()
+            + self.on_capture.len()
+            + self.on_check.len()
+            + self.on_promotion.len()
+            + self.on_game_end.len();
+        write!(f, "Listeners({})", count)
+    }
+}
+
+impl GameManager {
+    pub fn on_move(&mut self, f: impl Fn(&MoveRecord) + Send + Sync + 'static) {
+        self.listeners.on_move.push(Arc::new(f));
+    }
+
+    // Called with the captured piece
+    pub fn on_capture(&mut self, f: impl Fn(&Piece) + Send + Sync + 'static) {
+        self.listeners.on_capture.push(Arc::new(f));
+    }
+
+    // Called with the player in check
+    pub fn on_check(&mut self, f: impl Fn(&Player) + Send + Sync + 'static) {
+        self.listeners.on_check.push(Arc::new(f));
+    }
+
+    // Called with the kind the pawn was promoted to
+    pub fn on_promotion(&mut self, f: impl Fn(&Kind) + Send + Sync + 'static) {
+        self.listeners.on_promotion.push(Arc::new(f));
+    }
+
+    pub fn on_game_end(&mut self, f: impl Fn(&GameStatus) + Send + Sync + 'static) {
+        self.listeners.on_game_end.push(Arc::new(f));
+    }
+
+    // Runs the listeners for the last move of the history
+    pub(crate) fn emit_move_events(&self) {
+        let Some(record) = self.history.last() else {
+            return;
+        };
+        let listeners = &self.listeners;
+        for f in &listeners.on_move {
+            f(record);
+        }
+        if let Some(captured) = &record.captured {
+            for f in &listeners.on_capture {
+                f(captured);
+            }
+        }
+        if record.flags.check {
+            for f in &listeners.on_check {
+                f(&self.turn);
+            }
+        }
+        if let Some(kind) = &record.mv.promotion {
+            for f in &listeners.on_promotion {
+                f(kind);
+            }
+        }
+        if !listeners.on_game_end.is_empty() {
+            let status = self.status();
+            if status.is_over() {
+                self.emit_game_end(&status);
+            }
+        }
+    }
+
+    pub(crate) fn emit_game_end(&self, status: &GameStatus) {
+        for f in &self.listeners.on_game_end {
+            f(status);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "notation"))]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::{game_manager::GameManager, moves::Move, square::Square};
+
+    #[test]
+    fn test_listeners() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let mut gm = GameManager::new();
+        let log = events.clone();
+        gm.on_move(move |record| log.lock().unwrap().push(record.san.clone()));
+        let log = events.clone();
+        gm.on_capture(move |piece| log.lock().unwrap().push(format!("x{}", piece)));
+        let log = events.clone();
+        gm.on_check(move |player| log.lock().unwrap().push(format!("check {:?}", player)));
+        let log = events.clone();
+        gm.on_game_end(move |status| log.lock().unwrap().push(format!("{:?}", status)));
+        for mv in [
+            Move::new(Square::E2, Square::E4),
+            Move::new(Square::F7, Square::F5),
+            Move::new(Square::E4, Square::F5),
+            Move::new(Square::G7, Square::G5),
+            Move::new(Square::D1, Square::H5),
+        ] {
+            gm.play(&mv).unwrap();
+        }
+        assert!(gm.play(&Move::new(Square::A7, Square::A6)).is_err());
+        // Clones don't share the listeners
+        let mut copy = gm.clone();
+        copy.undo();
+        copy.play(&Move::new(Square::D1, Square::H5)).unwrap();
+        assert_eq!(
+            vec![
+                "e4",
+                "f5",
+                "exf5",
+                "xp",
+                "g5",
+                "Qh5#",
+                "check Black",
+                "Checkmate(White)",
+            ],
+            *events.lock().unwrap()
+        );
+    }
+}
@@ -0,0 +1,535 @@
+use std::{error::Error, fmt};
+
+use crate::{
+    builder::{position_problems, BuildErr},
+    chess960::CastlingFiles,
+    game_manager::GameManager,
+    piece::{BoardSize, Kind, Piece, PieceList, Player, Position, MAX_PIECES},
+};
+
+pub const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+// Gardner's minichess, on a `BoardSize::MINICHESS` board
+pub const MINICHESS_FEN: &str = "rnbqk/ppppp/5/PPPPP/RNBQK w - - 0 1";
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum FenErr {
+    MissingField,
+    InvalidBoard,
+    TooManyPieces,
+    InvalidTurn,
+    InvalidCastling,
+    InvalidEnPassant,
+    InvalidCounter,
+}
+
+impl fmt::Display for FenErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let field = match self {
+            FenErr::MissingField => return write!(f, "missing FEN field"),
+            FenErr::TooManyPieces => return write!(f, "too many pieces in FEN"),
+            FenErr::InvalidBoard => "board",
+            FenErr::InvalidTurn => "side to move",
+            FenErr::InvalidCastling => "castling",
+            FenErr::InvalidEnPassant => "en passant",
+            FenErr::InvalidCounter => "move counter",
+        };
+        write!(f, "invalid {} field in FEN", field)
+    }
+}
+
+impl Error for FenErr {}
+
+// Problem found by `validate_fen`
+#[derive(Debug, PartialEq, Eq)]
+pub enum FenProblem {
+    MissingField,
+    ExtraField,
+    // Holds the number of ranks found
+    RankCount(usize),
+    // Rank, numbered as on the board, with the number of squares it describes
+    RankLength(u8, usize),
+    InvalidPiece(char),
+    TooManyPieces(Player),
+    InvalidTurn,
+    InvalidCastling,
+    InvalidEnPassant,
+    InvalidCounter,
+    // The board reads fine but the position can't happen in a game
+    Position(BuildErr),
+}
+
+impl fmt::Display for FenProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FenProblem::MissingField => FenErr::MissingField.fmt(f),
+            FenProblem::ExtraField => write!(f, "too many FEN fields"),
+            FenProblem::RankCount(count) => write!(f, "{} ranks instead of 8", count),
+            FenProblem::RankLength(rank, squares) => {
+                write!(f, "rank {} has {} squares instead of 8", rank, squares)
+            }
+            FenProblem::InvalidPiece(c) => write!(f, "invalid piece {:?}", c),
+            FenProblem::TooManyPieces(player) => write!(f, "too many {:?} pieces", player),
+            FenProblem::InvalidTurn => FenErr::InvalidTurn.fmt(f),
+            FenProblem::InvalidCastling => FenErr::InvalidCastling.fmt(f),
+            FenProblem::InvalidEnPassant => FenErr::InvalidEnPassant.fmt(f),
+            FenProblem::InvalidCounter => FenErr::InvalidCounter.fmt(f),
+            FenProblem::Position(err) => err.fmt(f),
+        }
+    }
+}
+
+impl Error for FenProblem {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            FenProblem::Position(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+// Checks a standard FEN field by field then, when the board could be read, checks the
+// position could happen in a game. Unlike `from_fen`, which stops at the first error and
+// accepts impossible positions, every problem found is reported.
+pub fn validate_fen(fen: &str) -> Result<(), Vec<FenProblem>> {
+    let fields: Vec<&str> = fen.split_whitespace().collect();
+    let mut problems = vec![];
+    if fields.len() < 4 {
+        problems.push(FenProblem::MissingField);
+    } else if fields.len() > 6 {
+        problems.push(FenProblem::ExtraField);
+    }
+    let board = fields.first().copied().unwrap_or_default();
+    let board_problems = board_problems(board);
+    let board_ok = board_problems.is_empty();
+    problems.extend(board_problems);
+    // Fields checked on an empty board, so only their syntax matters
+    let turn = fields.get(1).copied();
+    let turn_ok = matches!(turn, Some("w" | "b"));
+    if turn.is_some() && !turn_ok {
+        problems.push(FenProblem::InvalidTurn);
+    }
+    let castling = fields.get(2).copied();
+    let castling_ok = castling.is_some_and(|castling| {
+        GameManager::from_fen(&format!("8/8/8/8/8/8/8/8 w {} -", castling)).is_ok()
+    });
+    if castling.is_some() && !castling_ok {
+        problems.push(FenProblem::InvalidCastling);
+    }
+    let en_passant = fields.get(3).copied();
+    let en_passant_ok = en_passant
+        .is_some_and(|ep| GameManager::from_fen(&format!("8/8/8/8/8/8/8/8 w - {}", ep)).is_ok());
+    if en_passant.is_some() && !en_passant_ok {
+        problems.push(FenProblem::InvalidEnPassant);
+    }
+    let counters = fields
+        .get(4..6.min(fields.len()))
+        .unwrap_or_default()
+        .join(" ");
+    if GameManager::from_fen(&format!("8/8/8/8/8/8/8/8 w - - {}", counters)).is_err() {
+        problems.push(FenProblem::InvalidCounter);
+    }
+    if board_ok {
+        // Broken fields are left out, what depends on them isn't checked
+        let fen = format!(
+            "{} {} {} {}",
+            board,
+            turn.filter(|_| turn_ok).unwrap_or("w"),
+            castling.filter(|_| castling_ok).unwrap_or("-"),
+            en_passant
+                .filter(|_| en_passant_ok && turn_ok)
+                .unwrap_or("-"),
+        );
+        if let Ok(gm) = GameManager::from_fen(&fen) {
+            problems.extend(
+                position_problems(&gm)
+                    .into_iter()
+                    .filter(|err| turn_ok || *err != BuildErr::OpponentInCheck)
+                    .map(FenProblem::Position),
+            );
+        }
+    }
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(problems)
+    }
+}
+
+// Problems of the board field of a standard FEN
+fn board_problems(board: &str) -> Vec<FenProblem> {
+    let mut problems = vec![];
+    let ranks: Vec<&str> = board.split('/').collect();
+    if ranks.len() != 8 {
+        problems.push(FenProblem::RankCount(ranks.len()));
+    }
+    let mut counts = [0; 2];
+    // Ranks past the eighth are only reported by their count
+    for (i, text) in ranks.iter().take(8).enumerate() {
+        let mut squares = 0;
+        for c in text.chars() {
+            if let Some(empty) = c.to_digit(10) {
+                squares += empty as usize;
+                continue;
+            }
+            match Piece::from_char(c, Position::new(0, 0)) {
+                Some(piece) => counts[piece.player.index()] += 1,
+                None => problems.push(FenProblem::InvalidPiece(c)),
+            }
+            squares += 1;
+        }
+        if squares != 8 {
+            problems.push(FenProblem::RankLength(8 - i as u8, squares));
+        }
+    }
+    for player in [Player::White, Player::Black] {
+        if counts[player.index()] > MAX_PIECES {
+            problems.push(FenProblem::TooManyPieces(player));
+        }
+    }
+    problems
+}
+
+impl GameManager {
+    // The move counters can be left out, they then default to "0 1"
+    pub fn from_fen(fen: &str) -> Result<Self, FenErr> {
+        Self::from_sized_fen(fen, BoardSize::STANDARD)
+    }
+
+    // FEN of a board of `size`, as many ranks of as many squares as it has
+    pub fn from_sized_fen(fen: &str, size: BoardSize) -> Result<Self, FenErr> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if fields.len() < 4 {
+            return Err(FenErr::MissingField);
+        } else if fields.len() > 6 {
+            return Err(FenErr::InvalidCounter);
+        }
+        let mut gm = GameManager::empty();
+        let (whites, blacks) = parse_board(fields[0], size)?;
+        gm.whites = whites;
+        gm.blacks = blacks;
+        gm.size = size;
+        gm.turn = match fields[1] {
+            "w" => Player::White,
+            "b" => Player::Black,
+            _ => return Err(FenErr::InvalidTurn),
+        };
+        if fields[2] != "-" {
+            parse_castling(&mut gm, fields[2])?;
+        }
+        if fields[3] != "-" {
+            let ep = Position::from_algebraic(fields[3]).ok_or(FenErr::InvalidEnPassant)?;
+            gm.en_passant = Some(ep);
+        }
+        if let Some(halfmove) = fields.get(4) {
+            gm.halfmove_clock = halfmove.parse().map_err(|_| FenErr::InvalidCounter)?;
+        }
+        if let Some(fullmove) = fields.get(5) {
+            gm.fullmove_number = match fullmove.parse() {
+                Ok(n) if n > 0 => n,
+                _ => return Err(FenErr::InvalidCounter),
+            };
+        }
+        Ok(gm)
+    }
+
+    pub fn to_fen(&self) -> String {
+        let mut fen = String::new();
+        for row in (0..self.size.rows()).rev() {
+            let mut empty = 0;
+            for column in 0..self.size.columns() {
+                let pos = Position::new(row, column);
+                match self.piece_at(&pos) {
+                    Some((_, piece)) => {
+                        if empty > 0 {
+                            fen.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        fen.push(piece.to_char());
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                fen.push_str(&empty.to_string());
+            }
+            if row > 0 {
+                fen.push('/');
+            }
+        }
+        fen.push_str(match self.turn {
+            Player::White => " w ",
+            Player::Black => " b ",
+        });
+        let files = self.castling_files();
+        let rights = [
+            (
+                self.castling.white_king_side,
+                Player::White,
+                files.king_side_rook,
+            ),
+            (
+                self.castling.white_queen_side,
+                Player::White,
+                files.queen_side_rook,
+            ),
+            (
+                self.castling.black_king_side,
+                Player::Black,
+                files.king_side_rook,
+            ),
+            (
+                self.castling.black_queen_side,
+                Player::Black,
+                files.queen_side_rook,
+            ),
+        ];
+        let len = fen.len();
+        for (right, player, rook) in rights {
+            if right {
+                fen.push(self.castling_char(player, rook, files.king));
+            }
+        }
+        if fen.len() == len {
+            fen.push('-');
+        }
+        match &self.en_passant {
+            Some(ep) => fen.push_str(&format!(" {}", ep)),
+            None => fen.push_str(" -"),
+        }
+        fen.push_str(&format!(
+            " {} {}",
+            self.halfmove_clock, self.fullmove_number
+        ));
+        fen
+    }
+}
+
+impl GameManager {
+    // K or Q, or the file of the rook as in X-FEN when another rook stands further on the same
+    // side of the king
+    fn castling_char(&self, player: Player, rook: u8, king: u8) -> char {
+        let row = self.size.back_row(player);
+        let outermost = !self.pieces_of(player).iter().any(|p| {
+            p.kind == Kind::Rook
+                && p.row == row
+                && (rook > king && p.column > rook || rook < king && p.column < rook)
+        });
+        let c = match (outermost, rook > king) {
+            (true, true) => 'k',
+            (true, false) => 'q',
+            (false, _) => (b'a' + rook) as char,
+        };
+        match player {
+            Player::White => c.to_ascii_uppercase(),
+            Player::Black => c,
+        }
+    }
+}
+
+// Reads KQkq, or the files of the castling rooks as in Shredder-FEN and X-FEN. The game is
+// played as Fischer Random Chess when the king or a castling rook isn't on its usual square.
+fn parse_castling(gm: &mut GameManager, castling: &str) -> Result<(), FenErr> {
+    let mut files = CastlingFiles::STANDARD;
+    let mut chess960 = false;
+    for c in castling.chars() {
+        let player = if c.is_ascii_uppercase() {
+            Player::White
+        } else {
+            Player::Black
+        };
+        let row = gm.size.back_row(player);
+        let back_rank = |kind: Kind| {
+            gm.pieces_of(player)
+                .iter()
+                .filter(move |p| p.kind == kind && p.row == row)
+                .map(|p| p.column)
+        };
+        // Rights without their pieces are caught when the position is validated
+        let king = back_rank(Kind::King).next().unwrap_or(files.king);
+        let rook = match c.to_ascii_lowercase() {
+            'k' => back_rank(Kind::Rook)
+                .filter(|column| *column > king)
+                .max()
+                .unwrap_or(CastlingFiles::STANDARD.king_side_rook),
+            'q' => back_rank(Kind::Rook)
+                .filter(|column| *column < king)
+                .min()
+                .unwrap_or(CastlingFiles::STANDARD.queen_side_rook),
+            file @ 'a'..='h' => {
+                chess960 = true;
+                file as u8 - b'a'
+            }
+            _ => return Err(FenErr::InvalidCastling),
+        };
+        let king_side = rook > king;
+        let right = match (player, king_side) {
+            (Player::White, true) => &mut gm.castling.white_king_side,
+            (Player::White, false) => &mut gm.castling.white_queen_side,
+            (Player::Black, true) => &mut gm.castling.black_king_side,
+            (Player::Black, false) => &mut gm.castling.black_queen_side,
+        };
+        if *right {
+            return Err(FenErr::InvalidCastling);
+        }
+        *right = true;
+        files.king = king;
+        if king_side {
+            files.king_side_rook = rook;
+        } else {
+            files.queen_side_rook = rook;
+        }
+    }
+    if chess960 || files != CastlingFiles::STANDARD {
+        gm.chess960 = Some(files);
+    }
+    Ok(())
+}
+
+fn parse_board(board: &str, size: BoardSize) -> Result<(PieceList, PieceList), FenErr> {
+    let rows: Vec<&str> = board.split('/').collect();
+    if rows.len() != size.rows() as usize {
+        return Err(FenErr::InvalidBoard);
+    }
+    let (mut whites, mut blacks) = (PieceList::new(), PieceList::new());
+    for (i, text) in rows.iter().enumerate() {
+        let row = size.rows() - 1 - i as u8;
+        let mut column: u8 = 0;
+        for c in text.chars() {
+            if let Some(empty) = c.to_digit(10) {
+                column = column
+                    .checked_add(empty as u8)
+                    .filter(|column| *column <= size.columns())
+                    .ok_or(FenErr::InvalidBoard)?;
+                continue;
+            }
+            let piece =
+                Piece::from_char(c, Position::new(row, column)).ok_or(FenErr::InvalidBoard)?;
+            if column >= size.columns() {
+                return Err(FenErr::InvalidBoard);
+            }
+            let pieces = match piece.player {
+                Player::White => &mut whites,
+                Player::Black => &mut blacks,
+            };
+            if pieces.len() == MAX_PIECES {
+                return Err(FenErr::TooManyPieces);
+            }
+            pieces.push(piece);
+            column += 1;
+        }
+        if column != size.columns() {
+            return Err(FenErr::InvalidBoard);
+        }
+    }
+    Ok((whites, blacks))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{validate_fen, FenErr, FenProblem, START_FEN};
+    use crate::{
+        builder::BuildErr,
+        game_manager::GameManager,
+        moves::Move,
+        piece::{Kind, Player},
+        square::Square,
+    };
+
+    #[test]
+    fn test_to_fen() {
+        let mut gm = GameManager::new();
+        assert_eq!(START_FEN, gm.to_fen());
+        gm.play(&Move::new(Square::E2, Square::E4)).unwrap();
+        assert_eq!(
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+            gm.to_fen()
+        );
+        gm.play(&Move::new(Square::G8, Square::F6)).unwrap();
+        gm.play(&Move::new(Square::E1, Square::E2)).unwrap();
+        assert_eq!(
+            "rnbqkb1r/pppppppp/5n2/8/4P3/8/PPPPKPPP/RNBQ1BNR b kq - 2 2",
+            gm.to_fen()
+        );
+    }
+
+    #[test]
+    fn test_from_fen() {
+        let gm = GameManager::from_fen(START_FEN).unwrap();
+        assert_eq!(GameManager::new().zobrist_hash(), gm.zobrist_hash());
+        assert_eq!(20, gm.legal_moves().len());
+
+        let fen = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b Kq e3 3 12";
+        let gm = GameManager::from_fen(fen).unwrap();
+        assert_eq!(fen, gm.to_fen());
+        assert_eq!(Player::Black, gm.turn);
+        assert_eq!(Some(Square::E3.into()), gm.en_passant);
+        assert!(gm.castling.white_king_side && !gm.castling.white_queen_side);
+        assert_eq!(12, gm.fullmove_number);
+        let queen = gm.blacks.iter().find(|p| p.kind == Kind::Queen).unwrap();
+        assert_eq!((6, 4), (queen.row, queen.column));
+
+        let gm = GameManager::from_fen("8/8/8/8/8/8/8/K6k w - -").unwrap();
+        assert_eq!((0, 1), (gm.halfmove_clock, gm.fullmove_number));
+    }
+
+    #[test]
+    fn test_validate_fen() {
+        assert_eq!(Ok(()), validate_fen(START_FEN));
+        assert_eq!(
+            Err(vec![
+                FenProblem::RankLength(7, 9),
+                FenProblem::InvalidPiece('x'),
+                FenProblem::InvalidTurn,
+                FenProblem::InvalidCounter,
+            ]),
+            validate_fen("rnbqkbnr/pppppppp1/8/8/3x4/8/PPPPPPPP/RNBQKBNR white KQkq - 0 0")
+        );
+        // Two white kings, a pawn on the first rank and castling without the rook
+        assert_eq!(
+            Err(vec![
+                FenProblem::Position(BuildErr::KingCount(Player::White, 2)),
+                FenProblem::Position(BuildErr::PawnOnBackRank(Square::A1.into())),
+                FenProblem::Position(BuildErr::InvalidCastling),
+            ]),
+            validate_fen("4k3/8/8/8/8/8/8/P2KK3 w k - 0 1")
+        );
+        // Black to move while giving check, and no pawn skipped e6
+        assert_eq!(
+            Err(vec![
+                FenProblem::Position(BuildErr::InvalidEnPassant),
+                FenProblem::Position(BuildErr::OpponentInCheck),
+            ]),
+            validate_fen("4k3/8/8/8/8/8/8/r3K3 b - e6 0 1")
+        );
+        assert_eq!(
+            Err(vec![FenProblem::MissingField, FenProblem::RankCount(7)]),
+            validate_fen("8/8/8/8/8/8/8 w")
+        );
+    }
+
+    #[test]
+    fn test_from_fen_errors() {
+        let cases = [
+            ("8/8/8/8/8/8/8 w - -", FenErr::InvalidBoard),
+            ("9/8/8/8/8/8/8/8 w - -", FenErr::InvalidBoard),
+            // Long runs of digits neither overflow the column nor wrap around
+            (
+                "999999999999999999999999999999/8/8/8/8/8/8/8 w - -",
+                FenErr::InvalidBoard,
+            ),
+            ("8/8/8/8/8/8/8/7X w - -", FenErr::InvalidBoard),
+            (
+                "pppppppp/pppppppp/p7/8/8/8/8/8 w - -",
+                FenErr::TooManyPieces,
+            ),
+            ("8/8/8/8/8/8/8/8 x - -", FenErr::InvalidTurn),
+            ("8/8/8/8/8/8/8/8 w KK -", FenErr::InvalidCastling),
+            ("8/8/8/8/8/8/8/8 w - e9", FenErr::InvalidEnPassant),
+            ("8/8/8/8/8/8/8/8 w - - 0 0", FenErr::InvalidCounter),
+            ("8/8/8/8/8/8/8/8 w", FenErr::MissingField),
+        ];
+        for (fen, err) in cases {
+            assert_eq!(Some(err), GameManager::from_fen(fen).err(), "{}", fen);
+        }
+    }
+}
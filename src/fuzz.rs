@@ -0,0 +1,199 @@
+use crate::{
+    game_manager::GameManager,
+    moves::Move,
+    piece::{Kind, Position},
+};
+
+// Longest game generated, in plies
+pub const MAX_PLIES: usize = 100;
+
+const PROMOTIONS: [Kind; 4] = [Kind::Queen, Kind::Rook, Kind::Bishop, Kind::Knight];
+
+// Games are played from the standard start or a Chess960 one, each pick choosing among the
+// legal moves. Fewer picks give a shorter game, so shrinking keeps the values valid.
+fn play_picks(chess960: Option<u16>, picks: &[u32]) -> GameManager {
+    let mut gm = chess960.and_then(GameManager::chess960).unwrap_or_default();
+    for pick in picks {
+        if gm.status().is_over() {
+            break;
+        }
+        let moves = gm.legal_moves();
+        if moves.is_empty() || gm.play(&moves[*pick as usize % moves.len()]).is_err() {
+            break;
+        }
+    }
+    gm
+}
+
+// Promotions only on the first and last rows
+fn make_move(from: Position, to: Position, promotion: Option<Kind>) -> Move {
+    Move {
+        from,
+        to,
+        promotion: promotion.filter(|_| to.row == 0 || to.row == 7),
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impls {
+    use arbitrary::{Arbitrary, Result, Unstructured};
+
+    use super::{make_move, play_picks, MAX_PLIES, PROMOTIONS};
+    use crate::{game_manager::GameManager, moves::Move, piece::Position};
+
+    impl<'a> Arbitrary<'a> for Position {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            Ok(Position::new(
+                u.int_in_range(0..=7)?,
+                u.int_in_range(0..=7)?,
+            ))
+        }
+    }
+
+    impl<'a> Arbitrary<'a> for Move {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let from = Position::arbitrary(u)?;
+            let mut to = Position::arbitrary(u)?;
+            if to == from {
+                to.column = (to.column + 1) % 8;
+            }
+            let promotion = if u.arbitrary()? {
+                Some(*u.choose(&PROMOTIONS)?)
+            } else {
+                None
+            };
+            Ok(make_move(from, to, promotion))
+        }
+    }
+
+    impl<'a> Arbitrary<'a> for GameManager {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let chess960 = if u.ratio(1, 8)? {
+                Some(u.int_in_range(0..=959)?)
+            } else {
+                None
+            };
+            let plies = u.int_in_range(0..=MAX_PLIES)?;
+            let picks = (0..plies)
+                .map(|_| u.arbitrary())
+                .collect::<Result<Vec<u32>>>()?;
+            Ok(play_picks(chess960, &picks))
+        }
+    }
+}
+
+#[cfg(feature = "proptest")]
+mod proptest_impls {
+    use proptest::{
+        arbitrary::{any, Arbitrary},
+        collection, option, sample,
+        strategy::{BoxedStrategy, Strategy},
+    };
+
+    use super::{make_move, play_picks, MAX_PLIES, PROMOTIONS};
+    use crate::{game_manager::GameManager, moves::Move, piece::Position};
+
+    impl Arbitrary for Position {
+        type Parameters = ();
+        type Strategy = BoxedStrategy<Self>;
+
+        fn arbitrary_with(_: ()) -> Self::Strategy {
+            (0..8u8, 0..8u8)
+                .prop_map(|(row, column)| Position::new(row, column))
+                .boxed()
+        }
+    }
+
+    impl Arbitrary for Move {
+        type Parameters = ();
+        type Strategy = BoxedStrategy<Self>;
+
+        fn arbitrary_with(_: ()) -> Self::Strategy {
+            let promotion = option::of(sample::select(&PROMOTIONS[..]));
+            (any::<Position>(), any::<Position>(), promotion)
+                .prop_filter("null move", |(from, to, _)| from != to)
+                .prop_map(|(from, to, promotion)| make_move(from, to, promotion))
+                .boxed()
+        }
+    }
+
+    impl Arbitrary for GameManager {
+        type Parameters = ();
+        type Strategy = BoxedStrategy<Self>;
+
+        fn arbitrary_with(_: ()) -> Self::Strategy {
+            let chess960 = option::weighted(0.125, 0..960u16);
+            let picks = collection::vec(any::<u32>(), 0..=MAX_PLIES);
+            (chess960, picks)
+                .prop_map(|(chess960, picks)| play_picks(chess960, &picks))
+                .boxed()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn test_arbitrary() {
+        use arbitrary::{Arbitrary, Unstructured};
+
+        use crate::{game_manager::GameManager, moves::Move};
+
+        let mut seed = 0x9e37_79b9_7f4a_7c15_u64;
+        let bytes: Vec<u8> = (0..4096)
+            .map(|_| {
+                seed ^= seed << 13;
+                seed ^= seed >> 7;
+                seed ^= seed << 17;
+                seed as u8
+            })
+            .collect();
+        let mut u = Unstructured::new(&bytes);
+        for _ in 0..16 {
+            let gm = GameManager::arbitrary(&mut u).unwrap();
+            assert_eq!(
+                gm.to_fen(),
+                GameManager::from_fen(&gm.to_fen()).unwrap().to_fen()
+            );
+            let mv = Move::arbitrary(&mut u).unwrap();
+            assert_ne!(mv.from, mv.to);
+            assert_eq!(Some(mv), Move::from_uci(&mv.to_uci()));
+        }
+    }
+
+    #[cfg(feature = "proptest")]
+    mod properties {
+        use proptest::prelude::*;
+
+        use crate::{game_manager::GameManager, moves::Move};
+
+        proptest! {
+            #![proptest_config(ProptestConfig::with_cases(32))]
+
+            #[test]
+            fn test_fen_round_trip(gm in any::<GameManager>()) {
+                let fen = gm.to_fen();
+                prop_assert_eq!(&fen, &GameManager::from_fen(&fen).unwrap().to_fen());
+            }
+
+            #[test]
+            fn test_uci_round_trip(mv in any::<Move>()) {
+                prop_assert_eq!(Some(mv), Move::from_uci(&mv.to_uci()));
+            }
+
+            #[test]
+            fn test_make_unmake(gm in any::<GameManager>()) {
+                let player = gm.turn;
+                let mut after = gm.clone();
+                for mv in gm.legal_moves() {
+                    let undo = after.make_move(&mv);
+                    prop_assert!(!after.is_in_check(&player), "{}", mv.to_uci());
+                    after.unmake_move(undo);
+                    prop_assert_eq!(gm.to_fen(), after.to_fen());
+                }
+                prop_assert!(gm == after);
+            }
+        }
+    }
+}
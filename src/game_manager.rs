@@ -1,191 +1,1768 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt,
+    hash::{Hash, Hasher},
+    sync::{Arc, OnceLock},
+    time::{Duration, SystemTime},
+};
+
+#[cfg(feature = "clock")]
+use crate::clock::Clock;
 use crate::{
-    move_validators::is_valid_move,
-    piece::{Kind, Piece, Player, Position, MAX_COLUMN, MAX_ROW},
+    attacks::{for_each_attack, AttackMap},
+    chess960::CastlingFiles,
+    events::Listeners,
+    move_validators::{is_pawn_attack, is_valid_move},
+    moves::Move,
+    piece::{BoardSize, Kind, Piece, PieceList, Player, Position},
+    premove::Pending,
+    rules::{self, Rules},
+    zobrist,
 };
 
 #[derive(Debug)]
 pub enum MoveErr {
     SamePosition,
-    FriendlyFire,
-    InvalidMove,
-    PieceBlocking,
+    // Holds the kind of the piece of the same side standing on the target square
+    FriendlyFire {
+        occupied_by: Kind,
+    },
+    InvalidMove {
+        piece: Kind,
+        from: Position,
+        to: Position,
+    },
+    // Holds the first square in the way
+    PieceBlocking {
+        at: Position,
+    },
+    // The side moving has no piece on the square
+    NoPiece {
+        at: Position,
+    },
+    // Move text that can't be read
+    Syntax,
+    KingInCheck,
+    WrongTurn,
+    // The board is being edited, see `enter_setup`
+    SetupMode,
+    // The game was resigned, abandoned or drawn off the board
+    GameOver,
 }
 
-#[derive(Debug)]
+impl fmt::Display for MoveErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            MoveErr::SamePosition => "the piece has to leave its square",
+            MoveErr::FriendlyFire { occupied_by } => {
+                return write!(f, "a {:?} of the same side stands there", occupied_by)
+            }
+            MoveErr::InvalidMove { piece, from, to } => {
+                return write!(f, "a {:?} can't move from {} to {}", piece, from, to)
+            }
+            MoveErr::PieceBlocking { at } => return write!(f, "a piece is in the way on {}", at),
+            MoveErr::NoPiece { at } => return write!(f, "no piece to move on {}", at),
+            MoveErr::Syntax => "unreadable move",
+            MoveErr::KingInCheck => "the move leaves the king in check",
+            MoveErr::WrongTurn => "not this side's turn",
+            MoveErr::SetupMode => "the board is being edited",
+            MoveErr::GameOver => "the game is over",
+        };
+        f.write_str(message)
+    }
+}
+
+impl Error for MoveErr {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CastlingRights {
+    pub white_king_side: bool,
+    pub white_queen_side: bool,
+    pub black_king_side: bool,
+    pub black_queen_side: bool,
+}
+
+impl CastlingRights {
+    pub const fn all() -> Self {
+        Self {
+            white_king_side: true,
+            white_queen_side: true,
+            black_king_side: true,
+            black_queen_side: true,
+        }
+    }
+
+    pub const fn none() -> Self {
+        Self {
+            white_king_side: false,
+            white_queen_side: false,
+            black_king_side: false,
+            black_queen_side: false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GameStatus {
+    Ongoing,
+    // Holds the winner
+    Checkmate(Player),
+    Stalemate,
+    // Holds the winner, the opponent's clock ran out
+    TimeForfeit(Player),
+    // The clock of a side ran out but the opponent can't checkmate anymore
+    TimeoutDraw,
+    // Holds the winner
+    Resignation(Player),
+    // Holds the winner, the opponent left the game
+    Abandonment(Player),
+    // Holds the winner, decided by the rules of a variant
+    VariantWin(Player),
+    DrawByAgreement,
+    DrawByRule(DrawRule),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DrawRule {
+    FiftyMoves,
+    ThreefoldRepetition,
+    // Neither side can checkmate anymore
+    InsufficientMaterial,
+}
+
+impl GameStatus {
+    pub fn is_over(&self) -> bool {
+        *self != GameStatus::Ongoing
+    }
+
+    pub fn winner(&self) -> Option<Player> {
+        match self {
+            GameStatus::Checkmate(winner)
+            | GameStatus::TimeForfeit(winner)
+            | GameStatus::Resignation(winner)
+            | GameStatus::Abandonment(winner)
+            | GameStatus::VariantWin(winner) => Some(*winner),
+            _ => None,
+        }
+    }
+
+    // Value of the PGN `Result` tag
+    pub fn result(&self) -> &'static str {
+        match (self, self.winner()) {
+            (GameStatus::Ongoing, _) => "*",
+            (_, Some(Player::White)) => "1-0",
+            (_, Some(Player::Black)) => "0-1",
+            (_, None) => "1/2-1/2",
+        }
+    }
+
+    // Value of the PGN `Termination` tag
+    pub fn termination(&self) -> &'static str {
+        match self {
+            GameStatus::Ongoing => "unterminated",
+            GameStatus::TimeForfeit(_) | GameStatus::TimeoutDraw => "time forfeit",
+            GameStatus::Abandonment(_) => "abandoned",
+            _ => "normal",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UndoInfo {
+    pub mv: Move,
+    pub captured: Option<Piece>,
+    pub castling: CastlingRights,
+    pub en_passant: Option<Position>,
+    pub halfmove_clock: u32,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MoveFlags {
+    pub capture: bool,
+    pub en_passant: bool,
+    pub castling: bool,
+    pub promotion: bool,
+    pub check: bool,
+    pub checkmate: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub check_kind: Option<CheckKind>,
+}
+
+// How a move gives check: by the piece that moved, by one it uncovered, or by both
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CheckKind {
+    Direct,
+    Discovered,
+    Double,
+}
+
+// Leaf counts of `perft_stats`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PerftStats {
+    pub nodes: u64,
+    pub captures: u64,
+    pub en_passants: u64,
+    pub castles: u64,
+    pub promotions: u64,
+    pub checks: u64,
+    pub checkmates: u64,
+}
+
+impl PerftStats {
+    fn add(&mut self, flags: &MoveFlags) {
+        self.nodes += 1;
+        self.captures += flags.capture as u64;
+        self.en_passants += flags.en_passant as u64;
+        self.castles += flags.castling as u64;
+        self.promotions += flags.promotion as u64;
+        self.checks += flags.check as u64;
+        self.checkmates += flags.checkmate as u64;
+    }
+}
+
+// Entry of the game history, kept by `play` for every move
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MoveRecord {
+    pub mv: Move,
+    #[cfg(feature = "notation")]
+    pub san: String,
+    pub captured: Option<Piece>,
+    pub flags: MoveFlags,
+    // Zobrist hash of the position reached by the move
+    pub position_hash: u64,
+    // When the move was played, the wall clock unless given to `play_at`
+    pub timestamp: SystemTime,
+    // Time since the previous move, `None` for the first move of the history
+    pub think_time: Option<Duration>,
+    // Time left to the player once the clock was pressed, `None` in untimed games
+    #[cfg(feature = "clock")]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub clock: Option<Duration>,
+    pub(crate) undo: UndoInfo,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GameManager {
-    pub whites: Vec<Piece>,
-    pub blacks: Vec<Piece>,
+    pub whites: PieceList,
+    pub blacks: PieceList,
     pub turn: Player,
+    pub castling: CastlingRights,
+    // Square skipped over by a pawn double step on the previous move
+    pub en_passant: Option<Position>,
+    // Moves since the last capture or pawn move, for the fifty-move rule
+    pub halfmove_clock: u32,
+    pub fullmove_number: u32,
+    // Moves played through `play`, oldest first
+    pub(crate) history: Vec<MoveRecord>,
+    // Moves taken back by `undo`, the next one to redo last
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) redo: Vec<Move>,
+    // Squares attacked by each side with the placement they were built for, built on first
+    // use then kept up to date by the moves
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) attacks: OnceLock<(u64, [AttackMap; 2])>,
+    // Legal moves of the position with the given hash, dropped by every move
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) legal_cache: OnceLock<(u64, Vec<Move>)>,
+    // Pressed by `play` and `move_piece` after every move
+    #[cfg(feature = "clock")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub clock: Option<Clock>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) listeners: Listeners,
+    // Set while the board is edited, moves can't be played then
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) setup: bool,
+    // End of the game decided off the board, e.g. a resignation
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) outcome: Option<GameStatus>,
+    // Side whose draw offer is waiting for an answer
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) draw_offer: Option<Player>,
+    // Free play, see `enter_lenient_mode`
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) lenient: bool,
+    // Premoves and conditional moves waiting for their turn
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) pending: Pending,
+    // Starting files of the king and rooks in Fischer Random Chess, `None` in standard chess
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) chess960: Option<CastlingFiles>,
+    // Standard chess unless a variant is set with `set_rules`
+    #[cfg_attr(feature = "serde", serde(skip, default = "rules::standard"))]
+    pub(crate) rules: Arc<dyn Rules>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) size: BoardSize,
+}
+
+// Squares of the king and the rook once a castling move is played
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Castle {
+    pub king_to: Position,
+    pub rook_from: Position,
+    pub rook_to: Position,
 }
 
 impl GameManager {
     pub fn new() -> Self {
-        let whites = create_whites();
-        let blacks = create_blacks_from_whites(&whites);
         Self {
-            whites,
-            blacks,
+            whites: START_WHITES,
+            blacks: START_BLACKS,
+            turn: Player::White,
+            castling: CastlingRights::all(),
+            en_passant: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            history: Vec::new(),
+            redo: Vec::new(),
+            attacks: OnceLock::new(),
+            legal_cache: OnceLock::new(),
+            #[cfg(feature = "clock")]
+            clock: None,
+            listeners: Listeners::default(),
+            setup: false,
+            outcome: None,
+            draw_offer: None,
+            lenient: false,
+            pending: Pending::default(),
+            chess960: None,
+            rules: rules::standard(),
+            size: BoardSize::STANDARD,
+        }
+    }
+
+    pub fn empty() -> Self {
+        Self {
+            whites: PieceList::new(),
+            blacks: PieceList::new(),
             turn: Player::White,
+            castling: CastlingRights::none(),
+            en_passant: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            history: Vec::new(),
+            redo: Vec::new(),
+            attacks: OnceLock::new(),
+            legal_cache: OnceLock::new(),
+            #[cfg(feature = "clock")]
+            clock: None,
+            listeners: Listeners::default(),
+            setup: false,
+            outcome: None,
+            draw_offer: None,
+            lenient: false,
+            pending: Pending::default(),
+            chess960: None,
+            rules: rules::standard(),
+            size: BoardSize::STANDARD,
+        }
+    }
+
+    pub fn zobrist_hash(&self) -> u64 {
+        let mut hash = self.placement_hash();
+        let rights = [
+            self.castling.white_king_side,
+            self.castling.white_queen_side,
+            self.castling.black_king_side,
+            self.castling.black_queen_side,
+        ];
+        for (i, right) in rights.iter().enumerate() {
+            if *right {
+                hash ^= zobrist::castle_key(i).unwrap_or(0);
+            }
+        }
+        // Polyglot only hashes the en passant file when the capture is actually available
+        if let Some(ep) = &self.en_passant {
+            for p in self.pieces_of(self.turn) {
+                if p.kind == Kind::Pawn && is_pawn_attack(p, ep) {
+                    hash ^= zobrist::en_passant_key(ep.column).unwrap_or(0);
+                    break;
+                }
+            }
+        }
+        if self.turn == Player::White {
+            hash ^= zobrist::turn_key();
+        }
+        hash
+    }
+
+    // Piece on every square, indexed like `Square`
+    pub(crate) fn board(&self) -> [Option<(Player, Kind)>; 64] {
+        let mut board = [None; 64];
+        for (player, p) in self.pieces() {
+            if let Some(index) = Position::from_piece(p).index() {
+                board[index as usize] = Some((player, p.kind));
+            }
         }
+        board
     }
 
     pub fn swap_turn(&mut self) {
-        self.turn = match self.turn {
-            Player::Black => Player::White,
-            Player::White => Player::Black,
-        };
+        self.turn = self.turn.opponent();
+        self.legal_cache = OnceLock::new();
+    }
+
+    // Standard unless the game was set up with `from_sized_fen`
+    pub fn board_size(&self) -> BoardSize {
+        self.size
+    }
+
+    pub fn pieces_of(&self, player: Player) -> &PieceList {
+        match player {
+            Player::Black => &self.blacks,
+            Player::White => &self.whites,
+        }
+    }
+
+    // Every piece on the board with its side, the white ones first
+    pub fn pieces(&self) -> impl Iterator<Item = (Player, &Piece)> {
+        let whites = self.whites.iter().map(|p| (Player::White, p));
+        whites.chain(self.blacks.iter().map(|p| (Player::Black, p)))
+    }
+
+    pub fn pieces_mut(&mut self) -> impl Iterator<Item = (Player, &mut Piece)> {
+        let whites = self.whites.iter_mut().map(|p| (Player::White, p));
+        whites.chain(self.blacks.iter_mut().map(|p| (Player::Black, p)))
+    }
+
+    // Zobrist keys of the pieces alone
+    fn placement_hash(&self) -> u64 {
+        self.pieces().fold(0, |hash, (_, piece)| {
+            hash ^ zobrist::piece_key(piece).unwrap_or(0)
+        })
+    }
+
+    pub fn attacked_squares(&self, player: &Player) -> AttackMap {
+        self.with_attack_maps(|maps| maps[player.index()].clone())
+    }
+
+    // Pieces edited by hand are noticed by the next query, this only frees the caches
+    pub fn refresh_attacks(&mut self) {
+        self.attacks = OnceLock::new();
+        self.legal_cache = OnceLock::new();
+    }
+
+    // Runs `f` on the cached maps, or on new ones when the pieces were edited since they were
+    // built
+    fn with_attack_maps<R>(&self, f: impl FnOnce(&[AttackMap; 2]) -> R) -> R {
+        let key = self.placement_hash();
+        let (cached, maps) = self.attacks.get_or_init(|| (key, self.build_attack_maps()));
+        if *cached == key {
+            f(maps)
+        } else {
+            f(&self.build_attack_maps())
+        }
+    }
+
+    fn build_attack_maps(&self) -> [AttackMap; 2] {
+        let mut maps = [AttackMap::new(), AttackMap::new()];
+        for (player, piece) in self.pieces() {
+            let map = &mut maps[player.index()];
+            for_each_attack(
+                piece,
+                |pos| !self.is_empty(pos),
+                |pos| {
+                    if self.size.contains(&pos) {
+                        map.add(&pos, 1)
+                    }
+                },
+            );
+        }
+        maps
+    }
+
+    // Adds `amount` to the attacks of every piece standing on or looking at a `changed` square
+    fn update_attacks(&self, maps: &mut [AttackMap; 2], changed: &[Position], amount: i8) {
+        for (_, piece) in self.pieces() {
+            let start = Position::from_piece(piece);
+            let slider = matches!(piece.kind, Kind::Queen | Kind::Rook | Kind::Bishop);
+            let affected = changed.iter().any(|pos| {
+                pos == &start
+                    || (slider && is_valid_move(piece, pos) && !self.is_piece_blocking(piece, pos))
+            });
+            if affected {
+                let map = &mut maps[piece.player.index()];
+                for_each_attack(
+                    piece,
+                    |pos| !self.is_empty(pos),
+                    |pos| {
+                        if self.size.contains(&pos) {
+                            map.add(&pos, amount)
+                        }
+                    },
+                );
+            }
+        }
+    }
+
+    // Runs `change`, which may only alter the occupancy of the `changed` squares, while
+    // keeping the attack maps in sync if they were already built for the current pieces.
+    fn with_attack_update<R>(
+        &mut self,
+        changed: &[Position],
+        change: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        self.legal_cache = OnceLock::new();
+        let key = self.placement_hash();
+        let mut maps = self
+            .attacks
+            .take()
+            .filter(|(cached, _)| *cached == key)
+            .map(|(_, maps)| maps);
+        if let Some(maps) = &mut maps {
+            self.update_attacks(maps, changed, -1);
+        }
+        let result = change(self);
+        if let Some(mut maps) = maps {
+            self.update_attacks(&mut maps, changed, 1);
+            self.attacks = OnceLock::from((self.placement_hash(), maps));
+        }
+        result
     }
 
     fn is_valid_move(&self, piece: &Piece, end: &Position) -> Option<MoveErr> {
         if piece.row == end.row && piece.column == end.column {
             return Some(MoveErr::SamePosition);
-        } else if is_friendly_fire(self, end) {
-            return Some(MoveErr::FriendlyFire);
-        } else if !is_valid_move(piece, end, &self.turn) {
-            return Some(MoveErr::InvalidMove);
-        } else if self.is_piece_blocking(piece, end) {
-            return Some(MoveErr::PieceBlocking);
+        } else if let Some(occupied_by) = friendly_fire(self, piece, end) {
+            return Some(MoveErr::FriendlyFire { occupied_by });
+        } else if !self.is_valid_geometry(piece, end) {
+            return Some(MoveErr::InvalidMove {
+                piece: piece.kind,
+                from: Position::from_piece(piece),
+                to: *end,
+            });
         }
-        None
+        self.blocking_piece(piece, end)
+            .map(|at| MoveErr::PieceBlocking { at })
+    }
+
+    // The way the piece moves, pawns step twice from the row the board allows it on
+    fn is_valid_geometry(&self, piece: &Piece, end: &Position) -> bool {
+        if piece.kind != Kind::Pawn || self.size == BoardSize::STANDARD {
+            return is_valid_move(piece, end);
+        }
+        let forward = match piece.player {
+            Player::White => end.row as i16 - piece.row as i16,
+            Player::Black => piece.row as i16 - end.row as i16,
+        };
+        match (forward, end.column.abs_diff(piece.column)) {
+            (1, 0 | 1) => true,
+            (2, 0) => self.size.double_step_row(piece.player) == Some(piece.row),
+            _ => false,
+        }
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(piece = %piece), err(level = "debug"))
+    )]
+    pub fn move_piece(&mut self, piece: &Piece, pos: impl Into<Position>) -> Result<(), MoveErr> {
+        let pos = pos.into();
+        if self.setup {
+            return Err(MoveErr::SetupMode);
+        } else if self.outcome.is_some() {
+            return Err(MoveErr::GameOver);
+        } else if piece.player != self.turn {
+            return Err(MoveErr::WrongTurn);
+        } else if !self.move_suggestion_iter(piece).any(|p| p == pos) {
+            return Err(self
+                .is_valid_move(piece, &pos)
+                .unwrap_or(MoveErr::InvalidMove {
+                    piece: piece.kind,
+                    from: Position::from_piece(piece),
+                    to: pos,
+                }));
+        }
+        let mut mv = Move::new(Position::from_piece(piece), pos);
+        if piece.kind == Kind::Pawn && mv.to.row == self.size.promotion_row(piece.player) {
+            mv.promotion = Some(Kind::Queen);
+        }
+        if self.find_piece(&mv.from).is_none() {
+            return Err(MoveErr::NoPiece { at: mv.from });
+        } else if self.leaves_king_in_check(&mv) {
+            return Err(MoveErr::KingInCheck);
+        }
+        self.apply_move(&mv);
+        self.press_clock(piece.player);
+        Ok(())
     }
 
-    pub fn move_piece(&mut self, piece: &Piece, pos: Position) -> Result<(), MoveErr> {
-        if let Some(err) = self.is_valid_move(piece, &pos) {
-            return Err(err);
+    #[cfg(feature = "clock")]
+    fn press_clock(&mut self, player: Player) {
+        if let Some(clock) = &mut self.clock {
+            clock.press_for(player);
         }
+    }
+
+    #[cfg(not(feature = "clock"))]
+    fn press_clock(&mut self, _: Player) {}
+
+    // Validates and plays `mv` for the side to move, keeping it in the game history
+    pub fn play(&mut self, mv: &Move) -> Result<(), MoveErr> {
+        self.play_at(mv, SystemTime::now())
+    }
+
+    // `play` with the time of the move given by the caller, e.g. a server receiving it. With
+    // the tracing feature, rejected moves are logged at debug level along with the reason.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "debug",
+            skip_all,
+            fields(mv = %mv.to_uci(), turn = ?self.turn),
+            err(level = "debug")
+        )
+    )]
+    pub fn play_at(&mut self, mv: &Move, timestamp: SystemTime) -> Result<(), MoveErr> {
+        let player = self.turn;
+        self.record_move(mv, timestamp)?;
+        self.press_clock(player);
+        #[cfg(feature = "clock")]
+        if let (Some(clock), Some(record)) = (&self.clock, self.history.last_mut()) {
+            record.clock = Some(clock.remaining(&player));
+        }
+        self.emit_move_events();
+        self.run_pending(mv);
+        Ok(())
+    }
+
+    fn record_move(&mut self, mv: &Move, timestamp: SystemTime) -> Result<(), MoveErr> {
+        if self.setup {
+            return Err(MoveErr::SetupMode);
+        } else if self.outcome.is_some() {
+            return Err(MoveErr::GameOver);
+        } else if self.lenient {
+            // Any side may move, the move is played as if it was its turn
+            self.turn = self.lenient_mover(mv)?;
+        } else if self.find_piece(&mv.from).is_none() {
+            let enemy = self
+                .pieces_of(self.turn.opponent())
+                .iter()
+                .any(|p| Position::from_piece(p) == mv.from);
+            return Err(if enemy {
+                MoveErr::WrongTurn
+            } else {
+                MoveErr::NoPiece { at: mv.from }
+            });
+        } else if !self.legal_moves_iter().any(|m| m == *mv) {
+            let pseudo_legal = self.pieces_of(self.turn).iter().any(|p| {
+                Position::from_piece(p) == mv.from
+                    && self.move_suggestion_iter(p).any(|to| to == mv.to)
+            });
+            return Err(if pseudo_legal && self.leaves_king_in_check(mv) {
+                MoveErr::KingInCheck
+            } else {
+                self.illegal_move_err(mv)
+            });
+        }
+        // The move is legal, SAN can't fail. Its suffix comes from the flags of the move.
+        #[cfg(feature = "notation")]
+        let mut san = self.algebraic(mv, false).unwrap_or_else(|_| mv.to_uci());
+        // Moving instead of answering declines a draw offer
+        if self.draw_offer == Some(self.turn.opponent()) {
+            self.draw_offer = None;
+        }
+        let (undo, flags) = self.make_flagged_move(mv);
+        #[cfg(feature = "notation")]
+        san.extend(flags.check_suffix());
+        #[cfg(feature = "tracing")]
+        tracing::debug!(?flags, "move played");
+        self.history.push(MoveRecord {
+            mv: *mv,
+            #[cfg(feature = "notation")]
+            san,
+            captured: undo.captured,
+            flags,
+            position_hash: self.zobrist_hash(),
+            timestamp,
+            think_time: self
+                .history
+                .last()
+                .and_then(|previous| timestamp.duration_since(previous.timestamp).ok()),
+            #[cfg(feature = "clock")]
+            clock: None,
+            undo,
+        });
+        if self.redo.last() == Some(mv) {
+            self.redo.pop();
+        } else {
+            self.redo.clear();
+        }
+        Ok(())
+    }
+
+    // Plays every move or none of them: on the first illegal one the game is rolled back to
+    // where it was and the index of the move is reported. Meant to sync a game from an
+    // external source, the clock isn't pressed and the listeners aren't run.
+    pub fn apply_moves(&mut self, moves: &[Move]) -> Result<(), (usize, MoveErr)> {
+        let redo = self.redo.clone();
+        for (i, mv) in moves.iter().enumerate() {
+            if let Err(err) = self.record_move(mv, SystemTime::now()) {
+                for _ in 0..i {
+                    self.undo();
+                }
+                self.redo = redo;
+                return Err((i, err));
+            }
+        }
+        Ok(())
+    }
+
+    // Takes back the last move played through `play`, the clock is left untouched. A game
+    // ended off the board goes on again.
+    pub fn undo(&mut self) -> Option<Move> {
+        let record = self.history.pop()?;
+        self.outcome = None;
+        self.unmake_move(record.undo);
+        self.redo.push(record.mv);
+        Some(record.mv)
+    }
+
+    // Plays again the last move taken back, a new move played in between forgets them
+    pub fn redo(&mut self) -> Option<Move> {
+        let mv = *self.redo.last()?;
+        self.record_move(&mv, SystemTime::now()).ok()?;
+        Some(mv)
+    }
+
+    // Back to the standard start position for a new game: history, outcome, pending moves
+    // and edits are dropped and the clock restarts from its time control. The listeners and
+    // the rules are kept.
+    pub fn reset(&mut self) {
+        #[cfg(feature = "clock")]
+        let clock = self
+            .clock
+            .as_ref()
+            .map(|clock| Clock::new(clock.time_control().clone()));
+        *self = GameManager {
+            listeners: std::mem::take(&mut self.listeners),
+            rules: self.rules.clone(),
+            #[cfg(feature = "clock")]
+            clock,
+            ..GameManager::new()
+        };
+    }
+
+    pub fn history(&self) -> &[MoveRecord] {
+        &self.history
+    }
+
+    // Pieces taken by `player` during the game, in the order they were captured
+    pub fn captured_by(&self, player: &Player) -> Vec<Piece> {
+        self.history
+            .iter()
+            .filter_map(|record| record.captured)
+            .filter(|piece| piece.player != *player)
+            .collect()
+    }
+
+    // Material on the board of `player` minus the one of the opponent, in pawns
+    pub fn material_difference(&self, player: &Player) -> i32 {
+        let material = |player: Player| -> i32 {
+            self.pieces_of(player)
+                .iter()
+                .map(|p| p.kind.material_value() as i32)
+                .sum()
+        };
+        material(*player) - material(player.opponent())
+    }
+
+    // The position before the first move of the history, the game not started yet: no
+    // outcome, no draw offer and the clock back to its time control, so the moves can be
+    // played again from it
+    pub fn initial_position(&self) -> GameManager {
+        let mut gm = self.clone();
+        while let Some(record) = gm.history.pop() {
+            gm.unmake_move(record.undo);
+        }
+        gm.outcome = None;
+        gm.draw_offer = None;
+        #[cfg(feature = "clock")]
+        if let Some(clock) = &mut gm.clock {
+            *clock = Clock::new(clock.time_control().clone());
+        }
+        gm
+    }
+
+    // Index of the piece of the side to move standing on `pos`
+    fn find_piece(&self, pos: &Position) -> Option<usize> {
+        self.pieces_of(self.turn)
+            .iter()
+            .position(|p| p.row == pos.row && p.column == pos.column)
+    }
+
+    // Plays `mv` for the side to move without any validation nor changing the turn,
+    // returns the captured piece if any.
+    fn apply_move(&mut self, mv: &Move) -> Option<Piece> {
+        let index = self.find_piece(&mv.from)?;
+        let kind = self.pieces_of(self.turn)[index].kind;
+        let castle = self.castle(kind, mv, &self.castling);
+        let changed = changed_squares(kind, mv, self.en_passant, castle);
+        let captured = self.with_attack_update(&changed, |gm| gm.move_pieces(index, mv, castle));
+        self.en_passant = None;
+        if kind == Kind::Pawn && mv.from.row.abs_diff(mv.to.row) == 2 {
+            let row = (mv.from.row + mv.to.row) / 2;
+            self.en_passant = Some(Position::new(row, mv.from.column));
+        }
+        self.update_castling_rights(mv);
+        if kind == Kind::Pawn || captured.is_some() {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock += 1;
+        }
+        if self.turn == Player::Black {
+            self.fullmove_number += 1;
+        }
+        captured
+    }
+
+    fn move_pieces(&mut self, index: usize, mv: &Move, castle: Option<Castle>) -> Option<Piece> {
         let (pieces, enemy) = match self.turn {
             Player::Black => (&mut self.blacks, &mut self.whites),
             Player::White => (&mut self.whites, &mut self.blacks),
         };
+        if let Some(castle) = castle {
+            // The king may land where the rook stands, find the rook first
+            let rook = pieces
+                .iter()
+                .position(|p| p.kind == Kind::Rook && Position::from_piece(p) == castle.rook_from);
+            pieces[index].r#move(castle.king_to.row, castle.king_to.column);
+            if let Some(rook) = rook {
+                pieces[rook].r#move(castle.rook_to.row, castle.rook_to.column);
+            }
+            return None;
+        }
+        let kind = pieces[index].kind;
+        let mut captured_pos = mv.to;
+        if kind == Kind::Pawn && self.en_passant.as_ref() == Some(&mv.to) {
+            captured_pos = Position::new(mv.from.row, mv.to.column);
+        }
+        let mut captured = None;
         for (i, p) in enemy.iter().enumerate() {
-            if p.row == pos.row && p.column == pos.column {
-                enemy.swap_remove(i);
+            if p.row == captured_pos.row && p.column == captured_pos.column {
+                captured = Some(enemy.swap_remove(i));
                 break;
             }
         }
-        for p in pieces {
-            if p.row == piece.row && p.column == piece.column {
-                p.column = pos.column;
-                p.row = pos.row;
-                return Ok(());
-            }
+        let piece = &mut pieces[index];
+        piece.r#move(mv.to.row, mv.to.column);
+        if let Some(promotion) = mv.promotion {
+            piece.kind = promotion;
         }
-        Err(MoveErr::InvalidMove)
+        captured
     }
 
-    pub fn move_suggestion(&self, piece: &Piece) -> Vec<Position> {
-        let mut positions = vec![];
-        for ci in 0..MAX_COLUMN {
-            for ri in 0..MAX_ROW {
-                let pos = Position::new(ri, ci);
-                if let None = self.is_valid_move(piece, &pos) {
-                    positions.push(pos.clone());
-                }
+    // Plays a legal move for the side to move and hands back what is needed to revert it.
+    // The move is not validated, use `legal_moves` to get valid ones.
+    pub fn make_move(&mut self, mv: &Move) -> UndoInfo {
+        let castling = self.castling;
+        let en_passant = self.en_passant;
+        let halfmove_clock = self.halfmove_clock;
+        let captured = self.apply_move(mv);
+        self.swap_turn();
+        UndoInfo {
+            mv: *mv,
+            captured,
+            castling,
+            en_passant,
+            halfmove_clock,
+        }
+    }
+
+    // `make_move` also telling what kind of move it was
+    pub(crate) fn make_flagged_move(&mut self, mv: &Move) -> (UndoInfo, MoveFlags) {
+        let moved = self
+            .pieces_of(self.turn)
+            .iter()
+            .find(|p| Position::from_piece(p) == mv.from)
+            .map_or(Kind::Pawn, |p| p.kind);
+        let castle = self.castle(moved, mv, &self.castling);
+        let undo = self.make_move(mv);
+        let check = self.is_in_check(&self.turn);
+        let flags = MoveFlags {
+            capture: undo.captured.is_some(),
+            en_passant: moved == Kind::Pawn && undo.en_passant == Some(mv.to),
+            castling: castle.is_some(),
+            promotion: mv.promotion.is_some(),
+            check,
+            checkmate: check && self.legal_moves_iter().next().is_none(),
+            check_kind: match castle {
+                _ if !check => None,
+                Some(castle) => self.checking_kind(&[castle.king_to, castle.rook_to]),
+                None => self.checking_kind(&[mv.to]),
+            },
+        };
+        (undo, flags)
+    }
+
+    // Check given to the side to move, direct when the only checking piece stands on one of
+    // the `moved` squares
+    fn checking_kind(&self, moved: &[Position]) -> Option<CheckKind> {
+        let king = self.king_position(&self.turn)?;
+        match self.attackers(king, &self.turn.opponent())[..] {
+            [] => None,
+            [from] if moved.contains(&from) => Some(CheckKind::Direct),
+            [_] => Some(CheckKind::Discovered),
+            _ => Some(CheckKind::Double),
+        }
+    }
+
+    // Kind of check `mv` of the side to move gives, `None` when it doesn't give check
+    pub fn check_kind(&self, mv: &Move) -> Option<CheckKind> {
+        self.clone().make_flagged_move(mv).1.check_kind
+    }
+
+    pub fn unmake_move(&mut self, undo: UndoInfo) {
+        self.swap_turn();
+        let mv = &undo.mv;
+        let has_piece = |gm: &Self, kind: Kind, pos: Position| {
+            gm.pieces_of(gm.turn)
+                .iter()
+                .any(|p| p.kind == kind && Position::from_piece(p) == pos)
+        };
+        // Only a king castled when one stands where the castling would have put it
+        let castle = self
+            .castle(Kind::King, mv, &undo.castling)
+            .filter(|castle| has_piece(self, Kind::King, castle.king_to));
+        let kind = match castle {
+            Some(_) => Kind::King,
+            None => self
+                .pieces_of(self.turn)
+                .iter()
+                .find(|p| p.row == mv.to.row && p.column == mv.to.column)
+                .map_or(Kind::Pawn, |p| p.kind),
+        };
+        let changed = changed_squares(kind, mv, undo.en_passant, castle);
+        self.with_attack_update(&changed, |gm| gm.restore_pieces(&undo, castle));
+        self.castling = undo.castling;
+        self.en_passant = undo.en_passant;
+        self.halfmove_clock = undo.halfmove_clock;
+        if self.turn == Player::Black {
+            self.fullmove_number -= 1;
+        }
+    }
+
+    fn restore_pieces(&mut self, undo: &UndoInfo, castle: Option<Castle>) {
+        let (pieces, enemy) = match self.turn {
+            Player::Black => (&mut self.blacks, &mut self.whites),
+            Player::White => (&mut self.whites, &mut self.blacks),
+        };
+        let mv = &undo.mv;
+        if let Some(castle) = castle {
+            let find = |kind: Kind, pos: Position| {
+                pieces
+                    .iter()
+                    .position(|p| p.kind == kind && Position::from_piece(p) == pos)
+            };
+            let king = find(Kind::King, castle.king_to);
+            let rook = find(Kind::Rook, castle.rook_to);
+            if let Some(king) = king {
+                pieces[king].r#move(mv.from.row, mv.from.column);
+            }
+            if let Some(rook) = rook {
+                pieces[rook].r#move(castle.rook_from.row, castle.rook_from.column);
             }
+            return;
         }
-        if piece.kind == Kind::Pawn {
-            let mut tmp: Vec<Position> = vec![];
-            for pos in positions {
-                if pos.column == piece.column {
-                    tmp.push(pos);
-                    continue;
+        for p in pieces.iter_mut() {
+            if p.row == mv.to.row && p.column == mv.to.column {
+                p.r#move(mv.from.row, mv.from.column);
+                if mv.promotion.is_some() {
+                    p.kind = Kind::Pawn;
                 }
-                let pieces = match self.turn {
-                    Player::Black => &self.whites,
-                    Player::White => &self.blacks,
-                };
-                for p in pieces {
-                    if p.row == pos.row && p.column == pos.column {
-                        tmp.push(pos);
-                        break;
+                break;
+            }
+        }
+        if let Some(captured) = undo.captured {
+            enemy.push(captured);
+        }
+    }
+
+    fn update_castling_rights(&mut self, mv: &Move) {
+        let files = self.castling_files();
+        let last = self.size.back_row(Player::Black);
+        for pos in [&mv.from, &mv.to] {
+            let (king_side, queen_side) = match pos.row {
+                0 => (
+                    &mut self.castling.white_king_side,
+                    &mut self.castling.white_queen_side,
+                ),
+                r if r == last => (
+                    &mut self.castling.black_king_side,
+                    &mut self.castling.black_queen_side,
+                ),
+                _ => continue,
+            };
+            if pos.column == files.king {
+                *king_side = false;
+                *queen_side = false;
+            } else if pos.column == files.king_side_rook {
+                *king_side = false;
+            } else if pos.column == files.queen_side_rook {
+                *queen_side = false;
+            }
+        }
+    }
+
+    // Where the king and the rook go when a piece of `kind` plays `mv` with the castling
+    // `rights`, `None` when it doesn't castle. Castling is a two squares king step in standard
+    // chess and the king taking its own rook in Fischer Random Chess.
+    pub(crate) fn castle(&self, kind: Kind, mv: &Move, rights: &CastlingRights) -> Option<Castle> {
+        let files = self.castling_files();
+        if kind != Kind::King || mv.from.row != mv.to.row || mv.from.column != files.king {
+            return None;
+        }
+        let king_side = mv.to.column > mv.from.column;
+        let rook_from = match self.chess960 {
+            None if mv.from.column.abs_diff(mv.to.column) != 2 => return None,
+            None if king_side => files.king_side_rook,
+            None => files.queen_side_rook,
+            Some(files) => {
+                let (king_right, queen_right) = match mv.from.row {
+                    0 => (rights.white_king_side, rights.white_queen_side),
+                    r if r == self.size.back_row(Player::Black) => {
+                        (rights.black_king_side, rights.black_queen_side)
                     }
+                    _ => return None,
+                };
+                let rook = if king_side {
+                    king_right.then_some(files.king_side_rook)
+                } else {
+                    queen_right.then_some(files.queen_side_rook)
+                };
+                rook.filter(|rook| *rook == mv.to.column)?
+            }
+        };
+        let (king_to, rook_to) = if king_side { (6, 5) } else { (2, 3) };
+        Some(Castle {
+            king_to: Position::new(mv.from.row, king_to),
+            rook_from: Position::new(mv.from.row, rook_from),
+            rook_to: Position::new(mv.from.row, rook_to),
+        })
+    }
+
+    // Checks the king of the side to move against the board as it would be once `mv` is
+    // played, without copying the game.
+    fn leaves_king_in_check(&self, mv: &Move) -> bool {
+        let pieces = self.pieces_of(self.turn);
+        let enemies = self.pieces_of(self.turn.opponent());
+        let moving = match pieces
+            .iter()
+            .find(|p| p.row == mv.from.row && p.column == mv.from.column)
+        {
+            Some(piece) => piece,
+            None => return false,
+        };
+        let castle = self.castle(moving.kind, mv, &self.castling);
+        let king = match (moving.kind, castle) {
+            (_, Some(castle)) => castle.king_to,
+            (Kind::King, None) => mv.to,
+            _ => match pieces.iter().find(|p| p.kind == Kind::King) {
+                Some(king) => Position::from_piece(king),
+                None => return false,
+            },
+        };
+        // A square already attacked stays attacked once the king stands on it
+        if moving.kind == Kind::King && self.is_square_attacked(king, &self.turn.opponent()) {
+            return true;
+        }
+        let mut captured = mv.to;
+        if moving.kind == Kind::Pawn && self.en_passant.as_ref() == Some(&mv.to) {
+            captured = Position::new(mv.from.row, mv.to.column);
+        }
+        // Board occupancy once the move is played, the rook moved away may uncover the king
+        let (filled, vacated) = match castle {
+            Some(castle) => ([castle.king_to, castle.rook_to], castle.rook_from),
+            None => ([mv.to, mv.to], captured),
+        };
+        let occupied = |pos: &Position| {
+            filled.contains(pos) || (pos != &mv.from && pos != &vacated && !self.is_empty(pos))
+        };
+        for attacker in enemies {
+            if attacker.row == captured.row && attacker.column == captured.column {
+                continue;
+            }
+            let attacks = match attacker.kind {
+                Kind::Pawn => is_pawn_attack(attacker, &king),
+                _ => {
+                    is_valid_move(attacker, &king)
+                        && !Position::from_piece(attacker)
+                            .ray_to(&king)
+                            .any(|pos| occupied(&pos))
                 }
+            };
+            if attacks {
+                return true;
             }
-            positions = tmp;
         }
+        false
+    }
+
+    pub fn status(&self) -> GameStatus {
+        if let Some(outcome) = self.outcome {
+            return outcome;
+        }
+        let status = self.rules.status(self);
+        if status != GameStatus::Ongoing {
+            status
+        } else if let Some(flagged) = self.flagged() {
+            let winner = flagged.opponent();
+            if self.has_mating_material(&winner) {
+                GameStatus::TimeForfeit(winner)
+            } else {
+                GameStatus::TimeoutDraw
+            }
+        } else {
+            GameStatus::Ongoing
+        }
+    }
+
+    // Player whose time ran out
+    #[cfg(feature = "clock")]
+    fn flagged(&self) -> Option<Player> {
+        self.clock
+            .as_ref()
+            .and_then(|c| c.check_flag(std::time::Instant::now()))
+    }
+
+    #[cfg(not(feature = "clock"))]
+    fn flagged(&self) -> Option<Player> {
+        None
+    }
+
+    // Ends the game in favor of the opponent of `player`, false when it's already over
+    pub fn resign(&mut self, player: &Player) -> bool {
+        self.end(GameStatus::Resignation(player.opponent()))
+    }
+
+    pub fn abandon(&mut self, player: &Player) -> bool {
+        self.end(GameStatus::Abandonment(player.opponent()))
+    }
+
+    pub fn agree_draw(&mut self) -> bool {
+        self.end(GameStatus::DrawByAgreement)
+    }
+
+    // The offer stands until the opponent agrees, declines or moves. False once the game is
+    // over or when the opponent already offered a draw, which can be agreed to instead.
+    pub fn offer_draw(&mut self, player: &Player) -> bool {
+        if self.status().is_over() || self.draw_offer == Some(player.opponent()) {
+            return false;
+        }
+        self.draw_offer = Some(*player);
+        true
+    }
+
+    pub fn draw_offer(&self) -> Option<Player> {
+        self.draw_offer
+    }
+
+    pub fn decline_draw(&mut self) -> bool {
+        self.draw_offer.take().is_some()
+    }
+
+    // Ends the game as a draw when `rule` applies to the current position
+    pub fn claim_draw(&mut self, rule: DrawRule) -> bool {
+        let applies = match rule {
+            DrawRule::FiftyMoves => self.halfmove_clock >= 100,
+            DrawRule::ThreefoldRepetition => self.repetition_count() >= 3,
+            DrawRule::InsufficientMaterial => {
+                self.status() == GameStatus::DrawByRule(DrawRule::InsufficientMaterial)
+            }
+        };
+        applies && self.end(GameStatus::DrawByRule(rule))
+    }
+
+    // Times the current position occurred in the game, itself included. Only the positions
+    // since the last capture or pawn move can repeat.
+    pub fn repetition_count(&self) -> usize {
+        // The last record is the current position
+        let Some((_, earlier)) = self.history.split_last() else {
+            return 1;
+        };
+        let current = self.zobrist_hash();
+        let reversible = self.halfmove_clock as usize;
+        let window = reversible.min(earlier.len());
+        let mut count = 1 + earlier[earlier.len() - window..]
+            .iter()
+            .filter(|record| record.position_hash == current)
+            .count();
+        if reversible >= self.history.len() && self.initial_position().zobrist_hash() == current {
+            count += 1;
+        }
+        count
+    }
+
+    fn end(&mut self, outcome: GameStatus) -> bool {
+        if self.status().is_over() {
+            return false;
+        }
+        self.outcome = Some(outcome);
+        self.emit_game_end(&outcome);
+        true
+    }
+
+    // Whether `player` could mate by any series of legal moves, as FIDE rules ask when
+    // deciding a game lost on time. A lone king can't, a single knight or bishops on squares
+    // of the same color can't either against a lone king.
+    pub fn has_mating_material(&self, player: &Player) -> bool {
+        let pieces: Vec<&Piece> = self
+            .pieces_of(*player)
+            .iter()
+            .filter(|p| p.kind != Kind::King)
+            .collect();
+        let opponent_alone = self
+            .pieces_of(player.opponent())
+            .iter()
+            .all(|p| p.kind == Kind::King);
+        match pieces.as_slice() {
+            [] => false,
+            [piece] if piece.kind == Kind::Knight => !opponent_alone,
+            _ if pieces.iter().all(|p| p.kind == Kind::Bishop) => {
+                let color = |p: &Piece| Position::from_piece(p).color();
+                !opponent_alone || pieces.iter().any(|p| color(p) != color(pieces[0]))
+            }
+            _ => true,
+        }
+    }
+
+    pub fn is_in_check(&self, player: &Player) -> bool {
+        for piece in self.pieces_of(*player) {
+            if piece.kind == Kind::King {
+                return self.is_square_attacked(Position::from_piece(piece), &player.opponent());
+            }
+        }
+        false
+    }
+
+    pub fn is_square_attacked(&self, pos: impl Into<Position>, by: &Player) -> bool {
+        let pos = pos.into();
+        self.with_attack_maps(|maps| maps[by.index()].is_attacked(pos))
+    }
+
+    // Kept until the position changes, so asking again before the next move is cheap. The
+    // hash catches fields edited by hand.
+    pub fn legal_moves(&self) -> Vec<Move> {
+        self.cached_legal_moves()
+            .map_or_else(|| self.legal_moves_iter().collect(), <[Move]>::to_vec)
+    }
+
+    fn cached_legal_moves(&self) -> Option<&[Move]> {
+        let hash = self.zobrist_hash();
+        let (key, moves) = self
+            .legal_cache
+            .get_or_init(|| (hash, self.legal_moves_iter().collect()));
+        (*key == hash).then_some(moves.as_slice())
+    }
+
+    // Same as `legal_moves` but fills a caller owned buffer so it can be reused between calls
+    pub fn legal_moves_into(&self, moves: &mut Vec<Move>) {
+        moves.clear();
+        moves.extend(self.legal_moves_iter());
+    }
+
+    // Moves allowed by the rules of the game, see `set_rules`
+    pub fn legal_moves_iter(&self) -> impl Iterator<Item = Move> + '_ {
+        self.rules.legal_moves(self)
+    }
+
+    // Legal moves of standard chess, pawns promoting to the pieces the rules allow
+    pub fn standard_legal_moves(&self) -> impl Iterator<Item = Move> + '_ {
+        self.pseudo_legal_moves()
+            .filter(move |mv| !self.leaves_king_in_check(mv))
+    }
+
+    // Moves following the way the pieces move, the king may be left in check
+    pub fn pseudo_legal_moves(&self) -> impl Iterator<Item = Move> + '_ {
+        let promotions = self.rules.promotions();
+        self.pieces_of(self.turn).iter().flat_map(move |piece| {
+            self.move_suggestion_iter(piece).flat_map(move |to| {
+                let from = Position::from_piece(piece);
+                let promoting =
+                    piece.kind == Kind::Pawn && to.row == self.size.promotion_row(piece.player);
+                let count = if promoting { promotions.len() } else { 1 };
+                (0..count).map(move |i| Move {
+                    from,
+                    to,
+                    promotion: promoting.then(|| promotions[i]),
+                })
+            })
+        })
+    }
+
+    // Whether `mv` of the side to move takes a piece, en passant included
+    pub fn is_capture(&self, mv: &Move) -> bool {
+        let enemy = self
+            .pieces_of(self.turn.opponent())
+            .iter()
+            .any(|p| Position::from_piece(p) == mv.to);
+        enemy
+            || self.en_passant == Some(mv.to)
+                && self
+                    .pieces_of(self.turn)
+                    .iter()
+                    .any(|p| p.kind == Kind::Pawn && Position::from_piece(p) == mv.from)
+    }
 
+    // Forced capture mode: only the captures among `moves` when there is one, all of them
+    // otherwise
+    pub fn forced_captures(&self, moves: impl Iterator<Item = Move>) -> Vec<Move> {
+        let (captures, quiet): (Vec<_>, Vec<_>) = moves.partition(|mv| self.is_capture(mv));
+        if captures.is_empty() {
+            quiet
+        } else {
+            captures
+        }
+    }
+
+    // Checkmate, stalemate and draws by insufficient material
+    pub fn standard_status(&self) -> GameStatus {
+        let stuck = match self.cached_legal_moves() {
+            Some(moves) => moves.is_empty(),
+            None => self.legal_moves_iter().next().is_none(),
+        };
+        if stuck {
+            if self.is_in_check(&self.turn) {
+                GameStatus::Checkmate(self.turn.opponent())
+            } else {
+                GameStatus::Stalemate
+            }
+        } else if !self.has_mating_material(&Player::White)
+            && !self.has_mating_material(&Player::Black)
+        {
+            GameStatus::DrawByRule(DrawRule::InsufficientMaterial)
+        } else {
+            GameStatus::Ongoing
+        }
+    }
+
+    // Uniformly picked legal move, `rng` has to return uniformly distributed numbers. The same
+    // sequence of numbers gives the same moves, for reproducible random play.
+    pub fn random_legal_move(&self, rng: &mut impl FnMut() -> u64) -> Option<Move> {
+        let moves = self.legal_moves();
+        if moves.is_empty() {
+            return None;
+        }
+        Some(moves[uniform_below(rng, moves.len() as u64) as usize])
+    }
+
+    // Legal moves of the side to move grouped by the square they start from. Every piece of
+    // that side has an entry, empty when it can't move.
+    pub fn legal_targets_map(&self) -> HashMap<Position, Vec<Move>> {
+        let mut targets: HashMap<Position, Vec<Move>> = self
+            .pieces_of(self.turn)
+            .iter()
+            .map(|p| (Position::from_piece(p), vec![]))
+            .collect();
+        for mv in self.legal_moves() {
+            targets.entry(mv.from).or_default().push(mv);
+        }
+        targets
+    }
+
+    // Number of legal moves `player` would have with the move, without collecting them
+    pub fn mobility(&self, player: &Player) -> u32 {
+        if *player == self.turn {
+            return self.legal_moves_iter().count() as u32;
+        }
+        let mut gm = self.clone();
+        gm.swap_turn();
+        gm.en_passant = None;
+        gm.legal_moves_iter().count() as u32
+    }
+
+    pub fn perft(&self, depth: u8) -> u64 {
+        self.clone().perft_in_place(depth)
+    }
+
+    fn perft_in_place(&mut self, depth: u8) -> u64 {
+        if depth == 0 {
+            return 1;
+        } else if depth == 1 {
+            return self.legal_moves_iter().count() as u64;
+        }
+        let mut nodes = 0;
+        // Collected without the cache, the positions of the tree are never looked at twice
+        for mv in self.legal_moves_iter().collect::<Vec<_>>() {
+            let undo = self.make_move(&mv);
+            nodes += self.perft_in_place(depth - 1);
+            self.unmake_move(undo);
+        }
+        nodes
+    }
+
+    // `perft` also counting the kinds of the moves at the last ply, as in the reference
+    // tables. En passant captures count as captures too.
+    pub fn perft_stats(&self, depth: u8) -> PerftStats {
+        let mut stats = PerftStats::default();
+        if depth == 0 {
+            stats.nodes = 1;
+        } else {
+            self.clone().perft_stats_in_place(depth, &mut stats);
+        }
+        stats
+    }
+
+    fn perft_stats_in_place(&mut self, depth: u8, stats: &mut PerftStats) {
+        for mv in self.legal_moves_iter().collect::<Vec<_>>() {
+            if depth > 1 {
+                let undo = self.make_move(&mv);
+                self.perft_stats_in_place(depth - 1, stats);
+                self.unmake_move(undo);
+                continue;
+            }
+            let (undo, flags) = self.make_flagged_move(&mv);
+            stats.add(&flags);
+            self.unmake_move(undo);
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    pub fn par_perft(&self, depth: u8) -> u64 {
+        use rayon::prelude::*;
+
+        if depth <= 1 {
+            return self.perft(depth);
+        }
+        self.legal_moves_iter()
+            .collect::<Vec<_>>()
+            .par_iter()
+            .map(|mv| {
+                let mut gm = self.clone();
+                gm.make_move(mv);
+                gm.perft_in_place(depth - 1)
+            })
+            .sum()
+    }
+
+    pub fn move_suggestion(&self, piece: &Piece) -> Vec<Position> {
+        self.move_suggestion_iter(piece).collect()
+    }
+
+    pub fn move_suggestion_iter<'a>(
+        &'a self,
+        piece: &'a Piece,
+    ) -> impl Iterator<Item = Position> + 'a {
+        let castling = match piece.kind {
+            Kind::King => self.castling_suggestion(piece),
+            _ => [None, None],
+        };
+        (0..self.size.columns())
+            .flat_map(|ci| (0..self.size.rows()).map(move |ri| Position::new(ri, ci)))
+            .filter(move |pos| {
+                self.is_valid_move(piece, pos).is_none() && self.is_valid_pawn_target(piece, pos)
+            })
+            .chain(castling.into_iter().flatten())
+    }
+
+    // Pawns only move forward to empty squares and only capture diagonally
+    fn is_valid_pawn_target(&self, piece: &Piece, pos: &Position) -> bool {
+        if piece.kind != Kind::Pawn {
+            return true;
+        }
+        let occupied = self
+            .pieces_of(piece.player.opponent())
+            .iter()
+            .any(|p| p.row == pos.row && p.column == pos.column);
+        if pos.column == piece.column {
+            return !occupied;
+        }
+        occupied || self.en_passant.as_ref() == Some(pos)
+    }
+
+    // Castling targets, the king square in standard chess and the rook one in Fischer Random
+    fn castling_suggestion(&self, piece: &Piece) -> [Option<Position>; 2] {
+        let (row, king_side, queen_side, enemy) = match piece.player {
+            Player::Black => (
+                self.size.back_row(Player::Black),
+                self.castling.black_king_side,
+                self.castling.black_queen_side,
+                Player::White,
+            ),
+            Player::White => (
+                0,
+                self.castling.white_king_side,
+                self.castling.white_queen_side,
+                Player::Black,
+            ),
+        };
+        let mut positions = [None, None];
+        let files = self.castling_files();
+        let start = Position::from_piece(piece);
+        if piece.row != row || piece.column != files.king || self.is_square_attacked(start, &enemy)
+        {
+            return positions;
+        }
+        let sides = [
+            (king_side, files.king_side_rook, 6, 5),
+            (queen_side, files.queen_side_rook, 2, 3),
+        ];
+        for (position, (right, rook, king_to, rook_to)) in positions.iter_mut().zip(sides) {
+            if right && self.can_castle(piece, rook, king_to, rook_to, &enemy) {
+                let target = if self.chess960.is_some() {
+                    rook
+                } else {
+                    king_to
+                };
+                *position = Some(Position::new(row, target));
+            }
+        }
         positions
     }
+
+    // Squares the king and the rook go through have to be empty but for the two of them, the
+    // ones of the king can't be attacked
+    fn can_castle(&self, king: &Piece, rook: u8, king_to: u8, rook_to: u8, enemy: &Player) -> bool {
+        let row = king.row;
+        let span = |a: u8, b: u8| a.min(b)..=a.max(b);
+        self.has_rook(&Position::new(row, rook), king.player)
+            && span(king.column, king_to)
+                .chain(span(rook, rook_to))
+                .all(|c| c == king.column || c == rook || self.is_empty(&Position::new(row, c)))
+            && span(king.column, king_to)
+                .all(|c| !self.is_square_attacked(Position::new(row, c), enemy))
+    }
+
+    fn has_rook(&self, pos: &Position, player: Player) -> bool {
+        self.pieces_of(player)
+            .iter()
+            .any(|p| p.kind == Kind::Rook && p.row == pos.row && p.column == pos.column)
+    }
+
+    // Piece standing on `pos` with its side
+    pub fn piece_at(&self, pos: &Position) -> Option<(Player, &Piece)> {
+        for (player, pieces) in [(Player::White, &self.whites), (Player::Black, &self.blacks)] {
+            if let Some(p) = pieces
+                .iter()
+                .find(|p| p.row == pos.row && p.column == pos.column)
+            {
+                return Some((player, p));
+            }
+        }
+        None
+    }
+
+    pub(crate) fn is_empty(&self, pos: &Position) -> bool {
+        self.piece_at(pos).is_none()
+    }
+
     fn is_piece_blocking(&self, piece: &Piece, end: &Position) -> bool {
-        use crate::piece::Direction::*;
-
-        let pieces = self.whites.iter().chain(self.blacks.iter());
-        let positions: Vec<Position> = pieces.map(Position::from_piece).collect();
-        let start = &Position::from_piece(piece);
-        let direction = start.get_direction(end).unwrap();
-        for pos in positions {
-            if pos.row == piece.row && pos.column == piece.column {
-                continue;
-            } else if !is_valid_move(piece, &pos, &self.turn) {
+        self.blocking_piece(piece, end).is_some()
+    }
+
+    fn blocking_piece(&self, piece: &Piece, end: &Position) -> Option<Position> {
+        Position::from_piece(piece)
+            .ray_to(end)
+            .find(|pos| !self.is_empty(pos))
+    }
+
+    // Error for `mv` when it isn't legal for the side to move
+    pub(crate) fn illegal_move_err(&self, mv: &Move) -> MoveErr {
+        match self.find_piece(&mv.from) {
+            Some(index) => MoveErr::InvalidMove {
+                piece: self.pieces_of(self.turn)[index].kind,
+                from: mv.from,
+                to: mv.to,
+            },
+            None => MoveErr::NoPiece { at: mv.from },
+        }
+    }
+
+    fn king_position(&self, player: &Player) -> Option<Position> {
+        self.pieces_of(*player)
+            .iter()
+            .find(|p| p.kind == Kind::King)
+            .map(Position::from_piece)
+    }
+
+    // Pieces of `player` standing alone between their king and an enemy slider
+    pub fn pinned_pieces(&self, player: &Player) -> Vec<Position> {
+        let mut pinned = vec![];
+        let king = match self.king_position(player) {
+            Some(king) => king,
+            None => return pinned,
+        };
+        for enemy in self.pieces_of(player.opponent()) {
+            let slider = matches!(enemy.kind, Kind::Queen | Kind::Rook | Kind::Bishop);
+            if !slider || !is_valid_move(enemy, &king) {
                 continue;
-            };
-            let dir = start.get_direction(&pos).unwrap();
-            let is_blocking = match (&dir, &direction) {
-                (Up(a), Up(b)) => a < b,
-                (Left(a), Left(b)) => a < b,
-                (Down(a), Down(b)) => a < b,
-                (Right(a), Right(b)) => a < b,
-                (UpLeft(a), UpLeft(b)) => a < b,
-                (UpRight(a), UpRight(b)) => a < b,
-                (DownLeft(a), DownLeft(b)) => a < b,
-                (DownRight(a), DownRight(b)) => a < b,
-                _ => false,
-            };
-            if is_blocking {
-                return is_blocking;
+            }
+            let mut between = Position::from_piece(enemy)
+                .ray_to(&king)
+                .filter(|pos| !self.is_empty(pos));
+            if let (Some(pos), None) = (between.next(), between.next()) {
+                if self
+                    .pieces_of(*player)
+                    .iter()
+                    .any(|p| Position::from_piece(p) == pos)
+                {
+                    pinned.push(pos);
+                }
             }
         }
-        false
+        pinned
+    }
+
+    // Enemy pieces giving check to the king of `player`
+    pub fn checkers(&self, player: &Player) -> Vec<Position> {
+        match self.king_position(player) {
+            Some(king) => self.attackers(king, &player.opponent()),
+            None => vec![],
+        }
+    }
+
+    // Pieces of `by` attacking `pos`, whatever stands there
+    pub fn attackers(&self, pos: impl Into<Position>, by: &Player) -> Vec<Position> {
+        let pos = pos.into();
+        self.pieces_of(*by)
+            .iter()
+            .filter(|p| Position::from_piece(p) != pos)
+            .filter(|p| match p.kind {
+                Kind::Pawn => is_pawn_attack(p, &pos),
+                _ => is_valid_move(p, &pos) && !self.is_piece_blocking(p, &pos),
+            })
+            .map(Position::from_piece)
+            .collect()
+    }
+}
+
+// Squares whose occupancy changes when a piece of `kind` plays `mv`, duplicates included
+fn changed_squares(
+    kind: Kind,
+    mv: &Move,
+    en_passant: Option<Position>,
+    castle: Option<Castle>,
+) -> [Position; 5] {
+    let mut changed = [mv.from, mv.to, mv.to, mv.to, mv.to];
+    if kind == Kind::Pawn && en_passant == Some(mv.to) {
+        changed[2] = Position::new(mv.from.row, mv.to.column);
+    }
+    if let Some(castle) = castle {
+        changed[2] = castle.king_to;
+        changed[3] = castle.rook_from;
+        changed[4] = castle.rook_to;
+    }
+    changed
+}
+
+impl Default for GameManager {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
-fn is_friendly_fire(gm: &GameManager, end: &Position) -> bool {
-    let pieces = match gm.turn {
-        Player::Black => &gm.blacks,
-        Player::White => &gm.whites,
-    };
-    for piece in pieces {
-        if piece.row == end.row && piece.column == end.column {
-            return true;
-        }
+// Games are equal when they stand on the same position, as their FENs without the move
+// counters would be. The order of the piece lists, the history and the clock don't matter.
+impl PartialEq for GameManager {
+    fn eq(&self, other: &Self) -> bool {
+        self.turn == other.turn
+            && self.castling == other.castling
+            && self.chess960 == other.chess960
+            && self.size == other.size
+            && self.en_passant == other.en_passant
+            && self.board() == other.board()
+    }
+}
+
+impl Eq for GameManager {}
+
+impl Hash for GameManager {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Equal positions share their Zobrist hash
+        self.zobrist_hash().hash(state);
     }
-    false
 }
 
-fn create_blacks_from_whites(whites: &Vec<Piece>) -> Vec<Piece> {
-    let mut blacks = Vec::with_capacity(16);
-    for i in whites {
-        blacks.push(Piece::new(i.kind, 7 - i.row, i.column))
+fn friendly_fire(gm: &GameManager, piece: &Piece, end: &Position) -> Option<Kind> {
+    gm.pieces_of(piece.player)
+        .iter()
+        .find(|p| p.row == end.row && p.column == end.column)
+        .map(|p| p.kind)
+}
+
+// Pieces of the standard starting position, the back rank then the pawns
+pub const START_WHITES: PieceList = start_pieces(Player::White);
+pub const START_BLACKS: PieceList = start_pieces(Player::Black);
+
+const BACK_RANK: [Kind; 8] = [
+    Kind::Rook,
+    Kind::Knight,
+    Kind::Bishop,
+    Kind::Queen,
+    Kind::King,
+    Kind::Bishop,
+    Kind::Knight,
+    Kind::Rook,
+];
+
+const fn start_pieces(player: Player) -> PieceList {
+    let (back_row, pawn_row) = match player {
+        Player::White => (0, 1),
+        Player::Black => (7, 6),
+    };
+    let mut pieces = PieceList::new();
+    let mut column = 0;
+    while column < 8 {
+        let kind = BACK_RANK[column as usize];
+        let _ = pieces.try_push(Piece::new(player, kind, back_row, column));
+        column += 1;
     }
-    blacks
+    column = 0;
+    while column < 8 {
+        let _ = pieces.try_push(Piece::new(player, Kind::Pawn, pawn_row, column));
+        column += 1;
+    }
+    pieces
 }
 
-fn create_whites() -> Vec<Piece> {
-    let mut whites: Vec<Piece> = Vec::with_capacity(16);
-    whites.push(Piece::new(Kind::Rook, 0, 0));
-    whites.push(Piece::new(Kind::Knight, 0, 1));
-    whites.push(Piece::new(Kind::Bishop, 0, 2));
-    whites.push(Piece::new(Kind::Queen, 0, 3));
-    whites.push(Piece::new(Kind::King, 0, 4));
-    whites.push(Piece::new(Kind::Bishop, 0, 5));
-    whites.push(Piece::new(Kind::Knight, 0, 6));
-    whites.push(Piece::new(Kind::Rook, 0, 7));
-    for i in 0..8 {
-        whites.push(Piece::new(Kind::Pawn, 1, i));
-    }
-    whites
+// Number below `bound` drawn from `rng` without modulo bias: the numbers past the largest
+// multiple of `bound` are drawn again.
+pub(crate) fn uniform_below(rng: &mut impl FnMut() -> u64, bound: u64) -> u64 {
+    let rejected = (u64::MAX % bound + 1) % bound;
+    loop {
+        let value = rng();
+        if value <= u64::MAX - rejected {
+            return value % bound;
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashMap;
+    #[cfg(feature = "clock")]
+    use std::time::Duration;
+    use std::{
+        collections::HashMap,
+        hash::{DefaultHasher, Hash, Hasher},
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    };
 
+    #[cfg(feature = "clock")]
+    use crate::clock::{Clock, TimeControl};
     use crate::{
+        fen::MINICHESS_FEN,
         move_validators::is_pawn_in_start_pos,
-        piece::{Piece, Player, Position},
+        moves::Move,
+        piece::{BoardSize, Piece, Player, Position},
+        square::Square,
     };
 
-    use super::{GameManager, Kind};
+    use super::{
+        CastlingRights, CheckKind, DrawRule, GameManager, GameStatus, Kind, MoveErr, MoveFlags,
+        PerftStats,
+    };
 
     #[test]
     fn test_piece_at_start() {
@@ -208,7 +1785,7 @@ mod tests {
         // check correct Pawn position for whites
         for piece in &gm.whites {
             if let Kind::Pawn = piece.kind {
-                assert!(is_pawn_in_start_pos(&piece, &Player::White));
+                assert!(is_pawn_in_start_pos(piece));
                 continue;
             }
             match positions.get(&usize::from(piece.column)) {
@@ -223,7 +1800,7 @@ mod tests {
         // check correct Pawn position for blacks
         for piece in &gm.blacks {
             if let Kind::Pawn = piece.kind {
-                assert!(is_pawn_in_start_pos(piece, &Player::Black,));
+                assert!(is_pawn_in_start_pos(piece));
                 continue;
             }
             match positions.get(&usize::from(piece.column)) {
@@ -240,7 +1817,7 @@ mod tests {
     #[test]
     fn test_move_suggestion_pawn() {
         let gm = GameManager::new();
-        let piece = Piece::new(Kind::Pawn, 2, 2);
+        let piece = Piece::new(Player::White, Kind::Pawn, 2, 2);
         let expected_pos = Position::new(piece.row + 1, piece.column);
         let suggestion = gm.move_suggestion(&piece);
         if !(suggestion.len() == 1 && expected_pos == suggestion[0]) {
@@ -251,7 +1828,7 @@ mod tests {
     #[test]
     fn test_move_suggestion_pawn_white_at_start() {
         let gm = GameManager::new();
-        let piece = Piece::new(Kind::Pawn, 1, 2);
+        let piece = Piece::new(Player::White, Kind::Pawn, 1, 2);
         let expected_pos = [
             Position::new(piece.row + 1, piece.column),
             Position::new(piece.row + 2, piece.column),
@@ -279,31 +1856,109 @@ mod tests {
     }
 
     #[test]
-    fn test_move_suggestion_rook() {
-        let piece = Piece::new(Kind::Rook, 4, 4);
-        let mut targets = vec![];
+    fn test_random_legal_move() {
+        let play = |seed: u64| {
+            let mut state = seed;
+            let mut rng = || {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state
+            };
+            let mut gm = GameManager::new();
+            for _ in 0..40 {
+                let Some(mv) = gm.random_legal_move(&mut rng) else {
+                    break;
+                };
+                gm.play(&mv).unwrap();
+            }
+            gm.to_fen()
+        };
+        assert_eq!(play(42), play(42));
+        assert_ne!(play(42), play(7));
+        let mated = GameManager::from_fen("R3k3/8/4K3/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(None, mated.random_legal_move(&mut || 0));
+
+        // 20 moves: the top 16 numbers would favor the first ones, so they are drawn again
+        let gm = GameManager::new();
+        let mut draws = [u64::MAX - 15, u64::MAX - 16].into_iter();
+        let mv = gm.random_legal_move(&mut || draws.next().unwrap());
+        assert_eq!(Some(gm.legal_moves()[(u64::MAX - 16) as usize % 20]), mv);
+    }
+
+    #[test]
+    fn test_legal_targets_map() {
+        let gm = GameManager::new();
+        let targets = gm.legal_targets_map();
+        assert_eq!(16, targets.len());
+        assert_eq!(20, targets.values().map(Vec::len).sum::<usize>());
+        assert!(targets[&Position::from(Square::E1)].is_empty());
+        let mut knight: Vec<_> = targets[&Position::from(Square::G1)]
+            .iter()
+            .map(|mv| mv.to_uci())
+            .collect();
+        knight.sort();
+        assert_eq!(vec!["g1f3", "g1h3"], knight);
+
+        // Promotions are listed once per piece kind
+        let gm = GameManager::from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(4, gm.legal_targets_map()[&Position::from(Square::A7)].len());
+    }
+
+    #[test]
+    fn test_legal_moves_cache() {
+        let mut gm = GameManager::new();
+        assert_eq!(20, gm.legal_moves().len());
+        assert_eq!(Some(gm.zobrist_hash()), gm.legal_cache.get().map(|c| c.0));
+        assert_eq!(20, gm.legal_moves().len());
+
+        let undo = gm.make_move(&Move::new(Square::F2, Square::F3));
+        assert!(gm.legal_cache.get().is_none());
+        assert_eq!(GameStatus::Ongoing, gm.status());
+        assert_eq!(20, gm.legal_moves().len());
+        gm.unmake_move(undo);
+        assert_eq!(20, gm.legal_moves().len());
+
+        // Fields edited by hand don't match the cached position anymore
+        let mut gm = GameManager::from_fen("7k/8/8/8/8/8/8/K6R b - - 0 1").unwrap();
+        assert_eq!(2, gm.legal_moves().len());
+        gm.turn = Player::White;
+        assert_eq!(gm.legal_moves_iter().collect::<Vec<_>>(), gm.legal_moves());
+        gm.turn = Player::Black;
+        gm.whites.push(Piece::new(Player::White, Kind::Rook, 0, 6));
+        gm.refresh_attacks();
+        assert!(gm.legal_moves().is_empty());
+        assert_eq!(GameStatus::Checkmate(Player::White), gm.status());
+    }
 
-        targets.push(Position::new(piece.row - 2, piece.column));
-        targets.push(Position::new(piece.row, piece.column - 2));
-        targets.push(Position::new(piece.row + 2, piece.column));
-        targets.push(Position::new(piece.row, piece.column + 2));
+    #[test]
+    fn test_move_suggestion_rook() {
+        let piece = Piece::new(Player::White, Kind::Rook, 4, 4);
+        let targets = vec![
+            Position::new(piece.row - 2, piece.column),
+            Position::new(piece.row, piece.column - 2),
+            Position::new(piece.row + 2, piece.column),
+            Position::new(piece.row, piece.column + 2),
+        ];
 
         let gm = GameManager {
             turn: Player::White,
-            whites: vec![],
+            whites: [].into(),
             blacks: targets
                 .iter()
-                .map(|p| Piece::new(Kind::Pawn, p.row, p.column))
+                .map(|p| Piece::new(Player::Black, Kind::Pawn, p.row, p.column))
                 .collect(),
+            ..GameManager::empty()
         };
 
-        let mut expected_pos: Vec<Position> = vec![];
-        expected_pos.push(Position::new(piece.row - 1, piece.column));
-        expected_pos.push(Position::new(piece.row, piece.column - 1));
-        expected_pos.push(Position::new(piece.row + 1, piece.column));
-        expected_pos.push(Position::new(piece.row, piece.column + 1));
+        let mut expected_pos: Vec<Position> = vec![
+            Position::new(piece.row - 1, piece.column),
+            Position::new(piece.row, piece.column - 1),
+            Position::new(piece.row + 1, piece.column),
+            Position::new(piece.row, piece.column + 1),
+        ];
         for target in &targets {
-            expected_pos.push(target.clone());
+            expected_pos.push(*target);
         }
         let suggestion = gm.move_suggestion(&piece);
         println!("{:?}", suggestion);
@@ -330,30 +1985,32 @@ mod tests {
 
     #[test]
     fn test_move_suggestion_bishop() {
-        let piece = Piece::new(Kind::Bishop, 4, 4);
-        let mut targets = vec![];
-
-        targets.push(Position::new(piece.row - 2, piece.column - 2));
-        targets.push(Position::new(piece.row - 2, piece.column + 2));
-        targets.push(Position::new(piece.row + 2, piece.column + 2));
-        targets.push(Position::new(piece.row + 2, piece.column - 2));
+        let piece = Piece::new(Player::White, Kind::Bishop, 4, 4);
+        let targets = vec![
+            Position::new(piece.row - 2, piece.column - 2),
+            Position::new(piece.row - 2, piece.column + 2),
+            Position::new(piece.row + 2, piece.column + 2),
+            Position::new(piece.row + 2, piece.column - 2),
+        ];
 
         let gm = GameManager {
             turn: Player::White,
-            whites: vec![],
+            whites: [].into(),
             blacks: targets
                 .iter()
-                .map(|p| Piece::new(Kind::Pawn, p.row, p.column))
+                .map(|p| Piece::new(Player::Black, Kind::Pawn, p.row, p.column))
                 .collect(),
+            ..GameManager::empty()
         };
 
-        let mut expected_pos: Vec<Position> = vec![];
-        expected_pos.push(Position::new(piece.row - 1, piece.column - 1));
-        expected_pos.push(Position::new(piece.row - 1, piece.column + 1));
-        expected_pos.push(Position::new(piece.row + 1, piece.column + 1));
-        expected_pos.push(Position::new(piece.row + 1, piece.column - 1));
+        let mut expected_pos: Vec<Position> = vec![
+            Position::new(piece.row - 1, piece.column - 1),
+            Position::new(piece.row - 1, piece.column + 1),
+            Position::new(piece.row + 1, piece.column + 1),
+            Position::new(piece.row + 1, piece.column - 1),
+        ];
         for target in &targets {
-            expected_pos.push(target.clone());
+            expected_pos.push(*target);
         }
         let suggestion = gm.move_suggestion(&piece);
         if expected_pos.len() != suggestion.len() {
@@ -381,7 +2038,7 @@ mod tests {
     fn test_move_suggestion_pawn_black_at_start() {
         let mut gm = GameManager::new();
         gm.turn = Player::Black;
-        let piece = Piece::new(Kind::Pawn, 6, 2);
+        let piece = Piece::new(Player::Black, Kind::Pawn, 6, 2);
         let expected_pos = [
             Position::new(piece.row - 1, piece.column),
             Position::new(piece.row - 2, piece.column),
@@ -409,13 +2066,14 @@ mod tests {
     }
     #[test]
     fn test_move_suggestion_pawn_white_at_start_with_diag() {
-        let piece = Piece::new(Kind::Pawn, 1, 2);
-        let uleft = Piece::new(Kind::Pawn, piece.row + 1, piece.column - 1);
-        let uright = Piece::new(Kind::Pawn, piece.row + 1, piece.column + 1);
+        let piece = Piece::new(Player::White, Kind::Pawn, 1, 2);
+        let uleft = Piece::new(Player::White, Kind::Pawn, piece.row + 1, piece.column - 1);
+        let uright = Piece::new(Player::White, Kind::Pawn, piece.row + 1, piece.column + 1);
         let gm = GameManager {
             turn: Player::White,
-            whites: vec![],
-            blacks: vec![uleft.clone(), uright.clone()],
+            whites: [].into(),
+            blacks: [uleft, uright].into(),
+            ..GameManager::empty()
         };
 
         let expected_pos = [
@@ -448,13 +2106,14 @@ mod tests {
 
     #[test]
     fn test_move_suggestion_pawn_black_at_start_with_diag() {
-        let piece = Piece::new(Kind::Pawn, 6, 2);
-        let uleft = Piece::new(Kind::Pawn, piece.row - 1, piece.column - 1);
-        let uright = Piece::new(Kind::Pawn, piece.row - 1, piece.column + 1);
+        let piece = Piece::new(Player::Black, Kind::Pawn, 6, 2);
+        let uleft = Piece::new(Player::Black, Kind::Pawn, piece.row - 1, piece.column - 1);
+        let uright = Piece::new(Player::Black, Kind::Pawn, piece.row - 1, piece.column + 1);
         let gm = GameManager {
             turn: Player::Black,
-            whites: vec![uleft.clone(), uright.clone()],
-            blacks: vec![],
+            whites: [uleft, uright].into(),
+            blacks: [].into(),
+            ..GameManager::empty()
         };
 
         let expected_pos = [
@@ -491,11 +2150,24 @@ mod tests {
         let targetb = Position::new(3, 3);
         let gm = GameManager {
             turn: Player::White,
-            whites: vec![Piece::new(Kind::Pawn, targetw.row - 1, targetw.column)],
-            blacks: vec![Piece::new(Kind::Pawn, targetb.row - 1, targetb.column)],
+            whites: [Piece::new(
+                Player::White,
+                Kind::Pawn,
+                targetw.row - 1,
+                targetw.column,
+            )]
+            .into(),
+            blacks: [Piece::new(
+                Player::Black,
+                Kind::Pawn,
+                targetb.row - 1,
+                targetb.column,
+            )]
+            .into(),
+            ..GameManager::empty()
         };
         for target in [targetw, targetb] {
-            let piece = Piece::new(Kind::Pawn, target.row - 2, target.column);
+            let piece = Piece::new(Player::White, Kind::Pawn, target.row - 2, target.column);
             if !gm.is_piece_blocking(&piece, &target) {
                 panic!("Should be blocking")
             }
@@ -508,24 +2180,47 @@ mod tests {
         let targetb = Position::new(3, 3);
         let gm = GameManager {
             turn: Player::White,
-            whites: vec![
-                Piece::new(Kind::Pawn, targetw.row - 1, targetw.column - 1),
-                Piece::new(Kind::Pawn, targetw.row - 1, targetw.column + 1),
-            ],
-            blacks: vec![
-                Piece::new(Kind::Pawn, targetb.row - 1, targetb.column - 1),
-                Piece::new(Kind::Pawn, targetb.row - 1, targetb.column + 1),
-            ],
+            whites: [
+                Piece::new(
+                    Player::White,
+                    Kind::Pawn,
+                    targetw.row - 1,
+                    targetw.column - 1,
+                ),
+                Piece::new(
+                    Player::White,
+                    Kind::Pawn,
+                    targetw.row - 1,
+                    targetw.column + 1,
+                ),
+            ]
+            .into(),
+            blacks: [
+                Piece::new(
+                    Player::Black,
+                    Kind::Pawn,
+                    targetb.row - 1,
+                    targetb.column - 1,
+                ),
+                Piece::new(
+                    Player::Black,
+                    Kind::Pawn,
+                    targetb.row - 1,
+                    targetb.column + 1,
+                ),
+            ]
+            .into(),
+            ..GameManager::empty()
         };
         for target in [&targetw, &targetb] {
-            let piece = Piece::new(Kind::Pawn, target.row - 2, target.column - 2);
-            if !gm.is_piece_blocking(&piece, &target) {
+            let piece = Piece::new(Player::White, Kind::Pawn, target.row - 2, target.column - 2);
+            if !gm.is_piece_blocking(&piece, target) {
                 panic!("Should be blocking")
             }
         }
 
         for target in [targetw, targetb] {
-            let piece = Piece::new(Kind::Pawn, target.row - 2, target.column + 2);
+            let piece = Piece::new(Player::White, Kind::Pawn, target.row - 2, target.column + 2);
             if !gm.is_piece_blocking(&piece, &target) {
                 panic!("Should be blocking")
             }
@@ -538,11 +2233,24 @@ mod tests {
         let targetb = Position::new(3, 3);
         let gm = GameManager {
             turn: Player::Black,
-            whites: vec![Piece::new(Kind::Pawn, targetw.row + 1, targetw.column)],
-            blacks: vec![Piece::new(Kind::Pawn, targetb.row + 1, targetb.column)],
+            whites: [Piece::new(
+                Player::White,
+                Kind::Pawn,
+                targetw.row + 1,
+                targetw.column,
+            )]
+            .into(),
+            blacks: [Piece::new(
+                Player::Black,
+                Kind::Pawn,
+                targetb.row + 1,
+                targetb.column,
+            )]
+            .into(),
+            ..GameManager::empty()
         };
         for target in [targetw, targetb] {
-            let piece = Piece::new(Kind::Pawn, target.row + 2, target.column);
+            let piece = Piece::new(Player::Black, Kind::Pawn, target.row + 2, target.column);
             if !gm.is_piece_blocking(&piece, &target) {
                 panic!("Should be blocking")
             }
@@ -555,27 +2263,976 @@ mod tests {
         let targetb = Position::new(3, 3);
         let gm = GameManager {
             turn: Player::Black,
-            whites: vec![
-                Piece::new(Kind::Pawn, targetw.row + 1, targetw.column - 1),
-                Piece::new(Kind::Pawn, targetw.row + 1, targetw.column + 1),
-            ],
-            blacks: vec![
-                Piece::new(Kind::Pawn, targetb.row + 1, targetb.column - 1),
-                Piece::new(Kind::Pawn, targetb.row + 1, targetb.column + 1),
-            ],
+            whites: [
+                Piece::new(
+                    Player::White,
+                    Kind::Pawn,
+                    targetw.row + 1,
+                    targetw.column - 1,
+                ),
+                Piece::new(
+                    Player::White,
+                    Kind::Pawn,
+                    targetw.row + 1,
+                    targetw.column + 1,
+                ),
+            ]
+            .into(),
+            blacks: [
+                Piece::new(
+                    Player::Black,
+                    Kind::Pawn,
+                    targetb.row + 1,
+                    targetb.column - 1,
+                ),
+                Piece::new(
+                    Player::Black,
+                    Kind::Pawn,
+                    targetb.row + 1,
+                    targetb.column + 1,
+                ),
+            ]
+            .into(),
+            ..GameManager::empty()
         };
         for target in [&targetw, &targetb] {
-            let piece = Piece::new(Kind::Pawn, target.row + 2, target.column - 2);
-            if !gm.is_piece_blocking(&piece, &target) {
+            let piece = Piece::new(Player::Black, Kind::Pawn, target.row + 2, target.column - 2);
+            if !gm.is_piece_blocking(&piece, target) {
                 panic!("Should be blocking")
             }
         }
 
         for target in [targetw, targetb] {
-            let piece = Piece::new(Kind::Pawn, target.row + 2, target.column + 2);
+            let piece = Piece::new(Player::Black, Kind::Pawn, target.row + 2, target.column + 2);
             if !gm.is_piece_blocking(&piece, &target) {
                 panic!("Should be blocking")
             }
         }
     }
+
+    #[test]
+    fn test_move_piece_wrong_turn() {
+        let mut gm = GameManager::new();
+        let pawn = gm.blacks[8];
+        assert_eq!(Player::Black, pawn.player);
+        let end = Position::new(pawn.row - 1, pawn.column);
+        assert!(matches!(gm.move_piece(&pawn, end), Err(MoveErr::WrongTurn)));
+        gm.swap_turn();
+        assert!(gm.move_piece(&pawn, end).is_ok());
+    }
+
+    #[test]
+    fn test_square_accepted_as_position() {
+        let mut gm = GameManager::new();
+        assert!(gm.is_square_attacked(Square::F3, &Player::White));
+        assert!(!gm.is_square_attacked(Square::E4, &Player::White));
+        let pawn = gm.whites[12];
+        gm.move_piece(&pawn, Square::E4).unwrap();
+        assert_eq!(
+            Position::from(Square::E4),
+            Position::from_piece(&gm.whites[12])
+        );
+        assert_eq!(
+            Some(Square::E4.index()),
+            Position::from_piece(&gm.whites[12]).index()
+        );
+    }
+
+    #[test]
+    fn test_pieces_iterator() {
+        let mut gm = GameManager::from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1").unwrap();
+        assert_eq!(3, gm.pieces().count());
+        let whites = gm.pieces().filter(|(player, _)| *player == Player::White);
+        assert_eq!(2, whites.count());
+        assert!(gm.pieces().all(|(player, p)| player == p.player));
+
+        for (player, piece) in gm.pieces_mut() {
+            if player == Player::White && piece.kind == Kind::Pawn {
+                piece.r#move(2, 4);
+            }
+        }
+        gm.refresh_attacks();
+        assert_eq!("4k3/8/8/8/8/4P3/8/4K3 w - - 0 1", gm.to_fen());
+        assert!(gm.is_square_attacked(Square::D4, &Player::White));
+    }
+
+    #[test]
+    fn test_piece_at() {
+        let gm = GameManager::new();
+        let (player, piece) = gm.piece_at(&Square::D8.into()).unwrap();
+        assert_eq!((Player::Black, Kind::Queen), (player, piece.kind));
+        assert_eq!(Position::from(Square::D8), Position::from_piece(piece));
+        assert!(gm.piece_at(&Square::E4.into()).is_none());
+        assert!(gm.piece_at(&Position::new(9, 0)).is_none());
+    }
+
+    #[test]
+    fn test_pinned_pieces() {
+        let gm = GameManager {
+            whites: [
+                Piece::new(Player::White, Kind::King, 0, 4),
+                Piece::new(Player::White, Kind::Knight, 1, 4),
+                Piece::new(Player::White, Kind::Bishop, 1, 3),
+                Piece::new(Player::White, Kind::Pawn, 1, 5),
+                Piece::new(Player::White, Kind::Pawn, 2, 6),
+            ]
+            .into(),
+            blacks: [
+                Piece::new(Player::Black, Kind::Rook, 7, 4),
+                Piece::new(Player::Black, Kind::Queen, 4, 0),
+                Piece::new(Player::Black, Kind::Bishop, 3, 7),
+                Piece::new(Player::Black, Kind::Rook, 0, 0),
+                Piece::new(Player::Black, Kind::Pawn, 0, 2),
+            ]
+            .into(),
+            ..GameManager::empty()
+        };
+        // e2 knight by the rook, d2 bishop by the queen, the h4 bishop is blocked twice
+        // and the a1 rook by a black pawn
+        let pinned = gm.pinned_pieces(&Player::White);
+        assert_eq!(vec![Position::new(1, 4), Position::new(1, 3)], pinned);
+        assert!(gm
+            .legal_moves()
+            .iter()
+            .all(|mv| mv.from != Position::new(1, 4)));
+    }
+
+    #[test]
+    fn test_zobrist_hash_polyglot() {
+        let mut gm = GameManager::new();
+        assert_eq!(0x463b96181691fc9c, gm.zobrist_hash());
+
+        // e4 d5 e5 f5, reference keys from the Polyglot book format specification
+        let moves = [
+            ((1, 4), (3, 4), 0x823c9b50fd114196),
+            ((6, 3), (4, 3), 0x0756b94461c50fb0),
+            ((3, 4), (4, 4), 0x662fafb965db29d4),
+            ((6, 5), (4, 5), 0x22a48b5a8e47ff78),
+        ];
+        for ((fr, fc), (tr, tc), expected) in moves {
+            let pawn = Piece::new(gm.turn, Kind::Pawn, fr, fc);
+            gm.move_piece(&pawn, Position::new(tr, tc)).unwrap();
+            gm.swap_turn();
+            assert_eq!(expected, gm.zobrist_hash());
+        }
+    }
+
+    #[test]
+    fn test_legal_moves_into_reuses_buffer() {
+        let gm = GameManager::new();
+        let mut moves = Vec::with_capacity(64);
+        gm.legal_moves_into(&mut moves);
+        assert_eq!(20, moves.len());
+        gm.legal_moves_into(&mut moves);
+        assert_eq!(gm.legal_moves(), moves);
+        assert_eq!(64, moves.capacity());
+    }
+
+    #[test]
+    fn test_make_unmake_move_restores_position() {
+        // Castling, en passant and promotions all available in one position
+        let gm = GameManager {
+            whites: [
+                Piece::new(Player::White, Kind::Rook, 0, 0),
+                Piece::new(Player::White, Kind::King, 0, 4),
+                Piece::new(Player::White, Kind::Rook, 0, 7),
+                Piece::new(Player::White, Kind::Pawn, 4, 4),
+                Piece::new(Player::White, Kind::Pawn, 6, 1),
+            ]
+            .into(),
+            blacks: [
+                Piece::new(Player::Black, Kind::Rook, 7, 0),
+                Piece::new(Player::Black, Kind::King, 7, 4),
+                Piece::new(Player::Black, Kind::Bishop, 7, 2),
+                Piece::new(Player::Black, Kind::Pawn, 4, 3),
+            ]
+            .into(),
+            castling: CastlingRights {
+                white_king_side: true,
+                white_queen_side: true,
+                black_king_side: false,
+                black_queen_side: true,
+            },
+            en_passant: Some(Position::new(5, 3)),
+            ..GameManager::empty()
+        };
+        let mut copy = gm.clone();
+        for mv in gm.legal_moves() {
+            let undo = copy.make_move(&mv);
+            assert_ne!(gm.zobrist_hash(), copy.zobrist_hash());
+            copy.unmake_move(undo);
+            assert_eq!(gm.zobrist_hash(), copy.zobrist_hash(), "{:?}", mv);
+            assert_eq!(gm.castling, copy.castling);
+            assert_eq!(gm.en_passant, copy.en_passant);
+            assert_eq!(gm.whites.len(), copy.whites.len());
+            assert_eq!(gm.blacks.len(), copy.blacks.len());
+        }
+    }
+
+    #[test]
+    fn test_perft_start_position() {
+        let gm = GameManager::new();
+        for (depth, expected) in [(1, 20), (2, 400), (3, 8902)] {
+            assert_eq!(expected, gm.perft(depth), "perft({})", depth);
+        }
+    }
+
+    #[test]
+    fn test_perft_stats() {
+        let gm = GameManager::new();
+        let stats = gm.perft_stats(3);
+        assert_eq!(gm.perft(3), stats.nodes);
+        assert_eq!(
+            (34, 12, 0),
+            (stats.captures, stats.checks, stats.checkmates)
+        );
+        let kiwipete = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+        let gm = GameManager::from_fen(kiwipete).unwrap();
+        assert_eq!(
+            PerftStats {
+                nodes: 2039,
+                captures: 351,
+                en_passants: 1,
+                castles: 91,
+                promotions: 0,
+                checks: 3,
+                checkmates: 0,
+            },
+            gm.perft_stats(2)
+        );
+        // Promotions of the reference position 4, counted once per promoted piece
+        let gm = GameManager::from_fen("n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1").unwrap();
+        let stats = gm.perft_stats(1);
+        assert_eq!((24, 12), (stats.nodes, stats.promotions));
+    }
+
+    #[test]
+    fn test_board_size() {
+        assert!(GameManager::from_fen(MINICHESS_FEN).is_err());
+        let gm = GameManager::from_sized_fen(MINICHESS_FEN, BoardSize::MINICHESS).unwrap();
+        assert_eq!(BoardSize::new(5, 5), Some(gm.board_size()));
+        assert_eq!(MINICHESS_FEN, gm.to_fen());
+        for (depth, expected) in [(1, 7), (2, 53)] {
+            assert_eq!(expected, gm.perft(depth), "perft({})", depth);
+        }
+        let mut gm =
+            GameManager::from_sized_fen("4k/P4/5/5/4K w - - 0 1", BoardSize::MINICHESS).unwrap();
+        assert_eq!(
+            4,
+            gm.legal_moves()
+                .iter()
+                .filter(|mv| mv.promotion.is_some())
+                .count()
+        );
+        gm.play(&Move::with_promotion(Square::A4, Square::A5, Kind::Rook))
+            .unwrap();
+        assert_eq!("R3k/5/5/5/4K b - - 0 1", gm.to_fen());
+
+        // Demi chess pawns still step twice, and can be taken en passant
+        let demi = BoardSize::new(8, 4).unwrap();
+        let mut gm =
+            GameManager::from_sized_fen("kbnr/pppp/4/4/4/4/PPPP/KBNR w - - 0 1", demi).unwrap();
+        gm.play(&Move::new(Square::B2, Square::B4)).unwrap();
+        assert_eq!(Some(Position::from(Square::B3)), gm.en_passant);
+        assert_eq!(10, gm.legal_moves().len());
+
+        assert!(
+            GameManager::from_sized_fen("4k/5/4/5/4K w - - 0 1", BoardSize::MINICHESS).is_err()
+        );
+        assert_eq!(None, BoardSize::new(9, 8));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_par_perft_matches_perft() {
+        let gm = GameManager::new();
+        for depth in 0..4 {
+            assert_eq!(gm.perft(depth), gm.par_perft(depth), "par_perft({})", depth);
+        }
+    }
+
+    #[test]
+    fn test_perft_castling() {
+        // r3k2r/8/8/8/8/8/8/R3K2R w KQkq -
+        let gm = GameManager {
+            whites: [
+                Piece::new(Player::White, Kind::Rook, 0, 0),
+                Piece::new(Player::White, Kind::King, 0, 4),
+                Piece::new(Player::White, Kind::Rook, 0, 7),
+            ]
+            .into(),
+            blacks: [
+                Piece::new(Player::Black, Kind::Rook, 7, 0),
+                Piece::new(Player::Black, Kind::King, 7, 4),
+                Piece::new(Player::Black, Kind::Rook, 7, 7),
+            ]
+            .into(),
+            castling: CastlingRights::all(),
+            ..GameManager::empty()
+        };
+        for (depth, expected) in [(1, 26), (2, 568), (3, 13744)] {
+            assert_eq!(expected, gm.perft(depth), "perft({})", depth);
+        }
+    }
+
+    #[test]
+    fn test_perft_promotion() {
+        // n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - -
+        let gm = GameManager {
+            whites: [
+                Piece::new(Player::White, Kind::Pawn, 6, 0),
+                Piece::new(Player::White, Kind::Pawn, 6, 1),
+                Piece::new(Player::White, Kind::Pawn, 6, 2),
+                Piece::new(Player::White, Kind::King, 1, 4),
+                Piece::new(Player::White, Kind::Knight, 0, 5),
+                Piece::new(Player::White, Kind::Knight, 0, 7),
+            ]
+            .into(),
+            blacks: [
+                Piece::new(Player::Black, Kind::Knight, 7, 0),
+                Piece::new(Player::Black, Kind::Knight, 7, 2),
+                Piece::new(Player::Black, Kind::King, 6, 3),
+                Piece::new(Player::Black, Kind::Pawn, 1, 5),
+                Piece::new(Player::Black, Kind::Pawn, 1, 6),
+                Piece::new(Player::Black, Kind::Pawn, 1, 7),
+            ]
+            .into(),
+            turn: Player::Black,
+            ..GameManager::empty()
+        };
+        for (depth, expected) in [(1, 24), (2, 496), (3, 9483)] {
+            assert_eq!(expected, gm.perft(depth), "perft({})", depth);
+        }
+    }
+
+    #[test]
+    fn test_perft_en_passant_and_pins() {
+        // 8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - -
+        let gm = GameManager {
+            whites: [
+                Piece::new(Player::White, Kind::King, 4, 0),
+                Piece::new(Player::White, Kind::Pawn, 4, 1),
+                Piece::new(Player::White, Kind::Rook, 3, 1),
+                Piece::new(Player::White, Kind::Pawn, 1, 4),
+                Piece::new(Player::White, Kind::Pawn, 1, 6),
+            ]
+            .into(),
+            blacks: [
+                Piece::new(Player::Black, Kind::Pawn, 6, 2),
+                Piece::new(Player::Black, Kind::Pawn, 5, 3),
+                Piece::new(Player::Black, Kind::Rook, 4, 7),
+                Piece::new(Player::Black, Kind::Pawn, 3, 5),
+                Piece::new(Player::Black, Kind::King, 3, 7),
+            ]
+            .into(),
+            ..GameManager::empty()
+        };
+        for (depth, expected) in [(1, 14), (2, 191), (3, 2812)] {
+            assert_eq!(expected, gm.perft(depth), "perft({})", depth);
+        }
+    }
+
+    #[test]
+    fn test_play_records_history() {
+        let mut gm = GameManager::new();
+        assert!(matches!(
+            gm.play(&Move::new(Square::E7, Square::E5)),
+            Err(MoveErr::WrongTurn)
+        ));
+        assert!(matches!(
+            gm.play(&Move::new(Square::E2, Square::E5)),
+            Err(MoveErr::InvalidMove {
+                piece: Kind::Pawn,
+                ..
+            })
+        ));
+        gm.play(&Move::new(Square::E2, Square::E4)).unwrap();
+        gm.play(&Move::new(Square::D7, Square::D5)).unwrap();
+        gm.play(&Move::new(Square::F1, Square::B5)).unwrap();
+        assert!(matches!(
+            gm.play(&Move::new(Square::E8, Square::D7)),
+            Err(MoveErr::KingInCheck)
+        ));
+        assert_eq!(3, gm.history().len());
+        assert_eq!(2, gm.fullmove_number);
+        assert_eq!(1, gm.halfmove_clock);
+        let start = gm.initial_position();
+        assert_eq!(GameManager::new().zobrist_hash(), start.zobrist_hash());
+        assert_eq!(1, start.fullmove_number);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let mut gm = GameManager::new();
+        gm.play(&Move::new(Square::E2, Square::E4)).unwrap();
+        gm.play(&Move::new(Square::D7, Square::D5)).unwrap();
+        gm.play(&Move::new(Square::E4, Square::D5)).unwrap();
+        let json = serde_json::to_string(&gm).unwrap();
+        let mut restored: GameManager = serde_json::from_str(&json).unwrap();
+        assert_eq!(gm.to_fen(), restored.to_fen());
+        assert_eq!(3, restored.history().len());
+        assert!(restored.history()[2].captured.is_some());
+        assert_eq!(
+            gm.attacked_squares(&Player::White),
+            restored.attacked_squares(&Player::White)
+        );
+        assert_eq!(
+            GameManager::new().to_fen(),
+            restored.initial_position().to_fen()
+        );
+        restored.play(&Move::new(Square::D8, Square::D5)).unwrap();
+
+        let too_many = format!(
+            "[{}]",
+            vec![r#"{"player":"White","kind":"Pawn","row":1,"column":0}"#; 17].join(",")
+        );
+        assert!(serde_json::from_str::<crate::piece::PieceList>(&too_many).is_err());
+    }
+
+    #[test]
+    fn test_move_records() {
+        let mut gm = GameManager::from_fen("4k3/8/8/3pP3/8/8/8/R3K2R w KQ d6 0 1").unwrap();
+        gm.play(&Move::new(Square::E5, Square::D6)).unwrap();
+        gm.play(&Move::new(Square::E8, Square::F7)).unwrap();
+        gm.play(&Move::new(Square::E1, Square::G1)).unwrap();
+        let history = gm.history();
+        #[cfg(feature = "notation")]
+        assert_eq!("exd6", history[0].san);
+        assert_eq!(Some(Kind::Pawn), history[0].captured.map(|p| p.kind));
+        assert_eq!(
+            MoveFlags {
+                capture: true,
+                en_passant: true,
+                ..MoveFlags::default()
+            },
+            history[0].flags
+        );
+        assert_eq!(MoveFlags::default(), history[1].flags);
+        #[cfg(feature = "notation")]
+        assert_eq!("O-O+", history[2].san);
+        assert_eq!(
+            MoveFlags {
+                castling: true,
+                check: true,
+                check_kind: Some(CheckKind::Direct),
+                ..MoveFlags::default()
+            },
+            history[2].flags
+        );
+        assert_eq!(gm.zobrist_hash(), history[2].position_hash);
+        let hash = history[0].position_hash;
+        gm.undo();
+        gm.undo();
+        assert_eq!(hash, gm.zobrist_hash());
+    }
+
+    #[test]
+    fn test_check_kinds() {
+        let gm = GameManager::from_fen("4k3/8/8/8/4N3/8/8/Q3R2K w - - 0 1").unwrap();
+        assert_eq!(None, gm.check_kind(&Move::new(Square::H1, Square::H2)));
+        assert_eq!(
+            Some(CheckKind::Direct),
+            gm.check_kind(&Move::new(Square::A1, Square::A4))
+        );
+        assert_eq!(
+            Some(CheckKind::Discovered),
+            gm.check_kind(&Move::new(Square::E4, Square::C5))
+        );
+        assert_eq!(
+            Some(CheckKind::Double),
+            gm.check_kind(&Move::new(Square::E4, Square::D6))
+        );
+        // The pawn taken en passant was blocking the bishop
+        let mut gm = GameManager::from_fen("8/8/2k5/3pP3/8/8/8/K6B w - d6 0 1").unwrap();
+        gm.play(&Move::new(Square::E5, Square::D6)).unwrap();
+        assert_eq!(
+            Some(CheckKind::Discovered),
+            gm.history()[0].flags.check_kind
+        );
+    }
+
+    #[test]
+    fn test_captured_by() {
+        let mut gm = GameManager::new();
+        for mv in [
+            Move::new(Square::E2, Square::E4),
+            Move::new(Square::D7, Square::D5),
+            Move::new(Square::E4, Square::D5),
+            Move::new(Square::D8, Square::D5),
+            Move::new(Square::B1, Square::C3),
+            Move::new(Square::D5, Square::A2),
+            Move::new(Square::A1, Square::A2),
+        ] {
+            gm.play(&mv).unwrap();
+        }
+        let kinds = |pieces: Vec<Piece>| pieces.iter().map(|p| p.kind).collect::<Vec<_>>();
+        assert_eq!(
+            vec![Kind::Pawn, Kind::Queen],
+            kinds(gm.captured_by(&Player::White))
+        );
+        assert_eq!(
+            vec![Kind::Pawn, Kind::Pawn],
+            kinds(gm.captured_by(&Player::Black))
+        );
+        assert_eq!(8, gm.material_difference(&Player::White));
+        assert_eq!(-8, gm.material_difference(&Player::Black));
+        gm.undo();
+        assert_eq!(vec![Kind::Pawn], kinds(gm.captured_by(&Player::White)));
+    }
+
+    #[test]
+    fn test_error_messages() {
+        fn play(gm: &mut GameManager, uci: &str) -> Result<(), Box<dyn std::error::Error>> {
+            gm.play(&Move::from_uci(uci).ok_or("invalid UCI")?)?;
+            Ok(())
+        }
+        let mut gm = GameManager::new();
+        play(&mut gm, "e2e4").unwrap();
+        let err = play(&mut gm, "d2d4").unwrap_err();
+        assert_eq!("not this side's turn", err.to_string());
+        assert!(matches!(err.downcast_ref(), Some(MoveErr::WrongTurn)));
+        assert_eq!("invalid UCI", play(&mut gm, "e9").unwrap_err().to_string());
+        assert_eq!(
+            "invalid board field in FEN",
+            GameManager::from_fen("9/8 w - - 0 1")
+                .unwrap_err()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn test_move_error_payloads() {
+        let mut gm = GameManager::new();
+        let piece_at = |gm: &GameManager, square: Square| {
+            *gm.whites
+                .iter()
+                .find(|p| Position::from_piece(p) == square.into())
+                .unwrap()
+        };
+        let rook = piece_at(&gm, Square::A1);
+        assert!(matches!(
+            gm.move_piece(&rook, Square::A5),
+            Err(MoveErr::PieceBlocking { at }) if at == Square::A2.into()
+        ));
+        assert!(matches!(
+            gm.move_piece(&rook, Square::B1),
+            Err(MoveErr::FriendlyFire {
+                occupied_by: Kind::Knight
+            })
+        ));
+        let knight = piece_at(&gm, Square::G1);
+        let err = gm.move_piece(&knight, Square::G3).unwrap_err();
+        assert!(matches!(
+            err,
+            MoveErr::InvalidMove {
+                piece: Kind::Knight,
+                from,
+                to,
+            } if from == Square::G1.into() && to == Square::G3.into()
+        ));
+        assert_eq!("a Knight can't move from g1 to g3", err.to_string());
+        assert!(matches!(
+            gm.play(&Move::new(Square::E4, Square::E5)),
+            Err(MoveErr::NoPiece { at }) if at == Square::E4.into()
+        ));
+        #[cfg(feature = "notation")]
+        assert!(matches!(gm.parse_uci("e2e9"), Err(MoveErr::Syntax)));
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_tracing() {
+        use std::{
+            fmt::Write,
+            sync::{Arc, Mutex},
+        };
+
+        use tracing::{
+            field::{Field, Visit},
+            span, Event, Metadata, Subscriber,
+        };
+
+        // Keeps the fields of every event
+        #[derive(Clone, Default)]
+        struct Recorder(Arc<Mutex<Vec<String>>>);
+
+        struct Fields(String);
+
+        impl Visit for Fields {
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                write!(self.0, "{}={:?} ", field.name(), value).unwrap();
+            }
+        }
+
+        impl Subscriber for Recorder {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+            fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+                span::Id::from_u64(1)
+            }
+            fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+            fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+            fn event(&self, event: &Event<'_>) {
+                let mut fields = Fields(String::new());
+                event.record(&mut fields);
+                self.0.lock().unwrap().push(fields.0);
+            }
+            fn enter(&self, _: &span::Id) {}
+            fn exit(&self, _: &span::Id) {}
+        }
+
+        let recorder = Recorder::default();
+        tracing::subscriber::with_default(recorder.clone(), || {
+            let mut gm = GameManager::new();
+            gm.play(&Move::new(Square::E2, Square::E4)).unwrap();
+            assert!(gm.play(&Move::new(Square::D2, Square::D4)).is_err());
+            assert!(gm.play(&Move::new(Square::E8, Square::E7)).is_err());
+        });
+        let events = recorder.0.lock().unwrap();
+        assert_eq!(3, events.len(), "{:?}", events);
+        assert!(events[0].starts_with("message=move played"));
+        assert_eq!("error=not this side's turn ", events[1]);
+        assert_eq!("error=a King can't move from e8 to e7 ", events[2]);
+    }
+
+    #[cfg(feature = "clock")]
+    #[test]
+    fn test_clock_pressed_by_moves() {
+        let mut gm = GameManager::new();
+        gm.clock = Some(Clock::new(TimeControl::fischer(
+            Duration::from_secs(60),
+            Duration::from_secs(2),
+        )));
+        gm.play(&Move::new(Square::E2, Square::E4)).unwrap();
+        let clock = gm.clock.as_ref().unwrap();
+        assert_eq!(Some(Player::Black), clock.running());
+        assert_eq!(Duration::from_secs(62), clock.remaining(&Player::White));
+        let pawn = *gm
+            .blacks
+            .iter()
+            .find(|p| p.column == 4 && p.kind == Kind::Pawn)
+            .unwrap();
+        gm.move_piece(&pawn, Square::E5).unwrap();
+        let clock = gm.clock.as_ref().unwrap();
+        assert_eq!(Some(Player::White), clock.running());
+        assert_eq!(1, clock.moves(&Player::Black));
+        assert!(gm.play(&Move::new(Square::E4, Square::E5)).is_err());
+        assert_eq!(1, gm.clock.as_ref().unwrap().moves(&Player::White));
+    }
+
+    #[cfg(feature = "clock")]
+    #[test]
+    fn test_time_forfeit() {
+        let mut gm = GameManager::new();
+        let mut clock = Clock::new(TimeControl::sudden_death(Duration::from_secs(60)));
+        clock.start(Player::White);
+        clock.press_after(Duration::from_secs(61));
+        gm.clock = Some(clock.clone());
+        assert_eq!(GameStatus::TimeForfeit(Player::Black), gm.status());
+
+        let mut gm = GameManager::from_fen("4k3/8/8/8/8/8/4P3/2B1K1B1 w - - 0 1").unwrap();
+        gm.clock = Some(clock);
+        assert_eq!(GameStatus::TimeoutDraw, gm.status());
+        assert!(gm.has_mating_material(&Player::White));
+        assert!(!gm.has_mating_material(&Player::Black));
+        for (fen, mating) in [
+            ("4k3/8/8/8/8/8/8/4KN2 w - - 0 1", false),
+            ("4k3/4p3/8/8/8/8/8/4KN2 w - - 0 1", true),
+            ("4k3/8/8/8/8/8/8/2B1KB2 w - - 0 1", true),
+            ("4k3/8/8/8/8/8/8/3BKB2 w - - 0 1", false),
+            ("4k3/8/8/8/8/8/8/4KNN1 w - - 0 1", true),
+        ] {
+            let gm = GameManager::from_fen(fen).unwrap();
+            assert_eq!(mating, gm.has_mating_material(&Player::White), "{}", fen);
+        }
+    }
+
+    #[test]
+    fn test_game_outcomes() {
+        let mut gm = GameManager::new();
+        gm.play(&Move::new(Square::E2, Square::E4)).unwrap();
+        assert!(gm.resign(&Player::White));
+        assert_eq!(GameStatus::Resignation(Player::Black), gm.status());
+        assert_eq!(
+            ("0-1", "normal"),
+            (gm.status().result(), gm.status().termination())
+        );
+        assert!(matches!(
+            gm.play(&Move::new(Square::E7, Square::E5)),
+            Err(MoveErr::GameOver)
+        ));
+        assert!(!gm.agree_draw());
+        gm.undo();
+        assert_eq!(GameStatus::Ongoing, gm.status());
+        assert_eq!("unterminated", gm.status().termination());
+
+        assert!(gm.abandon(&Player::White));
+        assert_eq!(Some(Player::Black), gm.status().winner());
+        assert_eq!("abandoned", gm.status().termination());
+
+        let mut gm = GameManager::new();
+        assert!(!gm.claim_draw(DrawRule::FiftyMoves));
+        gm.halfmove_clock = 100;
+        assert!(gm.claim_draw(DrawRule::FiftyMoves));
+        assert_eq!("1/2-1/2", gm.status().result());
+
+        let mut gm = GameManager::from_fen("4k3/8/8/8/8/8/8/2B1K3 w - - 0 1").unwrap();
+        assert_eq!(
+            GameStatus::DrawByRule(DrawRule::InsufficientMaterial),
+            gm.status()
+        );
+        assert!(!gm.agree_draw());
+    }
+
+    #[test]
+    fn test_position_eq() {
+        let play = |moves: &[(Square, Square)]| {
+            let mut gm = GameManager::new();
+            for (from, to) in moves {
+                gm.play(&Move::new(*from, *to)).unwrap();
+            }
+            gm
+        };
+        let a = play(&[
+            (Square::G1, Square::F3),
+            (Square::G8, Square::F6),
+            (Square::B1, Square::C3),
+        ]);
+        let b = play(&[
+            (Square::B1, Square::C3),
+            (Square::G8, Square::F6),
+            (Square::G1, Square::F3),
+        ]);
+        assert!(a == b);
+        let fen = "rnbqkb1r/pppppppp/5n2/8/8/2N2N2/PPPPPPPP/R1BQKB1R b KQkq - 7 30";
+        assert!(a == GameManager::from_fen(fen).unwrap());
+        let hash = |gm: &GameManager| {
+            let mut hasher = DefaultHasher::new();
+            gm.hash(&mut hasher);
+            hasher.finish()
+        };
+        assert_eq!(hash(&a), hash(&b));
+        assert_ne!(hash(&a), hash(&GameManager::new()));
+
+        // Same board, only the en passant square differs
+        let ep = play(&[(Square::E2, Square::E4)]);
+        let fen = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1";
+        assert!(ep != GameManager::from_fen(fen).unwrap());
+        let mut castled = GameManager::new();
+        castled.castling.white_king_side = false;
+        assert!(castled != GameManager::new());
+    }
+
+    #[test]
+    fn test_repetition_count() {
+        let mut gm = GameManager::new();
+        assert_eq!(1, gm.repetition_count());
+        let shuffle = [
+            Move::new(Square::G1, Square::F3),
+            Move::new(Square::G8, Square::F6),
+            Move::new(Square::F3, Square::G1),
+            Move::new(Square::F6, Square::G8),
+        ];
+        for mv in &shuffle {
+            gm.play(mv).unwrap();
+        }
+        assert_eq!(2, gm.repetition_count());
+        assert!(!gm.claim_draw(DrawRule::ThreefoldRepetition));
+        gm.play(&shuffle[0]).unwrap();
+        assert_eq!(2, gm.repetition_count());
+        for mv in &shuffle[1..] {
+            gm.play(mv).unwrap();
+        }
+        assert_eq!(3, gm.repetition_count());
+        assert!(gm.claim_draw(DrawRule::ThreefoldRepetition));
+
+        // Pawn moves make the earlier positions unreachable
+        let mut gm = GameManager::new();
+        for mv in &shuffle {
+            gm.play(mv).unwrap();
+        }
+        gm.play(&Move::new(Square::E2, Square::E4)).unwrap();
+        gm.play(&Move::new(Square::E7, Square::E5)).unwrap();
+        assert_eq!(1, gm.repetition_count());
+        for mv in &shuffle {
+            gm.play(mv).unwrap();
+        }
+        assert_eq!(2, gm.repetition_count());
+    }
+
+    #[test]
+    fn test_apply_moves() {
+        let mut gm = GameManager::new();
+        gm.play(&Move::new(Square::E2, Square::E4)).unwrap();
+        let fen = gm.to_fen();
+        let moves = [
+            Move::new(Square::E7, Square::E5),
+            Move::new(Square::G1, Square::F3),
+            Move::new(Square::E8, Square::E6),
+        ];
+        assert!(matches!(
+            gm.apply_moves(&moves),
+            Err((
+                2,
+                MoveErr::InvalidMove {
+                    piece: Kind::King,
+                    ..
+                }
+            ))
+        ));
+        assert_eq!(fen, gm.to_fen());
+        assert_eq!(1, gm.history().len());
+        assert_eq!(None, gm.redo());
+        gm.apply_moves(&moves[..2]).unwrap();
+        assert_eq!(3, gm.history().len());
+        assert_eq!(Player::Black, gm.turn);
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut gm = GameManager::from_fen("4k3/8/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+        let moves = Arc::new(AtomicUsize::new(0));
+        let counter = moves.clone();
+        gm.on_move(move |_| {
+            counter.fetch_add(1, Ordering::Relaxed);
+        });
+        #[cfg(feature = "clock")]
+        {
+            gm.clock = Some(Clock::new(TimeControl::sudden_death(Duration::from_secs(
+                60,
+            ))));
+        }
+        gm.play(&Move::new(Square::H1, Square::H8)).unwrap();
+        gm.resign(&Player::Black);
+        gm.reset();
+        assert_eq!(GameManager::new().to_fen(), gm.to_fen());
+        assert!(gm.history().is_empty());
+        assert_eq!(GameStatus::Ongoing, gm.status());
+        #[cfg(feature = "clock")]
+        assert_eq!(None, gm.clock.as_ref().unwrap().running());
+        gm.play(&Move::new(Square::E2, Square::E4)).unwrap();
+        assert_eq!(2, moves.load(Ordering::Relaxed));
+        assert_eq!(GameManager::default(), gm.initial_position());
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut gm = GameManager::new();
+        assert_eq!(None, gm.undo());
+        let moves = [
+            Move::new(Square::E2, Square::E4),
+            Move::new(Square::D7, Square::D5),
+            Move::new(Square::E4, Square::D5),
+        ];
+        for mv in &moves {
+            gm.play(mv).unwrap();
+        }
+        let fen = gm.to_fen();
+        assert_eq!(Some(moves[2]), gm.undo());
+        assert_eq!(Some(moves[1]), gm.undo());
+        assert_eq!(16, gm.blacks.len());
+        assert_eq!(Player::Black, gm.turn);
+        assert_eq!(Some(moves[1]), gm.redo());
+        assert_eq!(Some(moves[2]), gm.redo());
+        assert_eq!(None, gm.redo());
+        assert_eq!(fen, gm.to_fen());
+        assert_eq!(15, gm.blacks.len());
+
+        gm.undo();
+        gm.undo();
+        // Playing the move that would be redone keeps the rest of the redo stack
+        gm.play(&moves[1]).unwrap();
+        assert_eq!(Some(moves[2]), gm.redo());
+        gm.undo();
+        gm.play(&Move::new(Square::G1, Square::F3)).unwrap();
+        assert_eq!(None, gm.redo());
+    }
+
+    #[test]
+    fn test_mobility() {
+        let mut gm = GameManager::new();
+        assert_eq!(20, gm.mobility(&Player::White));
+        assert_eq!(20, gm.mobility(&Player::Black));
+        gm.make_move(&Move::new(Square::E2, Square::E4));
+        assert_eq!(20, gm.mobility(&Player::Black));
+        assert_eq!(30, gm.mobility(&Player::White));
+        assert_eq!(Player::Black, gm.turn);
+    }
+
+    // Walks the game tree checking the incremental attack maps against a full rebuild
+    fn assert_attacks_in_sync(gm: &mut GameManager, depth: u8) {
+        let mut fresh = gm.clone();
+        fresh.refresh_attacks();
+        for player in [Player::White, Player::Black] {
+            assert_eq!(
+                fresh.attacked_squares(&player),
+                gm.attacked_squares(&player)
+            );
+        }
+        if depth == 0 {
+            return;
+        }
+        for mv in gm.legal_moves() {
+            let undo = gm.make_move(&mv);
+            assert_attacks_in_sync(gm, depth - 1);
+            gm.unmake_move(undo);
+        }
+    }
+
+    #[test]
+    fn test_attack_maps_incremental() {
+        // r3k2r/8/8/8/8/8/8/R3K2R w KQkq -
+        let mut gm = GameManager {
+            whites: [
+                Piece::new(Player::White, Kind::Rook, 0, 0),
+                Piece::new(Player::White, Kind::King, 0, 4),
+                Piece::new(Player::White, Kind::Rook, 0, 7),
+            ]
+            .into(),
+            blacks: [
+                Piece::new(Player::Black, Kind::Rook, 7, 0),
+                Piece::new(Player::Black, Kind::King, 7, 4),
+                Piece::new(Player::Black, Kind::Rook, 7, 7),
+            ]
+            .into(),
+            castling: CastlingRights::all(),
+            ..GameManager::empty()
+        };
+        assert_attacks_in_sync(&mut gm, 2);
+
+        let mut gm = GameManager::new();
+        assert_eq!(22, gm.attacked_squares(&Player::White).squares().count());
+        assert_eq!(3, gm.attacked_squares(&Player::White).count(Square::F3));
+        assert!(!gm.attacked_squares(&Player::Black).is_attacked(Square::E4));
+        assert_attacks_in_sync(&mut gm, 3);
+    }
+
+    #[test]
+    fn test_attack_maps_follow_edits() {
+        let mut gm = GameManager::from_fen("4k3/8/8/8/8/8/8/4K3 b - - 0 1").unwrap();
+        assert!(!gm.is_in_check(&Player::Black));
+        // No refresh after the edit, the maps are rebuilt for the new placement
+        gm.whites.push(Piece::new(Player::White, Kind::Rook, 5, 4));
+        assert!(gm.is_in_check(&Player::Black));
+        assert_eq!(GameStatus::Ongoing, gm.status());
+        gm.play(&Move::new(Square::E8, Square::D8)).unwrap();
+        assert_attacks_in_sync(&mut gm, 2);
+    }
+
+    #[test]
+    fn test_piece_off_the_board() {
+        let mut gm = GameManager::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let hash = gm.zobrist_hash();
+        for (row, column) in [(8, 0), (9, 4), (u8::MAX, u8::MAX)] {
+            gm.whites
+                .push(Piece::new(Player::White, Kind::Queen, row, column));
+        }
+        assert_eq!(hash, gm.zobrist_hash());
+        assert_eq!(GameStatus::Ongoing, gm.status());
+        assert!(!gm.is_in_check(&Player::Black));
+        // Whatever moves the stray queens get, generating them doesn't panic either
+        assert!(!gm.legal_moves().is_empty());
+    }
 }
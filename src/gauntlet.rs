@@ -0,0 +1,281 @@
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+    time::Duration,
+};
+
+use crate::{
+    book::PolyglotBook,
+    clock::{Clock, TimeControl},
+    eval::Evaluator,
+    game_manager::{DrawRule, GameManager},
+    moves::Move,
+    pgn::PgnWriter,
+    piece::Player,
+    search::{search, SearchLimits},
+};
+
+// Side of a match, given the game and the time it may spend on its move
+pub trait Engine {
+    fn name(&self) -> String;
+    fn best_move(&mut self, gm: &GameManager, movetime: Duration) -> Option<Move>;
+}
+
+// The built-in search with an evaluation of its own, limited in depth if `depth` is set
+#[derive(Clone, Debug)]
+pub struct SearchEngine<E> {
+    pub name: String,
+    pub eval: E,
+    pub depth: Option<u8>,
+}
+
+impl<E: Evaluator + Sync> Engine for SearchEngine<E> {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn best_move(&mut self, gm: &GameManager, movetime: Duration) -> Option<Move> {
+        let limits = SearchLimits {
+            depth: self.depth,
+            movetime: Some(movetime),
+            ..SearchLimits::default()
+        };
+        search(gm, &limits, &self.eval, |_| {}).map(|(mv, _)| mv)
+    }
+}
+
+// External engine spoken to over UCI
+pub struct UciProcess {
+    name: String,
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl UciProcess {
+    pub fn spawn(program: &str) -> io::Result<Self> {
+        let mut child = Command::new(program)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let missing = || io::Error::other("engine pipes not captured");
+        let stdin = child.stdin.take().ok_or_else(missing)?;
+        let stdout = BufReader::new(child.stdout.take().ok_or_else(missing)?);
+        let mut engine = Self {
+            name: program.to_string(),
+            child,
+            stdin,
+            stdout,
+        };
+        writeln!(engine.stdin, "uci")?;
+        while let Some(line) = engine.read_line()? {
+            if let Some(name) = line.strip_prefix("id name ") {
+                engine.name = name.to_string();
+            } else if line == "uciok" {
+                break;
+            }
+        }
+        Ok(engine)
+    }
+
+    fn read_line(&mut self) -> io::Result<Option<String>> {
+        let mut line = String::new();
+        if self.stdout.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        Ok(Some(line.trim().to_string()))
+    }
+
+    fn go(&mut self, gm: &GameManager, movetime: Duration) -> io::Result<Option<Move>> {
+        let mut position = format!("position fen {}", gm.initial_position().to_fen());
+        if !gm.history().is_empty() {
+            position.push_str(" moves");
+            for record in gm.history() {
+                position.push(' ');
+                position.push_str(&record.mv.to_uci());
+            }
+        }
+        writeln!(self.stdin, "{}", position)?;
+        writeln!(self.stdin, "go movetime {}", movetime.as_millis())?;
+        while let Some(line) = self.read_line()? {
+            if let Some(best) = line.strip_prefix("bestmove") {
+                let best = best.split_whitespace().next().unwrap_or("");
+                return Ok(Move::from_uci(best));
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl Engine for UciProcess {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    // A crashed engine has no move, it loses on time
+    fn best_move(&mut self, gm: &GameManager, movetime: Duration) -> Option<Move> {
+        self.go(gm, movetime).ok().flatten()
+    }
+}
+
+impl Drop for UciProcess {
+    fn drop(&mut self) {
+        let _ = writeln!(self.stdin, "quit");
+        let _ = self.child.wait();
+    }
+}
+
+// Series of games between two engines, each one playing White every other game
+#[derive(Clone, Debug)]
+pub struct Gauntlet {
+    pub games: u32,
+    pub time_control: TimeControl,
+    // Openings drawn at random from the book, up to `book_plies` plies
+    pub book: Option<PolyglotBook>,
+    pub book_plies: u32,
+    // Games still going after this many plies are drawn
+    pub max_plies: u32,
+}
+
+// Results from the point of view of the first engine
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GauntletReport {
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+    pub pgns: Vec<String>,
+}
+
+impl Gauntlet {
+    pub fn new(games: u32, time_control: TimeControl) -> Self {
+        Self {
+            games,
+            time_control,
+            book: None,
+            book_plies: 0,
+            max_plies: 400,
+        }
+    }
+
+    // `rng` picks the book moves, it has to return uniformly distributed numbers
+    pub fn run<'a>(
+        &self,
+        first: &mut (dyn Engine + 'a),
+        second: &mut (dyn Engine + 'a),
+        rng: &mut impl FnMut() -> u64,
+    ) -> GauntletReport {
+        let mut report = GauntletReport::default();
+        for round in 0..self.games {
+            let first_white = round % 2 == 0;
+            let (white, black) = if first_white {
+                (&mut *first, &mut *second)
+            } else {
+                (&mut *second, &mut *first)
+            };
+            let gm = self.play(white, black, rng);
+            match (gm.status().winner(), first_white) {
+                (None, _) => report.draws += 1,
+                (Some(Player::White), true) | (Some(Player::Black), false) => report.wins += 1,
+                _ => report.losses += 1,
+            }
+            let pgn = PgnWriter::new()
+                .tag("Event", "Gauntlet")
+                .tag("Round", &(round + 1).to_string())
+                .tag("White", &white.name())
+                .tag("Black", &black.name())
+                .write(&gm);
+            report.pgns.push(pgn);
+        }
+        report
+    }
+
+    fn play<'a>(
+        &self,
+        white: &mut (dyn Engine + 'a),
+        black: &mut (dyn Engine + 'a),
+        rng: &mut impl FnMut() -> u64,
+    ) -> GameManager {
+        let mut gm = GameManager::new();
+        let mut clock = Clock::new(self.time_control.clone());
+        clock.start(Player::White);
+        gm.clock = Some(clock);
+        while !gm.status().is_over() {
+            let ply = gm.history().len() as u32;
+            if ply >= self.max_plies {
+                gm.agree_draw();
+                break;
+            }
+            let book_move = match &self.book {
+                Some(book) if ply < self.book_plies => book.weighted_random_book_move(&gm, rng),
+                _ => None,
+            };
+            let engine = match gm.turn {
+                Player::White => &mut *white,
+                Player::Black => &mut *black,
+            };
+            let mv = book_move.or_else(|| engine.best_move(&gm, self.movetime(&gm)));
+            // An engine without a legal move to play forfeits
+            let played = mv.is_some_and(|mv| gm.play(&mv).is_ok());
+            if !played {
+                let turn = gm.turn;
+                gm.resign(&turn);
+                break;
+            }
+            if gm.repetition_count() >= 3 {
+                gm.claim_draw(DrawRule::ThreefoldRepetition);
+            } else if gm.halfmove_clock >= 100 {
+                gm.claim_draw(DrawRule::FiftyMoves);
+            }
+        }
+        if let Some(clock) = &mut gm.clock {
+            clock.stop();
+        }
+        gm
+    }
+
+    // A thirtieth of the time left, as if 30 moves were still to come
+    fn movetime(&self, gm: &GameManager) -> Duration {
+        gm.clock.as_ref().map_or(Duration::from_secs(1), |clock| {
+            clock.remaining(&gm.turn) / 30
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{Engine, Gauntlet, SearchEngine};
+    use crate::{clock::TimeControl, eval::ClassicalEval, game_manager::GameManager, moves::Move};
+
+    // Plays the first legal move it finds
+    struct FirstMove;
+
+    impl Engine for FirstMove {
+        fn name(&self) -> String {
+            "first move".to_string()
+        }
+
+        fn best_move(&mut self, gm: &GameManager, _: Duration) -> Option<Move> {
+            gm.legal_moves().first().copied()
+        }
+    }
+
+    #[test]
+    fn test_gauntlet() {
+        let mut engine = SearchEngine {
+            name: "classical".to_string(),
+            eval: ClassicalEval,
+            depth: Some(1),
+        };
+        let mut gauntlet = Gauntlet::new(2, TimeControl::sudden_death(Duration::from_secs(600)));
+        gauntlet.max_plies = 30;
+        let report = gauntlet.run(&mut engine, &mut FirstMove, &mut || 0);
+        assert_eq!(2, report.wins + report.draws + report.losses);
+        assert_eq!(0, report.losses);
+        assert_eq!(2, report.pgns.len());
+        assert!(report.pgns[0].contains("[White \"classical\"]"));
+        assert!(report.pgns[1].contains("[White \"first move\"]"));
+        assert!(report.pgns[1].contains("[Round \"2\"]"));
+    }
+}
@@ -0,0 +1,85 @@
+use crate::{
+    game_manager::GameManager,
+    piece::{Player, Position},
+    square::Square,
+};
+
+// Traditional odds given by the stronger player, who plays White but for pawn and move
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Handicap {
+    // Black plays without the f7 pawn, White moves first
+    PawnAndMove,
+    // White plays without the b1 knight
+    KnightOdds,
+    // White plays without the queen
+    QueenOdds,
+}
+
+impl Handicap {
+    // Side giving the odds and the square of the piece it removes
+    pub fn removed(&self) -> (Player, Square) {
+        match self {
+            Handicap::PawnAndMove => (Player::Black, Square::F7),
+            Handicap::KnightOdds => (Player::White, Square::B1),
+            Handicap::QueenOdds => (Player::White, Square::D1),
+        }
+    }
+}
+
+impl GameManager {
+    // Starting position without the piece given as odds
+    pub fn new_with_handicap(handicap: Handicap) -> Self {
+        let mut gm = GameManager::new();
+        let (player, square) = handicap.removed();
+        let pieces = match player {
+            Player::White => &mut gm.whites,
+            Player::Black => &mut gm.blacks,
+        };
+        let pos = Position::from(square);
+        if let Some(index) = pieces.iter().position(|p| Position::from_piece(p) == pos) {
+            pieces.swap_remove(index);
+        }
+        gm
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Handicap;
+    use crate::{game_manager::GameManager, moves::Move, square::Square};
+
+    #[test]
+    fn test_handicap() {
+        let cases = [
+            (
+                Handicap::PawnAndMove,
+                "rnbqkbnr/ppppp1pp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            ),
+            (
+                Handicap::KnightOdds,
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/R1BQKBNR w KQkq - 0 1",
+            ),
+            (
+                Handicap::QueenOdds,
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNB1KBNR w KQkq - 0 1",
+            ),
+        ];
+        for (handicap, fen) in cases {
+            let gm = GameManager::new_with_handicap(handicap);
+            assert_eq!(fen, gm.to_fen(), "{:?}", handicap);
+            assert_eq!(GameManager::from_fen(fen).unwrap(), gm);
+        }
+
+        let mut gm = GameManager::new_with_handicap(Handicap::QueenOdds);
+        // The king may step to d1
+        assert_eq!(21, gm.legal_moves().len());
+        gm.play(&Move::new(Square::E2, Square::E4)).unwrap();
+        #[cfg(feature = "notation")]
+        {
+            let pgn = crate::pgn::PgnWriter::new().write(&gm);
+            assert!(
+                pgn.contains("[FEN \"rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNB1KBNR w KQkq - 0 1\"]")
+            );
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use crate::{
+    game_manager::{Castle, GameManager},
+    moves::Move,
+    piece::{Kind, Player, Position, MAX_COLUMN, MAX_ROW},
+};
+
+// Squares a board UI highlights after a move, so it doesn't have to know the rules
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MoveHighlights {
+    pub from: Position,
+    // Square the king went to when the move castled
+    pub to: Position,
+    // Squares the rook left and reached when the move castled
+    pub rook: Option<(Position, Position)>,
+    // King of the side to move when it is in check
+    pub checked_king: Option<Position>,
+    pub checkers: Vec<Position>,
+}
+
+impl GameManager {
+    // Highlights of the last move played, `None` before the first one
+    pub fn last_move_highlights(&self) -> Option<MoveHighlights> {
+        let record = self.history().last()?;
+        let castle = record
+            .flags
+            .castling
+            .then(|| self.castle(Kind::King, &record.mv, &record.undo.castling))
+            .flatten();
+        Some(self.highlights_for(&record.mv, castle))
+    }
+
+    // Number of pieces of `player` attacking every square, indexed by row then column
+    pub fn attack_heatmap(&self, player: Player) -> [[u8; 8]; 8] {
+        let attacks = self.attacked_squares(&player);
+        let mut heatmap = [[0; 8]; 8];
+        for row in 0..MAX_ROW {
+            for column in 0..MAX_COLUMN {
+                heatmap[row as usize][column as usize] = attacks.count(Position::new(row, column));
+            }
+        }
+        heatmap
+    }
+
+    fn highlights_for(&self, mv: &Move, castle: Option<Castle>) -> MoveHighlights {
+        let rook = castle.map(|castle| (castle.rook_from, castle.rook_to));
+        let checkers = self.checkers(&self.turn);
+        let checked_king = if checkers.is_empty() {
+            None
+        } else {
+            self.pieces_of(self.turn)
+                .iter()
+                .find(|p| p.kind == Kind::King)
+                .map(Position::from_piece)
+        };
+        MoveHighlights {
+            from: mv.from,
+            to: castle.map_or(mv.to, |castle| castle.king_to),
+            rook,
+            checked_king,
+            checkers,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MoveHighlights;
+    use crate::{
+        game_manager::GameManager,
+        moves::Move,
+        piece::{Player, Position},
+        square::Square,
+    };
+
+    #[test]
+    fn test_move_highlights() {
+        let mut gm = GameManager::new();
+        assert_eq!(None, gm.last_move_highlights());
+        gm.play(&Move::new(Square::E2, Square::E4)).unwrap();
+        assert_eq!(
+            Some(MoveHighlights {
+                from: Square::E2.into(),
+                to: Square::E4.into(),
+                rook: None,
+                checked_king: None,
+                checkers: vec![],
+            }),
+            gm.last_move_highlights()
+        );
+
+        let mut gm = GameManager::from_fen("4k3/8/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+        gm.play(&Move::new(Square::E1, Square::G1)).unwrap();
+        let highlights = gm.last_move_highlights().unwrap();
+        assert_eq!(
+            Some((Position::from(Square::H1), Position::from(Square::F1))),
+            highlights.rook
+        );
+        assert_eq!(None, highlights.checked_king);
+
+        // Double check by the knight and the discovered rook
+        let mut gm = GameManager::from_fen("4k3/8/8/8/4N3/8/8/4RK2 w - - 0 1").unwrap();
+        gm.play(&Move::new(Square::E4, Square::D6)).unwrap();
+        let highlights = gm.last_move_highlights().unwrap();
+        assert_eq!(Some(Position::from(Square::E8)), highlights.checked_king);
+        let mut checkers = highlights.checkers;
+        checkers.sort_by_key(Position::index);
+        assert_eq!(
+            vec![Position::from(Square::E1), Position::from(Square::D6)],
+            checkers
+        );
+    }
+
+    #[test]
+    fn test_attack_heatmap() {
+        let gm = GameManager::new();
+        let heatmap = gm.attack_heatmap(Player::White);
+        // c3 by the b1 knight and the b2 and d2 pawns
+        assert_eq!(3, heatmap[2][2]);
+        assert_eq!(0, heatmap[0][0]);
+        assert_eq!(0, heatmap[3][4]);
+        assert_eq!([2, 2, 3, 2, 2, 3, 2, 2], heatmap[2]);
+        let black = gm.attack_heatmap(Player::Black);
+        assert_eq!([2, 2, 3, 2, 2, 3, 2, 2], black[5]);
+        assert_eq!(0, black[2].iter().sum::<u8>());
+    }
+}
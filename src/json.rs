@@ -0,0 +1,480 @@
+// Versioned JSON representation of a game for clients that don't know about the crate
+// internals. Unlike the serde derives it only relies on standard notations and stays the
+// same between releases, new fields may be added without changing the version.
+//
+// {
+//   "version": 1,
+//   "initial_fen": "<FEN the game started from>",
+//   "fen": "<FEN of the current position>",
+//   "turn": "white" | "black",
+//   "moves": [{ "uci": "e2e4", "san": "e4", "timestamp": <ms since the Unix epoch>,
+//               "think_time": <ms since the previous move> | null }, ...],
+//   "status": "ongoing" | "checkmate" | "stalemate" | "timeforfeit" | "timeoutdraw"
+//           | "resignation" | "abandonment" | "variantwin" | "agreement" | "fiftymoves"
+//           | "repetition" | "insufficientmaterial",
+//   "winner": "white" | "black" | null,
+//   "check": true | false,
+//   "clock": null | {
+//     "time_control": [{ "moves": <moves in the stage> | null, "time": <ms>,
+//                        "bonus": "none" | "fischer" | "bronstein", "bonus_time": <ms> }, ...],
+//     "white": { "remaining": <ms>, "moves": <moves timed> },
+//     "black": { "remaining": <ms>, "moves": <moves timed> },
+//     "running": "white" | "black" | null
+//   }
+// }
+//
+// `from_json` replays the UCI moves from `initial_fen` at their timestamp when there is one,
+// then restores the clock and an end of the game the moves don't tell, e.g. a resignation.
+// Every other field is derived from them and ignored when reading.
+use std::{
+    error::Error,
+    fmt,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "clock")]
+use crate::clock::{Bonus, Clock, Stage, TimeControl};
+use crate::{
+    fen::FenErr,
+    game_manager::{DrawRule, GameManager, GameStatus},
+    moves::Move,
+    piece::Player,
+};
+
+pub const JSON_VERSION: u32 = 1;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum JsonErr {
+    Syntax(String),
+    UnsupportedVersion(u32),
+    Fen(FenErr),
+    // Index in `moves` of the first move that can't be played
+    IllegalMove(usize),
+}
+
+impl fmt::Display for JsonErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonErr::Syntax(err) => write!(f, "invalid JSON: {}", err),
+            JsonErr::UnsupportedVersion(version) => write!(f, "unsupported version {}", version),
+            JsonErr::Fen(err) => err.fmt(f),
+            JsonErr::IllegalMove(index) => write!(f, "illegal move at index {}", index),
+        }
+    }
+}
+
+impl Error for JsonErr {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            JsonErr::Fen(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Side {
+    White,
+    Black,
+}
+
+impl From<Player> for Side {
+    fn from(player: Player) -> Self {
+        match player {
+            Player::White => Side::White,
+            Player::Black => Side::Black,
+        }
+    }
+}
+
+impl From<Side> for Player {
+    fn from(side: Side) -> Self {
+        match side {
+            Side::White => Player::White,
+            Side::Black => Player::Black,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Status {
+    #[default]
+    Ongoing,
+    Checkmate,
+    Stalemate,
+    TimeForfeit,
+    TimeoutDraw,
+    Resignation,
+    Abandonment,
+    VariantWin,
+    Agreement,
+    FiftyMoves,
+    Repetition,
+    InsufficientMaterial,
+}
+
+#[derive(Serialize)]
+struct GameJson {
+    version: u32,
+    initial_fen: String,
+    fen: String,
+    turn: Side,
+    moves: Vec<MoveJson>,
+    status: Status,
+    winner: Option<Side>,
+    check: bool,
+    clock: Option<ClockJson>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ClockJson {
+    time_control: Vec<StageJson>,
+    white: ClockSideJson,
+    black: ClockSideJson,
+    running: Option<Side>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StageJson {
+    moves: Option<u32>,
+    time: u64,
+    bonus: BonusJson,
+    #[serde(default)]
+    bonus_time: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum BonusJson {
+    None,
+    Fischer,
+    Bronstein,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ClockSideJson {
+    remaining: u64,
+    moves: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct MoveJson {
+    uci: String,
+    #[serde(default, skip_deserializing)]
+    san: String,
+    #[serde(default)]
+    timestamp: Option<u64>,
+    #[serde(default, skip_deserializing)]
+    think_time: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct GameInput {
+    version: u32,
+    initial_fen: String,
+    moves: Vec<MoveJson>,
+    #[serde(default)]
+    status: Status,
+    #[serde(default)]
+    winner: Option<Side>,
+    #[cfg(feature = "clock")]
+    #[serde(default)]
+    clock: Option<ClockJson>,
+}
+
+impl GameManager {
+    pub fn to_json(&self) -> String {
+        let initial_fen = self.initial_position().to_fen();
+        let mut moves = vec![];
+        for record in self.history() {
+            moves.push(MoveJson {
+                uci: record.mv.to_uci(),
+                san: record.san.clone(),
+                timestamp: record
+                    .timestamp
+                    .duration_since(UNIX_EPOCH)
+                    .ok()
+                    .map(|t| t.as_millis() as u64),
+                think_time: record.think_time.map(|t| t.as_millis() as u64),
+            });
+        }
+        let game_status = self.status();
+        let status = match game_status {
+            GameStatus::Ongoing => Status::Ongoing,
+            GameStatus::Checkmate(_) => Status::Checkmate,
+            GameStatus::Stalemate => Status::Stalemate,
+            GameStatus::TimeForfeit(_) => Status::TimeForfeit,
+            GameStatus::TimeoutDraw => Status::TimeoutDraw,
+            GameStatus::Resignation(_) => Status::Resignation,
+            GameStatus::Abandonment(_) => Status::Abandonment,
+            GameStatus::VariantWin(_) => Status::VariantWin,
+            GameStatus::DrawByAgreement => Status::Agreement,
+            GameStatus::DrawByRule(DrawRule::FiftyMoves) => Status::FiftyMoves,
+            GameStatus::DrawByRule(DrawRule::ThreefoldRepetition) => Status::Repetition,
+            GameStatus::DrawByRule(DrawRule::InsufficientMaterial) => Status::InsufficientMaterial,
+        };
+        let winner = game_status.winner().map(Side::from);
+        #[cfg(feature = "clock")]
+        let clock = self.clock.as_ref().map(clock_json);
+        #[cfg(not(feature = "clock"))]
+        let clock = None;
+        let game = GameJson {
+            version: JSON_VERSION,
+            initial_fen,
+            fen: self.to_fen(),
+            turn: self.turn.into(),
+            moves,
+            status,
+            winner,
+            check: self.is_in_check(&self.turn),
+            clock,
+        };
+        serde_json::to_string(&game).expect("the game state is always serializable")
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, JsonErr> {
+        let input: GameInput =
+            serde_json::from_str(json).map_err(|e| JsonErr::Syntax(e.to_string()))?;
+        if input.version != JSON_VERSION {
+            return Err(JsonErr::UnsupportedVersion(input.version));
+        }
+        let mut gm = GameManager::from_fen(&input.initial_fen).map_err(JsonErr::Fen)?;
+        for (i, mv) in input.moves.iter().enumerate() {
+            let timestamp = mv
+                .timestamp
+                .map_or_else(SystemTime::now, |t| UNIX_EPOCH + Duration::from_millis(t));
+            let mv = Move::from_uci(&mv.uci).ok_or(JsonErr::IllegalMove(i))?;
+            gm.play_at(&mv, timestamp)
+                .map_err(|_| JsonErr::IllegalMove(i))?;
+        }
+        #[cfg(feature = "clock")]
+        if let Some(clock) = &input.clock {
+            gm.clock = Some(clock_from_json(clock));
+        }
+        // Only the ends decided off the board are kept, the others follow from the moves
+        let outcome = game_status(input.status, input.winner.map(Player::from))?;
+        if outcome.is_over() && gm.status() != outcome {
+            if matches!(
+                outcome,
+                GameStatus::Checkmate(_) | GameStatus::Stalemate | GameStatus::VariantWin(_)
+            ) {
+                return Err(JsonErr::Syntax(format!(
+                    "the moves don't end in {:?}",
+                    outcome
+                )));
+            }
+            gm.outcome = Some(outcome);
+        }
+        Ok(gm)
+    }
+}
+
+fn game_status(status: Status, winner: Option<Player>) -> Result<GameStatus, JsonErr> {
+    let winner = || winner.ok_or_else(|| JsonErr::Syntax("missing winner".to_string()));
+    Ok(match status {
+        Status::Ongoing => GameStatus::Ongoing,
+        Status::Checkmate => GameStatus::Checkmate(winner()?),
+        Status::Stalemate => GameStatus::Stalemate,
+        Status::TimeForfeit => GameStatus::TimeForfeit(winner()?),
+        Status::TimeoutDraw => GameStatus::TimeoutDraw,
+        Status::Resignation => GameStatus::Resignation(winner()?),
+        Status::Abandonment => GameStatus::Abandonment(winner()?),
+        Status::VariantWin => GameStatus::VariantWin(winner()?),
+        Status::Agreement => GameStatus::DrawByAgreement,
+        Status::FiftyMoves => GameStatus::DrawByRule(DrawRule::FiftyMoves),
+        Status::Repetition => GameStatus::DrawByRule(DrawRule::ThreefoldRepetition),
+        Status::InsufficientMaterial => GameStatus::DrawByRule(DrawRule::InsufficientMaterial),
+    })
+}
+
+#[cfg(feature = "clock")]
+fn clock_json(clock: &Clock) -> ClockJson {
+    let side = |player: Player| ClockSideJson {
+        remaining: clock.remaining(&player).as_millis() as u64,
+        moves: clock.moves(&player),
+    };
+    ClockJson {
+        time_control: clock
+            .time_control()
+            .stages
+            .iter()
+            .map(|stage| {
+                let (bonus, bonus_time) = match stage.bonus {
+                    Bonus::None => (BonusJson::None, Duration::ZERO),
+                    Bonus::Fischer(time) => (BonusJson::Fischer, time),
+                    Bonus::Bronstein(time) => (BonusJson::Bronstein, time),
+                };
+                StageJson {
+                    moves: stage.moves,
+                    time: stage.time.as_millis() as u64,
+                    bonus,
+                    bonus_time: bonus_time.as_millis() as u64,
+                }
+            })
+            .collect(),
+        white: side(Player::White),
+        black: side(Player::Black),
+        running: clock.running().map(Side::from),
+    }
+}
+
+#[cfg(feature = "clock")]
+fn clock_from_json(json: &ClockJson) -> Clock {
+    let stages = json
+        .time_control
+        .iter()
+        .map(|stage| {
+            let bonus_time = Duration::from_millis(stage.bonus_time);
+            Stage {
+                moves: stage.moves,
+                time: Duration::from_millis(stage.time),
+                bonus: match stage.bonus {
+                    BonusJson::None => Bonus::None,
+                    BonusJson::Fischer => Bonus::Fischer(bonus_time),
+                    BonusJson::Bronstein => Bonus::Bronstein(bonus_time),
+                },
+            }
+        })
+        .collect();
+    let mut clock = Clock::new(TimeControl::stages(stages));
+    if let Some(running) = json.running {
+        clock.start(running.into());
+    }
+    for (player, side) in [(Player::White, &json.white), (Player::Black, &json.black)] {
+        clock.set_moves(&player, side.moves);
+        clock.set_remaining(&player, Duration::from_millis(side.remaining));
+    }
+    clock
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::JsonErr;
+    #[cfg(feature = "clock")]
+    use crate::clock::{Bonus, Clock, Stage, TimeControl};
+    use crate::{
+        fen::FenErr,
+        game_manager::{GameManager, GameStatus},
+        moves::Move,
+        piece::Player,
+        square::Square,
+    };
+
+    #[test]
+    fn test_json_round_trip() {
+        let mut gm = GameManager::new();
+        let moves = [
+            (Square::F2, Square::F3),
+            (Square::E7, Square::E5),
+            (Square::G2, Square::G4),
+            (Square::D8, Square::H4),
+        ];
+        for (i, (from, to)) in moves.into_iter().enumerate() {
+            let timestamp = UNIX_EPOCH + Duration::from_secs(1_000 + 5 * i as u64);
+            gm.play_at(&Move::new(from, to), timestamp).unwrap();
+        }
+        let json = gm.to_json();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(1, value["version"]);
+        assert_eq!("checkmate", value["status"]);
+        assert_eq!("black", value["winner"]);
+        assert_eq!("white", value["turn"]);
+        assert_eq!(true, value["check"]);
+        assert_eq!("d8h4", value["moves"][3]["uci"]);
+        assert_eq!("Qh4#", value["moves"][3]["san"]);
+        assert_eq!(1_015_000, value["moves"][3]["timestamp"]);
+        assert_eq!(5_000, value["moves"][3]["think_time"]);
+        assert!(value["moves"][0]["think_time"].is_null());
+
+        let restored = GameManager::from_json(&json).unwrap();
+        assert_eq!(gm.to_fen(), restored.to_fen());
+        assert_eq!(4, restored.history().len());
+        assert_eq!(
+            Some(Duration::from_secs(5)),
+            restored.history()[3].think_time
+        );
+    }
+
+    #[test]
+    fn test_json_resigned_game() {
+        let mut gm = GameManager::new();
+        #[cfg(feature = "clock")]
+        {
+            gm.clock = Some(Clock::new(TimeControl::stages(vec![
+                Stage {
+                    moves: Some(1),
+                    time: Duration::from_secs(60),
+                    bonus: Bonus::None,
+                },
+                Stage {
+                    moves: None,
+                    time: Duration::from_secs(30),
+                    bonus: Bonus::Fischer(Duration::from_secs(2)),
+                },
+            ])));
+        }
+        gm.play(&Move::new(Square::E2, Square::E4)).unwrap();
+        gm.play(&Move::new(Square::E7, Square::E5)).unwrap();
+        gm.resign(&Player::White);
+        let json = gm.to_json();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!("resignation", value["status"]);
+        assert_eq!("black", value["winner"]);
+
+        let restored = GameManager::from_json(&json).unwrap();
+        assert_eq!(GameStatus::Resignation(Player::Black), restored.status());
+        assert_eq!(2, restored.history().len());
+        #[cfg(feature = "clock")]
+        {
+            assert_eq!("white", value["clock"]["running"]);
+            assert_eq!(2_000, value["clock"]["time_control"][1]["bonus_time"]);
+            let clock = restored.clock.as_ref().unwrap();
+            assert_eq!(Some(Player::White), clock.running());
+            assert_eq!(1, clock.moves(&Player::Black));
+            // Both sides are in the second stage, 90 seconds less the few milliseconds spent
+            let remaining = clock.remaining(&Player::Black);
+            assert!(remaining > Duration::from_secs(89) && remaining <= Duration::from_secs(90));
+        }
+        // Draws agreed on are restored the same way
+        let mut value = value;
+        value["status"] = "agreement".into();
+        value["winner"] = serde_json::Value::Null;
+        assert_eq!(
+            GameStatus::DrawByAgreement,
+            GameManager::from_json(&value.to_string()).unwrap().status()
+        );
+    }
+
+    #[test]
+    fn test_json_errors() {
+        let start = r#""initial_fen": "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1""#;
+        let json = format!(r#"{{"version": 2, {}, "moves": []}}"#, start);
+        assert_eq!(
+            Err(JsonErr::UnsupportedVersion(2)),
+            GameManager::from_json(&json).map(|_| ())
+        );
+        let json = format!(
+            r#"{{"version": 1, {}, "moves": [{{"uci": "e2e4"}}, {{"uci": "e2e4"}}]}}"#,
+            start
+        );
+        assert_eq!(
+            Err(JsonErr::IllegalMove(1)),
+            GameManager::from_json(&json).map(|_| ())
+        );
+        let json = r#"{"version": 1, "initial_fen": "8/8 w - -", "moves": []}"#;
+        assert_eq!(
+            Err(JsonErr::Fen(FenErr::InvalidBoard)),
+            GameManager::from_json(json).map(|_| ())
+        );
+        assert!(matches!(
+            GameManager::from_json("{"),
+            Err(JsonErr::Syntax(_))
+        ));
+    }
+}
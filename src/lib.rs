@@ -1,3 +1,79 @@
+#[cfg(feature = "notation")]
+pub mod analysis;
+#[cfg(all(feature = "engine", feature = "notation"))]
+pub mod annotate;
+#[cfg(feature = "notation")]
+pub mod announce;
+pub mod antichess;
+pub mod attacks;
+#[cfg(feature = "audit")]
+pub mod audit;
+#[cfg(feature = "engine")]
+pub mod book;
+pub mod builder;
+pub mod chess960;
+#[cfg(feature = "chess")]
+pub mod chess_compat;
+#[cfg(feature = "clock")]
+pub mod clock;
+pub mod diff;
+pub mod eboard;
+pub mod editor;
+#[cfg(feature = "engine")]
+pub mod eval;
+pub mod events;
+pub mod fen;
+#[cfg(any(feature = "arbitrary", feature = "proptest"))]
+pub mod fuzz;
 pub mod game_manager;
+#[cfg(all(feature = "engine", feature = "notation", feature = "clock"))]
+pub mod gauntlet;
+pub mod handicap;
+pub mod highlight;
+#[cfg(feature = "json")]
+pub mod json;
+#[cfg(feature = "json")]
+pub mod lichess;
+#[cfg(feature = "clock")]
+pub mod match_manager;
+#[cfg(feature = "engine")]
+pub mod metrics;
+#[cfg(feature = "engine")]
+pub mod motifs;
 pub mod move_validators;
+pub mod moves;
+#[cfg(feature = "tokio")]
+pub mod network;
+#[cfg(feature = "notation")]
+pub mod notation;
+pub mod perspective;
+#[cfg(feature = "notation")]
+pub mod pgn;
 pub mod piece;
+pub mod premove;
+#[cfg(feature = "render")]
+pub mod render;
+#[cfg(feature = "notation")]
+pub mod repertoire;
+pub mod replay;
+pub mod rules;
+#[cfg(feature = "engine")]
+pub mod search;
+#[cfg(feature = "tokio")]
+pub mod session;
+#[cfg(feature = "shakmaty")]
+pub mod shakmaty_compat;
+pub mod snapshot;
+pub mod square;
+#[cfg(feature = "engine")]
+pub mod strength;
+#[cfg(feature = "svg")]
+pub mod svg;
+#[cfg(feature = "engine")]
+pub mod tablebase;
+#[cfg(feature = "engine")]
+pub mod tactics;
+pub mod transform;
+#[cfg(feature = "engine")]
+pub mod uci;
+pub mod zobrist;
@@ -0,0 +1,205 @@
+use serde::Deserialize;
+
+#[cfg(feature = "clock")]
+use std::time::Duration;
+
+use crate::{
+    fen::START_FEN,
+    game_manager::{GameManager, GameStatus},
+    json::JsonErr,
+    moves::Move,
+    piece::Player,
+};
+
+// Game read from the Lichess JSON export, requested with moves in UCI notation. The game
+// ends as it did on Lichess, resignations and time losses included.
+#[derive(Clone, Debug)]
+pub struct LichessGame {
+    pub id: String,
+    pub game: GameManager,
+    pub white: Option<String>,
+    pub black: Option<String>,
+    pub white_rating: Option<u32>,
+    pub black_rating: Option<u32>,
+    // Lichess status name, e.g. "mate", "resign" or "outoftime"
+    pub status: String,
+    pub winner: Option<Player>,
+    // Remaining time in centiseconds after every ply, empty for untimed games. With the
+    // `clock` feature they are also on the move records of `game`.
+    pub clocks: Vec<u32>,
+    // Initial time and increment in seconds
+    pub time_control: Option<(u32, u32)>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportJson {
+    id: String,
+    #[serde(default)]
+    moves: String,
+    #[serde(default)]
+    initial_fen: Option<String>,
+    #[serde(default)]
+    status: String,
+    #[serde(default)]
+    winner: Option<String>,
+    #[serde(default)]
+    players: PlayersJson,
+    #[serde(default)]
+    clocks: Vec<u32>,
+    #[serde(default)]
+    clock: Option<ClockJson>,
+}
+
+#[derive(Default, Deserialize)]
+struct PlayersJson {
+    #[serde(default)]
+    white: PlayerJson,
+    #[serde(default)]
+    black: PlayerJson,
+}
+
+#[derive(Default, Deserialize)]
+struct PlayerJson {
+    user: Option<UserJson>,
+    rating: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct UserJson {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct ClockJson {
+    initial: u32,
+    increment: u32,
+}
+
+impl LichessGame {
+    pub fn from_json(json: &str) -> Result<Self, JsonErr> {
+        let export: ExportJson =
+            serde_json::from_str(json).map_err(|e| JsonErr::Syntax(e.to_string()))?;
+        let fen = export.initial_fen.as_deref().unwrap_or(START_FEN);
+        let mut game = GameManager::from_fen(fen).map_err(JsonErr::Fen)?;
+        for (i, uci) in export.moves.split_whitespace().enumerate() {
+            let mv = Move::from_uci(uci).ok_or(JsonErr::IllegalMove(i))?;
+            game.play(&mv).map_err(|_| JsonErr::IllegalMove(i))?;
+        }
+        let winner = match export.winner.as_deref() {
+            Some("white") => Some(Player::White),
+            Some("black") => Some(Player::Black),
+            _ => None,
+        };
+        #[cfg(feature = "clock")]
+        for (record, centis) in game.history.iter_mut().zip(&export.clocks) {
+            record.clock = Some(Duration::from_millis(*centis as u64 * 10));
+        }
+        // Mates and stalemates follow from the moves, the other ends are restored
+        let outcome = match (export.status.as_str(), winner) {
+            ("resign", Some(winner)) => Some(GameStatus::Resignation(winner)),
+            ("outoftime", Some(winner)) => Some(GameStatus::TimeForfeit(winner)),
+            ("outoftime", None) => Some(GameStatus::TimeoutDraw),
+            ("timeout" | "noStart", Some(winner)) => Some(GameStatus::Abandonment(winner)),
+            ("draw", None) => Some(GameStatus::DrawByAgreement),
+            _ => None,
+        };
+        if let Some(outcome) = outcome.filter(|o| game.status() != *o && !game.status().is_over()) {
+            game.outcome = Some(outcome);
+        }
+        Ok(Self {
+            id: export.id,
+            game,
+            white: export.players.white.user.map(|u| u.name),
+            black: export.players.black.user.map(|u| u.name),
+            white_rating: export.players.white.rating,
+            black_rating: export.players.black.rating,
+            status: export.status,
+            winner,
+            clocks: export.clocks,
+            time_control: export.clock.map(|c| (c.initial, c.increment)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "clock")]
+    use std::time::Duration;
+
+    use super::LichessGame;
+    use crate::{game_manager::GameStatus, json::JsonErr, piece::Player};
+
+    #[test]
+    fn test_lichess_import() {
+        let json = r#"{
+            "id": "q7ZvsdUF",
+            "rated": true,
+            "variant": "standard",
+            "speed": "blitz",
+            "status": "mate",
+            "players": {
+                "white": {"user": {"name": "Alice", "id": "alice"}, "rating": 1500},
+                "black": {"user": {"name": "Bob", "id": "bob"}, "rating": 1620}
+            },
+            "winner": "black",
+            "moves": "f2f3 e7e5 g2g4 d8h4",
+            "clocks": [18003, 18003, 17899, 17811],
+            "clock": {"initial": 180, "increment": 2, "totalTime": 260}
+        }"#;
+        let game = LichessGame::from_json(json).unwrap();
+        assert_eq!("q7ZvsdUF", game.id);
+        assert_eq!(4, game.game.history().len());
+        assert!(game.game.legal_moves().is_empty());
+        assert_eq!(Some("Alice"), game.white.as_deref());
+        assert_eq!(Some(1620), game.black_rating);
+        assert_eq!(Some(Player::Black), game.winner);
+        assert_eq!("mate", game.status);
+        assert_eq!(4, game.clocks.len());
+        #[cfg(feature = "clock")]
+        assert_eq!(
+            Some(Duration::from_millis(178_110)),
+            game.game.history()[3].clock
+        );
+        assert_eq!(Some((180, 2)), game.time_control);
+    }
+
+    #[test]
+    fn test_lichess_import_outcome() {
+        let json = r#"{
+            "id": "resigned",
+            "status": "resign",
+            "winner": "white",
+            "moves": "e2e4 e7e5"
+        }"#;
+        let game = LichessGame::from_json(json).unwrap();
+        assert_eq!(GameStatus::Resignation(Player::White), game.game.status());
+        assert_eq!("1-0", game.game.status().result());
+        let json = json
+            .replace("resign", "outoftime")
+            .replace("white", "black");
+        let game = LichessGame::from_json(&json).unwrap();
+        assert_eq!(GameStatus::TimeForfeit(Player::Black), game.game.status());
+        let json = r#"{"id": "drawn", "status": "draw", "moves": "e2e4"}"#;
+        let game = LichessGame::from_json(json).unwrap();
+        assert_eq!(GameStatus::DrawByAgreement, game.game.status());
+    }
+
+    #[test]
+    fn test_lichess_import_from_position() {
+        let json = r#"{
+            "id": "abcdefgh",
+            "initialFen": "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1",
+            "moves": "e2e4 e8d7 e4e5 e5e6",
+            "players": {"white": {"aiLevel": 3}, "black": {}}
+        }"#;
+        assert_eq!(
+            Err(JsonErr::IllegalMove(3)),
+            LichessGame::from_json(json).map(|g| g.id)
+        );
+        let game = LichessGame::from_json(&json.replace(" e5e6", "")).unwrap();
+        assert_eq!("8/3k4/8/4P3/8/8/8/4K3 b - - 0 2", game.game.to_fen());
+        assert_eq!(None, game.white);
+        assert_eq!(None, game.winner);
+    }
+}
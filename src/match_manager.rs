@@ -0,0 +1,195 @@
+use std::{collections::HashMap, error::Error, fmt, hash::Hash, time::Instant};
+
+use crate::{
+    clock::{Clock, TimeControl},
+    game_manager::{GameManager, GameStatus, MoveErr},
+    moves::Move,
+    piece::Player,
+};
+
+#[derive(Debug)]
+pub enum MatchErr {
+    UnknownGame,
+    Move(MoveErr),
+}
+
+impl fmt::Display for MatchErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MatchErr::UnknownGame => write!(f, "unknown game"),
+            MatchErr::Move(err) => err.fmt(f),
+        }
+    }
+}
+
+impl Error for MatchErr {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            MatchErr::Move(err) => Some(err),
+            MatchErr::UnknownGame => None,
+        }
+    }
+}
+
+// Games played at the same time on a server, keyed by the id the server gives them
+#[derive(Clone, Debug)]
+pub struct MatchManager<K> {
+    games: HashMap<K, GameManager>,
+}
+
+impl<K: Eq + Hash> MatchManager<K> {
+    pub fn new() -> Self {
+        Self {
+            games: HashMap::new(),
+        }
+    }
+
+    // Adds a game, handing back the one that had the same id
+    pub fn insert(&mut self, id: K, game: GameManager) -> Option<GameManager> {
+        self.games.insert(id, game)
+    }
+
+    // Adds a game with a clock, started for the side to move
+    pub fn insert_timed(
+        &mut self,
+        id: K,
+        mut game: GameManager,
+        control: TimeControl,
+    ) -> Option<GameManager> {
+        let mut clock = Clock::new(control);
+        clock.start(game.turn);
+        game.clock = Some(clock);
+        self.insert(id, game)
+    }
+
+    pub fn remove(&mut self, id: &K) -> Option<GameManager> {
+        self.games.remove(id)
+    }
+
+    pub fn get(&self, id: &K) -> Option<&GameManager> {
+        self.games.get(id)
+    }
+
+    pub fn get_mut(&mut self, id: &K) -> Option<&mut GameManager> {
+        self.games.get_mut(id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.games.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.games.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &GameManager)> {
+        self.games.iter()
+    }
+
+    pub fn play(&mut self, id: &K, mv: &Move) -> Result<(), MatchErr> {
+        self.games
+            .get_mut(id)
+            .ok_or(MatchErr::UnknownGame)?
+            .play(mv)
+            .map_err(MatchErr::Move)
+    }
+
+    pub fn status(&self, id: &K) -> Option<GameStatus> {
+        self.games.get(id).map(GameManager::status)
+    }
+
+    pub fn to_move(&self, player: Player) -> impl Iterator<Item = &K> {
+        self.games
+            .iter()
+            .filter(move |(_, game)| game.turn == player)
+            .map(|(id, _)| id)
+    }
+
+    pub fn ongoing(&self) -> impl Iterator<Item = &K> {
+        self.games
+            .iter()
+            .filter(|(_, game)| game.status() == GameStatus::Ongoing)
+            .map(|(id, _)| id)
+    }
+
+    pub fn finished(&self) -> impl Iterator<Item = (&K, GameStatus)> {
+        self.games.iter().filter_map(|(id, game)| {
+            let status = game.status();
+            (status != GameStatus::Ongoing).then_some((id, status))
+        })
+    }
+
+    // Games whose clock ran out at `now`, with the side that lost on time
+    pub fn flagged(&self, now: Instant) -> impl Iterator<Item = (&K, Player)> {
+        self.games.iter().filter_map(move |(id, game)| {
+            let flagged = game.clock.as_ref()?.check_flag(now)?;
+            Some((id, flagged))
+        })
+    }
+}
+
+impl<K: Eq + Hash> Default for MatchManager<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{MatchErr, MatchManager};
+    use crate::{
+        clock::TimeControl,
+        game_manager::{GameManager, GameStatus},
+        moves::Move,
+        piece::Player,
+        square::Square,
+    };
+
+    #[test]
+    fn test_match_manager() {
+        let mut matches = MatchManager::new();
+        matches.insert("casual", GameManager::new());
+        matches.insert_timed(
+            "blitz",
+            GameManager::new(),
+            TimeControl::fischer(Duration::from_secs(180), Duration::from_secs(2)),
+        );
+        let mated = GameManager::from_fen("k6R/8/1K6/8/8/8/8/8 b - - 0 1").unwrap();
+        matches.insert("over", mated);
+        assert_eq!(3, matches.len());
+
+        matches
+            .play(&"casual", &Move::new(Square::E2, Square::E4))
+            .unwrap();
+        assert!(matches!(
+            matches.play(&"casual", &Move::new(Square::E2, Square::E4)),
+            Err(MatchErr::Move(_))
+        ));
+        assert!(matches!(
+            matches.play(&"unknown", &Move::new(Square::E2, Square::E4)),
+            Err(MatchErr::UnknownGame)
+        ));
+
+        let mut white: Vec<_> = matches.to_move(Player::White).collect();
+        white.sort();
+        assert_eq!(vec![&"blitz"], white);
+        let mut ongoing: Vec<_> = matches.ongoing().collect();
+        ongoing.sort();
+        assert_eq!(vec![&"blitz", &"casual"], ongoing);
+        assert_eq!(
+            vec![(&"over", GameStatus::Checkmate(Player::White))],
+            matches.finished().collect::<Vec<_>>()
+        );
+        assert_eq!(Some(GameStatus::Ongoing), matches.status(&"blitz"));
+
+        let later = Instant::now() + Duration::from_secs(200);
+        assert_eq!(
+            vec![(&"blitz", Player::White)],
+            matches.flagged(later).collect::<Vec<_>>()
+        );
+        assert!(matches.remove(&"over").is_some());
+        assert_eq!(2, matches.len());
+    }
+}
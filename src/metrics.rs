@@ -0,0 +1,123 @@
+use crate::{
+    game_manager::GameManager,
+    piece::{Kind, Player, Position, MAX_COLUMN},
+    square::Square,
+};
+
+// Positional features of one side, for teaching tools and hand written evaluations
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PositionalMetrics {
+    // Pseudo-legal moves of every piece, kept in the order of the piece list
+    pub mobility: Vec<(Position, u32)>,
+    // Attacks on d4, e4, d5 and e5, a square attacked twice counting twice
+    pub center_control: u32,
+    // Rooks on files without any pawn, or without pawns of their side only
+    pub rooks_on_open_files: Vec<Position>,
+    pub rooks_on_semi_open_files: Vec<Position>,
+    // Groups of pawns on adjacent files
+    pub pawn_islands: u32,
+    // Files around the king, its own included, with a pawn of its side one or two ranks
+    // ahead, from 0 to 3
+    pub king_shelter: u32,
+}
+
+const CENTER: [Square; 4] = [Square::D4, Square::E4, Square::D5, Square::E5];
+
+impl GameManager {
+    pub fn positional_metrics(&self, player: &Player) -> PositionalMetrics {
+        let pieces = self.pieces_of(*player);
+        let pawn_files = |player: Player| {
+            let mut files = [false; MAX_COLUMN as usize];
+            for p in self
+                .pieces_of(player)
+                .iter()
+                .filter(|p| p.kind == Kind::Pawn)
+            {
+                files[p.column as usize] = true;
+            }
+            files
+        };
+        let (own, enemy) = (pawn_files(*player), pawn_files(player.opponent()));
+        let rooks = || pieces.iter().filter(|p| p.kind == Kind::Rook);
+        PositionalMetrics {
+            mobility: pieces
+                .iter()
+                .map(|p| {
+                    let moves = self.move_suggestion_iter(p).count() as u32;
+                    (Position::from_piece(p), moves)
+                })
+                .collect(),
+            center_control: CENTER
+                .iter()
+                .map(|square| self.attacked_squares(player).count(*square) as u32)
+                .sum(),
+            rooks_on_open_files: rooks()
+                .filter(|p| !own[p.column as usize] && !enemy[p.column as usize])
+                .map(Position::from_piece)
+                .collect(),
+            rooks_on_semi_open_files: rooks()
+                .filter(|p| !own[p.column as usize] && enemy[p.column as usize])
+                .map(Position::from_piece)
+                .collect(),
+            pawn_islands: own.windows(2).filter(|w| !w[0] && w[1]).count() as u32 + own[0] as u32,
+            king_shelter: self.king_shelter(player),
+        }
+    }
+
+    pub(crate) fn king_shelter(&self, player: &Player) -> u32 {
+        let pieces = self.pieces_of(*player);
+        let Some(king) = pieces.iter().find(|p| p.kind == Kind::King) else {
+            return 0;
+        };
+        let ahead = |pawn_row: u8| match player {
+            Player::White => pawn_row > king.row && pawn_row - king.row <= 2,
+            Player::Black => pawn_row < king.row && king.row - pawn_row <= 2,
+        };
+        (king.column.saturating_sub(1)..=(king.column + 1).min(MAX_COLUMN - 1))
+            .filter(|column| {
+                pieces
+                    .iter()
+                    .any(|p| p.kind == Kind::Pawn && p.column == *column && ahead(p.row))
+            })
+            .count() as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        game_manager::GameManager,
+        piece::{Player, Position},
+        square::Square,
+    };
+
+    #[test]
+    fn test_positional_metrics() {
+        let gm = GameManager::new();
+        let white = gm.positional_metrics(&Player::White);
+        assert_eq!(20, white.mobility.iter().map(|(_, n)| n).sum::<u32>());
+        assert_eq!(0, white.center_control);
+        assert_eq!(1, white.pawn_islands);
+        assert_eq!(3, white.king_shelter);
+        assert!(white.rooks_on_open_files.is_empty());
+
+        let gm = GameManager::from_fen("3r2k1/5ppp/4p3/p7/3P4/8/P4PP1/2R1R1K1 w - - 0 1").unwrap();
+        let white = gm.positional_metrics(&Player::White);
+        assert_eq!(vec![Position::from(Square::C1)], white.rooks_on_open_files);
+        assert_eq!(
+            vec![Position::from(Square::E1)],
+            white.rooks_on_semi_open_files
+        );
+        assert_eq!(3, white.pawn_islands);
+        assert_eq!(2, white.king_shelter);
+        // The pawn hits e5, the rook e4 and e5
+        assert_eq!(3, white.center_control);
+        let black = gm.positional_metrics(&Player::Black);
+        assert_eq!(
+            vec![Position::from(Square::D8)],
+            black.rooks_on_semi_open_files
+        );
+        assert_eq!(2, black.pawn_islands);
+        assert_eq!(3, black.king_shelter);
+    }
+}
@@ -0,0 +1,268 @@
+use crate::{
+    game_manager::GameManager,
+    move_validators::is_valid_move,
+    piece::{Kind, Player, Position},
+};
+
+// Tactical pattern on the board, with the squares of the pieces involved. A piece is a target
+// when it is worth more than the one attacking it or isn't defended.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Motif {
+    // One piece attacking several targets at once
+    Fork {
+        attacker: Position,
+        targets: Vec<Position>,
+    },
+    // The piece in front can't move off the line without exposing a more valuable one
+    Pin {
+        attacker: Position,
+        pinned: Position,
+        behind: Position,
+    },
+    // The piece in front is worth more and has to move, leaving a target behind
+    Skewer {
+        attacker: Position,
+        front: Position,
+        behind: Position,
+    },
+    // The piece moved to `moved` opened the line of `attacker` onto a target
+    DiscoveredAttack {
+        moved: Position,
+        attacker: Position,
+        target: Position,
+    },
+    // King on its back rank with no way out of it, facing an enemy rook or queen
+    BackRankWeakness {
+        king: Position,
+    },
+}
+
+impl GameManager {
+    // Motifs `player` can play on, whoever's turn it is. Discovered attacks depend on a move
+    // and are only found by `last_move_motifs`.
+    pub fn motifs(&self, player: &Player) -> Vec<Motif> {
+        let mut motifs = self.forks(player);
+        motifs.extend(self.pins_and_skewers(player));
+        if let Some(king) = self.back_rank_weakness(&player.opponent()) {
+            motifs.push(Motif::BackRankWeakness { king });
+        }
+        motifs
+    }
+
+    // Motifs created by the last move: the ones the moved piece takes part in as the
+    // attacker, and the lines it opened
+    pub fn last_move_motifs(&self) -> Vec<Motif> {
+        let Some(record) = self.history().last() else {
+            return vec![];
+        };
+        let player = self.turn.opponent();
+        let (from, to) = (record.mv.from, record.mv.to);
+        let mut motifs: Vec<Motif> = self
+            .motifs(&player)
+            .into_iter()
+            .filter(|motif| match motif {
+                Motif::Fork { attacker, .. }
+                | Motif::Pin { attacker, .. }
+                | Motif::Skewer { attacker, .. } => *attacker == to,
+                _ => false,
+            })
+            .collect();
+        for slider in self.pieces_of(player).iter().filter(|p| is_slider(p.kind)) {
+            let attacker = Position::from_piece(slider);
+            if attacker == to {
+                continue;
+            }
+            for target in self.pieces_of(player.opponent()) {
+                let target = Position::from_piece(target);
+                if is_valid_move(slider, &target)
+                    && attacker.ray_to(&target).any(|pos| pos == from)
+                    && attacker.ray_to(&target).all(|pos| self.is_empty(&pos))
+                    && self.is_target(target, attacker)
+                {
+                    motifs.push(Motif::DiscoveredAttack {
+                        moved: to,
+                        attacker,
+                        target,
+                    });
+                }
+            }
+        }
+        motifs
+    }
+
+    fn forks(&self, player: &Player) -> Vec<Motif> {
+        let mut forks: Vec<Motif> = vec![];
+        for piece in self.pieces_of(*player) {
+            let attacker = Position::from_piece(piece);
+            let targets: Vec<Position> = self
+                .pieces_of(player.opponent())
+                .iter()
+                .map(Position::from_piece)
+                .filter(|target| {
+                    self.attackers(*target, player).contains(&attacker)
+                        && self.is_target(*target, attacker)
+                })
+                .collect();
+            if targets.len() > 1 {
+                forks.push(Motif::Fork { attacker, targets });
+            }
+        }
+        forks
+    }
+
+    fn pins_and_skewers(&self, player: &Player) -> Vec<Motif> {
+        let mut motifs = vec![];
+        let enemy = player.opponent();
+        for slider in self.pieces_of(*player).iter().filter(|p| is_slider(p.kind)) {
+            let attacker = Position::from_piece(slider);
+            for behind in self.pieces_of(enemy) {
+                let behind = Position::from_piece(behind);
+                if !is_valid_move(slider, &behind) {
+                    continue;
+                }
+                let mut between = attacker.ray_to(&behind).filter(|pos| !self.is_empty(pos));
+                let (Some(front), None) = (between.next(), between.next()) else {
+                    continue;
+                };
+                if self.piece_at(&front).map(|(p, _)| p) != Some(enemy) {
+                    continue;
+                }
+                let (front_value, behind_value) = (self.value_on(front), self.value_on(behind));
+                if behind_value > front_value {
+                    motifs.push(Motif::Pin {
+                        attacker,
+                        pinned: front,
+                        behind,
+                    });
+                } else if front_value > behind_value && self.is_target(behind, attacker) {
+                    motifs.push(Motif::Skewer {
+                        attacker,
+                        front,
+                        behind,
+                    });
+                }
+            }
+        }
+        motifs
+    }
+
+    // King of `player` on its back rank, walled in by its own pieces and the enemy attacks,
+    // while the enemy has a heavy piece to give mate with
+    fn back_rank_weakness(&self, player: &Player) -> Option<Position> {
+        let enemy = player.opponent();
+        let heavy = self
+            .pieces_of(enemy)
+            .iter()
+            .any(|p| matches!(p.kind, Kind::Rook | Kind::Queen));
+        let king = self
+            .pieces_of(*player)
+            .iter()
+            .find(|p| p.kind == Kind::King)
+            .map(Position::from_piece)?;
+        let back = self.size.back_row(*player);
+        if !heavy || king.row != back {
+            return None;
+        }
+        let next = if back == 0 { 1 } else { back - 1 };
+        let columns =
+            king.column.saturating_sub(1)..=(king.column + 1).min(self.size.columns() - 1);
+        columns
+            .map(|column| Position::new(next, column))
+            .all(|pos| {
+                self.piece_at(&pos).is_some_and(|(p, _)| p == *player)
+                    || self.is_square_attacked(pos, &enemy)
+            })
+            .then_some(king)
+    }
+
+    fn is_target(&self, target: Position, attacker: Position) -> bool {
+        let Some((player, _)) = self.piece_at(&target) else {
+            return false;
+        };
+        self.value_on(target) > self.value_on(attacker)
+            || self.attackers(target, &player).is_empty()
+    }
+}
+
+fn is_slider(kind: Kind) -> bool {
+    matches!(kind, Kind::Bishop | Kind::Rook | Kind::Queen)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Motif;
+    use crate::{
+        game_manager::GameManager,
+        moves::Move,
+        piece::{Player, Position},
+        square::Square,
+    };
+
+    #[test]
+    fn test_motifs() {
+        let gm = GameManager::from_fen("r3k3/2N5/8/8/8/8/8/4K3 b - - 0 1").unwrap();
+        assert_eq!(
+            vec![Motif::Fork {
+                attacker: Position::from(Square::C7),
+                targets: vec![Position::from(Square::A8), Position::from(Square::E8)],
+            }],
+            gm.motifs(&Player::White)
+                .into_iter()
+                .map(|motif| match motif {
+                    Motif::Fork {
+                        attacker,
+                        mut targets,
+                    } => {
+                        targets.sort_by_key(|pos| pos.column);
+                        Motif::Fork { attacker, targets }
+                    }
+                    motif => motif,
+                })
+                .collect::<Vec<_>>()
+        );
+
+        let gm = GameManager::from_fen("4k3/8/2n5/1B6/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            vec![Motif::Pin {
+                attacker: Position::from(Square::B5),
+                pinned: Position::from(Square::C6),
+                behind: Position::from(Square::E8),
+            }],
+            gm.motifs(&Player::White)
+        );
+        let gm = GameManager::from_fen("8/1q6/8/8/8/5k2/8/K6B b - - 0 1").unwrap();
+        assert_eq!(
+            vec![Motif::Skewer {
+                attacker: Position::from(Square::H1),
+                front: Position::from(Square::F3),
+                behind: Position::from(Square::B7),
+            }],
+            gm.motifs(&Player::White)
+        );
+
+        // No luft, the rook mates on a8
+        let gm = GameManager::from_fen("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1").unwrap();
+        assert_eq!(
+            vec![Motif::BackRankWeakness {
+                king: Position::from(Square::G8)
+            }],
+            gm.motifs(&Player::White)
+        );
+        assert!(gm.motifs(&Player::Black).is_empty());
+    }
+
+    #[test]
+    fn test_last_move_motifs() {
+        let mut gm = GameManager::from_fen("4k3/8/8/8/4N3/8/8/Q3R2K w - - 0 1").unwrap();
+        assert!(gm.last_move_motifs().is_empty());
+        gm.play(&Move::new(Square::E4, Square::C5)).unwrap();
+        assert_eq!(
+            vec![Motif::DiscoveredAttack {
+                moved: Position::from(Square::C5),
+                attacker: Position::from(Square::E1),
+                target: Position::from(Square::E8),
+            }],
+            gm.last_move_motifs()
+        );
+    }
+}
@@ -1,24 +1,27 @@
-use crate::piece::{Direction, Kind, Piece, Player, Position, MAX_ROW};
+use crate::{
+    piece::{Direction, Kind, Piece, Player, Position},
+    square::Rank,
+};
 
-pub fn is_pawn_in_start_pos(piece: &Piece, turn: &Player) -> bool {
-    match turn {
-        Player::Black => piece.row == MAX_ROW - 2,
-        Player::White => piece.row == 1,
-    }
+pub fn is_pawn_in_start_pos(piece: &Piece) -> bool {
+    let start = match piece.player {
+        Player::Black => Rank::SEVENTH,
+        Player::White => Rank::SECOND,
+    };
+    Rank::new(piece.row) == Some(start)
 }
 
-fn is_valid_pawn_move(piece: &Piece, end: &Position, turn: &Player) -> bool {
+fn is_valid_pawn_move(piece: &Piece, end: &Position) -> bool {
     use Direction::{Down, DownLeft, DownRight, Up, UpLeft, UpRight};
     use Player::*;
 
     let start = Position::from_piece(piece);
-    let direction = start.get_direction(end);
-    let in_start_pos = is_pawn_in_start_pos(piece, &turn);
-
-    if !direction.is_some() {
+    let Some(direction) = start.get_direction(end) else {
         return false;
-    }
-    match (&turn, direction.unwrap()) {
+    };
+    let in_start_pos = is_pawn_in_start_pos(piece);
+
+    match (piece.player, direction) {
         (White, Up(up)) => {
             if (in_start_pos && up <= 2) || up == 1 {
                 return true;
@@ -37,43 +40,47 @@ fn is_valid_pawn_move(piece: &Piece, end: &Position, turn: &Player) -> bool {
     }
 }
 
+pub fn is_pawn_attack(piece: &Piece, end: &Position) -> bool {
+    use Direction::{DownLeft, DownRight, UpLeft, UpRight};
+
+    let start = Position::from_piece(piece);
+    matches!(
+        (piece.player, start.get_direction(end)),
+        (Player::White, Some(UpLeft(1) | UpRight(1)))
+            | (Player::Black, Some(DownLeft(1) | DownRight(1)))
+    )
+}
+
 fn is_valid_bishop_move(piece: &Piece, end: &Position) -> bool {
     use Direction::{DownLeft, DownRight, UpLeft, UpRight};
 
     let start = Position::from_piece(piece);
-    let direction = start.get_direction(end);
-    if direction.is_none() {
+    let Some(direction) = start.get_direction(end) else {
         return false;
-    }
-    match direction.unwrap() {
-        DownLeft(_) | DownRight(_) | UpLeft(_) | UpRight(_) => true,
-        _ => false,
-    }
+    };
+    matches!(
+        direction,
+        DownLeft(_) | DownRight(_) | UpLeft(_) | UpRight(_)
+    )
 }
 
 fn is_valid_rook_move(piece: &Piece, end: &Position) -> bool {
     use Direction::{Down, Left, Right, Up};
     let start = Position::from_piece(piece);
-    let direction = start.get_direction(end);
-    if direction.is_none() {
+    let Some(direction) = start.get_direction(end) else {
         return false;
-    }
-    match direction.unwrap() {
-        Up(_) | Down(_) | Left(_) | Right(_) => true,
-        _ => false,
-    }
+    };
+    matches!(direction, Up(_) | Down(_) | Left(_) | Right(_))
 }
 
 fn is_valid_king_move(piece: &Piece, end: &Position) -> bool {
     use Direction::{Down, DownLeft, DownRight, Left, Right, Up, UpLeft, UpRight};
 
     let start = Position::from_piece(piece);
-    let direction = start.get_direction(end);
-
-    if direction.is_none() {
+    let Some(direction) = start.get_direction(end) else {
         return false;
-    }
-    match direction.unwrap() {
+    };
+    match direction {
         Down(d) | DownLeft(d) | DownRight(d) | Left(d) | Right(d) | Up(d) | UpLeft(d)
         | UpRight(d) => d == 1,
         _ => false,
@@ -84,16 +91,13 @@ fn is_valid_queen_move(piece: &Piece, end: &Position) -> bool {
     use Direction::{Down, DownLeft, DownRight, Left, Right, Up, UpLeft, UpRight};
 
     let start = Position::from_piece(piece);
-    let direction = start.get_direction(end);
-
-    if direction.is_none() {
+    let Some(direction) = start.get_direction(end) else {
         return false;
-    }
-    match direction.unwrap() {
-        Down(_) | DownLeft(_) | DownRight(_) | Left(_) | Right(_) | Up(_) | UpLeft(_)
-        | UpRight(_) => true,
-        _ => false,
-    }
+    };
+    matches!(
+        direction,
+        Down(_) | DownLeft(_) | DownRight(_) | Left(_) | Right(_) | Up(_) | UpLeft(_) | UpRight(_)
+    )
 }
 
 fn is_valid_knight_move(piece: &Piece, end: &Position) -> bool {
@@ -107,14 +111,14 @@ fn is_valid_knight_move(piece: &Piece, end: &Position) -> bool {
     false
 }
 
-pub fn is_valid_move(piece: &Piece, end: &Position, turn: &Player) -> bool {
+pub fn is_valid_move(piece: &Piece, end: &Position) -> bool {
     match piece.kind {
         Kind::King => is_valid_king_move(piece, end),
         Kind::Queen => is_valid_queen_move(piece, end),
         Kind::Knight => is_valid_knight_move(piece, end),
         Kind::Bishop => is_valid_bishop_move(piece, end),
         Kind::Rook => is_valid_rook_move(piece, end),
-        Kind::Pawn => is_valid_pawn_move(piece, end, turn),
+        Kind::Pawn => is_valid_pawn_move(piece, end),
     }
 }
 
@@ -124,15 +128,15 @@ mod test {
     use crate::piece::{Kind, Piece, Player, Position, MAX_COLUMN, MAX_ROW};
     #[test]
     fn test_pawn_move_white() {
-        let turn = &Player::White;
+        let turn = Player::White;
         for ci in 1..MAX_COLUMN {
             for ri in 1..MAX_ROW {
-                let piece = Piece::new(Kind::Pawn, ri, ci);
+                let piece = Piece::new(turn, Kind::Pawn, ri, ci);
                 let up = Position::new(piece.row + 1, piece.column);
                 let uleft = Position::new(piece.row + 1, piece.column - 1);
                 let uright = Position::new(piece.row + 1, piece.column + 1);
                 for end in [up, uleft, uright] {
-                    if !is_valid_pawn_move(&piece, &end, turn) {
+                    if !is_valid_pawn_move(&piece, &end) {
                         panic!("It should be possible to move {:?} to {:?}", &piece, end)
                     }
                 }
@@ -140,24 +144,24 @@ mod test {
         }
 
         // Checking starting position
-        let piece = Piece::new(Kind::Pawn, 1, 0);
+        let piece = Piece::new(turn, Kind::Pawn, 1, 0);
         let end = Position::new(piece.row + 2, piece.column);
-        if !is_valid_pawn_move(&piece, &end, turn) {
+        if !is_valid_pawn_move(&piece, &end) {
             panic!("It should be possible to move {:?} to {:?}", &piece, end)
         }
     }
 
     #[test]
     fn test_pawn_move_black() {
-        let turn = &Player::Black;
+        let turn = Player::Black;
         for ci in 1..MAX_COLUMN {
             for ri in 1..MAX_ROW {
-                let piece = Piece::new(Kind::Pawn, ri, ci);
+                let piece = Piece::new(turn, Kind::Pawn, ri, ci);
                 let up = Position::new(piece.row - 1, piece.column);
                 let dleft = Position::new(piece.row - 1, piece.column - 1);
                 let dright = Position::new(piece.row - 1, piece.column + 1);
                 for end in [up, dleft, dright] {
-                    if !is_valid_pawn_move(&piece, &end, turn) {
+                    if !is_valid_pawn_move(&piece, &end) {
                         panic!("It should be possible to move {:?} to {:?}", &piece, end)
                     }
                 }
@@ -165,9 +169,9 @@ mod test {
         }
 
         // Checking starting position
-        let piece = Piece::new(Kind::Pawn, 6, 0);
+        let piece = Piece::new(turn, Kind::Pawn, 6, 0);
         let end = Position::new(piece.row - 2, piece.column);
-        if !is_valid_pawn_move(&piece, &end, turn) {
+        if !is_valid_pawn_move(&piece, &end) {
             panic!("It should be possible to move {:?} to {:?}", &piece, end)
         }
     }
@@ -176,7 +180,7 @@ mod test {
     fn test_bishop_move() {
         for ci in 1..MAX_COLUMN {
             for ri in 1..MAX_ROW {
-                let start = Piece::new(Kind::Bishop, ri, ci);
+                let start = Piece::new(Player::White, Kind::Bishop, ri, ci);
                 let upleft = Position::new(start.row + 1, start.column - 1);
                 let upright = Position::new(start.row + 1, start.column + 1);
                 let downleft = Position::new(start.row - 1, start.column - 1);
@@ -194,7 +198,7 @@ mod test {
     fn test_rook_move() {
         for ci in 1..MAX_COLUMN {
             for ri in 1..MAX_ROW {
-                let start = Piece::new(Kind::Rook, ri, ci);
+                let start = Piece::new(Player::White, Kind::Rook, ri, ci);
                 let left = Position::new(start.row, start.column - 1);
                 let right = Position::new(start.row, start.column + 1);
                 let forward = Position::new(start.row + 1, start.column);
@@ -216,7 +220,7 @@ mod test {
         // I start from 2 to avoid overflow error, I check for correct move not necessary in a valid range
         for ci in 2..MAX_COLUMN {
             for ri in 2..MAX_ROW {
-                let start = Piece::new(Kind::Knight, ri, ci);
+                let start = Piece::new(Player::White, Kind::Knight, ri, ci);
                 let upleft = Position::new(start.row + 2, start.column - 1); // up -> up -> left
                 let upright = Position::new(start.row + 2, start.column + 1); // up -> up -> right
                 let downleft = Position::new(start.row - 2, start.column - 1); // down -> down -> left
@@ -244,7 +248,7 @@ mod test {
         // I start from 1 to avoid overflow error, I check for correct move not necessary in a valid range
         for ci in 1..MAX_COLUMN {
             for ri in 1..MAX_ROW {
-                let start = Piece::new(Kind::King, ri, ci);
+                let start = Piece::new(Player::White, Kind::King, ri, ci);
                 let up = Position::new(start.row + 1, start.column);
                 let down = Position::new(start.row - 1, start.column);
 
@@ -273,14 +277,14 @@ mod test {
     fn test_is_pawn_in_start_pos() {
         for ci in 0..MAX_COLUMN {
             for ri in 0..MAX_ROW {
-                let white = Piece::new(Kind::Pawn, ri, ci);
-                let black = Piece::new(Kind::Pawn, ri, ci);
-                if ri == 1 && !is_pawn_in_start_pos(&white, &Player::White) {
+                let white = Piece::new(Player::White, Kind::Pawn, ri, ci);
+                let black = Piece::new(Player::Black, Kind::Pawn, ri, ci);
+                if ri == 1 && !is_pawn_in_start_pos(&white) {
                     panic!(
                         "Should be in start position for whites {:?}",
                         Position::from_piece(&white)
                     )
-                } else if ri == 6 && !is_pawn_in_start_pos(&black, &Player::Black) {
+                } else if ri == 6 && !is_pawn_in_start_pos(&black) {
                     panic!(
                         "Should be in start position for blacks {:?}",
                         Position::from_piece(&black)
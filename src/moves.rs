@@ -0,0 +1,102 @@
+use crate::piece::{Kind, Position};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Move {
+    pub from: Position,
+    pub to: Position,
+    pub promotion: Option<Kind>,
+}
+
+impl Move {
+    pub fn new(from: impl Into<Position>, to: impl Into<Position>) -> Self {
+        Self {
+            from: from.into(),
+            to: to.into(),
+            promotion: None,
+        }
+    }
+
+    pub fn with_promotion(from: impl Into<Position>, to: impl Into<Position>, kind: Kind) -> Self {
+        Self {
+            from: from.into(),
+            to: to.into(),
+            promotion: Some(kind),
+        }
+    }
+
+    // Squares crossed from `from` to `to`, both included. Moves that don't follow a line,
+    // like the knight ones, jump straight to their target.
+    pub fn path(&self) -> Vec<Position> {
+        let mut path = vec![self.from];
+        path.extend(self.from.ray_to(&self.to));
+        path.push(self.to);
+        path
+    }
+
+    // Coordinate notation used by UCI, e.g. "e2e4" or "e7e8q"
+    pub fn to_uci(&self) -> String {
+        let mut uci = format!("{}{}", self.from, self.to);
+        if let Some(promotion) = self.promotion {
+            uci.push(promotion.to_char().to_ascii_lowercase());
+        }
+        uci
+    }
+
+    // Only checks the syntax, see `GameManager::parse_uci` to validate against a position
+    pub fn from_uci(uci: &str) -> Option<Self> {
+        if !uci.is_ascii() || (uci.len() != 4 && uci.len() != 5) {
+            return None;
+        }
+        let promotion = match uci[4..].chars().next() {
+            Some(c) => match Kind::from_char(c) {
+                Some(kind @ (Kind::Queen | Kind::Rook | Kind::Bishop | Kind::Knight))
+                    if c.is_ascii_lowercase() =>
+                {
+                    Some(kind)
+                }
+                _ => return None,
+            },
+            None => None,
+        };
+        Some(Self {
+            from: Position::from_algebraic(&uci[..2])?,
+            to: Position::from_algebraic(&uci[2..4])?,
+            promotion,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Move;
+    use crate::{piece::Kind, square::Square};
+
+    #[test]
+    fn test_uci_round_trip() {
+        let mv = Move::new(Square::E2, Square::E4);
+        assert_eq!("e2e4", mv.to_uci());
+        assert_eq!(Some(mv), Move::from_uci("e2e4"));
+        let mv = Move::with_promotion(Square::E7, Square::E8, Kind::Knight);
+        assert_eq!("e7e8n", mv.to_uci());
+        assert_eq!(Some(mv), Move::from_uci("e7e8n"));
+        for invalid in ["", "e2", "e2e9", "i2e4", "e7e8k", "e7e8Q", "e2e4qq"] {
+            assert_eq!(None, Move::from_uci(invalid), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_path() {
+        let path = |from, to| {
+            Move::new(from, to)
+                .path()
+                .iter()
+                .map(|pos| pos.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(vec!["a1", "b2", "c3", "d4"], path(Square::A1, Square::D4));
+        assert_eq!(vec!["h8", "h7", "h6"], path(Square::H8, Square::H6));
+        assert_eq!(vec!["e1", "f1", "g1"], path(Square::E1, Square::G1));
+        assert_eq!(vec!["g1", "f3"], path(Square::G1, Square::F3));
+    }
+}
@@ -0,0 +1,355 @@
+use std::{error::Error, fmt, io};
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::{
+    fen::FenErr,
+    game_manager::{GameManager, MoveErr},
+    moves::Move,
+    piece::Player,
+};
+
+// Wire protocol between two remote players. Every message is a big-endian u32 length followed
+// by that many bytes of UTF-8 text:
+//
+//   start <w|b> <fen>   sent once by the host, the side the client plays and the position
+//   move <uci>          move of the sender, e.g. "move e7e8q"
+//   offer-draw          the sender offers a draw
+//   accept-draw         the sender accepts the draw offered by its opponent
+//   decline-draw        the sender declines it, playing a move declines it as well
+//   resign              the sender resigns
+pub const MAX_MESSAGE_LEN: u32 = 1024;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Message {
+    Start { side: Player, fen: String },
+    Move(Move),
+    OfferDraw,
+    AcceptDraw,
+    DeclineDraw,
+    Resign,
+}
+
+impl Message {
+    pub fn encode(&self) -> String {
+        match self {
+            Message::Start { side, fen } => {
+                let side = match side {
+                    Player::White => 'w',
+                    Player::Black => 'b',
+                };
+                format!("start {} {}", side, fen)
+            }
+            Message::Move(mv) => format!("move {}", mv.to_uci()),
+            Message::OfferDraw => "offer-draw".to_string(),
+            Message::AcceptDraw => "accept-draw".to_string(),
+            Message::DeclineDraw => "decline-draw".to_string(),
+            Message::Resign => "resign".to_string(),
+        }
+    }
+
+    pub fn decode(text: &str) -> Option<Self> {
+        let (command, args) = text.split_once(' ').unwrap_or((text, ""));
+        let message = match (command, args) {
+            ("start", args) => {
+                let (side, fen) = args.split_once(' ')?;
+                let side = match side {
+                    "w" => Player::White,
+                    "b" => Player::Black,
+                    _ => return None,
+                };
+                Message::Start {
+                    side,
+                    fen: fen.to_string(),
+                }
+            }
+            ("move", uci) => Message::Move(Move::from_uci(uci)?),
+            ("offer-draw", "") => Message::OfferDraw,
+            ("accept-draw", "") => Message::AcceptDraw,
+            ("decline-draw", "") => Message::DeclineDraw,
+            ("resign", "") => Message::Resign,
+            _ => return None,
+        };
+        Some(message)
+    }
+}
+
+#[derive(Debug)]
+pub enum NetErr {
+    Io(io::Error),
+    // Message that isn't part of the protocol, or came when it couldn't
+    Protocol(String),
+    Fen(FenErr),
+    Move(MoveErr),
+    // Accepting or declining a draw nobody offered
+    NoDrawOffer,
+}
+
+impl fmt::Display for NetErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NetErr::Io(err) => err.fmt(f),
+            NetErr::Protocol(message) => write!(f, "unexpected message: {}", message),
+            NetErr::Fen(err) => err.fmt(f),
+            NetErr::Move(err) => err.fmt(f),
+            NetErr::NoDrawOffer => write!(f, "no draw was offered"),
+        }
+    }
+}
+
+impl Error for NetErr {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            NetErr::Io(err) => Some(err),
+            NetErr::Fen(err) => Some(err),
+            NetErr::Move(err) => Some(err),
+            NetErr::Protocol(_) | NetErr::NoDrawOffer => None,
+        }
+    }
+}
+
+impl From<io::Error> for NetErr {
+    fn from(err: io::Error) -> Self {
+        NetErr::Io(err)
+    }
+}
+
+pub async fn write_message(
+    stream: &mut (impl AsyncWrite + Unpin),
+    message: &Message,
+) -> io::Result<()> {
+    let text = message.encode();
+    stream.write_u32(text.len() as u32).await?;
+    stream.write_all(text.as_bytes()).await?;
+    stream.flush().await
+}
+
+pub async fn read_message(stream: &mut (impl AsyncRead + Unpin)) -> Result<Message, NetErr> {
+    let len = stream.read_u32().await?;
+    if len > MAX_MESSAGE_LEN {
+        return Err(NetErr::Protocol(format!("message of {} bytes", len)));
+    }
+    let mut bytes = vec![0; len as usize];
+    stream.read_exact(&mut bytes).await?;
+    let text = String::from_utf8(bytes).map_err(|_| NetErr::Protocol("not UTF-8".into()))?;
+    Message::decode(&text).ok_or(NetErr::Protocol(text))
+}
+
+// One end of a game between two remote players. Both ends keep their own copy of the game
+// and check the moves of the other one against it.
+pub struct NetworkPeer<S> {
+    stream: S,
+    gm: GameManager,
+    side: Player,
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> NetworkPeer<S> {
+    // Starts the game `gm` playing `side`, the client gets the other one
+    pub async fn host(mut stream: S, gm: GameManager, side: Player) -> io::Result<Self> {
+        let start = Message::Start {
+            side: side.opponent(),
+            fen: gm.to_fen(),
+        };
+        write_message(&mut stream, &start).await?;
+        Ok(Self { stream, gm, side })
+    }
+
+    // Waits for the game started by the host
+    pub async fn join(mut stream: S) -> Result<Self, NetErr> {
+        match read_message(&mut stream).await? {
+            Message::Start { side, fen } => Ok(Self {
+                stream,
+                gm: GameManager::from_fen(&fen).map_err(NetErr::Fen)?,
+                side,
+            }),
+            message => Err(NetErr::Protocol(message.encode())),
+        }
+    }
+
+    pub fn game(&self) -> &GameManager {
+        &self.gm
+    }
+
+    pub fn side(&self) -> Player {
+        self.side
+    }
+
+    // Draw offered by the opponent and not answered yet
+    pub fn has_draw_offer(&self) -> bool {
+        self.gm.draw_offer() == Some(self.side.opponent())
+    }
+
+    pub async fn play(&mut self, mv: &Move) -> Result<(), NetErr> {
+        self.apply(self.side, &Message::Move(*mv))?;
+        self.send(&Message::Move(*mv)).await
+    }
+
+    pub async fn offer_draw(&mut self) -> Result<(), NetErr> {
+        self.apply(self.side, &Message::OfferDraw)?;
+        self.send(&Message::OfferDraw).await
+    }
+
+    pub async fn accept_draw(&mut self) -> Result<(), NetErr> {
+        self.apply(self.side, &Message::AcceptDraw)?;
+        self.send(&Message::AcceptDraw).await
+    }
+
+    pub async fn decline_draw(&mut self) -> Result<(), NetErr> {
+        self.apply(self.side, &Message::DeclineDraw)?;
+        self.send(&Message::DeclineDraw).await
+    }
+
+    pub async fn resign(&mut self) -> Result<(), NetErr> {
+        self.apply(self.side, &Message::Resign)?;
+        self.send(&Message::Resign).await
+    }
+
+    // Waits for the next message of the opponent and plays it on the game
+    pub async fn receive(&mut self) -> Result<Message, NetErr> {
+        let message = read_message(&mut self.stream).await?;
+        self.apply(self.side.opponent(), &message)?;
+        Ok(message)
+    }
+
+    async fn send(&mut self, message: &Message) -> Result<(), NetErr> {
+        Ok(write_message(&mut self.stream, message).await?)
+    }
+
+    fn apply(&mut self, sender: Player, message: &Message) -> Result<(), NetErr> {
+        match message {
+            Message::Start { .. } => return Err(NetErr::Protocol(message.encode())),
+            Message::Move(mv) => {
+                if self.gm.turn != sender {
+                    return Err(NetErr::Move(MoveErr::WrongTurn));
+                }
+                self.gm.play(mv).map_err(NetErr::Move)?;
+            }
+            Message::OfferDraw => {
+                if self.gm.status().is_over() {
+                    return Err(NetErr::Move(MoveErr::GameOver));
+                }
+                if !self.gm.offer_draw(&sender) {
+                    return Err(NetErr::Protocol(message.encode()));
+                }
+            }
+            Message::AcceptDraw | Message::DeclineDraw => {
+                if self.gm.draw_offer() != Some(sender.opponent()) {
+                    return Err(NetErr::NoDrawOffer);
+                }
+                self.gm.decline_draw();
+                if *message == Message::AcceptDraw {
+                    self.gm.agree_draw();
+                }
+            }
+            Message::Resign => {
+                if !self.gm.resign(&sender) {
+                    return Err(NetErr::Move(MoveErr::GameOver));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::duplex;
+
+    use super::{write_message, Message, NetErr, NetworkPeer};
+    use crate::{
+        game_manager::{GameManager, GameStatus, MoveErr},
+        moves::Move,
+        piece::{Kind, Player},
+        square::Square,
+    };
+
+    #[test]
+    fn test_messages() {
+        let messages = [
+            Message::Start {
+                side: Player::Black,
+                fen: GameManager::new().to_fen(),
+            },
+            Message::Move(Move::with_promotion(Square::E7, Square::E8, Kind::Queen)),
+            Message::OfferDraw,
+            Message::AcceptDraw,
+            Message::DeclineDraw,
+            Message::Resign,
+        ];
+        for message in messages {
+            assert_eq!(Some(message.clone()), Message::decode(&message.encode()));
+        }
+        assert_eq!(
+            "move e7e8q",
+            Message::Move(Move::from_uci("e7e8q").unwrap()).encode()
+        );
+        for invalid in ["", "move", "move e9e4", "start x 8/8", "resign now"] {
+            assert_eq!(None, Message::decode(invalid), "{}", invalid);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_network_game() {
+        let (a, b) = duplex(1024);
+        let mut host = NetworkPeer::host(a, GameManager::new(), Player::White)
+            .await
+            .unwrap();
+        let mut client = NetworkPeer::join(b).await.unwrap();
+        assert_eq!(Player::Black, client.side());
+
+        let e4 = Move::new(Square::E2, Square::E4);
+        host.play(&e4).await.unwrap();
+        assert_eq!(Message::Move(e4), client.receive().await.unwrap());
+        assert!(matches!(
+            client.play(&Move::new(Square::E7, Square::E4)).await,
+            Err(NetErr::Move(MoveErr::InvalidMove {
+                piece: Kind::Pawn,
+                ..
+            }))
+        ));
+        assert!(matches!(
+            host.play(&Move::new(Square::D2, Square::D4)).await,
+            Err(NetErr::Move(MoveErr::WrongTurn))
+        ));
+
+        client.offer_draw().await.unwrap();
+        host.receive().await.unwrap();
+        assert!(host.has_draw_offer());
+        assert_eq!(Some(Player::Black), host.game().draw_offer());
+        assert!(matches!(host.offer_draw().await, Err(NetErr::Protocol(_))));
+        host.decline_draw().await.unwrap();
+        client.receive().await.unwrap();
+        assert!(matches!(
+            client.accept_draw().await,
+            Err(NetErr::NoDrawOffer)
+        ));
+        client.offer_draw().await.unwrap();
+        host.receive().await.unwrap();
+        host.accept_draw().await.unwrap();
+        client.receive().await.unwrap();
+        assert_eq!(GameStatus::DrawByAgreement, host.game().status());
+        assert_eq!(host.game().status(), client.game().status());
+
+        // An illegal move from the other end is refused
+        let (a, b) = duplex(1024);
+        let host = NetworkPeer::host(a, GameManager::new(), Player::Black);
+        let (host, client) = tokio::join!(host, NetworkPeer::join(b));
+        let (mut host, mut client) = (host.unwrap(), client.unwrap());
+        write_message(
+            &mut client.stream,
+            &Message::Move(Move::new(Square::E2, Square::E5)),
+        )
+        .await
+        .unwrap();
+        assert!(matches!(
+            host.receive().await,
+            Err(NetErr::Move(MoveErr::InvalidMove {
+                piece: Kind::Pawn,
+                ..
+            }))
+        ));
+        client.resign().await.unwrap();
+        assert_eq!(Message::Resign, host.receive().await.unwrap());
+        assert_eq!(GameStatus::Resignation(Player::Black), host.game().status());
+    }
+}
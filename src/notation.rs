@@ -0,0 +1,616 @@
+use std::{error::Error, fmt};
+
+use crate::{
+    game_manager::{GameManager, MoveErr, MoveFlags},
+    moves::Move,
+    piece::{Kind, Player, Position},
+    square::{File, Rank},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SanErr {
+    Syntax,
+    // Well formed but no legal move matches
+    Illegal,
+    // Several legal moves match, the origin is missing
+    Ambiguous,
+}
+
+impl fmt::Display for SanErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            SanErr::Syntax => "invalid SAN",
+            SanErr::Illegal => "no legal move matches the SAN",
+            SanErr::Ambiguous => "several legal moves match the SAN",
+        };
+        f.write_str(message)
+    }
+}
+
+impl Error for SanErr {}
+
+impl MoveFlags {
+    // "+" or "#" when the move gave check or mate
+    pub(crate) fn check_suffix(&self) -> Option<char> {
+        match (self.check, self.checkmate) {
+            (_, true) => Some('#'),
+            (true, false) => Some('+'),
+            (false, false) => None,
+        }
+    }
+}
+
+// Longest text `submit_move_text` reads, surrounding whitespace included
+pub const MAX_MOVE_TEXT_LEN: usize = 16;
+
+#[derive(Debug)]
+pub enum SubmitErr {
+    Empty,
+    TooLong(usize),
+    // Neither SAN nor coordinate notation
+    Syntax,
+    San(SanErr),
+    // The SAN names a legal move but misses or adds a capture, check or mate sign
+    Inexact { expected: String },
+    Move(MoveErr),
+}
+
+impl fmt::Display for SubmitErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SubmitErr::Empty => write!(f, "no move given"),
+            SubmitErr::TooLong(len) => write!(
+                f,
+                "move of {} bytes, at most {} are read",
+                len, MAX_MOVE_TEXT_LEN
+            ),
+            SubmitErr::Syntax => write!(f, "neither SAN nor coordinate notation"),
+            SubmitErr::San(err) => err.fmt(f),
+            SubmitErr::Inexact { expected } => write!(f, "the move is written {}", expected),
+            SubmitErr::Move(err) => err.fmt(f),
+        }
+    }
+}
+
+impl Error for SubmitErr {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SubmitErr::San(err) => Some(err),
+            SubmitErr::Move(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl GameManager {
+    // Standard algebraic notation of `mv`, which has to be legal for the side to move
+    pub fn san(&self, mv: &Move) -> Result<String, MoveErr> {
+        let mut san = self.algebraic(mv, false)?;
+        san.extend(self.check_suffix(mv));
+        Ok(san)
+    }
+
+    // Long algebraic notation of `mv`, which has to be legal for the side to move: both
+    // squares always written, e.g. "Ng1-f3", "e2-e4" or "Ra1xa8+". Easier to read than SAN
+    // for beginners.
+    pub fn lan(&self, mv: &Move) -> Result<String, MoveErr> {
+        let mut lan = self.algebraic(mv, true)?;
+        lan.extend(self.check_suffix(mv));
+        Ok(lan)
+    }
+
+    // SAN, or LAN when `long`, without the check suffix: they only differ in how the start of
+    // the move is written. `play` adds the suffix itself once the move is made.
+    pub(crate) fn algebraic(&self, mv: &Move, long: bool) -> Result<String, MoveErr> {
+        if !self.legal_moves_iter().any(|m| m == *mv) {
+            return Err(self.illegal_move_err(mv));
+        }
+        let kind = self
+            .pieces_of(self.turn)
+            .iter()
+            .find(|p| Position::from_piece(p) == mv.from)
+            .map(|p| p.kind)
+            .ok_or(MoveErr::NoPiece { at: mv.from })?;
+        let mut text = String::new();
+        if let Some(castle) = self.castle(kind, mv, &self.castling) {
+            text.push_str(if castle.king_to.column > mv.from.column {
+                "O-O"
+            } else {
+                "O-O-O"
+            });
+        } else {
+            let capture =
+                !self.is_empty(&mv.to) || (kind == Kind::Pawn && mv.from.column != mv.to.column);
+            if kind != Kind::Pawn {
+                text.push(kind.to_char());
+            }
+            if long {
+                text.push_str(&mv.from.to_string());
+                text.push(if capture { 'x' } else { '-' });
+            } else {
+                if kind != Kind::Pawn {
+                    self.push_disambiguation(&mut text, kind, mv);
+                } else if capture {
+                    text.extend(mv.from.file().map(|file| file.to_char()));
+                }
+                if capture {
+                    text.push('x');
+                }
+            }
+            text.push_str(&mv.to.to_string());
+            if let Some(promotion) = mv.promotion {
+                text.push('=');
+                text.push(promotion.to_char());
+            }
+        }
+        Ok(text)
+    }
+
+    // Moves of the game in long algebraic notation, numbered as in PGN movetext
+    pub fn lan_movetext(&self) -> String {
+        let mut gm = self.initial_position();
+        let mut tokens = vec![];
+        for record in self.history() {
+            let lan = gm.lan(&record.mv).unwrap_or_else(|_| record.mv.to_uci());
+            match gm.turn {
+                Player::White => tokens.push(format!("{}.", gm.fullmove_number)),
+                Player::Black if tokens.is_empty() => {
+                    tokens.push(format!("{}...", gm.fullmove_number))
+                }
+                Player::Black => (),
+            }
+            tokens.push(lan);
+            gm.make_move(&record.mv);
+        }
+        tokens.join(" ")
+    }
+
+    // "+" or "#" when `mv` gives check or mate
+    fn check_suffix(&self, mv: &Move) -> Option<char> {
+        let mut after = self.clone();
+        after.make_move(mv);
+        if !after.is_in_check(&after.turn) {
+            return None;
+        }
+        let mate = after.legal_moves_iter().next().is_none();
+        Some(if mate { '#' } else { '+' })
+    }
+
+    // Reads a UCI coordinate move and checks it is legal for the side to move
+    pub fn parse_uci(&self, uci: &str) -> Result<Move, MoveErr> {
+        let mv = Move::from_uci(uci).ok_or(MoveErr::Syntax)?;
+        if !self.legal_moves_iter().any(|m| m == mv) {
+            return Err(self.illegal_move_err(&mv));
+        }
+        Ok(mv)
+    }
+
+    // Reads a move in standard algebraic notation and finds the legal move it stands for.
+    // Check, mate and annotation suffixes are ignored, "0-0" is accepted for castling.
+    pub fn parse_san(&self, san: &str) -> Result<Move, SanErr> {
+        let san = san.trim_end_matches(['+', '#', '!', '?']);
+        let castling = match san {
+            "O-O" | "0-0" => Some(6),
+            "O-O-O" | "0-0-0" => Some(2),
+            _ => None,
+        };
+        if let Some(column) = castling {
+            let from = self
+                .pieces_of(self.turn)
+                .iter()
+                .find(|p| p.kind == Kind::King)
+                .map(Position::from_piece)
+                .ok_or(SanErr::Illegal)?;
+            return self
+                .legal_moves_iter()
+                .find(|mv| {
+                    self.castle(Kind::King, mv, &self.castling)
+                        .is_some_and(|castle| mv.from == from && castle.king_to.column == column)
+                })
+                .ok_or(SanErr::Illegal);
+        }
+        let mut chars: Vec<char> = san.chars().filter(|c| *c != 'x').collect();
+        let promotion = match chars.as_slice() {
+            [.., '=', p] | [.., '1'..='8', p] if p.is_ascii_uppercase() => {
+                let kind = Kind::from_char(*p).ok_or(SanErr::Syntax)?;
+                chars.pop();
+                if chars.last() == Some(&'=') {
+                    chars.pop();
+                }
+                Some(kind)
+            }
+            _ => None,
+        };
+        let kind = match chars.first() {
+            Some(c) if c.is_ascii_uppercase() => {
+                let kind = Kind::from_char(*c).ok_or(SanErr::Syntax)?;
+                chars.remove(0);
+                kind
+            }
+            _ => Kind::Pawn,
+        };
+        if chars.len() < 2 || chars.len() > 4 {
+            return Err(SanErr::Syntax);
+        }
+        let (origin, target) = chars.split_at(chars.len() - 2);
+        let to =
+            Position::from_algebraic(&target.iter().collect::<String>()).ok_or(SanErr::Syntax)?;
+        let (mut file, mut rank) = (None, None);
+        for c in origin {
+            match (File::from_char(*c), Rank::from_char(*c)) {
+                (Some(f), _) if file.is_none() => file = Some(u8::from(f)),
+                (_, Some(r)) if rank.is_none() => rank = Some(u8::from(r)),
+                _ => return Err(SanErr::Syntax),
+            }
+        }
+        let pieces = self.pieces_of(self.turn);
+        let mut candidates = self.legal_moves_iter().filter(|m| {
+            m.to == to
+                && m.promotion == promotion
+                && file.is_none_or(|f| m.from.column == f)
+                && rank.is_none_or(|r| m.from.row == r)
+                && pieces
+                    .iter()
+                    .any(|p| Position::from_piece(p) == m.from && p.kind == kind)
+        });
+        match (candidates.next(), candidates.next()) {
+            (Some(mv), None) => Ok(mv),
+            (Some(_), Some(_)) => Err(SanErr::Ambiguous),
+            (None, _) => Err(SanErr::Illegal),
+        }
+    }
+
+    // Plays a move sent by an untrusted client, in SAN ("Nf3", "exd8=Q+") or coordinate
+    // notation ("g1f3", "e7d8q"). Unlike `parse_san`, the input is held to the exact grammar:
+    // no annotations, no "0-0", a capture needs its "x".
+    pub fn submit_move_text(&mut self, text: &str) -> Result<Move, SubmitErr> {
+        if text.len() > MAX_MOVE_TEXT_LEN {
+            return Err(SubmitErr::TooLong(text.len()));
+        }
+        let text = text.trim_matches(|c: char| c.is_ascii_whitespace());
+        let mv = if text.is_empty() {
+            return Err(SubmitErr::Empty);
+        } else if is_coordinate_syntax(text) {
+            self.parse_uci(text).map_err(SubmitErr::Move)?
+        } else if is_san_syntax(text) {
+            let mv = self.parse_san(text).map_err(SubmitErr::San)?;
+            // `parse_san` reads past the signs, they have to tell the truth about the move
+            let expected = self.san(&mv).map_err(SubmitErr::Move)?;
+            let signs = |san: &str| {
+                let suffix = san.chars().last().filter(|c| matches!(c, '+' | '#'));
+                (san.contains('x'), suffix)
+            };
+            if signs(text) != signs(&expected) {
+                return Err(SubmitErr::Inexact { expected });
+            }
+            mv
+        } else {
+            return Err(SubmitErr::Syntax);
+        };
+        self.play(&mv).map_err(SubmitErr::Move)?;
+        Ok(mv)
+    }
+
+    // Replays a game given in SAN from the starting position. The index of the first move that
+    // can't be read or played is reported with the error.
+    pub fn from_moves(moves: &[&str]) -> Result<GameManager, (usize, SanErr)> {
+        let mut gm = GameManager::new();
+        for (i, san) in moves.iter().enumerate() {
+            let mv = gm.parse_san(san).map_err(|err| (i, err))?;
+            // Parsed moves are legal
+            gm.play(&mv).map_err(|_| (i, SanErr::Illegal))?;
+        }
+        Ok(gm)
+    }
+
+    // Adds the origin file, rank or square when another piece of the same kind can reach the
+    // same target
+    fn push_disambiguation(&self, san: &mut String, kind: Kind, mv: &Move) {
+        let pieces = self.pieces_of(self.turn);
+        let mut others = self
+            .legal_moves_iter()
+            .filter(|m| m.to == mv.to && m.from != mv.from)
+            .filter(|m| {
+                pieces
+                    .iter()
+                    .any(|p| Position::from_piece(p) == m.from && p.kind == kind)
+            })
+            .peekable();
+        if others.peek().is_none() {
+            return;
+        }
+        let (mut same_file, mut same_rank) = (false, false);
+        for other in others {
+            same_file |= other.from.column == mv.from.column;
+            same_rank |= other.from.row == mv.from.row;
+        }
+        if !same_file {
+            san.extend(mv.from.file().map(|file| file.to_char()));
+        } else if !same_rank {
+            san.extend(mv.from.rank().map(|rank| rank.to_char()));
+        } else {
+            san.push_str(&mv.from.to_string());
+        }
+    }
+}
+
+fn is_file(c: u8) -> bool {
+    (b'a'..=b'h').contains(&c)
+}
+
+fn is_rank(c: u8) -> bool {
+    (b'1'..=b'8').contains(&c)
+}
+
+// "e2e4" or "e7e8q"
+fn is_coordinate_syntax(text: &str) -> bool {
+    match text.as_bytes() {
+        [from_file, from_rank, to_file, to_rank, promotion @ ..] => {
+            is_file(*from_file)
+                && is_rank(*from_rank)
+                && is_file(*to_file)
+                && is_rank(*to_rank)
+                && matches!(promotion, [] | [b'q' | b'r' | b'b' | b'n'])
+        }
+        _ => false,
+    }
+}
+
+// Piece letter, origin file and rank when needed, "x" for captures, target square,
+// promotion, then "+" or "#"
+fn is_san_syntax(text: &str) -> bool {
+    let body = text.strip_suffix(['+', '#']).unwrap_or(text);
+    if body == "O-O" || body == "O-O-O" {
+        return true;
+    }
+    let (body, promotion) = match body.split_once('=') {
+        Some((body, promotion)) => (body, Some(promotion)),
+        None => (body, None),
+    };
+    if promotion.is_some_and(|p| !matches!(p, "Q" | "R" | "B" | "N")) {
+        return false;
+    }
+    let (piece, rest) = match body.as_bytes() {
+        [b'K' | b'Q' | b'R' | b'B' | b'N', rest @ ..] => (true, rest),
+        rest => (false, rest),
+    };
+    let [origin @ .., to_file, to_rank] = rest else {
+        return false;
+    };
+    let origin = origin.strip_suffix(b"x").unwrap_or(origin);
+    let origin = if piece {
+        promotion.is_none()
+            && match origin {
+                [] => true,
+                [c] => is_file(*c) || is_rank(*c),
+                [file, rank] => is_file(*file) && is_rank(*rank),
+                _ => false,
+            }
+    } else {
+        // Pawns only name their file, when capturing
+        match rest {
+            [file, b'x', _, _] => is_file(*file),
+            [_, _] => true,
+            _ => false,
+        }
+    };
+    origin && is_file(*to_file) && is_rank(*to_rank)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SanErr;
+    use crate::{
+        game_manager::{CastlingRights, GameManager},
+        moves::Move,
+        piece::{Kind, Piece, Player},
+        square::Square,
+    };
+
+    #[test]
+    fn test_san_basic_moves() {
+        let mut gm = GameManager::new();
+        assert_eq!("e4", gm.san(&Move::new(Square::E2, Square::E4)).unwrap());
+        assert_eq!("Nf3", gm.san(&Move::new(Square::G1, Square::F3)).unwrap());
+        assert!(gm.san(&Move::new(Square::E2, Square::E5)).is_err());
+        for (from, to) in [(Square::E2, Square::E4), (Square::D7, Square::D5)] {
+            gm.make_move(&Move::new(from, to));
+        }
+        assert_eq!("exd5", gm.san(&Move::new(Square::E4, Square::D5)).unwrap());
+    }
+
+    #[test]
+    fn test_san_check_mate_and_castling() {
+        // Fool's mate, 1. f3 e5 2. g4 Qh4#
+        let mut gm = GameManager::new();
+        let moves = [
+            (Square::F2, Square::F3),
+            (Square::E7, Square::E5),
+            (Square::G2, Square::G4),
+        ];
+        for (from, to) in moves {
+            gm.make_move(&Move::new(from, to));
+        }
+        assert_eq!("Qh4#", gm.san(&Move::new(Square::D8, Square::H4)).unwrap());
+
+        let gm = GameManager {
+            whites: [
+                Piece::new(Player::White, Kind::Rook, 0, 0),
+                Piece::new(Player::White, Kind::King, 0, 4),
+                Piece::new(Player::White, Kind::Rook, 0, 7),
+            ]
+            .into(),
+            blacks: [Piece::new(Player::Black, Kind::King, 7, 5)].into(),
+            castling: CastlingRights::all(),
+            ..GameManager::empty()
+        };
+        assert_eq!("O-O+", gm.san(&Move::new(Square::E1, Square::G1)).unwrap());
+        assert_eq!("O-O-O", gm.san(&Move::new(Square::E1, Square::C1)).unwrap());
+    }
+
+    #[test]
+    fn test_lan() {
+        let gm = GameManager::from_moves(&["e4", "e5", "Nf3", "Nc6", "Bb5", "a6", "Bxc6"]).unwrap();
+        assert_eq!(
+            "1. e2-e4 e7-e5 2. Ng1-f3 Nb8-c6 3. Bf1-b5 a7-a6 4. Bb5xc6",
+            gm.lan_movetext()
+        );
+        assert_eq!("d7xc6", gm.lan(&Move::new(Square::D7, Square::C6)).unwrap());
+        let gm = GameManager::from_fen("r3k3/8/8/8/8/8/8/R3K2R b KQq - 0 30").unwrap();
+        assert_eq!("O-O-O", gm.lan(&Move::new(Square::E8, Square::C8)).unwrap());
+        assert!(gm.lan(&Move::new(Square::E8, Square::E6)).is_err());
+        let mut gm = GameManager::from_fen("r3k3/1P6/8/8/8/8/8/R3K2R w KQq - 0 30").unwrap();
+        let mv = Move::with_promotion(Square::B7, Square::A8, Kind::Queen);
+        assert_eq!("b7xa8=Q+", gm.lan(&mv).unwrap());
+        gm.play(&Move::new(Square::A1, Square::A7)).unwrap();
+        gm.play(&Move::new(Square::A8, Square::A7)).unwrap();
+        assert_eq!("30. Ra1-a7 Ra8xa7", gm.lan_movetext());
+    }
+
+    #[test]
+    fn test_parse_uci() {
+        let gm = GameManager::new();
+        assert_eq!(
+            Move::new(Square::G1, Square::F3),
+            gm.parse_uci("g1f3").unwrap()
+        );
+        assert!(gm.parse_uci("e2e5").is_err());
+        assert!(gm.parse_uci("e7e5").is_err());
+        assert!(gm.parse_uci("nonsense").is_err());
+    }
+
+    #[test]
+    fn test_san_disambiguation_and_promotion() {
+        let gm = GameManager {
+            whites: [
+                Piece::new(Player::White, Kind::King, 1, 0),
+                Piece::new(Player::White, Kind::Rook, 0, 0),
+                Piece::new(Player::White, Kind::Rook, 0, 4),
+                Piece::new(Player::White, Kind::Knight, 2, 1),
+                Piece::new(Player::White, Kind::Knight, 4, 1),
+                Piece::new(Player::White, Kind::Queen, 3, 1),
+                Piece::new(Player::White, Kind::Queen, 3, 7),
+                Piece::new(Player::White, Kind::Queen, 0, 7),
+                Piece::new(Player::White, Kind::Pawn, 6, 6),
+            ]
+            .into(),
+            blacks: [Piece::new(Player::Black, Kind::King, 7, 2)].into(),
+            ..GameManager::empty()
+        };
+        assert_eq!("Rad1", gm.san(&Move::new(Square::A1, Square::D1)).unwrap());
+        assert_eq!("N3d4", gm.san(&Move::new(Square::B3, Square::D4)).unwrap());
+        assert_eq!("Qh4e4", gm.san(&Move::new(Square::H4, Square::E4)).unwrap());
+        // UCI requires the promotion piece
+        assert!(gm.parse_uci("g7g8").is_err());
+        assert!(gm.parse_uci("g7g8q").is_ok());
+        assert_eq!(
+            "g8=N",
+            gm.san(&Move::with_promotion(Square::G7, Square::G8, Kind::Knight))
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_submit_move_text() {
+        let mut gm = GameManager::new();
+        assert_eq!(
+            Move::new(Square::E2, Square::E4),
+            gm.submit_move_text("e4").unwrap()
+        );
+        assert_eq!(
+            Move::new(Square::E7, Square::E5),
+            gm.submit_move_text(" e7e5\n").unwrap()
+        );
+        gm.submit_move_text("Nf3").unwrap();
+        gm.submit_move_text("Nc6").unwrap();
+        gm.submit_move_text("Bb5").unwrap();
+        gm.submit_move_text("a6").unwrap();
+        for (text, expected) in [
+            ("Bc6", "the move is written Bxc6"),
+            ("Nxd4", "the move is written Nd4"),
+            ("Ba4+", "the move is written Ba4"),
+        ] {
+            let err = gm.submit_move_text(text).unwrap_err();
+            assert_eq!(expected, err.to_string(), "{:?}", text);
+        }
+        gm.submit_move_text("Bxc6").unwrap();
+        gm.submit_move_text("dxc6").unwrap();
+        assert_eq!(
+            Move::new(Square::E1, Square::G1),
+            gm.submit_move_text("O-O").unwrap()
+        );
+
+        let fen = gm.to_fen();
+        for (text, expected) in [
+            ("", "no move given"),
+            ("   ", "no move given"),
+            (
+                "e5e5e5e5e5e5e5e5e5",
+                "move of 18 bytes, at most 16 are read",
+            ),
+            ("Bc5!?", "neither SAN nor coordinate notation"),
+            ("0-0", "neither SAN nor coordinate notation"),
+            ("Qd8-d5", "neither SAN nor coordinate notation"),
+            ("d8=Q", "no legal move matches the SAN"),
+            ("exd5", "no legal move matches the SAN"),
+            ("d8d1", "a Queen can't move from d8 to d1"),
+            ("e2e4", "no piece to move on e2"),
+            ("Ke6", "no legal move matches the SAN"),
+            ("\u{fe0f}e5", "neither SAN nor coordinate notation"),
+        ] {
+            let err = gm.submit_move_text(text).unwrap_err();
+            assert_eq!(expected, err.to_string(), "{:?}", text);
+        }
+        assert_eq!(fen, gm.to_fen());
+    }
+
+    #[test]
+    fn test_parse_san() {
+        let gm = GameManager::from_moves(&[
+            "e4", "e5", "Nf3", "Nc6", "Bb5", "a6", "Ba4", "Nf6", "O-O", "Be7", "Re1", "b5", "Bb3",
+            "d6", "c3", "O-O", "h3",
+        ])
+        .unwrap();
+        assert_eq!(
+            "r1bq1rk1/2p1bppp/p1np1n2/1p2p3/4P3/1BP2N1P/PP1P1PP1/RNBQR1K1 b - - 0 9",
+            gm.to_fen()
+        );
+        assert_eq!(Err(SanErr::Illegal), gm.parse_san("Bf8"));
+        assert_eq!(Err(SanErr::Syntax), gm.parse_san("Zz9"));
+        assert_eq!(Ok(Move::new(Square::C6, Square::B8)), gm.parse_san("Nb8"));
+        assert_eq!(
+            Ok(Move::new(Square::F6, Square::D7)),
+            gm.parse_san("Nfd7!?")
+        );
+
+        let gm = GameManager::from_moves(&["Nf3", "Nf6", "d3", "d6"]).unwrap();
+        assert_eq!(Err(SanErr::Ambiguous), gm.parse_san("Nd2"));
+        assert_eq!(Ok(Move::new(Square::B1, Square::D2)), gm.parse_san("Nbd2"));
+        assert_eq!(Ok(Move::new(Square::F3, Square::D2)), gm.parse_san("N3d2"));
+        assert_eq!(Ok(Move::new(Square::B1, Square::D2)), gm.parse_san("Nb1d2"));
+
+        assert_eq!(
+            Err((2, SanErr::Illegal)),
+            GameManager::from_moves(&["e4", "e5", "Ke3"]).map(|gm| gm.to_fen())
+        );
+        assert_eq!(
+            Err((1, SanErr::Syntax)),
+            GameManager::from_moves(&["e4", "Nf"]).map(|gm| gm.to_fen())
+        );
+
+        let gm = GameManager {
+            whites: [
+                Piece::new(Player::White, Kind::King, 0, 4),
+                Piece::new(Player::White, Kind::Pawn, 6, 0),
+            ]
+            .into(),
+            blacks: [Piece::new(Player::Black, Kind::King, 7, 7)].into(),
+            ..GameManager::empty()
+        };
+        let promotion = Move::with_promotion(Square::A7, Square::A8, Kind::Knight);
+        assert_eq!(Ok(promotion), gm.parse_san("a8=N"));
+        assert_eq!(Ok(promotion), gm.parse_san("a8N"));
+        assert_eq!(Err(SanErr::Illegal), gm.parse_san("a8"));
+        assert_eq!(Err(SanErr::Illegal), gm.parse_san("O-O"));
+    }
+}
@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+
+use crate::{
+    game_manager::GameManager,
+    highlight::MoveHighlights,
+    moves::Move,
+    piece::{BoardSize, Player, Position},
+};
+
+// Cell of the board as drawn on screen, counted from the top left corner
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ScreenSquare {
+    pub x: u8,
+    pub y: u8,
+}
+
+// `MoveHighlights` in screen cells
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScreenHighlights {
+    pub from: ScreenSquare,
+    pub to: ScreenSquare,
+    pub rook: Option<(ScreenSquare, ScreenSquare)>,
+    pub checked_king: Option<ScreenSquare>,
+    pub checkers: Vec<ScreenSquare>,
+}
+
+// Board seen by one side, its pieces at the bottom: a1 in the bottom left corner for White,
+// h8 there for Black. Clients convert with it both ways instead of doing the flip themselves.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Perspective {
+    player: Player,
+    size: BoardSize,
+}
+
+impl Perspective {
+    pub fn new(player: Player, size: BoardSize) -> Self {
+        Self { player, size }
+    }
+
+    pub fn player(&self) -> Player {
+        self.player
+    }
+
+    pub fn to_screen(&self, pos: impl Into<Position>) -> ScreenSquare {
+        let pos = pos.into();
+        let (rows, columns) = (self.size.rows(), self.size.columns());
+        match self.player {
+            Player::White => ScreenSquare {
+                x: pos.column,
+                y: rows - 1 - pos.row,
+            },
+            Player::Black => ScreenSquare {
+                x: columns - 1 - pos.column,
+                y: pos.row,
+            },
+        }
+    }
+
+    // `None` outside of the board
+    pub fn to_board(&self, square: ScreenSquare) -> Option<Position> {
+        let (rows, columns) = (self.size.rows(), self.size.columns());
+        if square.x >= columns || square.y >= rows {
+            return None;
+        }
+        Some(match self.player {
+            Player::White => Position::new(rows - 1 - square.y, square.x),
+            Player::Black => Position::new(square.y, columns - 1 - square.x),
+        })
+    }
+
+    // Square under a point of a board drawn with `square_size` pixels per square, e.g. a click
+    pub fn at_pixel(&self, x: u32, y: u32, square_size: u32) -> Option<Position> {
+        let cell = |v: u32| u8::try_from(v / square_size.max(1)).ok();
+        self.to_board(ScreenSquare {
+            x: cell(x)?,
+            y: cell(y)?,
+        })
+    }
+
+    pub fn squares_to_screen<P: Into<Position>>(
+        &self,
+        squares: impl IntoIterator<Item = P>,
+    ) -> Vec<ScreenSquare> {
+        squares.into_iter().map(|pos| self.to_screen(pos)).collect()
+    }
+
+    // Start and end cells of every move, e.g. to draw arrows
+    pub fn moves_to_screen<'a>(
+        &self,
+        moves: impl IntoIterator<Item = &'a Move>,
+    ) -> Vec<(ScreenSquare, ScreenSquare)> {
+        moves
+            .into_iter()
+            .map(|mv| (self.to_screen(mv.from), self.to_screen(mv.to)))
+            .collect()
+    }
+
+    pub fn highlights_to_screen(&self, highlights: &MoveHighlights) -> ScreenHighlights {
+        ScreenHighlights {
+            from: self.to_screen(highlights.from),
+            to: self.to_screen(highlights.to),
+            rook: highlights
+                .rook
+                .map(|(from, to)| (self.to_screen(from), self.to_screen(to))),
+            checked_king: highlights.checked_king.map(|pos| self.to_screen(pos)),
+            checkers: self.squares_to_screen(highlights.checkers.iter().copied()),
+        }
+    }
+
+    // `legal_targets_map` in screen cells, the target cells of each piece's cell
+    pub fn targets_to_screen(
+        &self,
+        targets: &HashMap<Position, Vec<Move>>,
+    ) -> HashMap<ScreenSquare, Vec<ScreenSquare>> {
+        targets
+            .iter()
+            .map(|(from, moves)| {
+                let mut cells: Vec<_> = moves.iter().map(|mv| self.to_screen(mv.to)).collect();
+                // Promotions share their target
+                cells.dedup();
+                (self.to_screen(*from), cells)
+            })
+            .collect()
+    }
+}
+
+impl GameManager {
+    pub fn perspective(&self, player: Player) -> Perspective {
+        Perspective::new(player, self.board_size())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Perspective, ScreenSquare};
+    use crate::{
+        game_manager::GameManager,
+        moves::Move,
+        piece::{BoardSize, Player, Position},
+        square::Square,
+    };
+
+    #[test]
+    fn test_perspective() {
+        let white = Perspective::new(Player::White, BoardSize::STANDARD);
+        let black = Perspective::new(Player::Black, BoardSize::STANDARD);
+        assert_eq!(ScreenSquare { x: 0, y: 7 }, white.to_screen(Square::A1));
+        assert_eq!(ScreenSquare { x: 7, y: 0 }, black.to_screen(Square::A1));
+        assert_eq!(ScreenSquare { x: 3, y: 3 }, black.to_screen(Square::E4));
+        for pos in [Square::A1, Square::E4, Square::H7] {
+            let pos = Position::from(pos);
+            assert_eq!(Some(pos), white.to_board(white.to_screen(pos)));
+            assert_eq!(Some(pos), black.to_board(black.to_screen(pos)));
+        }
+        assert_eq!(None, white.to_board(ScreenSquare { x: 8, y: 0 }));
+        // Clicks on a board of 50 pixel squares
+        assert_eq!(
+            Some(Position::from(Square::H8)),
+            black.at_pixel(10, 390, 50)
+        );
+        assert_eq!(None, white.at_pixel(10, 400, 50));
+
+        let mut gm = GameManager::from_fen("4k3/8/8/8/8/8/8/4K2R w K - 0 1").unwrap();
+        gm.play(&Move::new(Square::E1, Square::G1)).unwrap();
+        let highlights = gm
+            .perspective(Player::Black)
+            .highlights_to_screen(&gm.last_move_highlights().unwrap());
+        assert_eq!(ScreenSquare { x: 3, y: 0 }, highlights.from);
+        assert_eq!(ScreenSquare { x: 1, y: 0 }, highlights.to);
+        assert_eq!(
+            Some((ScreenSquare { x: 0, y: 0 }, ScreenSquare { x: 2, y: 0 })),
+            highlights.rook
+        );
+
+        // The four promotions end on a single cell
+        let gm = GameManager::from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let targets = white.targets_to_screen(&gm.legal_targets_map());
+        assert_eq!(
+            vec![ScreenSquare { x: 0, y: 0 }],
+            targets[&ScreenSquare { x: 0, y: 1 }]
+        );
+        // Minichess boards flip around their own size
+        let small = Perspective::new(Player::Black, BoardSize::new(5, 5).unwrap());
+        assert_eq!(ScreenSquare { x: 4, y: 0 }, small.to_screen(Square::A1));
+        assert_eq!(
+            vec![(ScreenSquare { x: 4, y: 1 }, ScreenSquare { x: 4, y: 3 })],
+            small.moves_to_screen(&[Move::new(Square::A2, Square::A4)])
+        );
+    }
+}
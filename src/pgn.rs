@@ -0,0 +1,705 @@
+use std::{error::Error, fmt, time::Duration};
+
+use crate::{fen::START_FEN, game_manager::GameManager};
+
+const SEVEN_TAG_ROSTER: [&str; 7] = ["Event", "Site", "Date", "Round", "White", "Black", "Result"];
+const MAX_LINE_LENGTH: usize = 80;
+const RESULTS: [&str; 4] = ["1-0", "0-1", "1/2-1/2", "*"];
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum PgnErr {
+    UnterminatedTag,
+    UnterminatedComment,
+    UnbalancedVariation,
+    UnexpectedToken(String),
+}
+
+impl fmt::Display for PgnErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PgnErr::UnterminatedTag => write!(f, "unterminated PGN tag"),
+            PgnErr::UnterminatedComment => write!(f, "unterminated PGN comment"),
+            PgnErr::UnbalancedVariation => write!(f, "unbalanced PGN variation"),
+            PgnErr::UnexpectedToken(token) => write!(f, "unexpected PGN token {:?}", token),
+        }
+    }
+}
+
+impl Error for PgnErr {}
+
+// A parsed game keeping everything needed to write it back: tag order, comments, NAGs and
+// variations.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PgnGame {
+    pub tags: Vec<(String, String)>,
+    pub mainline: PgnLine,
+    pub result: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PgnLine {
+    // Comments written before the first move of the line
+    pub comments: Vec<String>,
+    pub moves: Vec<PgnMove>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PgnMove {
+    pub san: String,
+    // Move suffix annotation as written, e.g. "!?"
+    pub suffix: Option<String>,
+    pub nags: Vec<u8>,
+    pub comments: Vec<String>,
+    // Alternatives to this move
+    pub variations: Vec<PgnLine>,
+}
+
+// Evaluation of a `[%eval]` command, from White's point of view
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PgnEval {
+    Centipawns(i32),
+    // Moves to mate, negative when Black mates
+    Mate(i32),
+}
+
+impl fmt::Display for PgnEval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PgnEval::Centipawns(cp) => {
+                let sign = if *cp < 0 { "-" } else { "" };
+                write!(f, "{}{}.{:02}", sign, cp.abs() / 100, cp.abs() % 100)
+            }
+            PgnEval::Mate(moves) => write!(f, "#{}", moves),
+        }
+    }
+}
+
+impl PgnMove {
+    pub fn new(san: &str) -> Self {
+        Self {
+            san: san.to_string(),
+            suffix: None,
+            nags: vec![],
+            comments: vec![],
+            variations: vec![],
+        }
+    }
+
+    // Time left to the player after the move, from a `[%clk h:mm:ss]` command
+    pub fn clock(&self) -> Option<Duration> {
+        parse_duration(self.command("clk")?)
+    }
+
+    pub fn set_clock(&mut self, remaining: Duration) {
+        self.set_command("clk", &format_duration(remaining));
+    }
+
+    // Time spent on the move, from a `[%emt h:mm:ss]` command
+    pub fn think_time(&self) -> Option<Duration> {
+        parse_duration(self.command("emt")?)
+    }
+
+    // Evaluation of the position after the move, from a `[%eval]` command such as
+    // `[%eval -0.35]` or `[%eval #4]`. A search depth after a comma is ignored.
+    pub fn eval(&self) -> Option<PgnEval> {
+        let value = self.command("eval")?;
+        let value = value.split(',').next().unwrap_or(value);
+        if let Some(moves) = value.strip_prefix('#') {
+            return moves.parse().ok().map(PgnEval::Mate);
+        }
+        let (units, hundredths) = value.split_once('.').unwrap_or((value, "0"));
+        let negative = units.starts_with('-');
+        let units: i32 = units.trim_start_matches(['-', '+']).parse().ok()?;
+        if hundredths.is_empty() || !hundredths.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let hundredths: i32 = format!("{:0<2}", &hundredths[..hundredths.len().min(2)])
+            .parse()
+            .ok()?;
+        let cp = units.checked_mul(100)?.checked_add(hundredths)?;
+        Some(PgnEval::Centipawns(if negative { -cp } else { cp }))
+    }
+
+    pub fn set_eval(&mut self, eval: PgnEval) {
+        self.set_command("eval", &eval.to_string());
+    }
+
+    // Value of the `[%name value]` command embedded in the comments
+    pub fn command(&self, name: &str) -> Option<&str> {
+        let prefix = format!("[%{} ", name);
+        self.comments.iter().find_map(|comment| {
+            let start = comment.find(&prefix)? + prefix.len();
+            let len = comment[start..].find(']')?;
+            Some(comment[start..start + len].trim())
+        })
+    }
+
+    // Replaces the value of a command, a new one goes to the last comment when it only
+    // holds commands, e.g. `{[%clk 0:04:59] [%eval 0.21]}`.
+    pub fn set_command(&mut self, name: &str, value: &str) {
+        let prefix = format!("[%{} ", name);
+        for comment in self.comments.iter_mut() {
+            if let Some(start) = comment.find(&prefix) {
+                let start = start + prefix.len();
+                if let Some(len) = comment[start..].find(']') {
+                    comment.replace_range(start..start + len, value);
+                    return;
+                }
+            }
+        }
+        let command = format!("{}{}]", prefix, value);
+        match self.comments.last_mut() {
+            Some(comment) if comment.trim().starts_with("[%") && comment.trim().ends_with(']') => {
+                comment.truncate(comment.trim_end().len());
+                comment.push(' ');
+                comment.push_str(&command);
+            }
+            _ => self.comments.push(command),
+        }
+    }
+}
+
+impl PgnGame {
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    // Parses the first game of `pgn`
+    pub fn parse(pgn: &str) -> Result<Self, PgnErr> {
+        let mut game = PgnGame::default();
+        // Lines being read, the last one is the innermost variation
+        let mut lines = vec![PgnLine::default()];
+        let mut chars = pgn.chars().peekable();
+        while let Some(c) = chars.next() {
+            let line = lines.last_mut().expect("the mainline is never popped");
+            match c {
+                c if c.is_whitespace() => (),
+                '[' => {
+                    game.tags.push(read_tag(&mut chars)?);
+                }
+                '{' => {
+                    let mut comment = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => comment.push(c),
+                            None => return Err(PgnErr::UnterminatedComment),
+                        }
+                    }
+                    match line.moves.last_mut() {
+                        Some(mv) => mv.comments.push(comment),
+                        None => line.comments.push(comment),
+                    }
+                }
+                ';' => {
+                    let comment: String = chars.by_ref().take_while(|c| *c != '\n').collect();
+                    match line.moves.last_mut() {
+                        Some(mv) => mv.comments.push(comment),
+                        None => line.comments.push(comment),
+                    }
+                }
+                '(' => {
+                    if line.moves.is_empty() {
+                        return Err(PgnErr::UnbalancedVariation);
+                    }
+                    lines.push(PgnLine::default());
+                }
+                ')' => {
+                    if lines.len() == 1 {
+                        return Err(PgnErr::UnbalancedVariation);
+                    }
+                    let variation = lines.pop().expect("checked above");
+                    let parent = lines.last_mut().expect("checked above");
+                    let mv = parent.moves.last_mut().expect("checked when opened");
+                    mv.variations.push(variation);
+                }
+                _ => {
+                    let mut token = c.to_string();
+                    while let Some(c) =
+                        chars.next_if(|c| !c.is_whitespace() && !"{}()[];".contains(*c))
+                    {
+                        token.push(c);
+                    }
+                    if RESULTS.contains(&token.as_str()) {
+                        if lines.len() > 1 {
+                            return Err(PgnErr::UnbalancedVariation);
+                        }
+                        game.result = token;
+                        break;
+                    }
+                    read_move_token(line, &token)?;
+                }
+            }
+        }
+        if lines.len() > 1 {
+            return Err(PgnErr::UnbalancedVariation);
+        }
+        game.mainline = lines.pop().expect("the mainline is never popped");
+        if game.result.is_empty() {
+            game.result = "*".to_string();
+        }
+        Ok(game)
+    }
+
+    pub fn to_pgn(&self) -> String {
+        let mut pgn = String::new();
+        for (name, value) in &self.tags {
+            pgn.push_str(&format!("[{} \"{}\"]\n", name, escape(value)));
+        }
+        pgn.push('\n');
+        let mut tokens = vec![];
+        write_line(&self.mainline, self.first_ply(), &mut tokens);
+        tokens.push(self.result.clone());
+
+        let mut line_length = 0;
+        let mut previous = "";
+        for token in &tokens {
+            let spaced = previous != "(" && token != ")";
+            if line_length > 0 && spaced && line_length + 1 + token.len() > MAX_LINE_LENGTH {
+                pgn.push('\n');
+                line_length = 0;
+            } else if line_length > 0 && spaced {
+                pgn.push(' ');
+                line_length += 1;
+            }
+            line_length += token.len();
+            pgn.push_str(token);
+            previous = token;
+        }
+        pgn.push('\n');
+        pgn
+    }
+
+    // Half moves played before the first move, read from the FEN tag
+    fn first_ply(&self) -> u32 {
+        let fen = match self.tag("FEN") {
+            Some(fen) => fen,
+            None => return 0,
+        };
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        let fullmove: u32 = fields.get(5).and_then(|n| n.parse().ok()).unwrap_or(1);
+        let black = fields.get(1) == Some(&"b");
+        fullmove.saturating_sub(1) * 2 + u32::from(black)
+    }
+}
+
+// Reads a tag pair up to its closing bracket, the opening one being already read. The value
+// is read up to its closing quote so it may hold brackets.
+fn read_tag(chars: &mut impl Iterator<Item = char>) -> Result<(String, String), PgnErr> {
+    let mut chars = chars.skip_while(|c| c.is_whitespace());
+    let name: String = chars.by_ref().take_while(|c| !c.is_whitespace()).collect();
+    if name.is_empty()
+        || name.contains(['"', ']'])
+        || chars.find(|c| !c.is_whitespace()) != Some('"')
+    {
+        return Err(PgnErr::UnterminatedTag);
+    }
+    let mut value = String::new();
+    loop {
+        match chars.next() {
+            Some('"') => break,
+            Some('\\') => value.extend(chars.next()),
+            Some(c) => value.push(c),
+            None => return Err(PgnErr::UnterminatedTag),
+        }
+    }
+    match chars.find(|c| !c.is_whitespace()) {
+        Some(']') => Ok((name, value)),
+        _ => Err(PgnErr::UnterminatedTag),
+    }
+}
+
+// Handles move numbers, NAGs and moves with their suffix annotation
+fn read_move_token(line: &mut PgnLine, token: &str) -> Result<(), PgnErr> {
+    if let Some(nag) = token.strip_prefix('$') {
+        let nag = nag
+            .parse()
+            .map_err(|_| PgnErr::UnexpectedToken(token.to_string()))?;
+        return match line.moves.last_mut() {
+            Some(mv) => {
+                mv.nags.push(nag);
+                Ok(())
+            }
+            None => Err(PgnErr::UnexpectedToken(token.to_string())),
+        };
+    }
+    // Move numbers may be glued to the move, as in "12.e4"
+    let san = token.trim_start_matches(|c: char| c.is_ascii_digit() || c == '.');
+    if san.is_empty() {
+        return Ok(());
+    } else if san.len() != token.len() && !token[..token.len() - san.len()].ends_with('.') {
+        return Err(PgnErr::UnexpectedToken(token.to_string()));
+    }
+    let annotation = san.trim_end_matches(['!', '?']);
+    if annotation.is_empty() || !annotation.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return Err(PgnErr::UnexpectedToken(token.to_string()));
+    }
+    let mut mv = PgnMove::new(annotation);
+    if annotation.len() != san.len() {
+        mv.suffix = Some(san[annotation.len()..].to_string());
+    }
+    line.moves.push(mv);
+    Ok(())
+}
+
+fn write_line(line: &PgnLine, first_ply: u32, tokens: &mut Vec<String>) {
+    for comment in &line.comments {
+        tokens.push(format!("{{{}}}", comment));
+    }
+    let mut need_number = true;
+    for (i, mv) in line.moves.iter().enumerate() {
+        let ply = first_ply + i as u32;
+        if ply.is_multiple_of(2) {
+            tokens.push(format!("{}.", ply / 2 + 1));
+        } else if need_number {
+            tokens.push(format!("{}...", ply / 2 + 1));
+        }
+        tokens.push(format!("{}{}", mv.san, mv.suffix.as_deref().unwrap_or("")));
+        for nag in &mv.nags {
+            tokens.push(format!("${}", nag));
+        }
+        for comment in &mv.comments {
+            tokens.push(format!("{{{}}}", comment));
+        }
+        for variation in &mv.variations {
+            tokens.push("(".to_string());
+            write_line(variation, ply, tokens);
+            tokens.push(")".to_string());
+        }
+        // Black moves following a comment or a variation repeat their number
+        need_number = !mv.comments.is_empty() || !mv.variations.is_empty();
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PgnWriter {
+    tags: Vec<(String, String)>,
+    move_times: bool,
+}
+
+impl PgnWriter {
+    // Starts with the Seven Tag Roster filled with unknown values
+    pub fn new() -> Self {
+        let tags = SEVEN_TAG_ROSTER
+            .iter()
+            .map(|name| {
+                let value = match *name {
+                    "Date" => "????.??.??",
+                    "Result" => "*",
+                    _ => "?",
+                };
+                (name.to_string(), value.to_string())
+            })
+            .collect();
+        Self {
+            tags,
+            move_times: false,
+        }
+    }
+
+    // Sets a tag, tags outside of the roster are written in insertion order after it
+    pub fn tag(mut self, name: &str, value: &str) -> Self {
+        match self.tags.iter_mut().find(|(n, _)| n == name) {
+            Some(tag) => tag.1 = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+        self
+    }
+
+    // Writes the think time of every move in an `[%emt h:mm:ss]` command, the time left on
+    // the clock always goes in a `[%clk]` one
+    pub fn move_times(mut self, enabled: bool) -> Self {
+        self.move_times = enabled;
+        self
+    }
+
+    pub fn write(&self, gm: &GameManager) -> String {
+        self.game(gm).to_pgn()
+    }
+
+    // Same game as `write` but kept as a tree that can be edited before being written
+    pub fn game(&self, gm: &GameManager) -> PgnGame {
+        let start = gm.initial_position();
+        let fen = start.to_fen();
+        let status = gm.status();
+        let result = match self.tags.iter().find(|(n, _)| n == "Result") {
+            Some((_, value)) if value != "*" => value.clone(),
+            _ => status.result().to_string(),
+        };
+        let mut tags = self.tags.clone();
+        for (name, value) in tags.iter_mut() {
+            if name == "Result" {
+                *value = result.clone();
+            }
+        }
+        if status.is_over() && !tags.iter().any(|(n, _)| n == "Termination") {
+            tags.push(("Termination".to_string(), status.termination().to_string()));
+        }
+        if start.is_chess960() {
+            tags.push(("Variant".to_string(), "Chess960".to_string()));
+        }
+        if fen != START_FEN || start.is_chess960() {
+            tags.push(("SetUp".to_string(), "1".to_string()));
+            tags.push(("FEN".to_string(), fen));
+        }
+        let mut mainline = PgnLine::default();
+        for record in gm.history() {
+            let mut mv = PgnMove::new(&record.san);
+            #[cfg(feature = "clock")]
+            if let Some(remaining) = record.clock {
+                mv.set_clock(remaining);
+            }
+            if let Some(think_time) = record.think_time.filter(|_| self.move_times) {
+                mv.set_command("emt", &format_duration(think_time));
+            }
+            mainline.moves.push(mv);
+        }
+        PgnGame {
+            tags,
+            mainline,
+            result,
+        }
+    }
+}
+
+impl Default for PgnWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// h:mm:ss as in the embedded commands of the comments, rounded down to the second
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+// h:mm:ss with optional fractions of a second, as some servers write them
+fn parse_duration(text: &str) -> Option<Duration> {
+    let mut fields = text.split(':');
+    let (hours, minutes, seconds) = (fields.next()?, fields.next()?, fields.next()?);
+    if fields.next().is_some() {
+        return None;
+    }
+    let (seconds, fraction) = seconds.split_once('.').unwrap_or((seconds, ""));
+    let digits = |field: &str| !field.is_empty() && field.bytes().all(|b| b.is_ascii_digit());
+    if ![hours, minutes, seconds].into_iter().all(digits)
+        || !(fraction.is_empty() || digits(fraction))
+    {
+        return None;
+    }
+    // Absurd values are rejected instead of overflowing
+    let secs = hours
+        .parse::<u64>()
+        .ok()?
+        .checked_mul(3600)?
+        .checked_add(minutes.parse::<u64>().ok()?.checked_mul(60)?)?
+        .checked_add(seconds.parse::<u64>().ok()?)?;
+    let millis: u64 = format!("{:0<3}", &fraction[..fraction.len().min(3)])
+        .parse()
+        .ok()?;
+    Duration::from_secs(secs).checked_add(Duration::from_millis(millis))
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::{PgnErr, PgnEval, PgnGame, PgnMove, PgnWriter};
+    #[cfg(feature = "clock")]
+    use crate::clock::{Clock, TimeControl};
+    use crate::{
+        game_manager::GameManager,
+        moves::Move,
+        piece::{Kind, Piece, Player},
+        square::Square,
+    };
+
+    #[test]
+    fn test_pgn_export() {
+        let mut gm = GameManager::new();
+        let moves = [
+            (Square::F2, Square::F3),
+            (Square::E7, Square::E5),
+            (Square::G2, Square::G4),
+            (Square::D8, Square::H4),
+        ];
+        for (from, to) in moves {
+            gm.play(&Move::new(from, to)).unwrap();
+        }
+        let pgn = PgnWriter::new()
+            .tag("White", "Fool")
+            .tag("Black", "Quick \"Mate\"")
+            .tag("Annotator", "libchess")
+            .write(&gm);
+        let expected = "[Event \"?\"]\n\
+            [Site \"?\"]\n\
+            [Date \"????.??.??\"]\n\
+            [Round \"?\"]\n\
+            [White \"Fool\"]\n\
+            [Black \"Quick \\\"Mate\\\"\"]\n\
+            [Result \"0-1\"]\n\
+            [Annotator \"libchess\"]\n\
+            [Termination \"normal\"]\n\
+            \n\
+            1. f3 e5 2. g4 Qh4# 0-1\n";
+        assert_eq!(expected, pgn);
+    }
+
+    #[test]
+    fn test_pgn_export_from_position() {
+        let mut gm = GameManager {
+            whites: [Piece::new(Player::White, Kind::King, 0, 4)].into(),
+            blacks: [
+                Piece::new(Player::Black, Kind::King, 7, 4),
+                Piece::new(Player::Black, Kind::Rook, 7, 0),
+            ]
+            .into(),
+            turn: Player::Black,
+            fullmove_number: 40,
+            ..GameManager::empty()
+        };
+        gm.play(&Move::new(Square::A8, Square::A2)).unwrap();
+        gm.play(&Move::new(Square::E1, Square::F1)).unwrap();
+        let pgn = PgnWriter::new().tag("Result", "0-1").write(&gm);
+        assert!(pgn.contains("[SetUp \"1\"]\n[FEN \"r3k3/8/8/8/8/8/8/4K3 b - - 0 40\"]\n"));
+        assert!(pgn.ends_with("\n40... Ra2 41. Kf1 0-1\n"));
+    }
+
+    #[test]
+    fn test_pgn_move_times() {
+        let mut gm = GameManager::new();
+        let start = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        gm.play_at(&Move::new(Square::E2, Square::E4), start)
+            .unwrap();
+        gm.play_at(
+            &Move::new(Square::E7, Square::E5),
+            start + Duration::from_millis(3_723_900),
+        )
+        .unwrap();
+        let pgn = PgnWriter::new().move_times(true).write(&gm);
+        assert!(pgn.ends_with("\n1. e4 e5 {[%emt 1:02:03]} *\n"));
+        assert!(PgnWriter::new().write(&gm).ends_with("\n1. e4 e5 *\n"));
+    }
+
+    #[test]
+    fn test_pgn_embedded_commands() {
+        let pgn = "1. e4 { [%eval 0.17] [%clk 0:03:00] } 1... e5 { [%clk 0:02:59.5] } \
+            2. Qh5 { Bold [%eval #-3,20] } *";
+        let game = PgnGame::parse(pgn).unwrap();
+        let moves = &game.mainline.moves;
+        assert_eq!(Some(PgnEval::Centipawns(17)), moves[0].eval());
+        assert_eq!(Some(Duration::from_secs(180)), moves[0].clock());
+        assert_eq!(Some(Duration::from_millis(179_500)), moves[1].clock());
+        assert_eq!(None, moves[1].eval());
+        assert_eq!(Some(PgnEval::Mate(-3)), moves[2].eval());
+
+        let mut mv = moves[2].clone();
+        mv.set_eval(PgnEval::Centipawns(-150));
+        mv.set_clock(Duration::from_secs(3_725));
+        assert_eq!(vec![" Bold [%eval -1.50] ", "[%clk 1:02:05]"], mv.comments);
+        let mut mv = moves[1].clone();
+        mv.set_eval(PgnEval::Centipawns(5));
+        assert_eq!(vec![" [%clk 0:02:59.5] [%eval 0.05]"], mv.comments);
+        assert_eq!(Some(PgnEval::Centipawns(5)), mv.eval());
+
+        // Times too long for a duration are unreadable, not a panic
+        let pgn = "1. e4 { [%clk 99999999999999999:00:00] [%emt 0:00:00.250] } \
+            1... e5 { [%emt 0:18446744073709551615:00] } *";
+        let game = PgnGame::parse(pgn).unwrap();
+        let moves = &game.mainline.moves;
+        assert_eq!(None, moves[0].clock());
+        assert_eq!(Some(Duration::from_millis(250)), moves[0].think_time());
+        assert_eq!(None, moves[1].think_time());
+    }
+
+    #[cfg(feature = "clock")]
+    #[test]
+    fn test_pgn_clock_export() {
+        let mut gm = GameManager::new();
+        gm.clock = Some(Clock::new(TimeControl::fischer(
+            Duration::from_secs(180),
+            Duration::from_secs(2),
+        )));
+        gm.play(&Move::new(Square::E2, Square::E4)).unwrap();
+        let game = PgnWriter::new().game(&gm);
+        assert_eq!(vec!["[%clk 0:03:02]"], game.mainline.moves[0].comments);
+        let game = PgnGame::parse(&game.to_pgn()).unwrap();
+        assert_eq!(
+            Some(Duration::from_secs(182)),
+            game.mainline.moves[0].clock()
+        );
+    }
+
+    #[test]
+    fn test_pgn_round_trip() {
+        let pgn = "[Event \"Casual \\\"game\\\"\"]\n\
+            [White \"A\"]\n\
+            [Black \"B\"]\n\
+            [Opening \"Ruy Lopez\"]\n\
+            [Result \"1-0\"]\n\
+            \n\
+            {Played online} 1. e4 e5 2. Nf3 $1 {Main line} (2. f4!? exf4 (2... d5) 3. Nf3)\n\
+            2... Nc6 3. Bb5 a6?! 1-0\n";
+        let game = PgnGame::parse(pgn).unwrap();
+        assert_eq!(Some("Casual \"game\""), game.tag("Event"));
+        assert_eq!("Opening", game.tags[3].0);
+        assert_eq!("1-0", game.result);
+        assert_eq!(vec!["Played online"], game.mainline.comments);
+        let nf3 = &game.mainline.moves[2];
+        assert_eq!("Nf3", nf3.san);
+        assert_eq!(vec![1], nf3.nags);
+        assert_eq!(vec!["Main line"], nf3.comments);
+        assert_eq!(Some("!?"), nf3.variations[0].moves[0].suffix.as_deref());
+        assert_eq!(1, nf3.variations[0].moves[1].variations.len());
+        assert_eq!(pgn, game.to_pgn());
+
+        // Editing a move keeps every other annotation
+        let mut edited = game.clone();
+        edited.mainline.moves[4] = PgnMove::new("Bc4");
+        let reparsed = PgnGame::parse(&edited.to_pgn()).unwrap();
+        assert_eq!(edited, reparsed);
+        assert_eq!(game.mainline.moves[2], reparsed.mainline.moves[2]);
+    }
+
+    #[test]
+    fn test_pgn_tag_with_brackets() {
+        let pgn = "[Event \"Cup [A]\"]\n[Site \"\\\"]\\\" hall\" ]\n\n1. e4 *\n";
+        let game = PgnGame::parse(pgn).unwrap();
+        assert_eq!(Some("Cup [A]"), game.tag("Event"));
+        assert_eq!(Some("\"]\" hall"), game.tag("Site"));
+        assert_eq!("e4", game.mainline.moves[0].san);
+        assert_eq!(
+            Err(PgnErr::UnterminatedTag),
+            PgnGame::parse("[Event \"Cup [A]] 1. e4 *")
+        );
+    }
+
+    #[test]
+    fn test_pgn_parse_errors() {
+        assert_eq!(
+            Err(PgnErr::UnterminatedComment),
+            PgnGame::parse("1. e4 {oops")
+        );
+        assert_eq!(
+            Err(PgnErr::UnbalancedVariation),
+            PgnGame::parse("1. e4 (1. d4 *")
+        );
+        assert_eq!(
+            Err(PgnErr::UnbalancedVariation),
+            PgnGame::parse("1. e4 ) *")
+        );
+        assert_eq!(Err(PgnErr::UnterminatedTag), PgnGame::parse("[Event]"));
+        assert_eq!(
+            Err(PgnErr::UnexpectedToken("$x".to_string())),
+            PgnGame::parse("1. e4 $x *")
+        );
+    }
+}
@@ -1,13 +1,107 @@
-use std::cmp;
+use std::{
+    fmt,
+    ops::{Deref, DerefMut},
+    slice,
+    str::FromStr,
+};
 
-#[derive(Debug, PartialEq, Eq)]
+use crate::square::{File, Rank, Square, SquareColor};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Player {
+    #[default]
     White,
     Black,
 }
+
+impl Player {
+    pub fn opponent(&self) -> Player {
+        match self {
+            Player::Black => Player::White,
+            Player::White => Player::Black,
+        }
+    }
+
+    // Slot of the player in arrays holding something for each side, White first
+    pub fn index(&self) -> usize {
+        match self {
+            Player::White => 0,
+            Player::Black => 1,
+        }
+    }
+}
 pub const MAX_ROW: u8 = 8;
 pub const MAX_COLUMN: u8 = MAX_ROW;
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+// A side never has more pieces than it starts with, promotions only replace pawns
+pub const MAX_PIECES: usize = 16;
+// Rows and columns of the board, at most `MAX_ROW` by `MAX_COLUMN`. A smaller board is the
+// lower left corner of a standard one, its squares keep their names and indexes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BoardSize {
+    rows: u8,
+    columns: u8,
+}
+
+impl BoardSize {
+    pub const STANDARD: Self = Self {
+        rows: MAX_ROW,
+        columns: MAX_COLUMN,
+    };
+    pub const MINICHESS: Self = Self {
+        rows: 5,
+        columns: 5,
+    };
+
+    pub fn new(rows: u8, columns: u8) -> Option<Self> {
+        ((1..=MAX_ROW).contains(&rows) && (1..=MAX_COLUMN).contains(&columns))
+            .then_some(Self { rows, columns })
+    }
+
+    pub fn rows(&self) -> u8 {
+        self.rows
+    }
+
+    pub fn columns(&self) -> u8 {
+        self.columns
+    }
+
+    pub fn contains(&self, pos: &Position) -> bool {
+        pos.row < self.rows && pos.column < self.columns
+    }
+
+    // Row the pieces of `player` start on
+    pub fn back_row(&self, player: Player) -> u8 {
+        match player {
+            Player::White => 0,
+            Player::Black => self.rows - 1,
+        }
+    }
+
+    pub fn promotion_row(&self, player: Player) -> u8 {
+        self.back_row(player.opponent())
+    }
+
+    // Row the pawns of `player` step twice from, only on boards of 6 rows or more so that 5x5
+    // minichess pawns step once
+    pub fn double_step_row(&self, player: Player) -> Option<u8> {
+        let row = match player {
+            Player::White => 1,
+            Player::Black => self.rows - 2,
+        };
+        (self.rows >= 6).then_some(row)
+    }
+}
+
+impl Default for BoardSize {
+    fn default() -> Self {
+        Self::STANDARD
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Kind {
     Queen,
     King,
@@ -17,6 +111,43 @@ pub enum Kind {
     Rook,
 }
 
+impl Kind {
+    // Uppercase letter used by SAN and FEN, 'P' for pawns
+    pub fn to_char(&self) -> char {
+        match self {
+            Kind::Queen => 'Q',
+            Kind::King => 'K',
+            Kind::Pawn => 'P',
+            Kind::Bishop => 'B',
+            Kind::Knight => 'N',
+            Kind::Rook => 'R',
+        }
+    }
+
+    pub fn from_char(c: char) -> Option<Self> {
+        match c.to_ascii_uppercase() {
+            'Q' => Some(Kind::Queen),
+            'K' => Some(Kind::King),
+            'P' => Some(Kind::Pawn),
+            'B' => Some(Kind::Bishop),
+            'N' => Some(Kind::Knight),
+            'R' => Some(Kind::Rook),
+            _ => None,
+        }
+    }
+
+    // Usual material count in pawns, the king doesn't count
+    pub fn material_value(&self) -> u32 {
+        match self {
+            Kind::Queen => 9,
+            Kind::King => 0,
+            Kind::Pawn => 1,
+            Kind::Bishop | Kind::Knight => 3,
+            Kind::Rook => 5,
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum KnightDirection {
     UpLeft,
@@ -42,7 +173,9 @@ pub enum Direction {
     Knight(KnightDirection),
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+// Defaults to a1
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Position {
     pub row: u8,
     pub column: u8,
@@ -55,16 +188,91 @@ impl Position {
             column: piece.column,
         }
     }
-    pub fn new(row: u8, column: u8) -> Self {
+    pub const fn new(row: u8, column: u8) -> Self {
         Self { row, column }
     }
+
+    pub fn from_file_rank(file: File, rank: Rank) -> Self {
+        Self::new(rank.index(), file.index())
+    }
+
+    // Square name such as "e4"
+    pub fn from_algebraic(name: &str) -> Option<Self> {
+        let mut chars = name.chars();
+        let file = File::from_char(chars.next()?)?;
+        let rank = Rank::from_char(chars.next()?)?;
+        match chars.next() {
+            Some(_) => None,
+            None => Some(Self::from_file_rank(file, rank)),
+        }
+    }
+
+    pub fn file(&self) -> Option<File> {
+        File::new(self.column)
+    }
+
+    pub fn rank(&self) -> Option<Rank> {
+        Rank::new(self.row)
+    }
+
+    // Flat index from 0 (a1) to 63 (h8), see `Square`. None off the board.
+    pub fn index(&self) -> Option<u8> {
+        Square::try_from(*self).ok().map(|square| square.index())
+    }
+    pub fn color(&self) -> SquareColor {
+        if self.row % 2 == self.column % 2 {
+            SquareColor::Dark
+        } else {
+            SquareColor::Light
+        }
+    }
+
+    // Moves a king needs to go from `self` to `other`
+    pub fn distance(&self, other: &Self) -> u8 {
+        self.row
+            .abs_diff(other.row)
+            .max(self.column.abs_diff(other.column))
+    }
+
+    // Rook moves of one square from `self` to `other`
+    pub fn manhattan_distance(&self, other: &Self) -> u8 {
+        self.row
+            .abs_diff(other.row)
+            .saturating_add(self.column.abs_diff(other.column))
+    }
+
+    pub fn same_rank(&self, other: &Self) -> bool {
+        self.row == other.row
+    }
+
+    pub fn same_file(&self, other: &Self) -> bool {
+        self.column == other.column
+    }
+
+    // Either diagonal going through `self`
+    pub fn same_diagonal(&self, other: &Self) -> bool {
+        self.row.abs_diff(other.row) == self.column.abs_diff(other.column)
+    }
+
+    // Squares strictly between `self` and `other` when they share a rank, a file or a
+    // diagonal, nothing otherwise.
+    pub fn ray_to(&self, other: &Self) -> impl Iterator<Item = Position> {
+        let rows = other.row as i16 - self.row as i16;
+        let columns = other.column as i16 - self.column as i16;
+        let aligned = self.same_rank(other) || self.same_file(other) || self.same_diagonal(other);
+        let steps = if aligned {
+            self.distance(other) as i16
+        } else {
+            0
+        };
+        let (row, column) = (self.row as i16, self.column as i16);
+        let (dr, dc) = (rows.signum(), columns.signum());
+        (1..steps).map(move |i| Position::new((row + dr * i) as u8, (column + dc * i) as u8))
+    }
+
     pub fn get_direction(&self, other: &Self) -> Option<Direction> {
-        let rmax = cmp::max(self.row, other.row);
-        let rmin = cmp::min(self.row, other.row);
-        let cmax = cmp::max(self.column, other.column);
-        let cmin = cmp::min(self.column, other.column);
-        let x = rmax - rmin;
-        let y = cmax - cmin;
+        let x = self.row.abs_diff(other.row);
+        let y = self.column.abs_diff(other.column);
         if self.row == other.row {
             if self.column > other.column {
                 return Some(Direction::Left(y));
@@ -104,8 +312,46 @@ impl Position {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParsePositionErr;
+
+impl fmt::Display for ParsePositionErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid square, expected e.g. \"e4\"")
+    }
+}
+
+impl std::error::Error for ParsePositionErr {}
+
+impl FromStr for Position {
+    type Err = ParsePositionErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_algebraic(s).ok_or(ParsePositionErr)
+    }
+}
+
+impl TryFrom<&str> for Position {
+    type Error = ParsePositionErr;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (File::new(self.column), Rank::new(self.row)) {
+            (Some(file), Some(rank)) => write!(f, "{}{}", file.to_char(), rank.to_char()),
+            _ => write!(f, "({}, {})", self.row, self.column),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Piece {
+    pub player: Player,
     pub kind: Kind,
     pub row: u8,
     pub column: u8,
@@ -115,14 +361,310 @@ impl Piece {
         self.column = column;
         self.row = row;
     }
-    pub fn new(kind: Kind, row: u8, column: u8) -> Self {
-        Self { kind, row, column }
+    pub const fn new(player: Player, kind: Kind, row: u8, column: u8) -> Self {
+        Self {
+            player,
+            kind,
+            row,
+            column,
+        }
+    }
+
+    // FEN letter, uppercase for white and lowercase for black
+    pub fn to_char(&self) -> char {
+        match self.player {
+            Player::White => self.kind.to_char(),
+            Player::Black => self.kind.to_char().to_ascii_lowercase(),
+        }
+    }
+
+    pub fn from_char(c: char, pos: impl Into<Position>) -> Option<Self> {
+        let pos = pos.into();
+        let kind = Kind::from_char(c)?;
+        let player = if c.is_ascii_uppercase() {
+            Player::White
+        } else {
+            Player::Black
+        };
+        Some(Self::new(player, kind, pos.row, pos.column))
+    }
+}
+
+impl fmt::Display for Piece {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_char())
+    }
+}
+
+// Fixed capacity list of the pieces of one side, stored inline so copying a game is cheap
+#[derive(Clone, Copy)]
+pub struct PieceList {
+    pieces: [Piece; MAX_PIECES],
+    len: usize,
+}
+
+impl PieceList {
+    pub const fn new() -> Self {
+        Self {
+            pieces: [Piece::new(Player::White, Kind::Pawn, 0, 0); MAX_PIECES],
+            len: 0,
+        }
+    }
+
+    // Drops the piece when the list is full, `try_push` tells when it happens
+    pub fn push(&mut self, piece: Piece) {
+        let _ = self.try_push(piece);
+    }
+
+    // Gives the piece back when the list is full
+    pub const fn try_push(&mut self, piece: Piece) -> Result<(), Piece> {
+        if self.len == MAX_PIECES {
+            return Err(piece);
+        }
+        self.pieces[self.len] = piece;
+        self.len += 1;
+        Ok(())
+    }
+
+    pub fn swap_remove(&mut self, index: usize) -> Piece {
+        let last = self.len - 1;
+        self.pieces[..self.len].swap(index, last);
+        self.len = last;
+        self.pieces[last]
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl Default for PieceList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deref for PieceList {
+    type Target = [Piece];
+
+    fn deref(&self) -> &[Piece] {
+        &self.pieces[..self.len]
+    }
+}
+
+impl DerefMut for PieceList {
+    fn deref_mut(&mut self) -> &mut [Piece] {
+        &mut self.pieces[..self.len]
+    }
+}
+
+impl fmt::Debug for PieceList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+// Keeps the first `MAX_PIECES` pieces
+impl FromIterator<Piece> for PieceList {
+    fn from_iter<I: IntoIterator<Item = Piece>>(iter: I) -> Self {
+        let mut list = Self::new();
+        for piece in iter.into_iter().take(MAX_PIECES) {
+            list.push(piece);
+        }
+        list
+    }
+}
+
+// Gives the pieces back when there are too many of them
+impl TryFrom<Vec<Piece>> for PieceList {
+    type Error = Vec<Piece>;
+
+    fn try_from(pieces: Vec<Piece>) -> Result<Self, Self::Error> {
+        if pieces.len() > MAX_PIECES {
+            return Err(pieces);
+        }
+        Ok(pieces.into_iter().collect())
+    }
+}
+
+// Arrays too long for a side don't compile
+impl<const N: usize> From<[Piece; N]> for PieceList {
+    fn from(pieces: [Piece; N]) -> Self {
+        const { assert!(N <= MAX_PIECES, "a side can't have more than 16 pieces") };
+        pieces.into_iter().collect()
+    }
+}
+
+impl<'a> IntoIterator for &'a PieceList {
+    type Item = &'a Piece;
+    type IntoIter = slice::Iter<'a, Piece>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut PieceList {
+    type Item = &'a mut Piece;
+    type IntoIter = slice::IterMut<'a, Piece>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for PieceList {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for PieceList {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let pieces = Vec::<Piece>::deserialize(deserializer)?;
+        PieceList::try_from(pieces).map_err(|pieces| {
+            serde::de::Error::invalid_length(pieces.len(), &"at most 16 pieces per side")
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Direction, KnightDirection, Position};
+    use super::{
+        Direction, Kind, KnightDirection, ParsePositionErr, Piece, PieceList, Player, Position,
+        SquareColor, MAX_PIECES,
+    };
+
+    #[test]
+    fn test_piece_chars() {
+        let knight = Piece::new(Player::Black, Kind::Knight, 7, 1);
+        assert_eq!('n', knight.to_char());
+        assert_eq!("n", knight.to_string());
+        assert_eq!(
+            "Q",
+            Piece::new(Player::White, Kind::Queen, 0, 3).to_string()
+        );
+        let piece = Piece::from_char('n', Position::new(7, 1)).unwrap();
+        assert_eq!(
+            (Player::Black, Kind::Knight, 7, 1),
+            (piece.player, piece.kind, piece.row, piece.column)
+        );
+        let piece = Piece::from_char('K', Position::new(0, 4)).unwrap();
+        assert_eq!((Player::White, Kind::King), (piece.player, piece.kind));
+        assert!(Piece::from_char('x', Position::new(0, 0)).is_none());
+        for c in "PNBRQK".chars() {
+            assert_eq!(Some(c), Kind::from_char(c).map(|k| k.to_char()));
+        }
+    }
+
+    #[test]
+    fn test_position_algebraic() {
+        assert_eq!(Some(Position::new(3, 4)), Position::from_algebraic("e4"));
+        assert_eq!(Ok(Position::new(0, 0)), "a1".parse());
+        assert_eq!(Ok(Position::new(7, 7)), "h8".parse::<Position>());
+        for invalid in ["", "e", "e9", "i1", "E4", "e44"] {
+            assert_eq!(
+                Err(ParsePositionErr),
+                invalid.parse::<Position>(),
+                "{}",
+                invalid
+            );
+        }
+        assert_eq!("e4", Position::new(3, 4).to_string());
+        assert_eq!("(8, 2)", Position::new(8, 2).to_string());
+        assert_eq!(Some(28), Position::new(3, 4).index());
+        for off_board in [
+            Position::new(8, 0),
+            Position::new(0, 9),
+            Position::new(255, 255),
+        ] {
+            assert_eq!(None, off_board.index());
+            assert!(off_board.file().is_none() || off_board.rank().is_none());
+        }
+    }
+
+    #[test]
+    fn test_square_geometry() {
+        let (a1, h1, e4, c6) = (
+            Position::new(0, 0),
+            Position::new(0, 7),
+            Position::new(3, 4),
+            Position::new(5, 2),
+        );
+        assert_eq!(SquareColor::Dark, a1.color());
+        assert_eq!(SquareColor::Light, h1.color());
+        assert_eq!(SquareColor::Light, e4.color());
+        assert_eq!(4, e4.distance(&a1));
+        assert_eq!(7, e4.manhattan_distance(&a1));
+        assert_eq!(2, e4.distance(&c6));
+        assert!(a1.same_rank(&h1) && !a1.same_file(&h1));
+        assert!(e4.same_diagonal(&c6) && e4.same_diagonal(&h1) && !e4.same_diagonal(&a1));
+        assert!(Position::new(7, 7).same_diagonal(&a1));
+    }
+
+    #[test]
+    fn test_ray_to() {
+        let start = Position::new(0, 0);
+        let diagonal: Vec<Position> = start.ray_to(&Position::new(3, 3)).collect();
+        assert_eq!(vec![Position::new(1, 1), Position::new(2, 2)], diagonal);
+        let file: Vec<Position> = Position::new(6, 4).ray_to(&Position::new(3, 4)).collect();
+        assert_eq!(vec![Position::new(5, 4), Position::new(4, 4)], file);
+        let rank: Vec<Position> = Position::new(2, 7).ray_to(&Position::new(2, 5)).collect();
+        assert_eq!(vec![Position::new(2, 6)], rank);
+        assert_eq!(0, start.ray_to(&Position::new(0, 1)).count());
+        assert_eq!(0, start.ray_to(&Position::new(2, 1)).count());
+        assert_eq!(0, start.ray_to(&start).count());
+    }
+
+    #[test]
+    fn test_derived_traits() {
+        use std::collections::HashSet;
+
+        use crate::{game_manager::GameManager, moves::Move};
+
+        assert_eq!(Player::White, Player::default());
+        assert_eq!(Position::new(0, 0), Position::default());
+        assert_eq!(GameManager::new(), GameManager::default());
+
+        let gm = GameManager::default();
+        let pieces: HashSet<Piece> = gm.pieces().map(|(_, p)| *p).collect();
+        assert_eq!(32, pieces.len());
+        assert!(pieces.contains(&Piece::new(Player::Black, Kind::Queen, 7, 3)));
+        let kinds: HashSet<(Player, Kind)> = pieces.iter().map(|p| (p.player, p.kind)).collect();
+        assert_eq!(12, kinds.len());
+        let moves: HashSet<Move> = gm.legal_moves().into_iter().collect();
+        assert_eq!(20, moves.len());
+        assert!(moves.contains(&Move::from_uci("g1f3").unwrap()));
+    }
+
+    #[test]
+    fn test_piece_list() {
+        let mut list = PieceList::new();
+        for column in 0..4 {
+            list.push(Piece::new(Player::White, Kind::Pawn, 1, column));
+        }
+        let copy = list;
+        let removed = list.swap_remove(1);
+        assert_eq!(1, removed.column);
+        assert_eq!(3, list.len());
+        assert_eq!(3, list[1].column);
+        assert_eq!(4, copy.len());
+
+        let pawn = Piece::new(Player::White, Kind::Pawn, 1, 0);
+        let mut full: PieceList = [pawn; MAX_PIECES].into();
+        assert_eq!(Err(pawn), full.try_push(pawn));
+        full.push(pawn);
+        assert_eq!(MAX_PIECES, full.len());
+        // A seventeenth piece is refused or dropped, never a panic
+        let pawns = vec![pawn; MAX_PIECES + 1];
+        assert_eq!(
+            Some(pawns.clone()),
+            PieceList::try_from(pawns.clone()).err()
+        );
+        assert_eq!(MAX_PIECES, pawns.into_iter().collect::<PieceList>().len());
+    }
 
     #[test]
     fn test_direction_up() {
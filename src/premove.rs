@@ -0,0 +1,158 @@
+use crate::{
+    game_manager::{GameManager, MoveErr},
+    move_validators::is_valid_move,
+    moves::Move,
+    piece::{Player, Position},
+};
+
+// Moves queued by the players for when their turn comes. Like the listeners they belong to
+// the live game, clones start without any so replays don't run them.
+#[derive(Debug, Default)]
+pub(crate) struct Pending {
+    premoves: [Option<Move>; 2],
+    // Opponent move then reply, alternating, the first move being the next one expected
+    conditionals: [Vec<Vec<Move>>; 2],
+}
+
+impl Clone for Pending {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl GameManager {
+    // Queues `mv` for `player` until its next turn. Only the ownership of the piece and its
+    // geometry are checked, the move is played then if it is still legal.
+    pub fn set_premove(&mut self, player: Player, mv: Move) -> Result<(), MoveErr> {
+        let piece = self
+            .pieces_of(player)
+            .iter()
+            .find(|p| Position::from_piece(p) == mv.from)
+            .ok_or(MoveErr::NoPiece { at: mv.from })?;
+        if mv.from == mv.to {
+            return Err(MoveErr::SamePosition);
+        }
+        let castling = self.castle(piece.kind, &mv, &self.castling).is_some();
+        if !castling && !is_valid_move(piece, &mv.to) {
+            return Err(MoveErr::InvalidMove {
+                piece: piece.kind,
+                from: mv.from,
+                to: mv.to,
+            });
+        }
+        self.pending.premoves[player.index()] = Some(mv);
+        Ok(())
+    }
+
+    pub fn premove(&self, player: &Player) -> Option<Move> {
+        self.pending.premoves[player.index()]
+    }
+
+    pub fn cancel_premove(&mut self, player: &Player) {
+        self.pending.premoves[player.index()] = None;
+    }
+
+    // Registers a line for `player`: the expected opponent move, the reply to it, the next
+    // expected opponent move and so on. The line is dropped once the opponent deviates.
+    pub fn add_conditional_moves(&mut self, player: Player, line: Vec<Move>) {
+        if line.len() >= 2 {
+            self.pending.conditionals[player.index()].push(line);
+        }
+    }
+
+    pub fn conditional_moves(&self, player: &Player) -> &[Vec<Move>] {
+        &self.pending.conditionals[player.index()]
+    }
+
+    // Plays what the side to move queued, after `last` was played by its opponent
+    pub(crate) fn run_pending(&mut self, last: &Move) {
+        let player = self.turn;
+        let lines = std::mem::take(&mut self.pending.conditionals[player.index()]);
+        let mut lines: Vec<Vec<Move>> = lines
+            .into_iter()
+            .filter(|line| line[0] == *last)
+            .map(|line| line[1..].to_vec())
+            .collect();
+        let premove = self.pending.premoves[player.index()].take();
+        let reply = lines
+            .first()
+            .map(|line| line[0])
+            .filter(|mv| self.legal_moves_iter().any(|m| m == *mv))
+            .or(premove.filter(|mv| self.legal_moves_iter().any(|m| m == *mv)));
+        let Some(reply) = reply else {
+            return;
+        };
+        // Lines going on after the reply wait for the next opponent move
+        lines.retain(|line| line[0] == reply && line.len() >= 3);
+        self.pending.conditionals[player.index()] =
+            lines.into_iter().map(|line| line[1..].to_vec()).collect();
+        // Legal, can't fail
+        let _ = self.play(&reply);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        game_manager::{GameManager, MoveErr},
+        moves::Move,
+        piece::{Kind, Player},
+        square::Square,
+    };
+
+    #[test]
+    fn test_premove() {
+        let mut gm = GameManager::new();
+        gm.play(&Move::new(Square::E2, Square::E4)).unwrap();
+        assert!(matches!(
+            gm.set_premove(Player::White, Move::new(Square::G1, Square::G3)),
+            Err(MoveErr::InvalidMove {
+                piece: Kind::Knight,
+                ..
+            })
+        ));
+        assert!(gm
+            .set_premove(Player::White, Move::new(Square::E7, Square::E5))
+            .is_err());
+        // Not legal yet, the f1 bishop is free once e2 moved
+        let premove = Move::new(Square::F1, Square::C4);
+        gm.set_premove(Player::White, premove).unwrap();
+        assert_eq!(Some(premove), gm.premove(&Player::White));
+        gm.play(&Move::new(Square::E7, Square::E5)).unwrap();
+        assert_eq!(3, gm.history().len());
+        assert_eq!(None, gm.premove(&Player::White));
+        assert_eq!(Player::Black, gm.turn);
+
+        // Dropped when it became illegal
+        gm.set_premove(Player::Black, Move::new(Square::D8, Square::H4))
+            .unwrap();
+        gm.set_premove(Player::White, Move::new(Square::C4, Square::F7))
+            .unwrap();
+        gm.play(&Move::new(Square::G7, Square::G5)).unwrap();
+        assert_eq!(5, gm.history().len());
+        assert_eq!(Move::new(Square::C4, Square::F7), gm.history()[4].mv);
+        assert_eq!(None, gm.premove(&Player::Black));
+        gm.cancel_premove(&Player::White);
+    }
+
+    #[test]
+    fn test_conditional_moves() {
+        let mut gm = GameManager::new();
+        let e4 = Move::new(Square::E2, Square::E4);
+        let e5 = Move::new(Square::E7, Square::E5);
+        let nf3 = Move::new(Square::G1, Square::F3);
+        let nc6 = Move::new(Square::B8, Square::C6);
+        let bb5 = Move::new(Square::F1, Square::B5);
+        gm.play(&e4).unwrap();
+        gm.add_conditional_moves(Player::White, vec![e5, nf3, nc6, bb5]);
+        gm.add_conditional_moves(Player::White, vec![Move::new(Square::C7, Square::C5), nf3]);
+        gm.add_conditional_moves(Player::White, vec![e5]);
+        assert_eq!(2, gm.conditional_moves(&Player::White).len());
+        gm.play(&e5).unwrap();
+        assert_eq!(Some(nf3), gm.history().last().map(|r| r.mv));
+        assert_eq!(vec![vec![nc6, bb5]], gm.conditional_moves(&Player::White));
+        gm.play(&Move::new(Square::G8, Square::F6)).unwrap();
+        assert_eq!(Player::White, gm.turn);
+        assert!(gm.conditional_moves(&Player::White).is_empty());
+    }
+}
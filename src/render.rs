@@ -0,0 +1,186 @@
+use crate::{
+    game_manager::GameManager,
+    piece::{Kind, Piece, Player, Position},
+    square::{File, Rank},
+};
+
+impl Piece {
+    pub fn to_unicode(&self) -> char {
+        match (self.player, self.kind) {
+            (Player::White, Kind::King) => '♔',
+            (Player::White, Kind::Queen) => '♕',
+            (Player::White, Kind::Rook) => '♖',
+            (Player::White, Kind::Bishop) => '♗',
+            (Player::White, Kind::Knight) => '♘',
+            (Player::White, Kind::Pawn) => '♙',
+            (Player::Black, Kind::King) => '♚',
+            (Player::Black, Kind::Queen) => '♛',
+            (Player::Black, Kind::Rook) => '♜',
+            (Player::Black, Kind::Bishop) => '♝',
+            (Player::Black, Kind::Knight) => '♞',
+            (Player::Black, Kind::Pawn) => '♟',
+        }
+    }
+}
+
+// Text diagram of a position, highlighted squares are drawn between brackets
+#[derive(Clone, Debug)]
+pub struct BoardRenderer {
+    perspective: Player,
+    coordinates: bool,
+    ascii: bool,
+    highlights: Vec<Position>,
+}
+
+impl BoardRenderer {
+    pub fn new() -> Self {
+        Self {
+            perspective: Player::White,
+            coordinates: true,
+            ascii: false,
+            highlights: vec![],
+        }
+    }
+
+    // Side shown at the bottom of the board
+    pub fn perspective(mut self, player: Player) -> Self {
+        self.perspective = player;
+        self
+    }
+
+    pub fn coordinates(mut self, show: bool) -> Self {
+        self.coordinates = show;
+        self
+    }
+
+    // Draws pieces with their FEN letters instead of Unicode glyphs
+    pub fn ascii(mut self, ascii: bool) -> Self {
+        self.ascii = ascii;
+        self
+    }
+
+    pub fn highlight<P: Into<Position>>(mut self, squares: impl IntoIterator<Item = P>) -> Self {
+        self.highlights.extend(squares.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn render(&self, gm: &GameManager) -> String {
+        let flipped = self.perspective == Player::Black;
+        let size = gm.board_size();
+        let mut rows: Vec<u8> = (0..size.rows()).rev().collect();
+        let mut columns: Vec<u8> = (0..size.columns()).collect();
+        if flipped {
+            rows.reverse();
+            columns.reverse();
+        }
+        let mut board = String::new();
+        for row in &rows {
+            if self.coordinates {
+                board.push(Rank::new(*row).expect("row on the board").to_char());
+                board.push(' ');
+            }
+            for column in &columns {
+                let pos = Position::new(*row, *column);
+                let piece = gm.piece_at(&pos).map(|(_, p)| p);
+                let glyph = match (piece, self.ascii) {
+                    (Some(piece), true) => piece.to_char(),
+                    (Some(piece), false) => piece.to_unicode(),
+                    (None, true) => '.',
+                    (None, false) => '·',
+                };
+                let (open, close) = if self.highlights.contains(&pos) {
+                    ('[', ']')
+                } else {
+                    (' ', ' ')
+                };
+                board.push(open);
+                board.push(glyph);
+                board.push(close);
+            }
+            board.truncate(board.trim_end().len());
+            board.push('\n');
+        }
+        if self.coordinates {
+            board.push_str("  ");
+            for column in &columns {
+                board.push(' ');
+                board.push(File::new(*column).expect("column on the board").to_char());
+                board.push(' ');
+            }
+            board.truncate(board.trim_end().len());
+            board.push('\n');
+        }
+        board
+    }
+
+    // One diagram per position of the game history, the last move being highlighted
+    pub fn frames<'a>(&'a self, gm: &'a GameManager) -> impl Iterator<Item = String> + 'a {
+        gm.replay().map(move |(position, mv)| {
+            let mut renderer = self.clone();
+            renderer
+                .highlights
+                .extend(mv.iter().flat_map(|mv| [mv.from, mv.to]));
+            renderer.render(&position)
+        })
+    }
+}
+
+impl Default for BoardRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BoardRenderer;
+    use crate::{game_manager::GameManager, moves::Move, piece::Player, square::Square};
+
+    #[test]
+    fn test_render_start_position() {
+        let gm = GameManager::new();
+        let expected = "\
+8  ♜  ♞  ♝  ♛  ♚  ♝  ♞  ♜
+7  ♟  ♟  ♟  ♟  ♟  ♟  ♟  ♟
+6  ·  ·  ·  ·  ·  ·  ·  ·
+5  ·  ·  ·  ·  ·  ·  ·  ·
+4  ·  ·  ·  ·  ·  ·  ·  ·
+3  ·  ·  ·  ·  ·  ·  ·  ·
+2  ♙  ♙  ♙  ♙  ♙  ♙  ♙  ♙
+1  ♖  ♘  ♗  ♕  ♔  ♗  ♘  ♖
+   a  b  c  d  e  f  g  h
+";
+        assert_eq!(expected, BoardRenderer::new().render(&gm));
+    }
+
+    #[test]
+    fn test_render_flipped_with_highlights() {
+        let gm = GameManager::new();
+        let board = BoardRenderer::new()
+            .perspective(Player::Black)
+            .coordinates(false)
+            .highlight([Square::E2, Square::H1])
+            .render(&gm);
+        let lines: Vec<&str> = board.lines().collect();
+        assert_eq!(8, lines.len());
+        assert_eq!("[♖] ♘  ♗  ♔  ♕  ♗  ♘  ♖", lines[0]);
+        assert_eq!(" ♙  ♙  ♙ [♙] ♙  ♙  ♙  ♙", lines[1]);
+        assert_eq!(" ♜  ♞  ♝  ♚  ♛  ♝  ♞  ♜", lines[7]);
+    }
+
+    #[test]
+    fn test_render_frames() {
+        let mut gm = GameManager::new();
+        gm.play(&Move::new(Square::E2, Square::E4)).unwrap();
+        gm.play(&Move::new(Square::E7, Square::E5)).unwrap();
+        let renderer = BoardRenderer::new().ascii(true).coordinates(false);
+        let frames: Vec<String> = renderer.frames(&gm).collect();
+        assert_eq!(3, frames.len());
+        assert_eq!(" p  p  p  p  p  p  p  p", frames[0].lines().nth(1).unwrap());
+        assert!(!frames[0].contains('['));
+        assert_eq!(" P  P  P  P [.] P  P  P", frames[1].lines().nth(6).unwrap());
+        assert_eq!(" .  .  .  . [P] .  .  .", frames[1].lines().nth(4).unwrap());
+        assert_eq!(" p  p  p  p [.] p  p  p", frames[2].lines().nth(1).unwrap());
+        assert_eq!(" .  .  .  .  P  .  .  .", frames[2].lines().nth(4).unwrap());
+    }
+}
@@ -0,0 +1,174 @@
+use std::{collections::HashMap, error::Error, fmt};
+
+#[cfg(feature = "engine")]
+use crate::book::PolyglotBook;
+use crate::{
+    fen::{FenErr, START_FEN},
+    game_manager::GameManager,
+    moves::Move,
+    pgn::{PgnErr, PgnGame, PgnLine},
+};
+
+// Moves a player prepared, for training applications quizzing them on their openings
+pub trait Repertoire {
+    // Moves of the repertoire in the current position, empty once out of it
+    fn expected_moves(&self, gm: &GameManager) -> Vec<Move>;
+
+    fn is_in_book(&self, gm: &GameManager, mv: &Move) -> bool {
+        self.expected_moves(gm).contains(mv)
+    }
+
+    // Whether the last move of the game is one of the repertoire, `None` before any move
+    fn last_move_in_book(&self, gm: &GameManager) -> Option<bool> {
+        let mut before = gm.clone();
+        let mv = before.undo()?;
+        Some(self.is_in_book(&before, &mv))
+    }
+}
+
+// Heaviest moves first
+#[cfg(feature = "engine")]
+impl Repertoire for PolyglotBook {
+    fn expected_moves(&self, gm: &GameManager) -> Vec<Move> {
+        self.probe(gm).into_iter().map(|entry| entry.mv).collect()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RepertoireErr {
+    Pgn(PgnErr),
+    Fen(FenErr),
+    // SAN of the first move that can't be played
+    IllegalMove(String),
+}
+
+impl fmt::Display for RepertoireErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RepertoireErr::Pgn(err) => err.fmt(f),
+            RepertoireErr::Fen(err) => err.fmt(f),
+            RepertoireErr::IllegalMove(san) => write!(f, "illegal move {}", san),
+        }
+    }
+}
+
+impl Error for RepertoireErr {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RepertoireErr::Pgn(err) => Some(err),
+            RepertoireErr::Fen(err) => Some(err),
+            RepertoireErr::IllegalMove(_) => None,
+        }
+    }
+}
+
+// Repertoire written as PGN games, variations included. Positions are looked up by their
+// Zobrist key, so lines transposing into one another share their moves.
+#[derive(Clone, Debug, Default)]
+pub struct PgnRepertoire {
+    moves: HashMap<u64, Vec<Move>>,
+}
+
+impl PgnRepertoire {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn parse(pgn: &str) -> Result<Self, RepertoireErr> {
+        let game = PgnGame::parse(pgn).map_err(RepertoireErr::Pgn)?;
+        let mut repertoire = Self::new();
+        repertoire.add_game(&game)?;
+        Ok(repertoire)
+    }
+
+    // Adds every move of the game, starting from its FEN tag if it has one
+    pub fn add_game(&mut self, game: &PgnGame) -> Result<(), RepertoireErr> {
+        let gm = GameManager::from_fen(game.tag("FEN").unwrap_or(START_FEN))
+            .map_err(RepertoireErr::Fen)?;
+        self.add_line(gm, &game.mainline)
+    }
+
+    fn add_line(&mut self, mut gm: GameManager, line: &PgnLine) -> Result<(), RepertoireErr> {
+        for pgn_move in &line.moves {
+            // Variations replace the move they follow
+            for variation in &pgn_move.variations {
+                self.add_line(gm.clone(), variation)?;
+            }
+            let mv = gm
+                .parse_san(&pgn_move.san)
+                .map_err(|_| RepertoireErr::IllegalMove(pgn_move.san.clone()))?;
+            self.add_move(&gm, mv);
+            gm.make_move(&mv);
+        }
+        Ok(())
+    }
+
+    pub fn add_move(&mut self, gm: &GameManager, mv: Move) {
+        let moves = self.moves.entry(gm.zobrist_hash()).or_default();
+        if !moves.contains(&mv) {
+            moves.push(mv);
+        }
+    }
+
+    // Positions with at least one move
+    pub fn len(&self) -> usize {
+        self.moves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+}
+
+// Moves in the order they were first met
+impl Repertoire for PgnRepertoire {
+    fn expected_moves(&self, gm: &GameManager) -> Vec<Move> {
+        self.moves
+            .get(&gm.zobrist_hash())
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PgnRepertoire, Repertoire, RepertoireErr};
+    use crate::{game_manager::GameManager, moves::Move, square::Square};
+
+    #[test]
+    fn test_pgn_repertoire() {
+        let pgn = "1. e4 e5 (1... c5 2. Nf3) 2. Nf3 Nc6 3. Bb5 (3. Bc4 Nf6) *";
+        let repertoire = PgnRepertoire::parse(pgn).unwrap();
+        let mut gm = GameManager::new();
+        assert_eq!(
+            vec![Move::new(Square::E2, Square::E4)],
+            repertoire.expected_moves(&gm)
+        );
+        assert_eq!(None, repertoire.last_move_in_book(&gm));
+        gm.play(&Move::new(Square::E2, Square::E4)).unwrap();
+        assert_eq!(
+            vec![
+                Move::new(Square::C7, Square::C5),
+                Move::new(Square::E7, Square::E5)
+            ],
+            repertoire.expected_moves(&gm)
+        );
+        gm.play(&Move::new(Square::E7, Square::E5)).unwrap();
+        assert_eq!(Some(true), repertoire.last_move_in_book(&gm));
+        gm.play(&Move::new(Square::G1, Square::F3)).unwrap();
+        gm.play(&Move::new(Square::B8, Square::C6)).unwrap();
+        assert!(repertoire.is_in_book(&gm, &Move::new(Square::F1, Square::C4)));
+        gm.play(&Move::new(Square::F1, Square::E2)).unwrap();
+        assert_eq!(Some(false), repertoire.last_move_in_book(&gm));
+        assert!(repertoire.expected_moves(&gm).is_empty());
+
+        // Reached through another move order
+        let gm = GameManager::from_moves(&["Nf3", "Nc6", "e4", "e5"]).unwrap();
+        assert_eq!(2, repertoire.expected_moves(&gm).len());
+
+        assert_eq!(
+            Err(RepertoireErr::IllegalMove("Ke3".to_string())),
+            PgnRepertoire::parse("1. e4 e5 2. Ke3 *").map(|r| r.len())
+        );
+    }
+}
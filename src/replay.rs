@@ -0,0 +1,137 @@
+use crate::{game_manager::GameManager, moves::Move};
+
+// Walks the recorded history of a game, yielding the initial position then the position
+// after every ply along with the move that led to it.
+pub struct Replay<'a> {
+    game: &'a GameManager,
+    current: Option<GameManager>,
+    ply: usize,
+}
+
+impl<'a> Iterator for Replay<'a> {
+    type Item = (GameManager, Option<Move>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = match &mut self.current {
+            None => {
+                let start = self.game.initial_position();
+                self.current = Some(start.clone());
+                return Some((start, None));
+            }
+            Some(current) => current,
+        };
+        let record = self.game.history().get(self.ply)?;
+        self.ply += 1;
+        current.play(&record.mv).ok()?;
+        Some((current.clone(), Some(record.mv)))
+    }
+}
+
+impl<'a> Replay<'a> {
+    pub fn fens(self) -> impl Iterator<Item = String> + 'a {
+        self.map(|(position, _)| position.to_fen())
+    }
+}
+
+impl GameManager {
+    pub fn replay(&self) -> Replay<'_> {
+        Replay {
+            game: self,
+            current: None,
+            ply: 0,
+        }
+    }
+
+    // Number of moves played from the initial position
+    pub fn ply(&self) -> usize {
+        self.history.len()
+    }
+
+    // Takes back or replays moves until `ply` moves are on the board. Moves taken back stay
+    // available for redo, so a game can be scrubbed back and forth.
+    pub fn seek(&mut self, ply: usize) -> bool {
+        if ply > self.history.len() + self.redo.len() {
+            return false;
+        }
+        while self.history.len() > ply {
+            self.undo();
+        }
+        while self.history.len() < ply {
+            if self.redo().is_none() {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        game_manager::{GameManager, GameStatus},
+        moves::Move,
+        piece::Player,
+        square::Square,
+    };
+
+    #[test]
+    fn test_replay() {
+        let mut gm = GameManager::new();
+        let moves = [
+            Move::new(Square::E2, Square::E4),
+            Move::new(Square::E7, Square::E5),
+            Move::new(Square::G1, Square::F3),
+        ];
+        for mv in &moves {
+            gm.play(mv).unwrap();
+        }
+        let replay: Vec<_> = gm.replay().collect();
+        assert_eq!(4, replay.len());
+        assert_eq!(GameManager::new().to_fen(), replay[0].0.to_fen());
+        assert_eq!(None, replay[0].1);
+        assert_eq!(Some(moves[1]), replay[2].1);
+        assert_eq!(gm.to_fen(), replay[3].0.to_fen());
+        assert_eq!(3, replay[3].0.history().len());
+        let fens: Vec<String> = gm.replay().fens().collect();
+        assert_eq!(replay[1].0.to_fen(), fens[1]);
+    }
+
+    #[test]
+    fn test_replay_ended_game() {
+        let mut gm = GameManager::new();
+        gm.play(&Move::new(Square::E2, Square::E4)).unwrap();
+        gm.play(&Move::new(Square::E7, Square::E5)).unwrap();
+        gm.offer_draw(&Player::White);
+        assert!(gm.resign(&Player::Black));
+        let replay: Vec<_> = gm.replay().collect();
+        assert_eq!(3, replay.len());
+        assert_eq!(gm.to_fen(), replay[2].0.to_fen());
+        assert_eq!(GameStatus::Ongoing, replay[0].0.status());
+        assert_eq!(None, replay[0].0.draw_offer());
+        assert_eq!(GameStatus::Resignation(Player::White), gm.status());
+    }
+
+    #[test]
+    fn test_seek() {
+        let mut gm = GameManager::new();
+        for mv in [
+            Move::new(Square::E2, Square::E4),
+            Move::new(Square::E7, Square::E5),
+            Move::new(Square::G1, Square::F3),
+        ] {
+            gm.play(&mv).unwrap();
+        }
+        let fens: Vec<String> = gm.replay().fens().collect();
+        assert!(gm.seek(1));
+        assert_eq!(1, gm.ply());
+        assert_eq!(fens[1], gm.to_fen());
+        assert!(gm.seek(3));
+        assert_eq!(fens[3], gm.to_fen());
+        assert!(gm.seek(0));
+        assert_eq!(fens[0], gm.to_fen());
+        assert!(!gm.seek(4));
+        assert_eq!(0, gm.ply());
+        assert!(gm.seek(2));
+        assert_eq!(fens[2], gm.to_fen());
+    }
+}
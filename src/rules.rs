@@ -0,0 +1,142 @@
+use std::{
+    fmt::Debug,
+    sync::{Arc, OnceLock},
+};
+
+use crate::{
+    game_manager::{GameManager, GameStatus},
+    moves::Move,
+    piece::Kind,
+};
+
+pub const STANDARD_PROMOTIONS: [Kind; 4] = [Kind::Queen, Kind::Rook, Kind::Bishop, Kind::Knight];
+
+// Rule set a game is played under. Every method defaults to standard chess, so a variant only
+// overrides what it changes and can build on the `standard_*` methods of `GameManager`.
+pub trait Rules: Debug + Send + Sync {
+    fn name(&self) -> &str;
+
+    // Moves the side to move may play
+    fn legal_moves<'a>(&self, gm: &'a GameManager) -> Box<dyn Iterator<Item = Move> + 'a> {
+        Box::new(gm.standard_legal_moves())
+    }
+
+    // End of the game decided on the board, `GameStatus::Ongoing` if there is none. Clocks
+    // and results decided off the board are handled by `GameManager::status`.
+    fn status(&self, gm: &GameManager) -> GameStatus {
+        gm.standard_status()
+    }
+
+    // Pieces a pawn may promote to
+    fn promotions(&self) -> &[Kind] {
+        &STANDARD_PROMOTIONS
+    }
+
+    // Only true for standard chess itself, tools built for it such as the tablebases are
+    // turned off for every other rule set
+    fn is_standard(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StandardChess;
+
+impl Rules for StandardChess {
+    fn name(&self) -> &str {
+        "Standard"
+    }
+
+    fn is_standard(&self) -> bool {
+        true
+    }
+}
+
+pub(crate) fn standard() -> Arc<dyn Rules> {
+    Arc::new(StandardChess)
+}
+
+impl GameManager {
+    pub fn rules(&self) -> &dyn Rules {
+        self.rules.as_ref()
+    }
+
+    // Plays the game on under `rules` from now on
+    pub fn set_rules(&mut self, rules: impl Rules + 'static) {
+        self.rules = Arc::new(rules);
+        self.legal_cache = OnceLock::new();
+    }
+
+    pub fn with_rules(rules: impl Rules + 'static) -> Self {
+        let mut gm = GameManager::new();
+        gm.set_rules(rules);
+        gm
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rules;
+    use crate::{
+        game_manager::{GameManager, GameStatus},
+        moves::Move,
+        piece::{Kind, Player},
+        square::Square,
+    };
+
+    // Standard chess where pawns only promote to queens and the first capture wins
+    #[derive(Debug)]
+    struct FirstBlood;
+
+    impl Rules for FirstBlood {
+        fn name(&self) -> &str {
+            "First blood"
+        }
+
+        fn status(&self, gm: &GameManager) -> GameStatus {
+            match gm.history().iter().find_map(|record| record.captured) {
+                Some(captured) => GameStatus::Checkmate(captured.player.opponent()),
+                None => gm.standard_status(),
+            }
+        }
+
+        fn promotions(&self) -> &[Kind] {
+            &[Kind::Queen]
+        }
+    }
+
+    #[test]
+    fn test_rules() {
+        let gm = GameManager::new();
+        assert_eq!("Standard", gm.rules().name());
+
+        let mut gm = GameManager::from_fen("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            4,
+            gm.legal_moves()
+                .iter()
+                .filter(|mv| mv.promotion.is_some())
+                .count()
+        );
+        gm.set_rules(FirstBlood);
+        assert_eq!("First blood", gm.rules().name());
+        assert_eq!(
+            vec![Some(Kind::Queen)],
+            gm.legal_moves()
+                .iter()
+                .filter(|mv| mv.from == Square::B7.into())
+                .map(|mv| mv.promotion)
+                .collect::<Vec<_>>()
+        );
+
+        let mut gm = GameManager::with_rules(FirstBlood);
+        for (from, to) in [(Square::E2, Square::E4), (Square::D7, Square::D5)] {
+            gm.play(&Move::new(from, to)).unwrap();
+        }
+        assert_eq!(GameStatus::Ongoing, gm.status());
+        gm.play(&Move::new(Square::E4, Square::D5)).unwrap();
+        assert_eq!(GameStatus::Checkmate(Player::White), gm.status());
+        // Clones keep playing by the same rules
+        assert_eq!("First blood", gm.clone().rules().name());
+    }
+}
@@ -0,0 +1,833 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    eval::{ClassicalEval, Evaluator, Score},
+    game_manager::GameManager,
+    moves::Move,
+    piece::{Kind, Position},
+    tablebase::Wdl,
+};
+
+// Score of a mate on the board, mates further away score a bit less
+pub const MATE: Score = 100_000;
+
+// Best move for the side to move found by a `depth` plies search with the classical
+// evaluation, `None` when the game is over.
+pub fn best_move(gm: &GameManager, depth: u8) -> Option<(Move, Score)> {
+    best_move_with(gm, depth, &ClassicalEval)
+}
+
+pub fn best_move_with<E: Evaluator + Sync + ?Sized>(
+    gm: &GameManager,
+    depth: u8,
+    eval: &E,
+) -> Option<(Move, Score)> {
+    search_with_info(gm, depth, eval, |_| {})
+}
+
+// Progress of the search, reported once per completed depth
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SearchInfo {
+    pub depth: u8,
+    pub score: Score,
+    pub nodes: u64,
+    // Nodes per second
+    pub nps: u64,
+    pub time: Duration,
+    // Principal variation, the best line found starting with the best move
+    pub pv: Vec<Move>,
+}
+
+impl SearchInfo {
+    // UCI `info` line, e.g. "info depth 2 score cp 30 nodes 420 nps 84000 time 5 pv e2e4 e7e5"
+    pub fn to_uci(&self) -> String {
+        let score = match mate_in(self.score) {
+            Some(moves) => format!("mate {}", moves),
+            None => format!("cp {}", self.score),
+        };
+        let mut info = format!(
+            "info depth {} score {} nodes {} nps {} time {}",
+            self.depth,
+            score,
+            self.nodes,
+            self.nps,
+            self.time.as_millis()
+        );
+        if !self.pv.is_empty() {
+            info.push_str(" pv");
+            for mv in &self.pv {
+                info.push(' ');
+                info.push_str(&mv.to_uci());
+            }
+        }
+        info
+    }
+}
+
+// Searches depth 1 to `depth` one after the other, `on_info` is called after each of them
+pub fn search_with_info<E: Evaluator + Sync + ?Sized>(
+    gm: &GameManager,
+    depth: u8,
+    eval: &E,
+    on_info: impl FnMut(&SearchInfo),
+) -> Option<(Move, Score)> {
+    let limits = SearchLimits {
+        depth: Some(depth),
+        ..SearchLimits::default()
+    };
+    search(gm, &limits, eval, on_info)
+}
+
+// When to end a search, it goes on until `MAX_DEPTH` otherwise. The first depth always
+// completes so there is a move to play.
+#[derive(Clone, Debug, Default)]
+pub struct SearchLimits {
+    pub depth: Option<u8>,
+    pub movetime: Option<Duration>,
+    pub nodes: Option<u64>,
+    // Raised from another thread to end the search early
+    pub stop: Option<Arc<AtomicBool>>,
+    // Threads searching together, one when not set
+    pub threads: Option<usize>,
+}
+
+pub const MAX_DEPTH: u8 = 64;
+
+// Entries of the transposition table of a search, 16 bytes each
+const TT_ENTRIES: usize = 1 << 20;
+
+// Iterative deepening within `limits`, `on_info` is called after every completed depth.
+// In the endings of the tablebases, their move and score replace the ones searched.
+// With more than one thread, the other ones search the same position on their own and
+// only share the transposition table (Lazy SMP), the first thread reports and decides.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        level = "debug",
+        skip_all,
+        fields(fen = %gm.to_fen(), depth = ?limits.depth, threads = ?limits.threads)
+    )
+)]
+pub fn search<E: Evaluator + Sync + ?Sized>(
+    gm: &GameManager,
+    limits: &SearchLimits,
+    eval: &E,
+    mut on_info: impl FnMut(&SearchInfo),
+) -> Option<(Move, Score)> {
+    let start = Instant::now();
+    let tablebase = gm.tablebase_move().map(|(mv, probe)| {
+        let score = match (probe.wdl, probe.dtm) {
+            (Wdl::Win, Some(dtm)) => MATE - dtm as Score,
+            (Wdl::Loss, Some(dtm)) => -MATE + dtm as Score,
+            _ => 0,
+        };
+        (mv, score)
+    });
+    let shared = Shared::new(TT_ENTRIES);
+    thread::scope(|scope| {
+        for id in 1..limits.threads.unwrap_or(1) {
+            let mut gm = gm.clone();
+            let shared = &shared;
+            scope.spawn(move || {
+                let mut helper = Searcher::new(eval, shared);
+                helper.abortable = true;
+                // Every other helper is a ply ahead so they don't all search the same tree
+                for depth in 1 + (id % 2) as u8..=MAX_DEPTH {
+                    if helper.root(&mut gm, depth).is_none() {
+                        break;
+                    }
+                }
+            });
+        }
+
+        let mut gm = gm.clone();
+        let mut searcher = Searcher::new(eval, &shared);
+        searcher.deadline = limits.movetime.map(|time| start + time);
+        searcher.max_nodes = limits.nodes;
+        searcher.stop = limits.stop.as_deref();
+        let mut best = None;
+        for depth in 1..=limits.depth.unwrap_or(MAX_DEPTH).clamp(1, MAX_DEPTH) {
+            searcher.abortable = depth > 1;
+            if searcher.abortable && searcher.limits_reached() {
+                break;
+            }
+            let Some((mut score, mut pv)) = searcher.root(&mut gm, depth) else {
+                break;
+            };
+            if let Some((mv, exact)) = tablebase {
+                if pv[0] != mv {
+                    pv = vec![mv];
+                }
+                score = exact;
+            }
+            best = Some((pv[0], score));
+            let time = start.elapsed();
+            let nodes = searcher.total_nodes();
+            #[cfg(feature = "tracing")]
+            tracing::debug!(depth, score, nodes, best = %pv[0].to_uci(), "depth completed");
+            on_info(&SearchInfo {
+                depth,
+                score,
+                nodes,
+                nps: (nodes as f64 / time.as_secs_f64().max(1e-6)) as u64,
+                time,
+                pv,
+            });
+        }
+        shared.done.store(true, Ordering::Relaxed);
+        best
+    })
+}
+
+// Score of every legal move searched `depth` plies deep, each with a full window so they
+// can be compared with one another
+pub(crate) fn root_scores<E: Evaluator + ?Sized>(
+    gm: &GameManager,
+    depth: u8,
+    eval: &E,
+) -> Vec<(Move, Score)> {
+    let mut gm = gm.clone();
+    let shared = Shared::new(1 << 16);
+    let mut searcher = Searcher::new(eval, &shared);
+    let mut line = vec![];
+    searcher
+        .ordered_moves(&gm, 0, None)
+        .into_iter()
+        .map(|mv| {
+            let undo = gm.make_move(&mv);
+            let score =
+                -searcher.negamax(&mut gm, depth.max(1) - 1, 1, -MATE - 1, MATE + 1, &mut line);
+            gm.unmake_move(undo);
+            (mv, score)
+        })
+        .collect()
+}
+
+// Moves, not plies, to the mate a score announces, negative when the side to move gets mated
+pub(crate) fn mate_in(score: Score) -> Option<Score> {
+    if score > MATE - MAX_PLY {
+        Some((MATE - score + 1) / 2)
+    } else if score < -MATE + MAX_PLY {
+        Some(-(MATE + score) / 2)
+    } else {
+        None
+    }
+}
+
+// Deepest ply the search reaches, mate scores are at least MATE - MAX_PLY
+const MAX_PLY: Score = 256;
+
+// How a stored score relates to the real one, fail-high and fail-low nodes only give bounds
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct TtEntry {
+    depth: u8,
+    // Mates are counted from the stored position, not from the root
+    score: Score,
+    bound: Bound,
+    mv: Option<Move>,
+}
+
+const PROMOTIONS: [Kind; 4] = [Kind::Queen, Kind::Rook, Kind::Bishop, Kind::Knight];
+
+impl TtEntry {
+    // Score on the low 32 bits, then the depth, the bound and the move
+    fn pack(&self) -> u64 {
+        let bound = match self.bound {
+            Bound::Exact => 1,
+            Bound::Lower => 2,
+            Bound::Upper => 3,
+        };
+        let mv = self.mv.map_or(0, |mv| {
+            let promotion = mv
+                .promotion
+                .and_then(|kind| PROMOTIONS.iter().position(|p| *p == kind))
+                .map_or(0, |i| i as u64 + 1);
+            let (from, to) = square_indexes(&mv);
+            1 | (from as u64) << 1 | (to as u64) << 7 | promotion << 13
+        });
+        self.score as u32 as u64 | (self.depth as u64) << 32 | bound << 40 | mv << 42
+    }
+
+    fn unpack(data: u64) -> Option<Self> {
+        let bound = match (data >> 40) & 3 {
+            1 => Bound::Exact,
+            2 => Bound::Lower,
+            3 => Bound::Upper,
+            _ => return None,
+        };
+        let mv = data >> 42;
+        let square = |shift: u64| {
+            let index = ((mv >> shift) & 63) as u8;
+            Position::new(index / 8, index % 8)
+        };
+        Some(Self {
+            depth: (data >> 32) as u8,
+            score: data as u32 as i32,
+            bound,
+            mv: (mv & 1 == 1).then(|| Move {
+                from: square(1),
+                to: square(7),
+                promotion: ((mv >> 13) & 7)
+                    .checked_sub(1)
+                    .and_then(|i| PROMOTIONS.get(i as usize).copied()),
+            }),
+        })
+    }
+}
+
+// Scores of the positions already searched, by Zobrist key. The threads write to it without
+// locking: a slot holds the key xored with the data, so one torn by two writes at once no
+// longer matches its key and is ignored.
+struct TranspositionTable {
+    slots: Vec<[AtomicU64; 2]>,
+}
+
+impl TranspositionTable {
+    fn new(entries: usize) -> Self {
+        Self {
+            slots: (0..entries.max(1))
+                .map(|_| [AtomicU64::new(0), AtomicU64::new(0)])
+                .collect(),
+        }
+    }
+
+    fn probe(&self, key: u64) -> Option<TtEntry> {
+        let [check, data] = &self.slots[key as usize % self.slots.len()];
+        let data = data.load(Ordering::Relaxed);
+        if check.load(Ordering::Relaxed) ^ data != key {
+            return None;
+        }
+        TtEntry::unpack(data)
+    }
+
+    fn store(&self, key: u64, entry: &TtEntry) {
+        let [check, data] = &self.slots[key as usize % self.slots.len()];
+        let packed = entry.pack();
+        check.store(key ^ packed, Ordering::Relaxed);
+        data.store(packed, Ordering::Relaxed);
+    }
+}
+
+// Mate scores are stored relative to the position, so they stay right wherever it is found
+fn score_to_tt(score: Score, ply: u8) -> Score {
+    if score > MATE - MAX_PLY {
+        score + ply as Score
+    } else if score < -MATE + MAX_PLY {
+        score - ply as Score
+    } else {
+        score
+    }
+}
+
+fn score_from_tt(score: Score, ply: u8) -> Score {
+    if score > MATE - MAX_PLY {
+        score - ply as Score
+    } else if score < -MATE + MAX_PLY {
+        score + ply as Score
+    } else {
+        score
+    }
+}
+
+// State the threads of a search have in common
+struct Shared {
+    tt: TranspositionTable,
+    // Nodes of every thread, added by batches of `POLL_NODES`
+    nodes: AtomicU64,
+    // Raised once the first thread is done, the others then stop
+    done: AtomicBool,
+}
+
+impl Shared {
+    fn new(entries: usize) -> Self {
+        Self {
+            tt: TranspositionTable::new(entries),
+            nodes: AtomicU64::new(0),
+            done: AtomicBool::new(false),
+        }
+    }
+}
+
+// The clock and the other threads are only looked at every that many nodes
+const POLL_NODES: u64 = 1024;
+
+// Negamax with alpha-beta pruning. Quiet moves that caused a cutoff are tried early in the
+// sibling nodes (killer moves) and anywhere else in the tree (history table).
+struct Searcher<'a, E: ?Sized> {
+    eval: &'a E,
+    shared: &'a Shared,
+    nodes: u64,
+    // Two latest cutoff moves at every ply
+    killers: Vec<[Option<Move>; 2]>,
+    // Cutoffs by origin and target square, weighted by the depth left
+    history: [[u32; 64]; 64],
+    // Best line of the previous iteration, searched first
+    pv: Vec<Move>,
+    deadline: Option<Instant>,
+    max_nodes: Option<u64>,
+    stop: Option<&'a AtomicBool>,
+    // Whether the limits apply to the current depth, and whether one was reached
+    abortable: bool,
+    aborted: bool,
+}
+
+impl<'a, E: Evaluator + ?Sized> Searcher<'a, E> {
+    fn new(eval: &'a E, shared: &'a Shared) -> Self {
+        Self {
+            eval,
+            shared,
+            nodes: 0,
+            killers: vec![],
+            history: [[0; 64]; 64],
+            pv: vec![],
+            deadline: None,
+            max_nodes: None,
+            stop: None,
+            abortable: false,
+            aborted: false,
+        }
+    }
+
+    fn should_stop(&mut self) -> bool {
+        if self.nodes.is_multiple_of(POLL_NODES) {
+            self.shared.nodes.fetch_add(POLL_NODES, Ordering::Relaxed);
+            if self.abortable && !self.aborted {
+                self.aborted = self.limits_reached();
+            }
+        }
+        self.aborted
+    }
+
+    fn limits_reached(&self) -> bool {
+        self.max_nodes.is_some_and(|max| self.total_nodes() >= max)
+            || self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+            || self.stop.is_some_and(|stop| stop.load(Ordering::Relaxed))
+            || self.shared.done.load(Ordering::Relaxed)
+    }
+
+    // Nodes of all the threads, the ones of the others not added yet aside
+    fn total_nodes(&self) -> u64 {
+        self.shared.nodes.load(Ordering::Relaxed) + self.nodes % POLL_NODES
+    }
+
+    // Score and principal variation, `None` without legal moves or when a limit was reached
+    fn root(&mut self, gm: &mut GameManager, depth: u8) -> Option<(Score, Vec<Move>)> {
+        let mut best = None;
+        let mut alpha = -MATE - 1;
+        for mv in self.ordered_moves(gm, 0, None) {
+            let mut line = vec![];
+            let undo = gm.make_move(&mv);
+            let score = -self.negamax(gm, depth - 1, 1, -MATE - 1, -alpha, &mut line);
+            gm.unmake_move(undo);
+            if score > alpha {
+                alpha = score;
+                line.insert(0, mv);
+                best = Some((score, line));
+            }
+        }
+        if self.aborted {
+            return None;
+        }
+        let (score, pv) = best.as_ref()?;
+        let entry = TtEntry {
+            depth,
+            score: *score,
+            bound: Bound::Exact,
+            mv: pv.first().copied(),
+        };
+        self.shared.tt.store(gm.zobrist_hash(), &entry);
+        self.pv = pv.clone();
+        best
+    }
+
+    // Fills `pv` with the best line when the score lands between `alpha` and `beta`
+    fn negamax(
+        &mut self,
+        gm: &mut GameManager,
+        depth: u8,
+        ply: u8,
+        mut alpha: Score,
+        beta: Score,
+        pv: &mut Vec<Move>,
+    ) -> Score {
+        self.nodes += 1;
+        if self.should_stop() {
+            return 0;
+        }
+        // Leaves are only evaluated, they aren't worth storing
+        let key = (depth > 0).then(|| gm.zobrist_hash());
+        let entry = key.and_then(|key| self.shared.tt.probe(key));
+        if let Some(entry) = entry.filter(|entry| entry.depth >= depth) {
+            let score = score_from_tt(entry.score, ply);
+            match entry.bound {
+                Bound::Exact => return score.clamp(alpha, beta),
+                Bound::Lower if score >= beta => return beta,
+                Bound::Upper if score <= alpha => return alpha,
+                _ => {}
+            }
+        }
+        let moves = self.ordered_moves(gm, ply, entry.and_then(|entry| entry.mv));
+        if moves.is_empty() {
+            // The rules tell who won, in Antichess the side left without moves does
+            return match gm.rules().status(gm).winner() {
+                Some(winner) if winner == gm.turn => MATE - ply as Score,
+                Some(_) => -MATE + ply as Score,
+                None => 0,
+            };
+        } else if depth == 0 {
+            return self.eval.evaluate(gm);
+        }
+        let mut line = vec![];
+        let mut best = None;
+        for mv in moves {
+            let quiet = captured_value(gm, &mv) == 0;
+            let undo = gm.make_move(&mv);
+            line.clear();
+            let score = -self.negamax(gm, depth - 1, ply + 1, -beta, -alpha, &mut line);
+            gm.unmake_move(undo);
+            if self.aborted {
+                return 0;
+            } else if score >= beta {
+                if quiet {
+                    self.store_cutoff(mv, depth, ply);
+                }
+                self.store(key, depth, ply, beta, Bound::Lower, Some(mv));
+                return beta;
+            } else if score > alpha {
+                alpha = score;
+                best = Some(mv);
+                pv.clear();
+                pv.push(mv);
+                pv.append(&mut line);
+            }
+        }
+        let bound = if best.is_some() {
+            Bound::Exact
+        } else {
+            Bound::Upper
+        };
+        self.store(key, depth, ply, alpha, bound, best);
+        alpha
+    }
+
+    fn store(
+        &self,
+        key: Option<u64>,
+        depth: u8,
+        ply: u8,
+        score: Score,
+        bound: Bound,
+        mv: Option<Move>,
+    ) {
+        if let Some(key) = key {
+            let entry = TtEntry {
+                depth,
+                score: score_to_tt(score, ply),
+                bound,
+                mv,
+            };
+            self.shared.tt.store(key, &entry);
+        }
+    }
+
+    fn store_cutoff(&mut self, mv: Move, depth: u8, ply: u8) {
+        let ply = ply as usize;
+        if self.killers.len() <= ply {
+            self.killers.resize(ply + 1, [None; 2]);
+        }
+        let killers = &mut self.killers[ply];
+        if killers[0] != Some(mv) {
+            killers[1] = killers[0];
+            killers[0] = Some(mv);
+        }
+        let (from, to) = square_indexes(&mv);
+        let entry = &mut self.history[from][to];
+        *entry = entry.saturating_add(depth as u32 * depth as u32);
+    }
+
+    // The move of the previous best line first, then the one stored in the transposition
+    // table, captures of the most valuable pieces, the killers and the quiet moves by their
+    // history
+    fn ordered_moves(&self, gm: &GameManager, ply: u8, hash_move: Option<Move>) -> Vec<Move> {
+        let killers = self.killers.get(ply as usize).copied().unwrap_or_default();
+        let pv = self.pv.get(ply as usize);
+        let mut moves = gm.legal_moves_iter().collect::<Vec<_>>();
+        moves.sort_by_cached_key(|mv| {
+            let captured = captured_value(gm, mv);
+            let key = if pv == Some(mv) {
+                (5, 0)
+            } else if hash_move == Some(*mv) {
+                (4, 0)
+            } else if captured > 0 {
+                (3, captured)
+            } else if killers[0] == Some(*mv) {
+                (2, 1)
+            } else if killers[1] == Some(*mv) {
+                (2, 0)
+            } else {
+                let (from, to) = square_indexes(mv);
+                (1, self.history[from][to])
+            };
+            std::cmp::Reverse(key)
+        });
+        moves
+    }
+}
+
+fn captured_value(gm: &GameManager, mv: &Move) -> u32 {
+    gm.pieces_of(gm.turn.opponent())
+        .iter()
+        .find(|p| Position::from_piece(p) == mv.to)
+        .map_or(0, |p| p.kind.material_value() + 1)
+}
+
+// Squares of a move from the search, always on the board
+fn square_indexes(mv: &Move) -> (usize, usize) {
+    let index = |pos: Position| pos.index().unwrap_or_default() as usize;
+    (index(mv.from), index(mv.to))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{atomic::AtomicBool, Arc},
+        time::Duration,
+    };
+
+    use super::{
+        best_move, best_move_with, mate_in, score_from_tt, score_to_tt, search, search_with_info,
+        Bound, SearchInfo, SearchLimits, Searcher, Shared, TranspositionTable, TtEntry, MATE,
+    };
+    use crate::{
+        antichess::Antichess,
+        eval::{ClassicalEval, Score},
+        game_manager::GameManager,
+        moves::Move,
+        piece::{Kind, Position},
+        square::Square,
+    };
+
+    #[test]
+    fn test_best_move() {
+        let gm = GameManager::from_fen("k7/8/1K6/8/8/8/8/7R w - - 0 1").unwrap();
+        assert_eq!(
+            Some((Move::new(Square::H1, Square::H8), MATE - 1)),
+            best_move(&gm, 2)
+        );
+        // Takes the hanging queen
+        let gm = GameManager::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            Some(Move::new(Square::D2, Square::D5)),
+            best_move(&gm, 3).map(|(mv, _)| mv)
+        );
+        // Mated side has no move
+        let gm = GameManager::from_fen("R3k3/8/4K3/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(None, best_move(&gm, 3));
+
+        // An evaluation that only likes pushing the a pawn
+        let gm = GameManager::new();
+        let a_pawn = |gm: &GameManager| {
+            let pushed = gm
+                .pieces_of(gm.turn.opponent())
+                .iter()
+                .filter(|p| p.column == 0 && p.row == 3)
+                .count();
+            -(pushed as Score)
+        };
+        assert_eq!(
+            Some(Move::new(Square::A2, Square::A4)),
+            best_move_with(&gm, 1, &a_pawn).map(|(mv, _)| mv)
+        );
+    }
+
+    #[test]
+    fn test_search_tablebase() {
+        // The mate is far beyond depth 1, the tables give the move and its score
+        let gm = GameManager::from_fen("8/8/3k4/8/8/8/8/K6Q w - - 0 1").unwrap();
+        let (mv, probe) = gm.tablebase_move().unwrap();
+        assert_eq!(
+            Some((mv, MATE - probe.dtm.unwrap() as Score)),
+            best_move(&gm, 1)
+        );
+        // Not in Antichess, where the queen is only a burden
+        let mut gm = gm;
+        gm.set_rules(Antichess);
+        assert_eq!(None, gm.tablebase_move());
+        assert!(best_move(&gm, 1).is_some_and(|(_, score)| mate_in(score).is_none()));
+    }
+
+    #[test]
+    fn test_search_antichess() {
+        // Taking the last black piece hands Black the win, yet the capture is forced
+        let mut gm = GameManager::from_fen("r7/8/8/8/8/8/8/R7 w - - 0 1").unwrap();
+        gm.set_rules(Antichess);
+        assert_eq!(
+            Some((Move::new(Square::A1, Square::A8), -MATE + 1)),
+            best_move(&gm, 2)
+        );
+    }
+
+    #[test]
+    fn test_search_info() {
+        let gm = GameManager::from_fen("k7/8/1K6/8/8/8/8/7R w - - 0 1").unwrap();
+        let mut infos = vec![];
+        let best = search_with_info(&gm, 3, &ClassicalEval, |info| infos.push(info.clone()));
+        assert_eq!(Some((Move::new(Square::H1, Square::H8), MATE - 1)), best);
+        assert_eq!(
+            vec![1, 2, 3],
+            infos.iter().map(|i| i.depth).collect::<Vec<_>>()
+        );
+        assert!(infos.windows(2).all(|w| w[0].nodes < w[1].nodes));
+        let last = infos.last().unwrap();
+        assert_eq!(vec![Move::new(Square::H1, Square::H8)], last.pv);
+        assert!(last
+            .to_uci()
+            .starts_with("info depth 3 score mate 1 nodes "));
+        assert!(last.to_uci().ends_with(" pv h1h8"));
+
+        let info = SearchInfo {
+            depth: 2,
+            score: -25,
+            nodes: 400,
+            nps: 80000,
+            time: Duration::from_millis(5),
+            pv: vec![
+                Move::new(Square::E2, Square::E4),
+                Move::new(Square::E7, Square::E5),
+            ],
+        };
+        assert_eq!(
+            "info depth 2 score cp -25 nodes 400 nps 80000 time 5 pv e2e4 e7e5",
+            info.to_uci()
+        );
+        let mated = SearchInfo {
+            score: -MATE + 2,
+            pv: vec![],
+            ..info
+        };
+        assert!(mated.to_uci().contains("score mate -1 "));
+    }
+
+    #[test]
+    fn test_search_limits() {
+        let gm = GameManager::new();
+        let depths = |limits: &SearchLimits| {
+            let mut depths = vec![];
+            let best = search(&gm, limits, &ClassicalEval, |info| depths.push(info.depth));
+            assert!(best.is_some());
+            depths
+        };
+        let nodes = SearchLimits {
+            nodes: Some(1),
+            ..SearchLimits::default()
+        };
+        assert_eq!(vec![1], depths(&nodes));
+        let stopped = SearchLimits {
+            stop: Some(Arc::new(AtomicBool::new(true))),
+            ..SearchLimits::default()
+        };
+        assert_eq!(vec![1], depths(&stopped));
+        let timed = SearchLimits {
+            depth: Some(3),
+            movetime: Some(Duration::from_secs(60)),
+            ..SearchLimits::default()
+        };
+        assert_eq!(vec![1, 2, 3], depths(&timed));
+    }
+
+    #[test]
+    fn test_move_ordering() {
+        let gm = GameManager::from_fen("4k3/8/8/3p4/8/8/8/R3K2R w - - 0 1").unwrap();
+        let eval = ClassicalEval;
+        let shared = Shared::new(16);
+        let mut searcher = Searcher::new(&eval, &shared);
+        let kingside = Move::new(Square::H1, Square::H7);
+        let queenside = Move::new(Square::A1, Square::A8);
+        searcher.store_cutoff(kingside, 2, 2);
+        searcher.store_cutoff(queenside, 1, 2);
+        let quiet = Move::new(Square::E1, Square::F1);
+        searcher.store_cutoff(quiet, 3, 4);
+
+        let moves = searcher.ordered_moves(&gm, 2, None);
+        assert_eq!(&[queenside, kingside], &moves[..2]);
+        // Killers of another ply don't count, the history does
+        let moves = searcher.ordered_moves(&gm, 3, None);
+        assert_eq!(&[quiet, kingside, queenside], &moves[..3]);
+        searcher.store_cutoff(queenside, 1, 2);
+        assert_eq!(
+            &[queenside, kingside],
+            &searcher.ordered_moves(&gm, 2, None)[..2]
+        );
+        // The move of the transposition table comes before the killers
+        let moves = searcher.ordered_moves(&gm, 2, Some(quiet));
+        assert_eq!(&[quiet, queenside, kingside], &moves[..3]);
+    }
+
+    #[test]
+    fn test_transposition_table() {
+        let tt = TranspositionTable::new(64);
+        let entry = TtEntry {
+            depth: 7,
+            score: -MATE + 3,
+            bound: Bound::Lower,
+            mv: Some(Move {
+                from: Position::from(Square::B7),
+                to: Position::from(Square::A8),
+                promotion: Some(Kind::Knight),
+            }),
+        };
+        tt.store(12345, &entry);
+        assert_eq!(Some(entry), tt.probe(12345));
+        // Same slot, another position
+        assert_eq!(None, tt.probe(12345 + 64));
+        let quiet = TtEntry {
+            score: 42,
+            bound: Bound::Exact,
+            mv: None,
+            ..entry
+        };
+        tt.store(12345 + 64, &quiet);
+        assert_eq!(Some(quiet), tt.probe(12345 + 64));
+        assert_eq!(None, tt.probe(12345));
+        assert_eq!(-MATE + 5, score_from_tt(score_to_tt(-MATE + 5, 3), 3));
+        assert_eq!(MATE - 1, score_from_tt(score_to_tt(MATE - 4, 4), 1));
+    }
+
+    #[test]
+    fn test_threads() {
+        let limits = SearchLimits {
+            depth: Some(4),
+            threads: Some(4),
+            ..SearchLimits::default()
+        };
+        let gm = GameManager::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1").unwrap();
+        let mut depths = vec![];
+        let best = search(&gm, &limits, &ClassicalEval, |info| depths.push(info.depth));
+        assert_eq!(
+            Some(Move::new(Square::D2, Square::D5)),
+            best.map(|(mv, _)| mv)
+        );
+        assert_eq!(vec![1, 2, 3, 4], depths);
+        let gm = GameManager::from_fen("k7/8/1K6/8/8/8/8/7R w - - 0 1").unwrap();
+        assert_eq!(
+            Some((Move::new(Square::H1, Square::H8), MATE - 1)),
+            search(&gm, &limits, &ClassicalEval, |_| {})
+        );
+    }
+}
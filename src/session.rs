@@ -0,0 +1,133 @@
+use std::future::Future;
+
+use tokio::time;
+
+use crate::{
+    game_manager::{GameManager, GameStatus},
+    moves::Move,
+    piece::Player,
+};
+
+// One side of a session, e.g. a network client or an engine
+pub trait PlayerHandle: Send {
+    // Move to play in the position of `gm`, `None` resigns
+    fn next_move(&mut self, gm: &GameManager) -> impl Future<Output = Option<Move>> + Send;
+}
+
+// Game loop asking each player for its move in turn. With a clock set on the game, a player
+// who doesn't answer before its time runs out loses on time.
+pub struct GameSession<W, B> {
+    gm: GameManager,
+    white: W,
+    black: B,
+}
+
+impl<W: PlayerHandle, B: PlayerHandle> GameSession<W, B> {
+    pub fn new(gm: GameManager, white: W, black: B) -> Self {
+        Self { gm, white, black }
+    }
+
+    pub fn game(&self) -> &GameManager {
+        &self.gm
+    }
+
+    pub fn into_game(self) -> GameManager {
+        self.gm
+    }
+
+    // Plays until the game is over. An illegal move forfeits the game as a resignation would.
+    pub async fn run(&mut self) -> GameStatus {
+        if let Some(clock) = &mut self.gm.clock {
+            if clock.running().is_none() {
+                clock.start(self.gm.turn);
+            }
+        }
+        let mut status = self.gm.status();
+        while !status.is_over() {
+            let player = self.gm.turn;
+            let answer = match player {
+                Player::White => ask(&mut self.white, &self.gm).await,
+                Player::Black => ask(&mut self.black, &self.gm).await,
+            };
+            // Out of time, the flag shows in the status
+            if let Some(mv) = answer {
+                let played = mv.is_some_and(|mv| self.gm.play(&mv).is_ok());
+                if !played {
+                    self.gm.resign(&player);
+                }
+            }
+            status = self.gm.status();
+        }
+        if let Some(clock) = &mut self.gm.clock {
+            clock.stop();
+        }
+        status
+    }
+}
+
+// Answer of `handle`, `None` when its clock ran out first
+async fn ask(handle: &mut impl PlayerHandle, gm: &GameManager) -> Option<Option<Move>> {
+    let remaining = gm.clock.as_ref().map(|clock| clock.remaining(&gm.turn));
+    match remaining {
+        Some(remaining) => time::timeout(remaining, handle.next_move(gm)).await.ok(),
+        None => Some(handle.next_move(gm).await),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{future::Future, time::Duration};
+
+    use super::{GameSession, PlayerHandle};
+    use crate::{
+        clock::{Clock, TimeControl},
+        game_manager::{GameManager, GameStatus},
+        moves::Move,
+        piece::Player,
+    };
+
+    // Plays its moves in order then resigns
+    struct Script(Vec<Move>);
+
+    impl PlayerHandle for Script {
+        fn next_move(&mut self, _: &GameManager) -> impl Future<Output = Option<Move>> + Send {
+            let mv = (!self.0.is_empty()).then(|| self.0.remove(0));
+            async move { mv }
+        }
+    }
+
+    // Never answers
+    struct Asleep;
+
+    impl PlayerHandle for Asleep {
+        fn next_move(&mut self, _: &GameManager) -> impl Future<Output = Option<Move>> + Send {
+            std::future::pending()
+        }
+    }
+
+    fn script(moves: &[&str]) -> Script {
+        Script(moves.iter().filter_map(|uci| Move::from_uci(uci)).collect())
+    }
+
+    #[tokio::test]
+    async fn test_game_session() {
+        let white = script(&["f2f3", "g2g4"]);
+        let black = script(&["e7e5", "d8h4"]);
+        let mut session = GameSession::new(GameManager::new(), white, black);
+        assert_eq!(GameStatus::Checkmate(Player::Black), session.run().await);
+        assert_eq!(4, session.game().history().len());
+
+        let mut session = GameSession::new(GameManager::new(), script(&["e2e5"]), Asleep);
+        assert_eq!(GameStatus::Resignation(Player::Black), session.run().await);
+
+        let mut gm = GameManager::new();
+        gm.clock = Some(Clock::new(TimeControl::sudden_death(
+            Duration::from_millis(20),
+        )));
+        let mut session = GameSession::new(gm, script(&["e2e4"]), Asleep);
+        assert_eq!(GameStatus::TimeForfeit(Player::White), session.run().await);
+        let gm = session.into_game();
+        assert_eq!(1, gm.history().len());
+        assert_eq!(None, gm.clock.unwrap().running());
+    }
+}
@@ -0,0 +1,209 @@
+use std::{error::Error, fmt};
+
+use shakmaty::{
+    fen::Fen, uci::UciMove, CastlingMode, Chess, EnPassantMode, Role, Square as ShakmatySquare,
+};
+
+use crate::{
+    fen::FenErr,
+    game_manager::GameManager,
+    moves::Move,
+    piece::{Kind, Position},
+    square::Square,
+};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ShakmatyErr {
+    // Square outside of the 8x8 board
+    OffBoard(Position),
+    // Drops and null moves have no equivalent
+    UnsupportedMove,
+    // Position shakmaty refuses, with its reason
+    InvalidPosition(String),
+    Fen(FenErr),
+}
+
+impl fmt::Display for ShakmatyErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShakmatyErr::OffBoard(pos) => write!(f, "{} is off the board", pos),
+            ShakmatyErr::UnsupportedMove => write!(f, "drops and null moves aren't supported"),
+            ShakmatyErr::InvalidPosition(reason) => write!(f, "invalid position: {}", reason),
+            ShakmatyErr::Fen(err) => err.fmt(f),
+        }
+    }
+}
+
+impl Error for ShakmatyErr {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ShakmatyErr::Fen(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<Position> for ShakmatySquare {
+    type Error = ShakmatyErr;
+
+    fn try_from(pos: Position) -> Result<Self, Self::Error> {
+        let square = Square::try_from(pos).map_err(|_| ShakmatyErr::OffBoard(pos))?;
+        // Both number the squares from a1 to h8, rank by rank
+        Ok(ShakmatySquare::new(u32::from(square.index())))
+    }
+}
+
+impl From<ShakmatySquare> for Position {
+    fn from(square: ShakmatySquare) -> Self {
+        Position::new(square.rank().to_u32() as u8, square.file().to_u32() as u8)
+    }
+}
+
+impl From<Kind> for Role {
+    fn from(kind: Kind) -> Self {
+        match kind {
+            Kind::Pawn => Role::Pawn,
+            Kind::Knight => Role::Knight,
+            Kind::Bishop => Role::Bishop,
+            Kind::Rook => Role::Rook,
+            Kind::Queen => Role::Queen,
+            Kind::King => Role::King,
+        }
+    }
+}
+
+impl From<Role> for Kind {
+    fn from(role: Role) -> Self {
+        match role {
+            Role::Pawn => Kind::Pawn,
+            Role::Knight => Kind::Knight,
+            Role::Bishop => Kind::Bishop,
+            Role::Rook => Kind::Rook,
+            Role::Queen => Kind::Queen,
+            Role::King => Kind::King,
+        }
+    }
+}
+
+impl TryFrom<Move> for UciMove {
+    type Error = ShakmatyErr;
+
+    fn try_from(mv: Move) -> Result<Self, Self::Error> {
+        Ok(UciMove::Normal {
+            from: mv.from.try_into()?,
+            to: mv.to.try_into()?,
+            promotion: mv.promotion.map(Role::from),
+        })
+    }
+}
+
+impl TryFrom<UciMove> for Move {
+    type Error = ShakmatyErr;
+
+    fn try_from(uci: UciMove) -> Result<Self, Self::Error> {
+        match uci {
+            UciMove::Normal {
+                from,
+                to,
+                promotion,
+            } => Ok(Move {
+                from: from.into(),
+                to: to.into(),
+                promotion: promotion.map(Kind::from),
+            }),
+            UciMove::Put { .. } | UciMove::Null => Err(ShakmatyErr::UnsupportedMove),
+        }
+    }
+}
+
+// Castling becomes the two squares king step of standard chess, convert through
+// `UciMove::from_move` with `CastlingMode::Chess960` for Fischer Random games
+impl TryFrom<shakmaty::Move> for Move {
+    type Error = ShakmatyErr;
+
+    fn try_from(mv: shakmaty::Move) -> Result<Self, Self::Error> {
+        UciMove::from_standard(mv).try_into()
+    }
+}
+
+impl TryFrom<&GameManager> for Chess {
+    type Error = ShakmatyErr;
+
+    fn try_from(gm: &GameManager) -> Result<Self, Self::Error> {
+        let fen = Fen::from_ascii(gm.to_fen().as_bytes())
+            .map_err(|err| ShakmatyErr::InvalidPosition(err.to_string()))?;
+        let mode = if gm.is_chess960() {
+            CastlingMode::Chess960
+        } else {
+            CastlingMode::Standard
+        };
+        fen.into_position(mode)
+            .map_err(|err| ShakmatyErr::InvalidPosition(err.to_string()))
+    }
+}
+
+impl TryFrom<&Chess> for GameManager {
+    type Error = ShakmatyErr;
+
+    fn try_from(pos: &Chess) -> Result<Self, Self::Error> {
+        let fen = Fen::from_position(pos, EnPassantMode::Legal);
+        GameManager::from_fen(&fen.to_string()).map_err(ShakmatyErr::Fen)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use shakmaty::{uci::UciMove, Chess, Position as _, Role, Square as ShakmatySquare};
+
+    use super::ShakmatyErr;
+    use crate::{
+        game_manager::GameManager,
+        moves::Move,
+        piece::{Kind, Position},
+        square::Square,
+    };
+
+    #[test]
+    fn test_shakmaty_conversions() {
+        assert_eq!(
+            Ok(ShakmatySquare::E4),
+            ShakmatySquare::try_from(Position::from(Square::E4))
+        );
+        assert_eq!(Position::from(Square::H7), ShakmatySquare::H7.into());
+        assert_eq!(
+            Err(ShakmatyErr::OffBoard(Position::new(8, 0))),
+            ShakmatySquare::try_from(Position::new(8, 0))
+        );
+        assert_eq!(Kind::Knight, Role::from(Kind::Knight).into());
+
+        let mv = Move::with_promotion(Square::B7, Square::A8, Kind::Queen);
+        let uci = UciMove::try_from(mv).unwrap();
+        assert_eq!("b7a8q", uci.to_string());
+        assert_eq!(Ok(mv), Move::try_from(uci));
+        assert_eq!(
+            Err(ShakmatyErr::UnsupportedMove),
+            Move::try_from(UciMove::Null)
+        );
+
+        let fen = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+        let gm = GameManager::from_fen(fen).unwrap();
+        let pos = Chess::try_from(&gm).unwrap();
+        let moves = pos.legal_moves();
+        assert_eq!(gm.legal_moves().len(), moves.len());
+        for mv in moves {
+            assert!(gm.legal_moves().contains(&Move::try_from(mv).unwrap()));
+        }
+        let castle = UciMove::from_ascii(b"e1g1").unwrap().to_move(&pos).unwrap();
+        let pos = pos.play(castle).unwrap();
+        let mut expected = gm.clone();
+        expected.play(&Move::new(Square::E1, Square::G1)).unwrap();
+        assert_eq!(
+            expected.to_fen(),
+            GameManager::try_from(&pos).unwrap().to_fen()
+        );
+
+        let gm = GameManager::chess960(0).unwrap();
+        let pos = Chess::try_from(&gm).unwrap();
+        assert_eq!(gm, GameManager::try_from(&pos).unwrap());
+    }
+}
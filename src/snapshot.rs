@@ -0,0 +1,92 @@
+#[cfg(feature = "clock")]
+use crate::clock::{Clock, ClockState};
+use crate::game_manager::GameManager;
+
+// Whole state of a game in progress, to adjourn it and resume it later. With the serde
+// feature it can be stored anywhere. Variant rules aren't serialized, they have to be set
+// again on the restored game, as do the listeners and pending premoves.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameSnapshot {
+    // Position, history and draw offer
+    pub game: GameManager,
+    #[cfg(feature = "clock")]
+    pub clock: Option<ClockState>,
+}
+
+impl GameManager {
+    // The clock of the side to move keeps running, the time spent until the game is
+    // restored isn't debited.
+    pub fn snapshot(&self) -> GameSnapshot {
+        GameSnapshot {
+            game: GameManager {
+                #[cfg(feature = "clock")]
+                clock: None,
+                ..self.clone()
+            },
+            #[cfg(feature = "clock")]
+            clock: self.clock.as_ref().map(Clock::state),
+        }
+    }
+
+    pub fn restore(snapshot: GameSnapshot) -> GameManager {
+        GameManager {
+            #[cfg(feature = "clock")]
+            clock: snapshot.clock.map(Clock::from_state),
+            ..snapshot.game
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "clock")]
+    use std::time::Duration;
+
+    #[cfg(feature = "clock")]
+    use crate::clock::{Clock, TimeControl};
+    use crate::{
+        game_manager::{GameManager, GameStatus},
+        moves::Move,
+        piece::Player,
+    };
+
+    #[test]
+    fn test_snapshot_restore() {
+        let mut gm = GameManager::new();
+        #[cfg(feature = "clock")]
+        {
+            gm.clock = Some(Clock::new(TimeControl::fischer(
+                Duration::from_secs(300),
+                Duration::from_secs(3),
+            )));
+        }
+        for uci in ["e2e4", "e7e5", "g1f3"] {
+            gm.play(&Move::from_uci(uci).unwrap()).unwrap();
+        }
+        assert!(gm.offer_draw(&Player::White));
+        let snapshot = gm.snapshot();
+        #[cfg(feature = "serde")]
+        let snapshot = serde_json::from_str(&serde_json::to_string(&snapshot).unwrap()).unwrap();
+
+        let mut restored = GameManager::restore(snapshot);
+        assert_eq!(gm.to_fen(), restored.to_fen());
+        assert_eq!(3, restored.history().len());
+        assert_eq!(Some(Player::White), restored.draw_offer());
+        #[cfg(feature = "clock")]
+        {
+            let clock = restored.clock.as_ref().unwrap();
+            assert_eq!(Some(Player::Black), clock.running());
+            assert_eq!(2, clock.moves(&Player::White));
+            let white = clock.remaining(&Player::White);
+            assert!(white > Duration::from_secs(305) && white <= Duration::from_secs(306));
+        }
+        // Black moves on instead of answering
+        restored.play(&Move::from_uci("b8c6").unwrap()).unwrap();
+        assert_eq!(None, restored.draw_offer());
+        assert!(restored.offer_draw(&Player::Black));
+        assert!(!restored.offer_draw(&Player::White));
+        assert!(restored.agree_draw());
+        assert_eq!(GameStatus::DrawByAgreement, restored.status());
+    }
+}
@@ -0,0 +1,258 @@
+use crate::piece::{Position, MAX_COLUMN, MAX_ROW};
+
+// Column of the board, from A (0) to H (7)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct File(u8);
+
+// Row of the board, from FIRST (0) to EIGHTH (7)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rank(u8);
+
+// Shade of a square, a1 being dark
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SquareColor {
+    Light,
+    Dark,
+}
+
+impl File {
+    pub const A: File = File(0);
+    pub const B: File = File(1);
+    pub const C: File = File(2);
+    pub const D: File = File(3);
+    pub const E: File = File(4);
+    pub const F: File = File(5);
+    pub const G: File = File(6);
+    pub const H: File = File(7);
+
+    pub fn new(index: u8) -> Option<Self> {
+        (index < MAX_COLUMN).then_some(File(index))
+    }
+
+    pub fn index(&self) -> u8 {
+        self.0
+    }
+
+    // File `delta` columns away, None when it falls off the board
+    pub fn offset(&self, delta: i8) -> Option<Self> {
+        self.0.checked_add_signed(delta).and_then(File::new)
+    }
+
+    pub fn distance(&self, other: File) -> u8 {
+        self.0.abs_diff(other.0)
+    }
+
+    pub fn to_char(&self) -> char {
+        (b'a' + self.0) as char
+    }
+
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            'a'..='h' => File::new(c as u8 - b'a'),
+            _ => None,
+        }
+    }
+
+    // Every file from A to H
+    pub fn all() -> impl DoubleEndedIterator<Item = File> {
+        (0..MAX_COLUMN).map(File)
+    }
+}
+
+impl Rank {
+    pub const FIRST: Rank = Rank(0);
+    pub const SECOND: Rank = Rank(1);
+    pub const THIRD: Rank = Rank(2);
+    pub const FOURTH: Rank = Rank(3);
+    pub const FIFTH: Rank = Rank(4);
+    pub const SIXTH: Rank = Rank(5);
+    pub const SEVENTH: Rank = Rank(6);
+    pub const EIGHTH: Rank = Rank(7);
+
+    pub fn new(index: u8) -> Option<Self> {
+        (index < MAX_ROW).then_some(Rank(index))
+    }
+
+    pub fn index(&self) -> u8 {
+        self.0
+    }
+
+    // Rank `delta` rows away, None when it falls off the board
+    pub fn offset(&self, delta: i8) -> Option<Self> {
+        self.0.checked_add_signed(delta).and_then(Rank::new)
+    }
+
+    pub fn distance(&self, other: Rank) -> u8 {
+        self.0.abs_diff(other.0)
+    }
+
+    pub fn to_char(&self) -> char {
+        (b'1' + self.0) as char
+    }
+
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            '1'..='8' => Rank::new(c as u8 - b'1'),
+            _ => None,
+        }
+    }
+
+    // Every rank from FIRST to EIGHTH
+    pub fn all() -> impl DoubleEndedIterator<Item = Rank> {
+        (0..MAX_ROW).map(Rank)
+    }
+}
+
+impl From<File> for u8 {
+    fn from(file: File) -> Self {
+        file.0
+    }
+}
+
+impl From<Rank> for u8 {
+    fn from(rank: Rank) -> Self {
+        rank.0
+    }
+}
+
+// Squares indexed from 0 (a1) to 63 (h8), rank by rank
+#[rustfmt::skip]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Square {
+    A1, B1, C1, D1, E1, F1, G1, H1,
+    A2, B2, C2, D2, E2, F2, G2, H2,
+    A3, B3, C3, D3, E3, F3, G3, H3,
+    A4, B4, C4, D4, E4, F4, G4, H4,
+    A5, B5, C5, D5, E5, F5, G5, H5,
+    A6, B6, C6, D6, E6, F6, G6, H6,
+    A7, B7, C7, D7, E7, F7, G7, H7,
+    A8, B8, C8, D8, E8, F8, G8, H8,
+}
+
+impl Square {
+    #[rustfmt::skip]
+    pub const ALL: [Square; 64] = {
+        use Square::*;
+        [
+            A1, B1, C1, D1, E1, F1, G1, H1,
+            A2, B2, C2, D2, E2, F2, G2, H2,
+            A3, B3, C3, D3, E3, F3, G3, H3,
+            A4, B4, C4, D4, E4, F4, G4, H4,
+            A5, B5, C5, D5, E5, F5, G5, H5,
+            A6, B6, C6, D6, E6, F6, G6, H6,
+            A7, B7, C7, D7, E7, F7, G7, H7,
+            A8, B8, C8, D8, E8, F8, G8, H8,
+        ]
+    };
+
+    pub fn from_index(index: u8) -> Option<Self> {
+        Self::ALL.get(index as usize).copied()
+    }
+
+    pub fn from_row_column(row: u8, column: u8) -> Option<Self> {
+        if row >= MAX_ROW || column >= MAX_COLUMN {
+            return None;
+        }
+        Self::from_index(row * MAX_COLUMN + column)
+    }
+
+    pub fn index(&self) -> u8 {
+        *self as u8
+    }
+
+    pub fn row(&self) -> u8 {
+        self.index() / MAX_COLUMN
+    }
+
+    pub fn column(&self) -> u8 {
+        self.index() % MAX_COLUMN
+    }
+
+    pub fn file(&self) -> File {
+        File(self.column())
+    }
+
+    pub fn rank(&self) -> Rank {
+        Rank(self.row())
+    }
+
+    pub fn from_file_rank(file: File, rank: Rank) -> Self {
+        Self::ALL[(rank.0 * MAX_COLUMN + file.0) as usize]
+    }
+}
+
+impl From<Square> for Position {
+    fn from(square: Square) -> Self {
+        Position::new(square.row(), square.column())
+    }
+}
+
+impl TryFrom<Position> for Square {
+    type Error = Position;
+
+    fn try_from(pos: Position) -> Result<Self, Self::Error> {
+        Square::from_row_column(pos.row, pos.column).ok_or(pos)
+    }
+}
+
+impl From<Square> for (u8, u8) {
+    fn from(square: Square) -> Self {
+        (square.row(), square.column())
+    }
+}
+
+impl From<Square> for u8 {
+    fn from(square: Square) -> Self {
+        square.index()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{File, Rank, Square};
+    use crate::piece::Position;
+
+    #[test]
+    fn test_file_rank_helpers() {
+        let files: Vec<char> = File::all().map(|f| f.to_char()).collect();
+        assert_eq!(vec!['a', 'b', 'c', 'd', 'e', 'f', 'g', 'h'], files);
+        assert_eq!(8, Rank::all().count());
+        assert_eq!(Some(File::C), File::A.offset(2));
+        assert_eq!(None, File::H.offset(1));
+        assert_eq!(None, Rank::FIRST.offset(-1));
+        assert_eq!(Some(Rank::SIXTH), Rank::EIGHTH.offset(-2));
+        assert_eq!(5, File::B.distance(File::G));
+        assert_eq!(Some(File::E), File::from_char('e'));
+        assert_eq!(Some(Rank::FOURTH), Rank::from_char('4'));
+        assert_eq!(None, Rank::from_char('9'));
+        assert_eq!(Square::E4, Square::from_file_rank(File::E, Rank::FOURTH));
+        assert_eq!(Some(File::E), Position::from(Square::E4).file());
+        assert_eq!(Some(Rank::FOURTH), Position::from(Square::E4).rank());
+    }
+
+    #[test]
+    fn test_square_index_round_trip() {
+        for index in 0..64 {
+            let square = Square::from_index(index).unwrap();
+            assert_eq!(index, square.index());
+            let pos = Position::from(square);
+            assert_eq!(Ok(square), Square::try_from(pos));
+            assert_eq!(index, pos.row * 8 + pos.column);
+        }
+        assert_eq!(None, Square::from_index(64));
+        assert!(Square::try_from(Position::new(8, 0)).is_err());
+    }
+
+    #[test]
+    fn test_square_coordinates() {
+        assert_eq!((0, 0), Square::A1.into());
+        assert_eq!((0, 7), Square::H1.into());
+        assert_eq!((3, 4), Square::E4.into());
+        assert_eq!((7, 7), Square::H8.into());
+        assert_eq!(Some(Square::D5), Square::from_row_column(4, 3));
+    }
+}
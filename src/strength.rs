@@ -0,0 +1,133 @@
+use crate::{
+    eval::{ClassicalEval, Evaluator, Score},
+    game_manager::{uniform_below, GameManager},
+    moves::Move,
+    search::root_scores,
+};
+
+// How well the built-in engine plays, for opponents beginners can beat
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Strength {
+    pub depth: u8,
+    // Any move scoring this close to the best one may be played
+    pub margin: Score,
+    // Largest error added to every evaluation
+    pub noise: Score,
+}
+
+pub const MAX_LEVEL: u8 = 10;
+
+impl Strength {
+    // From 0, a one ply search making big mistakes, to `MAX_LEVEL`, the plain search
+    pub fn level(level: u8) -> Self {
+        let level = level.min(MAX_LEVEL);
+        let weakness = (MAX_LEVEL - level) as Score;
+        Self {
+            depth: 1 + level * 4 / MAX_LEVEL,
+            margin: weakness * 30,
+            noise: weakness * 20,
+        }
+    }
+
+    pub fn full() -> Self {
+        Self::level(MAX_LEVEL)
+    }
+}
+
+// Evaluation off by up to `noise` centipawns, always by the same amount for a position
+struct Noisy<'a, E: ?Sized> {
+    eval: &'a E,
+    noise: Score,
+    seed: u64,
+}
+
+impl<E: Evaluator + ?Sized> Evaluator for Noisy<'_, E> {
+    fn evaluate(&self, gm: &GameManager) -> Score {
+        let spread = 2 * self.noise as u64 + 1;
+        let error = ((gm.zobrist_hash() ^ self.seed) % spread) as Score - self.noise;
+        self.eval.evaluate(gm) + error
+    }
+}
+
+// Move played at `strength` with the classical evaluation, `rng` has to return uniformly
+// distributed numbers
+pub fn move_at_strength(
+    gm: &GameManager,
+    strength: &Strength,
+    rng: &mut impl FnMut() -> u64,
+) -> Option<(Move, Score)> {
+    move_at_strength_with(gm, strength, &ClassicalEval, rng)
+}
+
+pub fn move_at_strength_with<E: Evaluator + ?Sized>(
+    gm: &GameManager,
+    strength: &Strength,
+    eval: &E,
+    rng: &mut impl FnMut() -> u64,
+) -> Option<(Move, Score)> {
+    let noisy = Noisy {
+        eval,
+        noise: strength.noise.max(0),
+        seed: rng(),
+    };
+    let scores = root_scores(gm, strength.depth, &noisy);
+    let best = scores.iter().map(|(_, score)| *score).max()?;
+    let candidates: Vec<_> = scores
+        .into_iter()
+        .filter(|(_, score)| *score >= best - strength.margin.max(0))
+        .collect();
+    Some(candidates[uniform_below(rng, candidates.len() as u64) as usize])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::{move_at_strength, Strength};
+    use crate::{game_manager::GameManager, moves::Move, search::MATE, square::Square};
+
+    #[test]
+    fn test_strength_levels() {
+        assert_eq!(
+            Strength {
+                depth: 5,
+                margin: 0,
+                noise: 0
+            },
+            Strength::full()
+        );
+        assert_eq!(1, Strength::level(0).depth);
+        assert_eq!(Strength::full(), Strength::level(200));
+
+        let mut value = 0u64;
+        let mut rng = || {
+            value = value
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            value >> 33
+        };
+        let gm = GameManager::from_fen("k7/8/1K6/8/8/8/8/7R w - - 0 1").unwrap();
+        let strong = Strength {
+            depth: 2,
+            ..Strength::full()
+        };
+        assert_eq!(
+            Some((Move::new(Square::H1, Square::H8), MATE - 1)),
+            move_at_strength(&gm, &strong, &mut rng)
+        );
+
+        // The weakest level doesn't always play the same move
+        let gm = GameManager::new();
+        let moves: HashSet<_> = (0..20)
+            .map(|_| {
+                move_at_strength(&gm, &Strength::level(0), &mut rng)
+                    .unwrap()
+                    .0
+            })
+            .map(|mv| mv.to_uci())
+            .collect();
+        assert!(moves.len() > 1);
+        let mated = GameManager::from_fen("R3k3/8/4K3/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(None, move_at_strength(&mated, &Strength::full(), &mut rng));
+    }
+}
@@ -0,0 +1,201 @@
+use std::fmt::Write;
+
+use crate::{
+    game_manager::GameManager,
+    perspective::Perspective,
+    piece::{BoardSize, Player, Position},
+};
+
+const LIGHT: &str = "#f0d9b5";
+const DARK: &str = "#b58863";
+const HIGHLIGHT: &str = "#f7ec5e";
+const ARROW: &str = "#15781b";
+
+// SVG diagram of a position, pieces are drawn with their Unicode glyphs
+#[derive(Clone, Debug)]
+pub struct SvgRenderer {
+    perspective: Player,
+    square_size: u32,
+    highlights: Vec<Position>,
+    arrows: Vec<(Position, Position)>,
+}
+
+impl SvgRenderer {
+    pub fn new() -> Self {
+        Self {
+            perspective: Player::White,
+            square_size: 45,
+            highlights: vec![],
+            arrows: vec![],
+        }
+    }
+
+    // Side shown at the bottom of the board
+    pub fn perspective(mut self, player: Player) -> Self {
+        self.perspective = player;
+        self
+    }
+
+    pub fn square_size(mut self, size: u32) -> Self {
+        self.square_size = size;
+        self
+    }
+
+    pub fn highlight<P: Into<Position>>(mut self, squares: impl IntoIterator<Item = P>) -> Self {
+        self.highlights.extend(squares.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn arrow(mut self, from: impl Into<Position>, to: impl Into<Position>) -> Self {
+        self.arrows.push((from.into(), to.into()));
+        self
+    }
+
+    // Top left corner of `pos` in the picture
+    fn corner(&self, board: BoardSize, pos: &Position) -> (u32, u32) {
+        let cell = Perspective::new(self.perspective, board).to_screen(*pos);
+        (
+            u32::from(cell.x) * self.square_size,
+            u32::from(cell.y) * self.square_size,
+        )
+    }
+
+    fn center(&self, board: BoardSize, pos: &Position) -> (u32, u32) {
+        let (x, y) = self.corner(board, pos);
+        (x + self.square_size / 2, y + self.square_size / 2)
+    }
+
+    pub fn render(&self, gm: &GameManager) -> String {
+        let size = self.square_size;
+        let board = gm.board_size();
+        let width = size * u32::from(board.columns());
+        let height = size * u32::from(board.rows());
+        let mut svg = String::new();
+        // Writing to a String never fails
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+            w = width,
+            h = height
+        );
+        for row in 0..board.rows() {
+            for column in 0..board.columns() {
+                let pos = Position::new(row, column);
+                let (x, y) = self.corner(board, &pos);
+                let color = match (row + column) % 2 {
+                    0 => DARK,
+                    _ => LIGHT,
+                };
+                let _ = writeln!(
+                    svg,
+                    r#"<rect x="{}" y="{}" width="{s}" height="{s}" fill="{}"/>"#,
+                    x,
+                    y,
+                    color,
+                    s = size
+                );
+                if self.highlights.contains(&pos) {
+                    let _ = writeln!(
+                        svg,
+                        r#"<rect class="highlight" x="{}" y="{}" width="{s}" height="{s}" fill="{}" fill-opacity="0.6"/>"#,
+                        x,
+                        y,
+                        HIGHLIGHT,
+                        s = size
+                    );
+                }
+            }
+        }
+        for (_, piece) in gm.pieces() {
+            let (x, y) = self.center(board, &Position::from_piece(piece));
+            let _ = writeln!(
+                svg,
+                r#"<text x="{}" y="{}" font-size="{}" text-anchor="middle" dominant-baseline="central">{}</text>"#,
+                x,
+                y,
+                size * 4 / 5,
+                piece.to_unicode()
+            );
+        }
+        if !self.arrows.is_empty() {
+            let _ = writeln!(
+                svg,
+                r#"<defs><marker id="arrowhead" markerWidth="4" markerHeight="4" refX="2" refY="2" orient="auto"><path d="M0,0 L4,2 L0,4 z" fill="{}"/></marker></defs>"#,
+                ARROW
+            );
+        }
+        for (from, to) in &self.arrows {
+            let (x1, y1) = self.center(board, from);
+            let (x2, y2) = self.center(board, to);
+            let _ = writeln!(
+                svg,
+                r#"<line class="arrow" x1="{}" y1="{}" x2="{}" y2="{}" stroke="{}" stroke-width="{}" stroke-opacity="0.8" marker-end="url(#arrowhead)"/>"#,
+                x1,
+                y1,
+                x2,
+                y2,
+                ARROW,
+                size / 6
+            );
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    // One diagram per position of the game history, the last move being highlighted
+    pub fn frames<'a>(&'a self, gm: &'a GameManager) -> impl Iterator<Item = String> + 'a {
+        gm.replay().map(move |(position, mv)| {
+            let mut renderer = self.clone();
+            renderer
+                .highlights
+                .extend(mv.iter().flat_map(|mv| [mv.from, mv.to]));
+            renderer.render(&position)
+        })
+    }
+}
+
+impl Default for SvgRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SvgRenderer;
+    use crate::{game_manager::GameManager, moves::Move, piece::Player, square::Square};
+
+    #[test]
+    fn test_svg_render() {
+        let gm = GameManager::new();
+        let svg = SvgRenderer::new()
+            .square_size(10)
+            .highlight([Square::E2])
+            .arrow(Square::E2, Square::E4)
+            .render(&gm);
+        assert!(
+            svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="80" height="80""#)
+        );
+        assert!(svg.ends_with("</svg>\n"));
+        assert_eq!(64 + 1, svg.matches("<rect").count());
+        assert_eq!(32, svg.matches("<text").count());
+        // e2 is the fifth column of the seventh row from the top
+        assert!(svg.contains(r#"<rect class="highlight" x="40" y="60""#));
+        assert!(svg.contains(r#"x1="45" y1="65" x2="45" y2="45""#));
+        assert!(svg.contains(r#"<text x="45" y="5" font-size="8" text-anchor="middle" dominant-baseline="central">♚</text>"#));
+
+        let flipped = SvgRenderer::new()
+            .square_size(10)
+            .perspective(Player::Black)
+            .render(&gm);
+        assert!(flipped.contains(r#"<text x="35" y="75" font-size="8" text-anchor="middle" dominant-baseline="central">♚</text>"#));
+        assert!(!flipped.contains("arrowhead"));
+
+        let mut gm = GameManager::new();
+        gm.play(&Move::new(Square::E2, Square::E4)).unwrap();
+        let frames: Vec<String> = SvgRenderer::new().frames(&gm).collect();
+        assert_eq!(2, frames.len());
+        assert_eq!(0, frames[0].matches("highlight").count());
+        assert_eq!(2, frames[1].matches("highlight").count());
+    }
+}
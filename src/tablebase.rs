@@ -0,0 +1,612 @@
+use std::sync::OnceLock;
+
+use crate::{
+    game_manager::GameManager,
+    moves::Move,
+    piece::{BoardSize, Kind, Player, Position},
+};
+
+// Endings the tables are built for, the strong side having the pieces besides its king
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Material {
+    Kqk,
+    Krk,
+    Kpk,
+    Kbnk,
+}
+
+impl Material {
+    pub const ALL: [Material; 4] = [Material::Kqk, Material::Krk, Material::Kpk, Material::Kbnk];
+
+    // Pieces of the strong side besides its king, in the order they are indexed
+    pub fn pieces(&self) -> &'static [Kind] {
+        match self {
+            Material::Kqk => &[Kind::Queen],
+            Material::Krk => &[Kind::Rook],
+            Material::Kpk => &[Kind::Pawn],
+            Material::Kbnk => &[Kind::Bishop, Kind::Knight],
+        }
+    }
+
+    fn of(pieces: &[Kind]) -> Option<Material> {
+        Material::ALL.into_iter().find(|m| m.pieces() == pieces)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Wdl {
+    Win,
+    Draw,
+    Loss,
+}
+
+// Outcome with perfect play for the side to move, with the plies left until mate
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Probe {
+    pub wdl: Wdl,
+    pub dtm: Option<u8>,
+}
+
+// Exact results of every position of an ending, built by retrograde analysis: from the mates
+// back to the positions leading to them, one ply at a time. The strong side is indexed as
+// White with its pawns going up, the positions are mirrored when Black has the pieces.
+#[derive(Clone, Debug)]
+pub struct Tablebase {
+    material: Material,
+    // Plies to mate plus one for each position, odd plies winning for the side to move, 0
+    // for draws
+    values: Vec<u8>,
+}
+
+const ILLEGAL: u8 = u8::MAX;
+// Square of a piece taken off the board
+const NONE: u8 = 64;
+const KING_STEPS: [(i8, i8); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+const KNIGHT_STEPS: [(i8, i8); 8] = [
+    (-2, -1),
+    (-2, 1),
+    (-1, -2),
+    (-1, 2),
+    (1, -2),
+    (1, 2),
+    (2, -1),
+    (2, 1),
+];
+
+// Strong king, weak king, then the other pieces in the order of `Material::pieces`
+type Squares = [u8; 4];
+
+static TABLES: [OnceLock<Tablebase>; 4] = [
+    OnceLock::new(),
+    OnceLock::new(),
+    OnceLock::new(),
+    OnceLock::new(),
+];
+
+impl Tablebase {
+    // Table of `material`, generated on first use then kept for the whole process. KBNK has
+    // 33 million positions and takes a while, the other ones are quick.
+    pub fn get(material: Material) -> &'static Tablebase {
+        let index = Material::ALL.iter().position(|m| *m == material);
+        TABLES[index.unwrap_or(0)].get_or_init(|| Tablebase::generate(material))
+    }
+
+    pub fn generate(material: Material) -> Tablebase {
+        Generator::new(material).run()
+    }
+
+    pub fn material(&self) -> Material {
+        self.material
+    }
+
+    // `None` when the position isn't of this ending or castling is still possible
+    pub fn probe(&self, gm: &GameManager) -> Option<Probe> {
+        let (material, index) = table_index(gm)?;
+        (material == self.material).then(|| self.probe_index(index))?
+    }
+
+    fn probe_index(&self, index: usize) -> Option<Probe> {
+        match self.values[index] {
+            ILLEGAL => None,
+            0 => Some(Probe {
+                wdl: Wdl::Draw,
+                dtm: None,
+            }),
+            value => {
+                let dtm = value - 1;
+                let wdl = if dtm % 2 == 1 { Wdl::Win } else { Wdl::Loss };
+                Some(Probe {
+                    wdl,
+                    dtm: Some(dtm),
+                })
+            }
+        }
+    }
+}
+
+// Result of `gm` from the tables, generating the one needed on first use. Positions left
+// without mating material count as draws. The tables only hold for standard chess.
+pub fn probe(gm: &GameManager) -> Option<Probe> {
+    if !gm.rules().is_standard() {
+        return None;
+    }
+    if !gm.has_mating_material(&Player::White) && !gm.has_mating_material(&Player::Black) {
+        return Some(Probe {
+            wdl: Wdl::Draw,
+            dtm: None,
+        });
+    }
+    let (material, index) = table_index(gm)?;
+    Tablebase::get(material).probe_index(index)
+}
+
+impl GameManager {
+    // Move keeping the best result the tables promise: the fastest mate when winning, the
+    // longest resistance when losing. `None` outside of the endings of the tables.
+    pub fn tablebase_move(&self) -> Option<(Move, Probe)> {
+        probe(self)?;
+        let mut gm = self.clone();
+        let mut best: Option<(Move, Probe, i32)> = None;
+        for mv in self.legal_moves() {
+            let undo = gm.make_move(&mv);
+            let reply = probe(&gm);
+            gm.unmake_move(undo);
+            let reply = reply?;
+            // Higher is better for the side to move
+            let rank = match (reply.wdl, reply.dtm) {
+                (Wdl::Loss, Some(dtm)) => 1000 - dtm as i32,
+                (Wdl::Win, Some(dtm)) => -1000 + dtm as i32,
+                _ => 0,
+            };
+            if best.is_none_or(|(_, _, best)| rank > best) {
+                let probe = match reply.wdl {
+                    Wdl::Loss => Probe {
+                        wdl: Wdl::Win,
+                        dtm: reply.dtm.map(|dtm| dtm + 1),
+                    },
+                    Wdl::Win => Probe {
+                        wdl: Wdl::Loss,
+                        dtm: reply.dtm.map(|dtm| dtm + 1),
+                    },
+                    Wdl::Draw => reply,
+                };
+                best = Some((mv, probe, rank));
+            }
+        }
+        best.map(|(mv, probe, _)| (mv, probe))
+    }
+}
+
+fn table_index(gm: &GameManager) -> Option<(Material, usize)> {
+    if gm.board_size() != BoardSize::STANDARD
+        || gm.castling.white_king_side
+        || gm.castling.white_queen_side
+        || gm.castling.black_king_side
+        || gm.castling.black_queen_side
+    {
+        return None;
+    }
+    let (strong, weak) = if gm.pieces_of(Player::Black).len() == 1 {
+        (Player::White, Player::Black)
+    } else {
+        (Player::Black, Player::White)
+    };
+    if gm.pieces_of(weak).len() != 1 {
+        return None;
+    }
+    let mut pieces: Vec<_> = gm
+        .pieces_of(strong)
+        .iter()
+        .filter(|p| p.kind != Kind::King)
+        .map(|p| p.kind)
+        .collect();
+    pieces.sort_by_key(|kind| {
+        Material::ALL
+            .iter()
+            .flat_map(|m| m.pieces())
+            .position(|k| k == kind)
+    });
+    let material = Material::of(&pieces)?;
+    let square = |pos: Position| {
+        let row = if strong == Player::White {
+            pos.row
+        } else {
+            7 - pos.row
+        };
+        row * 8 + pos.column
+    };
+    let king = |player: Player| {
+        gm.pieces_of(player)
+            .iter()
+            .find(|p| p.kind == Kind::King)
+            .map(|p| square(Position::from_piece(p)))
+    };
+    let mut squares = [NONE; 4];
+    squares[0] = king(strong)?;
+    squares[1] = king(weak)?;
+    for (i, kind) in material.pieces().iter().enumerate() {
+        let piece = gm.pieces_of(strong).iter().find(|p| {
+            p.kind == *kind && !squares[2..2 + i].contains(&square(Position::from_piece(p)))
+        })?;
+        squares[2 + i] = square(Position::from_piece(piece));
+    }
+    let count = 2 + material.pieces().len();
+    let side = if gm.turn == strong { 0 } else { 1 };
+    Some((material, encode(side, &squares, count)))
+}
+
+fn encode(side: usize, squares: &Squares, count: usize) -> usize {
+    squares[..count]
+        .iter()
+        .fold(side, |index, sq| index << 6 | *sq as usize)
+}
+
+fn decode(index: usize, count: usize) -> (usize, Squares) {
+    let mut squares = [NONE; 4];
+    for (i, sq) in squares[..count].iter_mut().enumerate() {
+        *sq = (index >> (6 * (count - 1 - i)) & 63) as u8;
+    }
+    (index >> (6 * count), squares)
+}
+
+fn step(sq: u8, (rows, columns): (i8, i8)) -> Option<u8> {
+    let row = (sq / 8) as i8 + rows;
+    let column = (sq % 8) as i8 + columns;
+    ((0..8).contains(&row) && (0..8).contains(&column)).then(|| (row * 8 + column) as u8)
+}
+
+fn adjacent(a: u8, b: u8) -> bool {
+    (a / 8).abs_diff(b / 8) <= 1 && (a % 8).abs_diff(b % 8) <= 1
+}
+
+fn directions(kind: Kind) -> &'static [(i8, i8)] {
+    match kind {
+        Kind::Bishop => &[(-1, -1), (-1, 1), (1, -1), (1, 1)],
+        Kind::Rook => &[(-1, 0), (0, -1), (0, 1), (1, 0)],
+        _ => &KING_STEPS,
+    }
+}
+
+struct Generator {
+    material: Material,
+    count: usize,
+    values: Vec<u8>,
+    // Replies of the weak side not known to lose yet
+    replies: Vec<u8>,
+}
+
+impl Generator {
+    fn new(material: Material) -> Self {
+        let count = 2 + material.pieces().len();
+        Self {
+            material,
+            count,
+            values: vec![0; 2 << (6 * count)],
+            replies: vec![0; 1 << (6 * count)],
+        }
+    }
+
+    fn run(mut self) -> Tablebase {
+        let weak_side = 1 << (6 * self.count);
+        // Positions won through a promotion, by plies to mate
+        let mut promotions: Vec<Vec<usize>> = vec![];
+        let mut bucket = vec![];
+        for index in 0..self.values.len() {
+            let (side, squares) = decode(index, self.count);
+            if !self.is_legal(side, &squares) {
+                self.values[index] = ILLEGAL;
+            } else if side == 1 {
+                let mut replies = 0;
+                self.weak_moves(&squares, |_| replies += 1);
+                self.replies[index - weak_side] = replies;
+                if replies == 0 && self.strong_attacks(&squares, squares[1]) {
+                    self.values[index] = 1;
+                    bucket.push(index);
+                }
+            } else if let Some(dtm) = self.promotion_dtm(&squares) {
+                promotions.resize(promotions.len().max(dtm + 1), vec![]);
+                promotions[dtm].push(index);
+            }
+        }
+        let (mut values, mut replies) = (
+            std::mem::take(&mut self.values),
+            std::mem::take(&mut self.replies),
+        );
+        let mut dtm = 0;
+        while !bucket.is_empty() || dtm < promotions.len() {
+            for index in promotions.get(dtm).into_iter().flatten() {
+                if values[*index] == 0 {
+                    values[*index] = dtm as u8 + 1;
+                    bucket.push(*index);
+                }
+            }
+            let mut next = vec![];
+            for index in bucket {
+                let (side, squares) = decode(index, self.count);
+                let value = dtm as u8 + 2;
+                if side == 1 {
+                    self.strong_unmoves(&squares, |previous| {
+                        let index = encode(0, &previous, self.count);
+                        if values[index] == 0 {
+                            values[index] = value;
+                            next.push(index);
+                        }
+                    });
+                } else {
+                    self.weak_unmoves(&squares, |previous| {
+                        let index = encode(1, &previous, self.count);
+                        if values[index] == 0 {
+                            let replies = &mut replies[index - weak_side];
+                            *replies -= 1;
+                            if *replies == 0 {
+                                values[index] = value;
+                                next.push(index);
+                            }
+                        }
+                    });
+                }
+            }
+            bucket = next;
+            dtm += 1;
+        }
+        Tablebase {
+            material: self.material,
+            values,
+        }
+    }
+
+    fn pieces(&self) -> impl Iterator<Item = (usize, Kind)> {
+        self.material.pieces().iter().copied().enumerate()
+    }
+
+    fn is_occupied(&self, squares: &Squares, sq: u8) -> bool {
+        squares[..self.count].contains(&sq)
+    }
+
+    fn is_legal(&self, side: usize, squares: &Squares) -> bool {
+        let distinct = (0..self.count).all(|i| !squares[..i].contains(&squares[i]));
+        let pawns = self
+            .pieces()
+            .all(|(i, kind)| kind != Kind::Pawn || (1..7).contains(&(squares[2 + i] / 8)));
+        distinct
+            && pawns
+            && !adjacent(squares[0], squares[1])
+            && (side == 1 || !self.strong_attacks(squares, squares[1]))
+    }
+
+    // Whether a strong piece attacks `target`, taken pieces being on `NONE`
+    fn strong_attacks(&self, squares: &Squares, target: u8) -> bool {
+        adjacent(squares[0], target) && squares[0] != target
+            || self.pieces().any(|(i, kind)| {
+                let from = squares[2 + i];
+                from != NONE && from != target && self.attacks(squares, kind, from, target)
+            })
+    }
+
+    fn attacks(&self, squares: &Squares, kind: Kind, from: u8, target: u8) -> bool {
+        let rows = (target / 8) as i8 - (from / 8) as i8;
+        let columns = (target % 8) as i8 - (from % 8) as i8;
+        let slides = match kind {
+            Kind::Pawn => return rows == 1 && columns.abs() == 1,
+            Kind::Knight => return rows.abs() * columns.abs() == 2,
+            Kind::King => return rows.abs().max(columns.abs()) == 1,
+            Kind::Bishop => rows.abs() == columns.abs(),
+            Kind::Rook => rows == 0 || columns == 0,
+            Kind::Queen => rows.abs() == columns.abs() || rows == 0 || columns == 0,
+        };
+        if !slides || from == target {
+            return false;
+        }
+        let direction = (rows.signum(), columns.signum());
+        let mut sq = from;
+        loop {
+            sq = match step(sq, direction) {
+                Some(sq) => sq,
+                None => return false,
+            };
+            if sq == target {
+                return true;
+            }
+            if self.is_occupied(squares, sq) {
+                return false;
+            }
+        }
+    }
+
+    // Legal king moves of the weak side, captures leaving the table included
+    fn weak_moves(&self, squares: &Squares, mut f: impl FnMut(Squares)) {
+        for direction in KING_STEPS {
+            let Some(to) = step(squares[1], direction) else {
+                continue;
+            };
+            if to == squares[0] {
+                continue;
+            }
+            let mut next = *squares;
+            next[1] = to;
+            for sq in &mut next[2..self.count] {
+                if *sq == to {
+                    *sq = NONE;
+                }
+            }
+            if !self.strong_attacks(&next, to) {
+                f(next);
+            }
+        }
+    }
+
+    // Positions the weak king could have come from to reach `squares`
+    fn weak_unmoves(&self, squares: &Squares, mut f: impl FnMut(Squares)) {
+        for direction in KING_STEPS {
+            if let Some(from) = step(squares[1], direction) {
+                if !self.is_occupied(squares, from) {
+                    let mut previous = *squares;
+                    previous[1] = from;
+                    f(previous);
+                }
+            }
+        }
+    }
+
+    // Positions a strong piece could have moved from to reach `squares`, promotions aside
+    fn strong_unmoves(&self, squares: &Squares, mut f: impl FnMut(Squares)) {
+        let mut moved = |i: usize, from: u8| {
+            let mut previous = *squares;
+            previous[i] = from;
+            f(previous);
+        };
+        for direction in KING_STEPS {
+            if let Some(from) = step(squares[0], direction) {
+                if !self.is_occupied(squares, from) {
+                    moved(0, from);
+                }
+            }
+        }
+        for (i, kind) in self.pieces() {
+            let to = squares[2 + i];
+            match kind {
+                Kind::Pawn => {
+                    let row = to / 8;
+                    if row >= 2 && !self.is_occupied(squares, to - 8) {
+                        moved(2 + i, to - 8);
+                        if row == 3 && !self.is_occupied(squares, to - 16) {
+                            moved(2 + i, to - 16);
+                        }
+                    }
+                }
+                Kind::Knight => {
+                    for direction in KNIGHT_STEPS {
+                        if let Some(from) = step(to, direction) {
+                            if !self.is_occupied(squares, from) {
+                                moved(2 + i, from);
+                            }
+                        }
+                    }
+                }
+                _ => {
+                    let kinds: &[Kind] = match kind {
+                        Kind::Queen => &[Kind::Bishop, Kind::Rook],
+                        _ => &[kind],
+                    };
+                    for direction in kinds.iter().flat_map(|kind| directions(*kind)) {
+                        let mut sq = to;
+                        while let Some(from) = step(sq, *direction) {
+                            if self.is_occupied(squares, from) {
+                                break;
+                            }
+                            moved(2 + i, from);
+                            sq = from;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // Plies to mate of the quickest win by promoting the pawn, looked up in the KQK and KRK
+    // tables, the strong side being to move
+    fn promotion_dtm(&self, squares: &Squares) -> Option<usize> {
+        let pawn = self.pieces().find(|(_, kind)| *kind == Kind::Pawn)?.0 + 2;
+        let to = squares[pawn] + 8;
+        if squares[pawn] / 8 != 6 || self.is_occupied(squares, to) {
+            return None;
+        }
+        [Material::Kqk, Material::Krk]
+            .into_iter()
+            .filter_map(|material| {
+                let promoted = [squares[0], squares[1], to, NONE];
+                Tablebase::get(material).probe_index(encode(1, &promoted, 3))
+            })
+            .filter(|probe| probe.wdl == Wdl::Loss)
+            .filter_map(|probe| probe.dtm)
+            .min()
+            .map(|dtm| dtm as usize + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{probe, Material, Probe, Tablebase, Wdl};
+    use crate::game_manager::{GameManager, GameStatus};
+
+    #[test]
+    fn test_tablebase() {
+        let krk = Tablebase::get(Material::Krk);
+        let gm = GameManager::from_fen("k7/8/1K6/8/8/8/8/7R w - - 0 1").unwrap();
+        assert_eq!(
+            Some(Probe {
+                wdl: Wdl::Win,
+                dtm: Some(1)
+            }),
+            krk.probe(&gm)
+        );
+        assert_eq!(None, Tablebase::get(Material::Kqk).probe(&gm));
+        // Longest wins of the endings, in plies
+        let longest = |table: &Tablebase| {
+            table
+                .values
+                .iter()
+                .filter(|v| **v != u8::MAX && **v % 2 == 0)
+                .max()
+                .map(|v| v - 1)
+        };
+        assert_eq!(Some(31), longest(krk));
+        assert_eq!(Some(19), longest(Tablebase::get(Material::Kqk)));
+
+        // King on the sixth rank in front of its pawn wins, not in front of a rook pawn
+        let gm = GameManager::from_fen("4k3/8/4K3/4P3/8/8/8/8 w - - 0 1").unwrap();
+        assert_eq!(Some(Wdl::Win), probe(&gm).map(|p| p.wdl));
+        let gm = GameManager::from_fen("k7/8/8/8/P7/8/8/7K w - - 0 1").unwrap();
+        assert_eq!(Some(Wdl::Draw), probe(&gm).map(|p| p.wdl));
+        // Colors swapped, the black pawn going down
+        let gm = GameManager::from_fen("8/8/8/8/4p3/4k3/8/4K3 b - - 0 1").unwrap();
+        assert_eq!(Some(Wdl::Win), probe(&gm).map(|p| p.wdl));
+
+        // Both sides following the tables: mate comes exactly when announced
+        let mut gm = GameManager::from_fen("8/8/3k4/8/8/8/8/K6Q w - - 0 1").unwrap();
+        let (_, first) = gm.tablebase_move().unwrap();
+        let mut plies = 0;
+        while let Some((mv, _)) = gm.tablebase_move() {
+            gm.play(&mv).unwrap();
+            plies += 1;
+        }
+        assert_eq!(first.dtm, Some(plies));
+        assert_eq!(
+            GameStatus::Checkmate(crate::piece::Player::White),
+            gm.status()
+        );
+    }
+
+    // Generating KBNK takes seconds in release builds and much longer in debug ones, run it
+    // with `cargo test --release -- --ignored`
+    #[test]
+    #[ignore = "slow, generates the KBNK table"]
+    fn test_tablebase_kbnk() {
+        let gm = GameManager::from_fen("7k/8/8/8/8/8/8/KBN5 w - - 0 1").unwrap();
+        assert_eq!(
+            Some(Probe {
+                wdl: Wdl::Win,
+                dtm: Some(57)
+            }),
+            probe(&gm)
+        );
+        // Black to move, its king already in a corner of the bishop's color
+        let gm = GameManager::from_fen("8/8/8/8/8/8/8/KBN4k b - - 0 1").unwrap();
+        assert_eq!(
+            Some(Probe {
+                wdl: Wdl::Loss,
+                dtm: Some(48)
+            }),
+            probe(&gm)
+        );
+    }
+}
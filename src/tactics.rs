@@ -0,0 +1,375 @@
+use std::{error::Error, fmt};
+
+use crate::{
+    game_manager::GameManager,
+    moves::Move,
+    piece::{Kind, Player, Position},
+};
+
+// Piece the opponent wins material against by exchanging on its square
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EnPrise {
+    pub position: Position,
+    pub kind: Kind,
+    pub attackers: usize,
+    pub defenders: usize,
+    // Material the opponent wins, per `see`, capturing first with its least valuable piece
+    pub loss: i32,
+}
+
+// What the opponent of the side to move would do if it could move again right away
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Threats {
+    // Captures winning material per `see`, best first, with what they win
+    pub captures: Vec<(Move, i32)>,
+    pub checks: Vec<Move>,
+    // Checks that would mate, also listed among `checks`
+    pub mates: Vec<Move>,
+}
+
+// Why a position isn't a sound mate in N puzzle
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PuzzleErr {
+    // The side to move can't force mate in that many moves
+    NoMate,
+    // Mate can be forced in fewer moves, the number of which is held
+    Shorter(u8),
+    // Every first move forcing the mate, when there are several
+    Cooked(Vec<Move>),
+}
+
+impl fmt::Display for PuzzleErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PuzzleErr::NoMate => write!(f, "no forced mate"),
+            PuzzleErr::Shorter(moves) => write!(f, "mate in {} already", moves),
+            PuzzleErr::Cooked(keys) => write!(f, "{} key moves", keys.len()),
+        }
+    }
+}
+
+impl Error for PuzzleErr {}
+
+impl GameManager {
+    // Checks the side to move mates in `moves` moves whatever the defense, not sooner, and
+    // with a single first move, the key, which is returned
+    pub fn validate_mate_puzzle(&self, moves: u8) -> Result<Move, PuzzleErr> {
+        let mut gm = self.clone();
+        for shorter in 1..moves {
+            if gm.forces_mate(shorter) {
+                return Err(PuzzleErr::Shorter(shorter));
+            }
+        }
+        let mut keys: Vec<Move> = gm
+            .legal_moves()
+            .into_iter()
+            .filter(|mv| gm.mates_after(mv, moves))
+            .collect();
+        match keys.len() {
+            0 => Err(PuzzleErr::NoMate),
+            1 => Ok(keys.remove(0)),
+            _ => Err(PuzzleErr::Cooked(keys)),
+        }
+    }
+
+    // Whether the side to move mates in at most `moves` moves against any defense
+    fn forces_mate(&mut self, moves: u8) -> bool {
+        self.legal_moves()
+            .iter()
+            .any(|mv| self.mates_after(mv, moves))
+    }
+
+    fn mates_after(&mut self, mv: &Move, moves: u8) -> bool {
+        let undo = self.make_move(mv);
+        let replies = self.legal_moves();
+        let mates = if replies.is_empty() {
+            self.is_in_check(&self.turn)
+        } else {
+            moves > 1
+                && replies.iter().all(|reply| {
+                    let undo = self.make_move(reply);
+                    let mates = self.forces_mate(moves - 1);
+                    self.unmake_move(undo);
+                    mates
+                })
+        };
+        self.unmake_move(undo);
+        mates
+    }
+
+    // Pieces of `player` attacked by the opponent and defended by nothing, the king aside
+    pub fn hanging_pieces(&self, player: &Player) -> Vec<Position> {
+        self.pieces_of(*player)
+            .iter()
+            .filter(|p| p.kind != Kind::King)
+            .map(Position::from_piece)
+            .filter(|pos| {
+                !self.attackers(*pos, &player.opponent()).is_empty()
+                    && self.attackers(*pos, player).is_empty()
+            })
+            .collect()
+    }
+
+    // Pieces of `player` the opponent wins material against, whoever's turn it is, the king
+    // aside. Unlike `hanging_pieces`, a defended piece attacked by a cheaper one counts.
+    pub fn en_prise(&self, player: &Player) -> Vec<EnPrise> {
+        let mut gm = self.clone();
+        gm.turn = player.opponent();
+        gm.en_passant = None;
+        gm.pieces_of(*player)
+            .iter()
+            .filter(|p| p.kind != Kind::King)
+            .filter_map(|p| {
+                let position = Position::from_piece(p);
+                let from = gm.least_valuable_attacker(position)?;
+                let promotion = gm.promotion_on(from, position);
+                let loss = gm.see(&Move {
+                    from,
+                    to: position,
+                    promotion,
+                });
+                (loss > 0).then(|| EnPrise {
+                    position,
+                    kind: p.kind,
+                    attackers: gm.attackers(position, &player.opponent()).len(),
+                    defenders: gm.attackers(position, player).len(),
+                    loss,
+                })
+            })
+            .collect()
+    }
+
+    // `en_prise` for both sides, whites first, to show the pieces in danger after a move
+    pub fn en_prise_report(&self) -> (Vec<EnPrise>, Vec<EnPrise>) {
+        (self.en_prise(&Player::White), self.en_prise(&Player::Black))
+    }
+
+    // Ideas of the opponent found by letting it move instead of the side to move, as if the
+    // latter passed. `None` when the side to move is in check, passing isn't possible then.
+    pub fn threats(&self) -> Option<Threats> {
+        if self.is_in_check(&self.turn) {
+            return None;
+        }
+        let mut gm = self.clone();
+        gm.swap_turn();
+        gm.en_passant = None;
+        let mut threats = Threats::default();
+        for mv in gm.legal_moves() {
+            if gm.is_capture(&mv) {
+                let gain = gm.see(&mv);
+                if gain > 0 {
+                    threats.captures.push((mv, gain));
+                }
+            }
+            let (undo, flags) = gm.make_flagged_move(&mv);
+            gm.unmake_move(undo);
+            if flags.check {
+                threats.checks.push(mv);
+            }
+            if flags.checkmate {
+                threats.mates.push(mv);
+            }
+        }
+        threats.captures.sort_by_key(|(_, gain)| -gain);
+        Some(threats)
+    }
+
+    // Static exchange evaluation: material won by the side to move playing `mv` when both
+    // sides then keep recapturing on its target with their least valuable piece, each one
+    // free to stop when going on would lose. Pins are not looked at.
+    pub fn see(&self, mv: &Move) -> i32 {
+        let mut gm = self.clone();
+        let mut gains = vec![gm.value_on(mv.to)];
+        let mut mv = *mv;
+        loop {
+            let moved = gm.value_on(mv.from);
+            gm.make_move(&mv);
+            let Some(from) = gm.least_valuable_attacker(mv.to) else {
+                break;
+            };
+            gains.push(moved - gains.last().copied().unwrap_or(0));
+            mv = Move {
+                from,
+                to: mv.to,
+                promotion: gm.promotion_on(from, mv.to),
+            };
+        }
+        // Each side only takes when it pays, from the last capture back to the first
+        while gains.len() > 1 {
+            let last = gains.pop().unwrap_or(0);
+            let previous = gains.last_mut().expect("two gains at least");
+            *previous = -(-*previous).max(last);
+        }
+        gains[0]
+    }
+
+    // Best material the side to move wins right away with a capture, per `see`
+    pub fn best_capture(&self) -> Option<(Move, i32)> {
+        self.legal_moves_iter()
+            .filter(|mv| self.value_on(mv.to) > 0)
+            .map(|mv| (mv, self.see(&mv)))
+            .filter(|(_, gain)| *gain > 0)
+            .max_by_key(|(_, gain)| *gain)
+    }
+
+    // Material the last move lets the opponent win at once beyond what it captured, `None`
+    // when it looks safe. Meant as instant feedback, not as a replacement of a search.
+    pub fn last_move_blunder(&self) -> Option<i32> {
+        let record = self.history().last()?;
+        let captured = record
+            .captured
+            .map_or(0, |p| p.kind.material_value() as i32);
+        let (_, lost) = self.best_capture()?;
+        (lost > captured).then_some(lost - captured)
+    }
+
+    // Material value of the piece on `pos`, the king outweighing everything else
+    pub(crate) fn value_on(&self, pos: Position) -> i32 {
+        self.piece_at(&pos).map_or(0, |(_, p)| match p.kind {
+            Kind::King => 100,
+            kind => kind.material_value() as i32,
+        })
+    }
+
+    fn least_valuable_attacker(&self, pos: Position) -> Option<Position> {
+        self.attackers(pos, &self.turn)
+            .into_iter()
+            .min_by_key(|from| self.value_on(*from))
+    }
+
+    fn promotion_on(&self, from: Position, to: Position) -> Option<Kind> {
+        let pawn = self
+            .pieces_of(self.turn)
+            .iter()
+            .any(|p| p.kind == Kind::Pawn && Position::from_piece(p) == from);
+        (pawn && to.row == self.size.promotion_row(self.turn)).then_some(Kind::Queen)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        game_manager::GameManager,
+        moves::Move,
+        piece::{Kind, Player, Position},
+        square::Square,
+        tactics::{EnPrise, PuzzleErr},
+    };
+
+    #[test]
+    fn test_threats() {
+        let mut gm = GameManager::new();
+        for uci in ["e2e4", "e7e5", "f1c4", "b8c6", "d1h5"] {
+            gm.play(&Move::from_uci(uci).unwrap()).unwrap();
+        }
+        // Black to move, White threatens Qxf7# and Qxe5+
+        let threats = gm.threats().unwrap();
+        let f7 = Move::new(Square::H5, Square::F7);
+        assert_eq!(vec![f7], threats.mates);
+        assert!(threats.checks.contains(&f7));
+        assert!(threats.checks.contains(&Move::new(Square::H5, Square::E5)));
+        assert_eq!(f7, threats.captures[0].0);
+        // e5 is guarded by the knight
+        assert!(threats
+            .captures
+            .iter()
+            .all(|(mv, _)| mv.to != Position::from(Square::E5)));
+
+        gm.play(&Move::from_uci("g7g6").unwrap()).unwrap();
+        let threats = gm.threats().unwrap();
+        assert!(threats.mates.is_empty());
+
+        let gm = GameManager::from_fen("4k3/8/8/8/8/8/8/4K2r w - - 0 1").unwrap();
+        assert_eq!(None, gm.threats());
+    }
+
+    #[test]
+    fn test_see() {
+        // Pawn defended by a pawn, the rook only wins it if nothing recaptures
+        let gm = GameManager::from_fen("4k3/8/2p5/3p4/8/8/8/3RK3 w - - 0 1").unwrap();
+        assert_eq!(-4, gm.see(&Move::new(Square::D1, Square::D5)));
+        let gm = GameManager::from_fen("4k3/8/8/3p4/8/8/8/3RK3 w - - 0 1").unwrap();
+        assert_eq!(1, gm.see(&Move::new(Square::D1, Square::D5)));
+        // Black can't take back, White has more pieces on the file
+        let gm = GameManager::from_fen("3rk3/3r4/8/3n4/8/4N3/3R4/3RK3 w - - 0 1").unwrap();
+        assert_eq!(3, gm.see(&Move::new(Square::E3, Square::D5)));
+        assert_eq!(Some(3), gm.best_capture().map(|(_, gain)| gain));
+    }
+
+    #[test]
+    fn test_en_prise() {
+        // The knight is defended but the pawn still wins it, whoever's turn it is
+        let gm = GameManager::from_fen("4k3/8/2p5/3N4/8/8/8/3RK2r w - - 0 1").unwrap();
+        let (whites, blacks) = gm.en_prise_report();
+        assert_eq!(
+            vec![EnPrise {
+                position: Position::from(Square::D5),
+                kind: Kind::Knight,
+                attackers: 1,
+                defenders: 1,
+                loss: 2,
+            }],
+            whites
+        );
+        assert!(blacks.is_empty());
+        assert!(gm.hanging_pieces(&Player::White).is_empty());
+    }
+
+    #[test]
+    fn test_mate_puzzles() {
+        let gm = GameManager::from_fen("k7/8/1K6/8/8/8/8/7R w - - 0 1").unwrap();
+        assert_eq!(
+            Ok(Move::new(Square::H1, Square::H8)),
+            gm.validate_mate_puzzle(1)
+        );
+        assert_eq!(Err(PuzzleErr::Shorter(1)), gm.validate_mate_puzzle(2));
+        let gm = GameManager::from_fen("k7/8/1K6/8/8/8/8/6RR w - - 0 1").unwrap();
+        assert_eq!(
+            Err(PuzzleErr::Cooked(vec![
+                Move::new(Square::G1, Square::G8),
+                Move::new(Square::H1, Square::H8),
+            ])),
+            gm.validate_mate_puzzle(1).map_err(|err| match err {
+                PuzzleErr::Cooked(mut keys) => {
+                    keys.sort_by_key(|mv| mv.from.column);
+                    PuzzleErr::Cooked(keys)
+                }
+                err => err,
+            })
+        );
+        // Only Kb6 boxes the king in, the rook mates on the next move
+        let gm = GameManager::from_fen("k7/8/8/1K6/8/8/8/7R w - - 0 1").unwrap();
+        assert_eq!(
+            Ok(Move::new(Square::B5, Square::B6)),
+            gm.validate_mate_puzzle(2)
+        );
+        assert_eq!(Err(PuzzleErr::NoMate), gm.validate_mate_puzzle(1));
+    }
+
+    #[test]
+    fn test_hanging_pieces_and_blunders() {
+        let mut gm = GameManager::new();
+        for (from, to) in [
+            (Square::E2, Square::E4),
+            (Square::E7, Square::E5),
+            (Square::D1, Square::H5),
+        ] {
+            gm.play(&Move::new(from, to)).unwrap();
+        }
+        // f7 is attacked too but the king guards it
+        assert_eq!(
+            vec![Position::from(Square::E5)],
+            gm.hanging_pieces(&Player::Black)
+        );
+        assert_eq!(None, gm.last_move_blunder());
+        gm.play(&Move::new(Square::G7, Square::G6)).unwrap();
+        assert_eq!(Some(1), gm.last_move_blunder());
+        // The pawn doesn't pay for the queen
+        gm.play(&Move::new(Square::H5, Square::G6)).unwrap();
+        assert_eq!(Some(8), gm.last_move_blunder());
+        assert_eq!(
+            vec![Position::from(Square::G6)],
+            gm.hanging_pieces(&Player::White)
+        );
+    }
+}
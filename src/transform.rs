@@ -0,0 +1,120 @@
+use crate::{
+    builder::{validate_position, BuildErr},
+    game_manager::{CastlingRights, GameManager},
+    piece::{Piece, PieceList, Player, Position},
+};
+
+// Symmetric versions of a position, for data augmentation and puzzle generation. The new
+// games start from the transformed position, without history nor clock.
+impl GameManager {
+    // Files reversed, a1 becoming h1. Castling can't be mirrored, the rights are dropped.
+    pub fn mirror(&self) -> GameManager {
+        let columns = self.size.columns();
+        let mut gm = self.transformed(|pos| Position::new(pos.row, columns - 1 - pos.column));
+        gm.castling = CastlingRights::none();
+        gm.chess960 = None;
+        gm
+    }
+
+    // Ranks reversed with each piece keeping its side. The pawns then walk back towards their
+    // own side, so the position isn't always legal, e.g. with a pawn giving check.
+    pub fn flip(&self) -> Result<GameManager, BuildErr> {
+        let rows = self.size.rows();
+        let mut gm = self.transformed(|pos| Position::new(rows - 1 - pos.row, pos.column));
+        gm.castling = CastlingRights::none();
+        gm.chess960 = None;
+        gm.en_passant = None;
+        validate_position(&gm)?;
+        Ok(gm)
+    }
+
+    // Same position seen from the other side: ranks reversed, colors and turn swapped
+    pub fn swap_colors(&self) -> GameManager {
+        let rows = self.size.rows();
+        let mut gm = self.transformed(|pos| Position::new(rows - 1 - pos.row, pos.column));
+        let swap = |pieces: &PieceList, player: Player| -> PieceList {
+            pieces.iter().map(|p| Piece { player, ..*p }).collect()
+        };
+        (gm.whites, gm.blacks) = (
+            swap(&gm.blacks, Player::White),
+            swap(&gm.whites, Player::Black),
+        );
+        gm.turn = self.turn.opponent();
+        gm.castling = CastlingRights {
+            white_king_side: self.castling.black_king_side,
+            white_queen_side: self.castling.black_queen_side,
+            black_king_side: self.castling.white_king_side,
+            black_queen_side: self.castling.white_queen_side,
+        };
+        gm
+    }
+
+    // Copy of the position with every square moved by `map`
+    fn transformed(&self, map: impl Fn(Position) -> Position) -> GameManager {
+        let moved = |pieces: &PieceList| -> PieceList {
+            pieces
+                .iter()
+                .map(|p| {
+                    let pos = map(Position::from_piece(p));
+                    Piece::new(p.player, p.kind, pos.row, pos.column)
+                })
+                .collect()
+        };
+        GameManager {
+            whites: moved(&self.whites),
+            blacks: moved(&self.blacks),
+            turn: self.turn,
+            castling: self.castling,
+            en_passant: self.en_passant.map(&map),
+            halfmove_clock: self.halfmove_clock,
+            fullmove_number: self.fullmove_number,
+            chess960: self.chess960,
+            rules: self.rules.clone(),
+            size: self.size,
+            ..GameManager::empty()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{builder::BuildErr, game_manager::GameManager, moves::Move};
+
+    #[test]
+    fn test_transforms() {
+        let fen = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+        let gm = GameManager::from_fen(fen).unwrap();
+
+        let mirrored = gm.mirror();
+        assert_eq!(
+            "r2k3r/1bpqpp1p/1pnp2nb/3NP3/3P2p1/p1Q2N2/PPPBBPPP/R2K3R w - - 0 1",
+            mirrored.to_fen()
+        );
+        assert_eq!(gm.to_fen().replace("KQkq", "-"), mirrored.mirror().to_fen());
+
+        // The color-reversed position has the same moves, seen from the other side
+        let swapped = gm.swap_colors();
+        assert_eq!(
+            "r3k2r/pppbbppp/2n2q1P/1P2p3/3pn3/BN2PNP1/P1PPQPB1/R3K2R b KQkq - 0 1",
+            swapped.to_fen()
+        );
+        assert_eq!(gm.to_fen(), swapped.swap_colors().to_fen());
+        assert_eq!(gm.perft(3), swapped.perft(3));
+        let mut after = gm.clone();
+        after.play(&Move::from_uci("e5f7").unwrap()).unwrap();
+        let mut swapped_after = swapped.clone();
+        swapped_after
+            .play(&Move::from_uci("e4f2").unwrap())
+            .unwrap();
+        assert_eq!(after.swap_colors(), swapped_after);
+
+        let gm = GameManager::from_fen("4k3/8/8/8/8/8/3P4/4K3 w - - 0 1").unwrap();
+        assert_eq!(
+            "4K3/3P4/8/8/8/8/8/4k3 w - - 0 1",
+            gm.flip().unwrap().to_fen()
+        );
+        // The pawn now checks the king that just moved
+        let gm = GameManager::from_fen("k7/8/8/8/4K3/3p4/8/8 b - - 0 1").unwrap();
+        assert_eq!(Err(BuildErr::OpponentInCheck), gm.flip());
+    }
+}
@@ -0,0 +1,356 @@
+use std::{
+    error::Error,
+    fmt,
+    io::{self, BufRead, Write},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use crate::{
+    eval::ClassicalEval,
+    fen::{FenErr, START_FEN},
+    game_manager::GameManager,
+    moves::Move,
+    piece::Player,
+    search::{search, SearchLimits, MAX_DEPTH},
+};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum UciErr {
+    Syntax,
+    Fen(FenErr),
+    // Index in the move list of the first move that can't be played
+    IllegalMove(usize),
+}
+
+impl fmt::Display for UciErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UciErr::Syntax => write!(f, "invalid position command"),
+            UciErr::Fen(err) => err.fmt(f),
+            UciErr::IllegalMove(index) => write!(f, "illegal move at index {}", index),
+        }
+    }
+}
+
+impl Error for UciErr {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            UciErr::Fen(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl GameManager {
+    // Sets up the game described by the payload of a UCI `position` command, e.g.
+    // "startpos moves e2e4 e7e5" or "fen <fen> moves ...". The "position" keyword is optional.
+    pub fn from_uci_position(command: &str) -> Result<GameManager, UciErr> {
+        let mut tokens = command.split_whitespace().peekable();
+        tokens.next_if_eq(&"position");
+        let mut gm = match tokens.next() {
+            Some("startpos") => GameManager::from_fen(START_FEN).map_err(UciErr::Fen)?,
+            Some("fen") => {
+                let mut fen = vec![];
+                while let Some(field) = tokens.next_if(|t| *t != "moves") {
+                    fen.push(field);
+                }
+                GameManager::from_fen(&fen.join(" ")).map_err(UciErr::Fen)?
+            }
+            _ => return Err(UciErr::Syntax),
+        };
+        match tokens.next() {
+            None => return Ok(gm),
+            Some("moves") => {}
+            Some(_) => return Err(UciErr::Syntax),
+        }
+        for (i, uci) in tokens.enumerate() {
+            let mv = Move::from_uci(uci).ok_or(UciErr::IllegalMove(i))?;
+            gm.play(&mv).map_err(|_| UciErr::IllegalMove(i))?;
+        }
+        Ok(gm)
+    }
+}
+
+// Engine side of the UCI protocol. Commands are read line by line and the answers written to
+// `out`, searches run on their own thread so `stop` and `isready` are answered meanwhile.
+pub struct UciEngine<W> {
+    out: Arc<Mutex<W>>,
+    game: GameManager,
+    // Used by `go` without any limit, set with the "Depth" option
+    depth: u8,
+    // Set with the "Threads" option
+    threads: usize,
+    stop: Arc<AtomicBool>,
+    search: Option<JoinHandle<()>>,
+}
+
+const DEFAULT_DEPTH: u8 = 5;
+const MAX_THREADS: usize = 256;
+
+impl<W: Write + Send + 'static> UciEngine<W> {
+    pub fn new(out: W) -> Self {
+        Self {
+            out: Arc::new(Mutex::new(out)),
+            game: GameManager::new(),
+            depth: DEFAULT_DEPTH,
+            threads: 1,
+            stop: Arc::new(AtomicBool::new(false)),
+            search: None,
+        }
+    }
+
+    // Answers the commands of `input` until "quit" or the end of the input, where the last
+    // search is left to complete
+    pub fn run(&mut self, input: impl BufRead) -> io::Result<()> {
+        for line in input.lines() {
+            if !self.handle(&line?)? {
+                self.stop_search();
+                return Ok(());
+            }
+        }
+        if let Some(search) = self.search.take() {
+            let _ = search.join();
+        }
+        Ok(())
+    }
+
+    // Answers one command, returns false on "quit". Unknown commands are ignored as the
+    // protocol asks.
+    pub fn handle(&mut self, line: &str) -> io::Result<bool> {
+        let line = line.trim();
+        let (command, args) = line.split_once(' ').unwrap_or((line, ""));
+        match command {
+            "uci" => {
+                let mut out = self.lock();
+                writeln!(out, "id name libchess {}", env!("CARGO_PKG_VERSION"))?;
+                writeln!(
+                    out,
+                    "option name Depth type spin default {} min 1 max {}",
+                    DEFAULT_DEPTH, MAX_DEPTH
+                )?;
+                writeln!(
+                    out,
+                    "option name Threads type spin default 1 min 1 max {}",
+                    MAX_THREADS
+                )?;
+                writeln!(out, "uciok")?;
+            }
+            "isready" => writeln!(self.lock(), "readyok")?,
+            "ucinewgame" => {
+                self.stop_search();
+                self.game = GameManager::new();
+            }
+            "position" => {
+                self.stop_search();
+                match GameManager::from_uci_position(args) {
+                    Ok(game) => self.game = game,
+                    Err(err) => writeln!(self.lock(), "info string invalid position {:?}", err)?,
+                }
+            }
+            "setoption" => self.set_option(args),
+            "go" => self.go(args),
+            "stop" => self.stop_search(),
+            "quit" => return Ok(false),
+            _ => {}
+        }
+        Ok(true)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, W> {
+        self.out.lock().expect("engine output poisoned")
+    }
+
+    // "name <id> value <x>", only the depth and the threads can be set
+    fn set_option(&mut self, args: &str) {
+        let Some((name, value)) = args
+            .strip_prefix("name ")
+            .and_then(|args| args.split_once(" value "))
+        else {
+            return;
+        };
+        let name = name.trim();
+        if name.eq_ignore_ascii_case("depth") {
+            if let Ok(depth) = value.trim().parse::<u8>() {
+                self.depth = depth.clamp(1, MAX_DEPTH);
+            }
+        } else if name.eq_ignore_ascii_case("threads") {
+            if let Ok(threads) = value.trim().parse::<usize>() {
+                self.threads = threads.clamp(1, MAX_THREADS);
+            }
+        }
+    }
+
+    fn go(&mut self, args: &str) {
+        self.stop_search();
+        let limits = self.limits(args);
+        let game = self.game.clone();
+        let out = Arc::clone(&self.out);
+        self.search = Some(thread::spawn(move || {
+            let write = |line: String| {
+                // The GUI went away, nothing left to report to
+                let _ = writeln!(out.lock().expect("engine output poisoned"), "{}", line);
+            };
+            let best = search(&game, &limits, &ClassicalEval, |info| write(info.to_uci()));
+            let best = best.map_or("0000".to_string(), |(mv, _)| mv.to_uci());
+            write(format!("bestmove {}", best));
+        }));
+    }
+
+    // Limits of a "go" command: depth, nodes, movetime, the clocks or infinite
+    fn limits(&self, args: &str) -> SearchLimits {
+        self.stop.store(false, Ordering::Relaxed);
+        let mut limits = SearchLimits {
+            stop: Some(Arc::clone(&self.stop)),
+            threads: Some(self.threads),
+            ..SearchLimits::default()
+        };
+        let mut infinite = false;
+        let (mut time, mut increment) = (None, 0);
+        let mut tokens = args.split_whitespace();
+        while let Some(token) = tokens.next() {
+            let mut value = || tokens.next().and_then(|v| v.parse::<u64>().ok());
+            match (token, self.game.turn) {
+                ("depth", _) => limits.depth = value().map(|d| d.min(MAX_DEPTH as u64) as u8),
+                ("nodes", _) => limits.nodes = value(),
+                ("movetime", _) => limits.movetime = value().map(Duration::from_millis),
+                ("wtime", Player::White) | ("btime", Player::Black) => time = value(),
+                ("winc", Player::White) | ("binc", Player::Black) => {
+                    increment = value().unwrap_or(0)
+                }
+                ("infinite", _) => infinite = true,
+                _ => {}
+            }
+        }
+        if let (None, Some(time)) = (limits.movetime, time) {
+            // A slice of the remaining time, as if 30 moves were left
+            limits.movetime = Some(Duration::from_millis(time / 30 + increment / 2));
+        }
+        let unlimited = limits.depth.is_none() && limits.nodes.is_none();
+        if unlimited && limits.movetime.is_none() && !infinite {
+            limits.depth = Some(self.depth);
+        }
+        limits
+    }
+
+    // Ends the running search, which reports its best move
+    fn stop_search(&mut self) {
+        if let Some(search) = self.search.take() {
+            self.stop.store(true, Ordering::Relaxed);
+            // A panicking search has nothing to report
+            let _ = search.join();
+        }
+    }
+}
+
+impl<W> Drop for UciEngine<W> {
+    fn drop(&mut self) {
+        if let Some(search) = self.search.take() {
+            self.stop.store(true, Ordering::Relaxed);
+            let _ = search.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{self, Write},
+        sync::{Arc, Mutex},
+    };
+
+    use super::{UciEngine, UciErr};
+    use crate::{fen::FenErr, game_manager::GameManager};
+
+    // Output the test can read while the engine owns it
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Shared {
+        fn lines(&self) -> Vec<String> {
+            String::from_utf8(self.0.lock().unwrap().clone())
+                .unwrap()
+                .lines()
+                .map(str::to_string)
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_uci_engine() {
+        let out = Shared::default();
+        let mut engine = UciEngine::new(out.clone());
+        let input = "uci\nisready\nsetoption name Depth value 2\nsetoption name Threads value 2\n\
+            position fen k7/8/1K6/8/8/8/8/7R w - - 0 1\ngo\n";
+        engine.run(input.as_bytes()).unwrap();
+        let lines = out.lines();
+        assert!(lines[0].starts_with("id name libchess"));
+        assert!(lines.contains(&"uciok".to_string()));
+        assert!(lines.contains(&"readyok".to_string()));
+        assert!(lines
+            .iter()
+            .any(|l| l.starts_with("info depth 2 score mate 1")));
+        assert!(!lines.iter().any(|l| l.starts_with("info depth 3")));
+        assert_eq!(Some(&"bestmove h1h8".to_string()), lines.last());
+
+        // Stopped before it ends, the best move so far is given
+        let out = Shared::default();
+        let mut engine = UciEngine::new(out.clone());
+        engine.handle("position startpos moves e2e4").unwrap();
+        engine.handle("go infinite").unwrap();
+        engine.handle("stop").unwrap();
+        let lines = out.lines();
+        assert!(lines.last().unwrap().starts_with("bestmove "));
+        assert_eq!(
+            1,
+            lines.iter().filter(|l| l.starts_with("bestmove")).count()
+        );
+
+        // Game over
+        let out = Shared::default();
+        let mut engine = UciEngine::new(out.clone());
+        engine
+            .run("position fen R3k3/8/4K3/8/8/8/8/8 b - - 0 1\ngo depth 3\n".as_bytes())
+            .unwrap();
+        assert_eq!(vec!["bestmove 0000"], out.lines());
+    }
+
+    #[test]
+    fn test_uci_position() {
+        let gm = GameManager::from_uci_position("position startpos moves e2e4 e7e5 g1f3").unwrap();
+        assert_eq!(
+            "rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2",
+            gm.to_fen()
+        );
+        assert_eq!(3, gm.history().len());
+        assert_eq!(
+            GameManager::new().to_fen(),
+            GameManager::from_uci_position("startpos").unwrap().to_fen()
+        );
+        let gm =
+            GameManager::from_uci_position("fen 4k3/P7/8/8/8/8/8/4K3 w - - 0 1 moves a7a8q e8d7")
+                .unwrap();
+        assert_eq!("Q7/3k4/8/8/8/8/8/4K3 w - - 1 2", gm.to_fen());
+
+        let err = |command| GameManager::from_uci_position(command).map(|gm| gm.to_fen());
+        assert_eq!(Err(UciErr::Syntax), err("position"));
+        assert_eq!(Err(UciErr::Syntax), err("startpos e2e4"));
+        assert_eq!(Err(UciErr::Fen(FenErr::MissingField)), err("fen 8/8 moves"));
+        assert_eq!(Err(UciErr::IllegalMove(1)), err("startpos moves e2e4 e2e4"));
+        assert_eq!(Err(UciErr::IllegalMove(0)), err("startpos moves e2"));
+    }
+}